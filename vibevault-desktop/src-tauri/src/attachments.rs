@@ -0,0 +1,79 @@
+use tauri::State;
+
+use vibevault_core::AttachmentInfo;
+
+use crate::{with_vault, AppState};
+
+#[tauri::command]
+pub fn list_attachments(
+    state: State<AppState>,
+    token: String,
+    id: i64,
+) -> Result<Vec<AttachmentInfo>, String> {
+    with_vault(&state, |vault| vault.list_attachments(&token, id))
+}
+
+/// Start a chunked upload; returns the upload ID for the following calls
+#[tauri::command]
+pub fn begin_attachment_upload(
+    state: State<AppState>,
+    token: String,
+    id: i64,
+    file_name: String,
+    mime_type: Option<String>,
+    size: u64,
+) -> Result<String, String> {
+    with_vault(&state, |vault| {
+        vault.begin_attachment_upload(&token, id, &file_name, mime_type.as_deref(), size)
+    })
+}
+
+#[tauri::command]
+pub fn upload_attachment_chunk(
+    state: State<AppState>,
+    token: String,
+    upload_id: String,
+    data: Vec<u8>,
+) -> Result<(), String> {
+    with_vault(&state, |vault| vault.upload_attachment_chunk(&token, &upload_id, &data))
+}
+
+#[tauri::command]
+pub fn finish_attachment_upload(
+    state: State<AppState>,
+    token: String,
+    upload_id: String,
+) -> Result<AttachmentInfo, String> {
+    with_vault(&state, |vault| vault.finish_attachment_upload(&token, &upload_id))
+}
+
+#[tauri::command]
+pub fn cancel_attachment_upload(
+    state: State<AppState>,
+    token: String,
+    upload_id: String,
+) -> Result<String, String> {
+    with_vault(&state, |vault| vault.cancel_attachment_upload(&token, &upload_id))?;
+    Ok("Upload cancelled".to_string())
+}
+
+/// Download one decrypted chunk; call for 0..chunkCount and concatenate
+#[tauri::command]
+pub fn read_attachment_chunk(
+    state: State<AppState>,
+    token: String,
+    attachment_id: i64,
+    index: u32,
+) -> Result<Vec<u8>, String> {
+    with_vault(&state, |vault| vault.read_attachment_chunk(&token, attachment_id, index))
+}
+
+#[tauri::command]
+pub fn delete_attachment(
+    state: State<AppState>,
+    token: String,
+    attachment_id: i64,
+) -> Result<String, String> {
+    with_vault(&state, |vault| vault.delete_attachment(&token, attachment_id))?;
+    Ok("Deleted".to_string())
+}
@@ -0,0 +1,28 @@
+use tauri::State;
+
+use vibevault_core::{BitwardenFolderMapping, BitwardenImportReport};
+
+use crate::{with_vault, AppState};
+
+/// Import a Bitwarden JSON export file; `password` is needed for
+/// password-protected exports. Folders become folders of the active
+/// profile unless `mapping` says profiles.
+#[tauri::command]
+pub fn import_bitwarden(
+    state: State<AppState>,
+    token: String,
+    path: String,
+    password: Option<String>,
+    mapping: Option<BitwardenFolderMapping>,
+) -> Result<BitwardenImportReport, String> {
+    let json =
+        std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    with_vault(&state, |vault| {
+        vault.import_bitwarden(
+            &token,
+            &json,
+            password.as_deref(),
+            mapping.unwrap_or_default(),
+        )
+    })
+}
@@ -1,6 +1,7 @@
-pub mod pairing;
 pub mod protocol;
-pub mod transfer;
 
 #[cfg(target_os = "linux")]
 pub mod peripheral;
+
+// Pairing crypto and the sync payload format are platform-independent
+pub use vibevault_core::sync::{pairing, transfer};
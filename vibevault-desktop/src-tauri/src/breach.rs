@@ -0,0 +1,48 @@
+use std::path::{Path, PathBuf};
+
+use tauri::{AppHandle, Manager, State};
+
+use vibevault_core::breach::build_breach_index;
+use vibevault_core::health::HealthScope;
+use vibevault_core::{BreachIndexInfo, BreachReport};
+
+use crate::{with_vault, AppState};
+
+/// The breach index lives next to the vault database
+fn index_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    Ok(dir.join("hibp.idx"))
+}
+
+/// Index a HIBP SHA-1 file (sorted `HASH:COUNT`) or a directory of range
+/// files, replacing any previous index. Takes minutes for the full dataset,
+/// so it runs off the main thread and does not hold the vault lock.
+#[tauri::command]
+pub async fn import_breach_data(
+    app: AppHandle,
+    source_path: String,
+) -> Result<BreachIndexInfo, String> {
+    let index = index_path(&app)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        build_breach_index(Path::new(&source_path), &index)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Look up the vault's passwords in the breach index
+#[tauri::command]
+pub fn check_breached_passwords(
+    app: AppHandle,
+    state: State<AppState>,
+    token: String,
+    scope: Option<HealthScope>,
+) -> Result<BreachReport, String> {
+    let index = index_path(&app)?;
+    with_vault(&state, |vault| {
+        vault.breach_report(&token, &index, scope.unwrap_or_default())
+    })
+}
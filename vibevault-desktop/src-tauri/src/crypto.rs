@@ -1,33 +0,0 @@
-use aes_gcm::{
-    aead::{Aead, KeyInit},
-    Aes256Gcm, Nonce,
-};
-use rand::RngCore;
-
-/// Encrypt data with AES-256-GCM, returns (ciphertext, nonce)
-pub fn encrypt_aes256_gcm(key: &[u8; 32], plaintext: &[u8]) -> Result<(Vec<u8>, Vec<u8>), String> {
-    let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| "Encryption init failed")?;
-    let mut nonce_bytes = [0u8; 12];
-    rand::thread_rng().fill_bytes(&mut nonce_bytes);
-    let nonce = Nonce::from_slice(&nonce_bytes);
-    let ciphertext = cipher
-        .encrypt(nonce, plaintext)
-        .map_err(|_| "Encryption failed")?;
-    Ok((ciphertext, nonce_bytes.to_vec()))
-}
-
-/// Decrypt data with AES-256-GCM
-pub fn decrypt_aes256_gcm(
-    key: &[u8; 32],
-    ciphertext: &[u8],
-    nonce_bytes: &[u8],
-) -> Result<Vec<u8>, String> {
-    if nonce_bytes.len() != 12 {
-        return Err("Invalid nonce length".to_string());
-    }
-    let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| "Decryption init failed")?;
-    let nonce = Nonce::from_slice(nonce_bytes);
-    cipher
-        .decrypt(nonce, ciphertext)
-        .map_err(|_| "Decryption failed — wrong password or corrupted data".to_string())
-}
@@ -0,0 +1,82 @@
+use tauri::State;
+
+use vibevault_core::Folder;
+
+use crate::{with_vault, AppState};
+
+#[tauri::command]
+pub fn list_folders(state: State<AppState>, token: String) -> Result<Vec<Folder>, String> {
+    with_vault(&state, |vault| vault.list_folders(&token))
+}
+
+/// Create a folder; returns its ID
+#[tauri::command]
+pub fn create_folder(
+    state: State<AppState>,
+    token: String,
+    name: String,
+    parent_id: Option<String>,
+) -> Result<String, String> {
+    with_vault(&state, |vault| {
+        vault.create_folder(&token, &name, parent_id.as_deref())
+    })
+}
+
+#[tauri::command]
+pub fn rename_folder(
+    state: State<AppState>,
+    token: String,
+    id: String,
+    name: String,
+) -> Result<String, String> {
+    with_vault(&state, |vault| vault.rename_folder(&token, &id, &name))?;
+    Ok("Renamed".to_string())
+}
+
+#[tauri::command]
+pub fn move_folder(
+    state: State<AppState>,
+    token: String,
+    id: String,
+    parent_id: Option<String>,
+) -> Result<String, String> {
+    with_vault(&state, |vault| vault.move_folder(&token, &id, parent_id.as_deref()))?;
+    Ok("Moved".to_string())
+}
+
+/// Delete a folder and its subfolders; their entries move to its parent
+#[tauri::command]
+pub fn delete_folder(state: State<AppState>, token: String, id: String) -> Result<String, String> {
+    with_vault(&state, |vault| vault.delete_folder(&token, &id))?;
+    Ok("Deleted".to_string())
+}
+
+#[tauri::command]
+pub fn move_entries_to_folder(
+    state: State<AppState>,
+    token: String,
+    ids: Vec<i64>,
+    folder_id: Option<String>,
+) -> Result<String, String> {
+    with_vault(&state, |vault| {
+        vault.move_entries_to_folder(&token, &ids, folder_id.as_deref())
+    })?;
+    Ok("Moved".to_string())
+}
+
+#[tauri::command]
+pub fn apply_tags(
+    state: State<AppState>,
+    token: String,
+    ids: Vec<i64>,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<String, String> {
+    with_vault(&state, |vault| vault.apply_tags(&token, &ids, &add, &remove))?;
+    Ok("Updated".to_string())
+}
+
+#[tauri::command]
+pub fn list_tags(state: State<AppState>, token: String) -> Result<Vec<String>, String> {
+    with_vault(&state, |vault| vault.list_tags(&token))
+}
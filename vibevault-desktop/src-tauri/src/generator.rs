@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+
+use tauri::State;
+
+use vibevault_core::strength::estimate_strength;
+use vibevault_core::{
+    GeneratedSecret, GeneratedValue, PassphraseOptions, PasswordOptions, SiteRules,
+    StrengthEstimate,
+};
+
+use crate::{with_vault, AppState};
+
+/// Generate a password; missing options fall back to the defaults. With
+/// the entry's URL, the site's password rules are followed.
+#[tauri::command]
+pub fn generate_password(
+    state: State<AppState>,
+    token: String,
+    options: Option<PasswordOptions>,
+    url: Option<String>,
+) -> Result<GeneratedSecret, String> {
+    let options = options.unwrap_or_default();
+    with_vault(&state, |vault| match url {
+        Some(url) => vault.generate_password_for_url(&token, &url, &options),
+        None => vault.generate_password(&token, &options),
+    })
+}
+
+/// Generate a passphrase from the EFF wordlist
+#[tauri::command]
+pub fn generate_passphrase(
+    state: State<AppState>,
+    token: String,
+    options: Option<PassphraseOptions>,
+) -> Result<GeneratedSecret, String> {
+    with_vault(&state, |vault| {
+        vault.generate_passphrase(&token, &options.unwrap_or_default())
+    })
+}
+
+#[tauri::command]
+pub fn get_generator_history(
+    state: State<AppState>,
+    token: String,
+) -> Result<Vec<GeneratedValue>, String> {
+    with_vault(&state, |vault| vault.generator_history(&token))
+}
+
+#[tauri::command]
+pub fn clear_generator_history(state: State<AppState>, token: String) -> Result<String, String> {
+    with_vault(&state, |vault| vault.clear_generator_history(&token))?;
+    Ok("Cleared".to_string())
+}
+
+/// Estimate password strength for the entry editor and the setup screen.
+/// `user_inputs` are the username, title, URLs and the like, which an
+/// attacker would try first. Needs no session; nothing is stored.
+#[tauri::command]
+pub fn estimate_password_strength(
+    password: String,
+    user_inputs: Option<Vec<String>>,
+) -> Result<StrengthEstimate, String> {
+    let user_inputs = user_inputs.unwrap_or_default();
+    let inputs: Vec<&str> = user_inputs.iter().map(String::as_str).collect();
+    Ok(estimate_strength(&password, &inputs))
+}
+
+#[tauri::command]
+pub fn get_password_rules(
+    state: State<AppState>,
+    token: String,
+    url: String,
+) -> Result<Option<SiteRules>, String> {
+    with_vault(&state, |vault| vault.password_rules_for_url(&token, &url))
+}
+
+/// Replace the bundled rules with a newer `password-rules.json`
+#[tauri::command]
+pub fn import_password_rules(
+    state: State<AppState>,
+    token: String,
+    source_path: String,
+) -> Result<usize, String> {
+    let json = std::fs::read_to_string(&source_path)
+        .map_err(|e| format!("Failed to read {}: {}", source_path, e))?;
+    with_vault(&state, |vault| vault.import_password_rules(&token, &json))
+}
+
+#[tauri::command]
+pub fn get_password_rule_overrides(
+    state: State<AppState>,
+    token: String,
+) -> Result<BTreeMap<String, String>, String> {
+    with_vault(&state, |vault| vault.password_rule_overrides(&token))
+}
+
+/// Set the user's own rules for a domain; `rules: null` removes them
+#[tauri::command]
+pub fn set_password_rule_override(
+    state: State<AppState>,
+    token: String,
+    domain: String,
+    rules: Option<String>,
+) -> Result<String, String> {
+    with_vault(&state, |vault| {
+        vault.set_password_rule_override(&token, &domain, rules.as_deref())
+    })?;
+    Ok("Updated".to_string())
+}
@@ -1,60 +1,56 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod attachments;
 mod auth;
+mod bitwarden;
+mod breach;
 mod ble;
-mod crypto;
-mod db;
+mod folders;
+mod generator;
+mod otp_import;
 mod profiles;
 mod sync;
 mod vault;
 
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
-use tauri::Manager;
-use zeroize::Zeroize;
+use tauri::{Manager, State};
 
-use db::DatabaseManager;
-
-// --- SESSION STATE ---
-pub struct SessionState {
-    pub token: String,
-    pub encryption_key: [u8; 32],
-}
-
-impl Drop for SessionState {
-    fn drop(&mut self) {
-        self.encryption_key.zeroize();
-        self.token.zeroize();
-    }
-}
+use vibevault_core::Vault;
 
 // --- APP STATE ---
 pub struct AppState {
-    pub db: Arc<Mutex<Option<DatabaseManager>>>,
-    pub active_profile_id: Arc<Mutex<i64>>,
-    pub session: Arc<Mutex<Option<SessionState>>>,
-    pub last_activity: Arc<Mutex<Instant>>,
-    pub auto_lock_seconds: Arc<Mutex<u64>>,
+    pub vault: Arc<Mutex<Option<Vault>>>,
+}
+
+/// Lock the app state and run `f` against the opened vault.
+pub fn with_vault<T>(
+    state: &State<AppState>,
+    f: impl FnOnce(&mut Vault) -> Result<T, String>,
+) -> Result<T, String> {
+    let mut guard = state.vault.lock().map_err(|_| "Lock failed")?;
+    let vault = guard.as_mut().ok_or("DB not init")?;
+    f(vault)
 }
 
 // --- MAIN ---
 fn main() {
     let app_state = AppState {
-        db: Arc::new(Mutex::new(None)),
-        active_profile_id: Arc::new(Mutex::new(1)),
-        session: Arc::new(Mutex::new(None)),
-        last_activity: Arc::new(Mutex::new(Instant::now())),
-        auto_lock_seconds: Arc::new(Mutex::new(900)), // 15 minutes default
+        vault: Arc::new(Mutex::new(None)),
     };
 
     tauri::Builder::default()
         .manage(app_state)
         .setup(|app| {
-            let handle = app.handle();
-            let db_mgr = DatabaseManager::new(handle)
+            let app_dir = app
+                .path()
+                .app_data_dir()
+                .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+            std::fs::create_dir_all(&app_dir)
+                .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+            let vault = Vault::open(&app_dir.join("vibevault.db"))
                 .map_err(|e| Box::<dyn std::error::Error>::from(e))?;
             let state = app.state::<AppState>();
-            *state.db.lock().unwrap() = Some(db_mgr);
+            *state.vault.lock().unwrap() = Some(vault);
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -62,15 +58,79 @@ fn main() {
             auth::register_user,
             auth::unlock_vault,
             auth::lock_vault,
+            auth::get_master_password_policy,
+            auth::set_master_password_policy,
+            auth::change_master_password,
             vault::save_entry,
             vault::update_entry,
             vault::delete_entry,
             vault::get_all_vault_entries,
             vault::get_totp_token,
+            vault::get_entry_totp,
+            vault::get_profile_totp_codes,
+            vault::get_vault_items,
+            vault::search_entries,
+            vault::list_entry_summaries,
+            vault::record_entry_use,
+            vault::set_favorite,
+            vault::recently_used_entries,
+            vault::favorite_entries,
+            vault::get_vault_item,
+            vault::save_vault_item,
+            vault::update_vault_item,
+            vault::get_password_history,
+            vault::restore_password_from_history,
+            vault::get_password_history_depth,
+            vault::set_password_history_depth,
+            vault::get_recovery_codes,
+            vault::get_recovery_code_status,
+            vault::add_recovery_codes,
+            vault::mark_recovery_code_used,
+            vault::list_deleted_entries,
+            vault::restore_entry,
+            vault::purge_entry,
+            vault::get_entry_revisions,
+            vault::diff_entry_revisions,
+            vault::rollback_entry,
+            vault::compact_revisions,
+            vault::get_revision_retention,
+            vault::set_revision_retention,
+            vault::get_health_report,
+            breach::import_breach_data,
+            breach::check_breached_passwords,
+            otp_import::import_totp_qr,
+            otp_import::import_authenticator_backup,
+            bitwarden::import_bitwarden,
+            attachments::list_attachments,
+            attachments::begin_attachment_upload,
+            attachments::upload_attachment_chunk,
+            attachments::finish_attachment_upload,
+            attachments::cancel_attachment_upload,
+            attachments::read_attachment_chunk,
+            attachments::delete_attachment,
+            folders::list_folders,
+            folders::create_folder,
+            folders::rename_folder,
+            folders::move_folder,
+            folders::delete_folder,
+            folders::move_entries_to_folder,
+            folders::apply_tags,
+            folders::list_tags,
+            generator::generate_password,
+            generator::generate_passphrase,
+            generator::get_generator_history,
+            generator::clear_generator_history,
+            generator::estimate_password_strength,
+            generator::get_password_rules,
+            generator::import_password_rules,
+            generator::get_password_rule_overrides,
+            generator::set_password_rule_override,
             profiles::create_profile,
             profiles::get_all_profiles,
             profiles::rename_profile,
             profiles::delete_profile,
+            profiles::move_entries,
+            profiles::copy_entries,
             profiles::get_active_profile,
             profiles::set_active_profile,
             sync::get_paired_devices,
@@ -0,0 +1,40 @@
+use tauri::State;
+
+use vibevault_core::otp_import::parse_otp_qr_image;
+use vibevault_core::{ImportedOtp, OtpImportReport};
+
+use crate::{with_vault, AppState};
+
+/// Decode the QR code(s) of an image, such as a screenshot of a 2FA
+/// enrollment code or a Google Authenticator export, and store the
+/// accounts. With `entry_id` the single account is attached to that entry;
+/// otherwise each account gets a new entry. The image is decoded before
+/// the vault is locked, and either every account is stored or none.
+#[tauri::command]
+pub fn import_totp_qr(
+    state: State<AppState>,
+    token: String,
+    path: String,
+    entry_id: Option<i64>,
+) -> Result<Vec<ImportedOtp>, String> {
+    let image =
+        std::fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let accounts = parse_otp_qr_image(&image)?;
+    with_vault(&state, |vault| vault.import_otp_accounts(&token, &accounts, entry_id))
+}
+
+/// Import an Aegis, 2FAS or andOTP JSON backup file into the active
+/// profile; `password` is needed for encrypted backups
+#[tauri::command]
+pub fn import_authenticator_backup(
+    state: State<AppState>,
+    token: String,
+    path: String,
+    password: Option<String>,
+) -> Result<OtpImportReport, String> {
+    let json =
+        std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    with_vault(&state, |vault| {
+        vault.import_authenticator_backup(&token, &json, password.as_deref())
+    })
+}
@@ -1,8 +1,8 @@
 use tauri::State;
-use rusqlite::params;
 
-use crate::AppState;
-use crate::auth::{validate_session, get_db_and_session};
+use vibevault_core::Profile;
+
+use crate::{with_vault, AppState};
 
 #[tauri::command]
 pub fn create_profile(
@@ -10,56 +10,15 @@ pub fn create_profile(
     token: String,
     name: String,
 ) -> Result<i64, String> {
-    let (db_guard, _key, _profile) = get_db_and_session(&state, &token)?;
-    let db = db_guard.as_ref().unwrap();
-
-    db.conn
-        .execute("INSERT INTO profiles (name) VALUES (?1)", params![name])
-        .map_err(|e| e.to_string())?;
-
-    let id = db.conn.last_insert_rowid();
-    Ok(id)
+    with_vault(&state, |vault| vault.create_profile(&token, &name))
 }
 
 #[tauri::command]
 pub fn get_all_profiles(
     state: State<AppState>,
     token: String,
-) -> Result<Vec<serde_json::Value>, String> {
-    let (db_guard, _key, _profile) = get_db_and_session(&state, &token)?;
-    let db = db_guard.as_ref().unwrap();
-
-    let mut stmt = db
-        .conn
-        .prepare(
-            "SELECT p.id, p.name, p.created_at, COUNT(v.id) as entry_count
-             FROM profiles p
-             LEFT JOIN vault_entries v ON v.profile_id = p.id AND v.deleted_at IS NULL
-             GROUP BY p.id
-             ORDER BY p.id",
-        )
-        .map_err(|e| e.to_string())?;
-
-    let rows = stmt
-        .query_map([], |row| {
-            let id: i64 = row.get(0)?;
-            let name: String = row.get(1)?;
-            let created_at: String = row.get(2)?;
-            let entry_count: i64 = row.get(3)?;
-            Ok(serde_json::json!({
-                "id": id,
-                "name": name,
-                "createdAt": created_at,
-                "entryCount": entry_count
-            }))
-        })
-        .map_err(|e| e.to_string())?;
-
-    let mut profiles = Vec::new();
-    for row in rows {
-        profiles.push(row.map_err(|e| e.to_string())?);
-    }
-    Ok(profiles)
+) -> Result<Vec<Profile>, String> {
+    with_vault(&state, |vault| vault.list_profiles(&token))
 }
 
 #[tauri::command]
@@ -69,66 +28,47 @@ pub fn rename_profile(
     id: i64,
     name: String,
 ) -> Result<String, String> {
-    let (db_guard, _key, _profile) = get_db_and_session(&state, &token)?;
-    let db = db_guard.as_ref().unwrap();
-
-    db.conn
-        .execute(
-            "UPDATE profiles SET name = ?1 WHERE id = ?2",
-            params![name, id],
-        )
-        .map_err(|e| e.to_string())?;
-
+    with_vault(&state, |vault| vault.rename_profile(&token, id, &name))?;
     Ok("Renamed".to_string())
 }
 
+/// Delete a profile; one with entries needs `move_entries_to` set
 #[tauri::command]
 pub fn delete_profile(
     state: State<AppState>,
     token: String,
     id: i64,
+    move_entries_to: Option<i64>,
 ) -> Result<String, String> {
-    let (db_guard, _key, _profile) = get_db_and_session(&state, &token)?;
-    let db = db_guard.as_ref().unwrap();
-
-    // Check if profile has any active entries
-    let entry_count: i64 = db
-        .conn
-        .query_row(
-            "SELECT COUNT(*) FROM vault_entries WHERE profile_id = ?1 AND deleted_at IS NULL",
-            params![id],
-            |row| row.get(0),
-        )
-        .unwrap_or(0);
-
-    if entry_count > 0 {
-        return Err(
-            "Cannot delete profile with entries. Move or delete entries first.".to_string(),
-        );
-    }
-
-    // Check if it's the last profile
-    let profile_count: i64 = db
-        .conn
-        .query_row("SELECT COUNT(*) FROM profiles", [], |row| row.get(0))
-        .unwrap_or(0);
-
-    if profile_count <= 1 {
-        return Err("Cannot delete the last profile.".to_string());
-    }
+    with_vault(&state, |vault| vault.delete_profile(&token, id, move_entries_to))?;
+    Ok("Deleted".to_string())
+}
 
-    db.conn
-        .execute("DELETE FROM profiles WHERE id = ?1", params![id])
-        .map_err(|e| e.to_string())?;
+#[tauri::command]
+pub fn move_entries(
+    state: State<AppState>,
+    token: String,
+    ids: Vec<i64>,
+    target_profile: i64,
+) -> Result<String, String> {
+    with_vault(&state, |vault| vault.move_entries(&token, &ids, target_profile))?;
+    Ok("Moved".to_string())
+}
 
-    Ok("Deleted".to_string())
+/// Copy entries into another profile; returns the copies' entry UUIDs
+#[tauri::command]
+pub fn copy_entries(
+    state: State<AppState>,
+    token: String,
+    ids: Vec<i64>,
+    target_profile: i64,
+) -> Result<Vec<String>, String> {
+    with_vault(&state, |vault| vault.copy_entries(&token, &ids, target_profile))
 }
 
 #[tauri::command]
 pub fn get_active_profile(state: State<AppState>, token: String) -> Result<i64, String> {
-    validate_session(&state, &token)?;
-    let active_id = state.active_profile_id.lock().map_err(|_| "Lock failed")?;
-    Ok(*active_id)
+    with_vault(&state, |vault| vault.active_profile(&token))
 }
 
 #[tauri::command]
@@ -137,8 +77,6 @@ pub fn set_active_profile(
     token: String,
     id: i64,
 ) -> Result<String, String> {
-    validate_session(&state, &token)?;
-    let mut active_id = state.active_profile_id.lock().map_err(|_| "Lock failed")?;
-    *active_id = id;
+    with_vault(&state, |vault| vault.set_active_profile(&token, id))?;
     Ok("Active profile set".to_string())
 }
@@ -1,33 +1,11 @@
-use rusqlite::params;
 use serde::{Deserialize, Serialize};
 use tauri::State;
 
-use crate::auth::get_db_and_session;
-use crate::AppState;
+use vibevault_core::sync::{PairedDevice, SyncHistoryEntry};
 
-// --- Types for frontend communication ---
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PairedDevice {
-    pub id: i64,
-    pub device_name: String,
-    pub device_id: String,
-    pub paired_at: String,
-    pub last_sync_at: Option<String>,
-}
+use crate::{with_vault, AppState};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SyncHistoryEntry {
-    pub id: i64,
-    pub device_id: String,
-    pub direction: String,
-    pub entries_sent: i64,
-    pub entries_received: i64,
-    pub status: String,
-    pub started_at: String,
-    pub completed_at: Option<String>,
-    pub error_message: Option<String>,
-}
+// --- Types for frontend communication ---
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncProgress {
@@ -44,34 +22,7 @@ pub fn get_paired_devices(
     state: State<AppState>,
     token: String,
 ) -> Result<Vec<PairedDevice>, String> {
-    let (db_guard, _key, _profile) = get_db_and_session(&state, &token)?;
-    let db = db_guard.as_ref().unwrap();
-
-    let mut stmt = db
-        .conn
-        .prepare(
-            "SELECT id, device_name, device_id, paired_at, last_sync_at
-             FROM paired_devices ORDER BY paired_at DESC",
-        )
-        .map_err(|e| e.to_string())?;
-
-    let rows = stmt
-        .query_map([], |row| {
-            Ok(PairedDevice {
-                id: row.get(0)?,
-                device_name: row.get(1)?,
-                device_id: row.get(2)?,
-                paired_at: row.get(3)?,
-                last_sync_at: row.get(4)?,
-            })
-        })
-        .map_err(|e| e.to_string())?;
-
-    let mut devices = Vec::new();
-    for row in rows {
-        devices.push(row.map_err(|e| e.to_string())?);
-    }
-    Ok(devices)
+    with_vault(&state, |vault| vault.paired_devices(&token))
 }
 
 #[tauri::command]
@@ -80,16 +31,7 @@ pub fn forget_device(
     token: String,
     device_id: String,
 ) -> Result<String, String> {
-    let (db_guard, _key, _profile) = get_db_and_session(&state, &token)?;
-    let db = db_guard.as_ref().unwrap();
-
-    db.conn
-        .execute(
-            "DELETE FROM paired_devices WHERE device_id = ?1",
-            params![device_id],
-        )
-        .map_err(|e| e.to_string())?;
-
+    with_vault(&state, |vault| vault.forget_device(&token, &device_id))?;
     Ok("Device forgotten".to_string())
 }
 
@@ -98,37 +40,5 @@ pub fn get_sync_history(
     state: State<AppState>,
     token: String,
 ) -> Result<Vec<SyncHistoryEntry>, String> {
-    let (db_guard, _key, _profile) = get_db_and_session(&state, &token)?;
-    let db = db_guard.as_ref().unwrap();
-
-    let mut stmt = db
-        .conn
-        .prepare(
-            "SELECT id, device_id, direction, entries_sent, entries_received,
-                    status, started_at, completed_at, error_message
-             FROM sync_log ORDER BY started_at DESC LIMIT 50",
-        )
-        .map_err(|e| e.to_string())?;
-
-    let rows = stmt
-        .query_map([], |row| {
-            Ok(SyncHistoryEntry {
-                id: row.get(0)?,
-                device_id: row.get(1)?,
-                direction: row.get(2)?,
-                entries_sent: row.get(3)?,
-                entries_received: row.get(4)?,
-                status: row.get(5)?,
-                started_at: row.get(6)?,
-                completed_at: row.get(7)?,
-                error_message: row.get(8)?,
-            })
-        })
-        .map_err(|e| e.to_string())?;
-
-    let mut entries = Vec::new();
-    for row in rows {
-        entries.push(row.map_err(|e| e.to_string())?);
-    }
-    Ok(entries)
+    with_vault(&state, |vault| vault.sync_history(&token))
 }
@@ -1,88 +1,27 @@
 use tauri::State;
-use rusqlite::params;
-use uuid::Uuid;
 
-use crate::AppState;
-use crate::auth::{validate_session, get_db_and_session};
-use crate::crypto::{encrypt_aes256_gcm, decrypt_aes256_gcm};
-use crate::db::DatabaseManager;
+use vibevault_core::{
+    listing::DEFAULT_PAGE_SIZE, DeletedItem, EntryPage, EntryRevision, EntrySort, EntrySummary,
+    EntryTotp, FieldChange, HealthOptions, HealthReport, ItemType, PasswordHistoryEntry,
+    RecoveryCode, RecoveryCodeStatus, RevisionRetention, SearchHit, StoredItem, TotpCode,
+    VaultEntry, VaultItem,
+};
 
-/// Migrate plaintext entries to encrypted (called after unlock)
-pub fn migrate_plaintext_entries(db: &DatabaseManager, key: &[u8; 32]) -> Result<(), String> {
-    let mut stmt = db
-        .conn
-        .prepare("SELECT id, data_blob, nonce FROM vault_entries WHERE length(nonce) = 0")
-        .map_err(|e| e.to_string())?;
+use crate::{with_vault, AppState};
 
-    let rows: Vec<(i64, Vec<u8>, Vec<u8>)> = stmt
-        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
-        .map_err(|e| e.to_string())?
-        .filter_map(|r| r.ok())
-        .collect();
-
-    for (id, plaintext_blob, _nonce) in rows {
-        let (ciphertext, new_nonce) = encrypt_aes256_gcm(key, &plaintext_blob)?;
-        db.conn
-            .execute(
-                "UPDATE vault_entries SET data_blob = ?1, nonce = ?2 WHERE id = ?3",
-                params![ciphertext, new_nonce, id],
-            )
-            .map_err(|e| e.to_string())?;
-    }
-
-    Ok(())
-}
-
-fn now_iso() -> String {
-    chrono::Utc::now().to_rfc3339()
-}
+/// Entries shown in the "recently used" list when the frontend does not say
+const DEFAULT_RECENT_ENTRIES: usize = 10;
 
 #[tauri::command]
 pub fn get_all_vault_entries(
     state: State<AppState>,
     token: String,
-) -> Result<Vec<serde_json::Value>, String> {
-    let (db_guard, key, active_profile) = get_db_and_session(&state, &token)?;
-    let db = db_guard.as_ref().unwrap();
-
-    let mut stmt = db
-        .conn
-        .prepare(
-            "SELECT id, uuid, data_blob, nonce, entry_uuid FROM vault_entries
-             WHERE profile_id = ?1 AND deleted_at IS NULL",
-        )
-        .map_err(|e| e.to_string())?;
-
-    let rows = stmt
-        .query_map(params![active_profile], |row| {
-            let id: i64 = row.get(0)?;
-            let uuid: String = row.get(1)?;
-            let blob: Vec<u8> = row.get(2)?;
-            let nonce: Vec<u8> = row.get(3)?;
-            let entry_uuid: Option<String> = row.get(4)?;
-            Ok((id, uuid, blob, nonce, entry_uuid))
-        })
-        .map_err(|e| e.to_string())?;
-
-    let mut entries = Vec::new();
-    for row in rows {
-        let (id, uuid, blob, nonce, entry_uuid) = row.map_err(|e| e.to_string())?;
-
-        // Decrypt: empty nonce = legacy plaintext fallback
-        let plaintext = if nonce.is_empty() {
-            blob
-        } else {
-            decrypt_aes256_gcm(&key, &blob, &nonce)?
-        };
-
-        entries.push(serde_json::json!({
-            "id": id,
-            "uuid": uuid,
-            "data_blob": plaintext,
-            "entry_uuid": entry_uuid
-        }));
-    }
-    Ok(entries)
+    item_type: Option<ItemType>,
+) -> Result<Vec<VaultEntry>, String> {
+    with_vault(&state, |vault| match item_type {
+        Some(item_type) => vault.list_entries_of_type(&token, item_type),
+        None => vault.list_entries(&token),
+    })
 }
 
 #[tauri::command]
@@ -93,22 +32,7 @@ pub fn save_entry(
     blob: Vec<u8>,
     profile_id: Option<i64>,
 ) -> Result<String, String> {
-    let (db_guard, key, active_profile) = get_db_and_session(&state, &token)?;
-    let target_profile = profile_id.unwrap_or(active_profile);
-    let db = db_guard.as_ref().unwrap();
-
-    let (ciphertext, nonce) = encrypt_aes256_gcm(&key, &blob)?;
-    let entry_uuid = Uuid::new_v4().to_string();
-    let now = now_iso();
-
-    db.conn
-        .execute(
-            "INSERT INTO vault_entries (uuid, data_blob, nonce, profile_id, entry_uuid, created_at, updated_at, sync_version)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 1)",
-            params![uuid, ciphertext, nonce, target_profile, entry_uuid, now, now],
-        )
-        .map_err(|e| e.to_string())?;
-
+    with_vault(&state, |vault| vault.save_entry(&token, &uuid, &blob, profile_id))?;
     Ok("Saved".to_string())
 }
 
@@ -120,68 +44,320 @@ pub fn update_entry(
     uuid: String,
     blob: Vec<u8>,
 ) -> Result<String, String> {
-    let (db_guard, key, active_profile) = get_db_and_session(&state, &token)?;
-    let db = db_guard.as_ref().unwrap();
-
-    let (ciphertext, nonce) = encrypt_aes256_gcm(&key, &blob)?;
-    let now = now_iso();
-
-    // Update entry, bump sync_version, update timestamp
-    let rows_updated = db
-        .conn
-        .execute(
-            "UPDATE vault_entries
-             SET uuid = ?1, data_blob = ?2, nonce = ?3, updated_at = ?4, sync_version = sync_version + 1
-             WHERE id = ?5 AND profile_id = ?6 AND deleted_at IS NULL",
-            params![uuid, ciphertext, nonce, now, id, active_profile],
+    with_vault(&state, |vault| vault.update_entry(&token, id, &uuid, &blob))?;
+    Ok("Updated".to_string())
+}
+
+#[tauri::command]
+pub fn delete_entry(state: State<AppState>, token: String, id: i64) -> Result<String, String> {
+    with_vault(&state, |vault| vault.delete_entry(&token, id))?;
+    Ok("Deleted".to_string())
+}
+
+/// Current and next TOTP code for `secret` (base32, `steam://` or
+/// `otpauth://` URI; HOTP URIs are only previewed); pass `entry_id` to count
+/// it as a use of that entry
+#[tauri::command]
+pub fn get_totp_token(
+    state: State<AppState>,
+    token: String,
+    secret: String,
+    entry_id: Option<i64>,
+) -> Result<TotpCode, String> {
+    with_vault(&state, |vault| {
+        let code = vault.totp_token(&token, &secret)?;
+        if let Some(id) = entry_id {
+            vault.record_entry_use(&token, id)?;
+        }
+        Ok(code)
+    })
+}
+
+/// Current and next TOTP code of an entry of the active profile; the
+/// secret is decrypted in the backend and counts as a use of the entry.
+/// Each call advances the counter of an HOTP entry.
+#[tauri::command]
+pub fn get_entry_totp(
+    state: State<AppState>,
+    token: String,
+    entry_id: i64,
+) -> Result<TotpCode, String> {
+    with_vault(&state, |vault| {
+        let code = vault.entry_totp(&token, entry_id)?;
+        vault.record_entry_use(&token, entry_id)?;
+        Ok(code)
+    })
+}
+
+/// Codes of every TOTP and Steam entry in `profile_id` (default: the active
+/// profile); HOTP entries are left out
+#[tauri::command]
+pub fn get_profile_totp_codes(
+    state: State<AppState>,
+    token: String,
+    profile_id: Option<i64>,
+) -> Result<Vec<EntryTotp>, String> {
+    with_vault(&state, |vault| vault.profile_totp_codes(&token, profile_id))
+}
+
+#[tauri::command]
+pub fn get_vault_items(
+    state: State<AppState>,
+    token: String,
+    item_type: Option<ItemType>,
+) -> Result<Vec<StoredItem>, String> {
+    with_vault(&state, |vault| vault.list_items(&token, item_type))
+}
+
+#[tauri::command]
+pub fn search_entries(
+    state: State<AppState>,
+    token: String,
+    query: String,
+) -> Result<Vec<SearchHit>, String> {
+    with_vault(&state, |vault| vault.search_entries(&token, &query))
+}
+
+/// One page of entry titles, usernames and favicon domains, without
+/// secrets; open an entry with `get_vault_item`
+#[tauri::command]
+pub fn list_entry_summaries(
+    state: State<AppState>,
+    token: String,
+    sort: Option<EntrySort>,
+    cursor: Option<String>,
+    limit: Option<usize>,
+) -> Result<EntryPage, String> {
+    with_vault(&state, |vault| {
+        vault.list_entry_summaries(
+            &token,
+            sort.unwrap_or_default(),
+            cursor.as_deref(),
+            limit.unwrap_or(DEFAULT_PAGE_SIZE),
         )
-        .map_err(|e| e.to_string())?;
+    })
+}
 
-    if rows_updated == 0 {
-        return Err("Entry not found or belongs to different profile".to_string());
-    }
+/// Called when the user reveals or copies an entry's secret
+#[tauri::command]
+pub fn record_entry_use(state: State<AppState>, token: String, id: i64) -> Result<String, String> {
+    with_vault(&state, |vault| vault.record_entry_use(&token, id))?;
+    Ok("Recorded".to_string())
+}
 
+#[tauri::command]
+pub fn set_favorite(
+    state: State<AppState>,
+    token: String,
+    id: i64,
+    favorite: bool,
+) -> Result<String, String> {
+    with_vault(&state, |vault| vault.set_favorite(&token, id, favorite))?;
     Ok("Updated".to_string())
 }
 
 #[tauri::command]
-pub fn delete_entry(state: State<AppState>, token: String, id: i64) -> Result<String, String> {
-    let (db_guard, _key, active_profile) = get_db_and_session(&state, &token)?;
-    let db = db_guard.as_ref().unwrap();
-
-    let now = now_iso();
-
-    // Soft delete: set deleted_at timestamp instead of removing the row
-    let rows_updated = db
-        .conn
-        .execute(
-            "UPDATE vault_entries
-             SET deleted_at = ?1, updated_at = ?1, sync_version = sync_version + 1
-             WHERE id = ?2 AND profile_id = ?3 AND deleted_at IS NULL",
-            params![now, id, active_profile],
-        )
-        .map_err(|e| e.to_string())?;
+pub fn recently_used_entries(
+    state: State<AppState>,
+    token: String,
+    limit: Option<usize>,
+) -> Result<Vec<EntrySummary>, String> {
+    with_vault(&state, |vault| {
+        vault.recently_used_entries(&token, limit.unwrap_or(DEFAULT_RECENT_ENTRIES))
+    })
+}
 
-    if rows_updated == 0 {
-        return Err("Entry not found or belongs to different profile".to_string());
-    }
+#[tauri::command]
+pub fn favorite_entries(state: State<AppState>, token: String) -> Result<Vec<EntrySummary>, String> {
+    with_vault(&state, |vault| vault.favorite_entries(&token))
+}
 
-    Ok("Deleted".to_string())
+#[tauri::command]
+pub fn get_vault_item(
+    state: State<AppState>,
+    token: String,
+    id: i64,
+) -> Result<StoredItem, String> {
+    with_vault(&state, |vault| vault.get_item(&token, id))
 }
 
 #[tauri::command]
-pub fn get_totp_token(
+pub fn save_vault_item(
     state: State<AppState>,
     token: String,
-    secret: String,
+    item: VaultItem,
+    profile_id: Option<i64>,
+) -> Result<String, String> {
+    with_vault(&state, |vault| vault.save_item(&token, &item, profile_id))
+}
+
+#[tauri::command]
+pub fn update_vault_item(
+    state: State<AppState>,
+    token: String,
+    id: i64,
+    item: VaultItem,
+) -> Result<String, String> {
+    with_vault(&state, |vault| vault.update_item(&token, id, &item))?;
+    Ok("Updated".to_string())
+}
+
+#[tauri::command]
+pub fn get_password_history(
+    state: State<AppState>,
+    token: String,
+    id: i64,
+) -> Result<Vec<PasswordHistoryEntry>, String> {
+    with_vault(&state, |vault| vault.password_history(&token, id))
+}
+
+#[tauri::command]
+pub fn restore_password_from_history(
+    state: State<AppState>,
+    token: String,
+    id: i64,
+    index: usize,
+) -> Result<String, String> {
+    with_vault(&state, |vault| vault.restore_password(&token, id, index))?;
+    Ok("Restored".to_string())
+}
+
+#[tauri::command]
+pub fn get_password_history_depth(state: State<AppState>) -> Result<usize, String> {
+    with_vault(&state, |vault| Ok(vault.password_history_depth()))
+}
+
+#[tauri::command]
+pub fn set_password_history_depth(
+    state: State<AppState>,
+    token: String,
+    depth: usize,
+) -> Result<String, String> {
+    with_vault(&state, |vault| vault.set_password_history_depth(&token, depth))?;
+    Ok(format!("Password history depth set to {}", depth))
+}
+
+#[tauri::command]
+pub fn get_recovery_codes(
+    state: State<AppState>,
+    token: String,
+    id: i64,
+) -> Result<Vec<RecoveryCode>, String> {
+    with_vault(&state, |vault| vault.recovery_codes(&token, id))
+}
+
+#[tauri::command]
+pub fn get_recovery_code_status(
+    state: State<AppState>,
+    token: String,
+    id: i64,
+) -> Result<RecoveryCodeStatus, String> {
+    with_vault(&state, |vault| vault.recovery_code_status(&token, id))
+}
+
+/// Add the codes a site handed out; ones the entry already has are skipped
+#[tauri::command]
+pub fn add_recovery_codes(
+    state: State<AppState>,
+    token: String,
+    id: i64,
+    codes: Vec<String>,
+) -> Result<RecoveryCodeStatus, String> {
+    with_vault(&state, |vault| vault.add_recovery_codes(&token, id, &codes))
+}
+
+#[tauri::command]
+pub fn mark_recovery_code_used(
+    state: State<AppState>,
+    token: String,
+    id: i64,
+    code: String,
+) -> Result<RecoveryCodeStatus, String> {
+    with_vault(&state, |vault| vault.mark_recovery_code_used(&token, id, &code))
+}
+
+#[tauri::command]
+pub fn get_entry_revisions(
+    state: State<AppState>,
+    token: String,
+    id: i64,
+) -> Result<Vec<EntryRevision>, String> {
+    with_vault(&state, |vault| vault.entry_revisions(&token, id))
+}
+
+#[tauri::command]
+pub fn diff_entry_revisions(
+    state: State<AppState>,
+    token: String,
+    id: i64,
+    from_revision: i64,
+    to_revision: i64,
+) -> Result<Vec<FieldChange>, String> {
+    with_vault(&state, |vault| {
+        vault.diff_revisions(&token, id, from_revision, to_revision)
+    })
+}
+
+#[tauri::command]
+pub fn rollback_entry(
+    state: State<AppState>,
+    token: String,
+    id: i64,
+    revision_id: i64,
 ) -> Result<String, String> {
-    validate_session(&state, &token)?;
-    let clean_secret = secret.replace(" ", "").replace("=", "").to_uppercase();
-    let secret_bytes =
-        base32::decode(base32::Alphabet::RFC4648 { padding: false }, &clean_secret)
-            .ok_or("Invalid Base32 Secret")?;
-
-    let totp =
-        totp_rs::TOTP::new_unchecked(totp_rs::Algorithm::SHA1, 6, 1, 30, secret_bytes);
-    Ok(totp.generate_current().map_err(|e| e.to_string())?)
+    with_vault(&state, |vault| vault.rollback_entry(&token, id, revision_id))?;
+    Ok("Rolled back".to_string())
+}
+
+#[tauri::command]
+pub fn compact_revisions(state: State<AppState>, token: String) -> Result<usize, String> {
+    with_vault(&state, |vault| vault.compact_revisions(&token))
+}
+
+#[tauri::command]
+pub fn get_revision_retention(state: State<AppState>) -> Result<RevisionRetention, String> {
+    with_vault(&state, |vault| Ok(vault.revision_retention()))
+}
+
+#[tauri::command]
+pub fn set_revision_retention(
+    state: State<AppState>,
+    token: String,
+    retention: RevisionRetention,
+) -> Result<String, String> {
+    with_vault(&state, |vault| vault.set_revision_retention(&token, retention))?;
+    Ok("Revision retention updated".to_string())
+}
+
+#[tauri::command]
+pub fn list_deleted_entries(
+    state: State<AppState>,
+    token: String,
+) -> Result<Vec<DeletedItem>, String> {
+    with_vault(&state, |vault| vault.list_deleted_entries(&token))
+}
+
+#[tauri::command]
+pub fn restore_entry(state: State<AppState>, token: String, id: i64) -> Result<String, String> {
+    with_vault(&state, |vault| vault.restore_entry(&token, id))?;
+    Ok("Restored".to_string())
+}
+
+#[tauri::command]
+pub fn purge_entry(state: State<AppState>, token: String, id: i64) -> Result<String, String> {
+    with_vault(&state, |vault| vault.purge_entry(&token, id))?;
+    Ok("Purged".to_string())
+}
+
+/// Health report of the active profile (or all profiles); computed in
+/// memory on every call
+#[tauri::command]
+pub fn get_health_report(
+    state: State<AppState>,
+    token: String,
+    options: Option<HealthOptions>,
+) -> Result<HealthReport, String> {
+    with_vault(&state, |vault| {
+        vault.health_report(&token, &options.unwrap_or_default())
+    })
 }
@@ -1,6 +1,6 @@
 import { useState, useEffect, useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import type { VaultEntry, Profile, DashboardProps, RawVaultEntry, PairedDevice, SyncHistoryEntry, SyncProgress } from "../types";
+import type { VaultEntry, Profile, DashboardProps, RawVaultEntry, PairedDevice, SyncHistoryEntry, SyncProgress, TotpCode } from "../types";
 
 export default function Dashboard({ onLogout, sessionToken }: DashboardProps) {
     const [view, setView] = useState<"home" | "add" | "detail" | "sync" | "profiles">("home");
@@ -76,24 +76,43 @@ export default function Dashboard({ onLogout, sessionToken }: DashboardProps) {
         }
     }, [highlightedUuid]);
 
+    // HOTP codes use up a counter each time, so they are only generated on request
+    const isHotp = /^otpauth:\/\/hotp\//i.test(currentEntry.totpSecret?.trim() || "");
+
+    function generateHotpCode() {
+        if (!currentEntry.id) return;
+        invoke<TotpCode>("get_entry_totp", { entryId: currentEntry.id, token: sessionToken })
+            .then((totp) => setTotpCode(totp.code))
+            .catch(() => setTotpCode("Error"));
+    }
+
     // TOTP Timer Logic
     useEffect(() => {
         let interval: ReturnType<typeof setInterval> | undefined;
-        if (view === "detail" && currentEntry.totpSecret) {
+        setTotpCode("------");
+        if (view === "detail" && currentEntry.totpSecret && !isHotp) {
+            let period = 30;
             const fetchCode = () => {
-                invoke<string>("get_totp_token", { secret: currentEntry.totpSecret, token: sessionToken })
-                    .then((code) => setTotpCode(code))
+                const request = currentEntry.id
+                    ? invoke<TotpCode>("get_entry_totp", { entryId: currentEntry.id, token: sessionToken })
+                    : invoke<TotpCode>("get_totp_token", { secret: currentEntry.totpSecret, token: sessionToken });
+                request
+                    .then((totp) => {
+                        period = totp.period;
+                        setTotpCode(totp.code);
+                        setTimeLeft(totp.remainingSeconds);
+                    })
                     .catch(() => setTotpCode("Error"));
             };
             fetchCode();
             interval = setInterval(() => {
-                const sec = 30 - (Math.floor(Date.now() / 1000) % 30);
+                const sec = period - (Math.floor(Date.now() / 1000) % period);
                 setTimeLeft(sec);
-                if (sec === 30) fetchCode();
+                if (sec === period) fetchCode();
             }, 1000);
         }
         return () => clearInterval(interval);
-    }, [view, currentEntry.totpSecret, sessionToken]);
+    }, [view, currentEntry.id, currentEntry.totpSecret, isHotp, sessionToken]);
 
     async function handleProfileSwitch(profile: Profile) {
         try {
@@ -169,19 +188,21 @@ export default function Dashboard({ onLogout, sessionToken }: DashboardProps) {
                 return;
             }
 
-            // Validate TOTP secret format if provided (Base32: A-Z, 2-7 only)
+            // Validate TOTP secret format if provided (Base32: A-Z, 2-7 only,
+            // or an otpauth:// or steam:// URI which only the backend can check)
             if (currentEntry.totpSecret) {
+                const isUri = /^(otpauth|steam):\/\//i.test(currentEntry.totpSecret.trim());
                 const cleanSecret = currentEntry.totpSecret.replace(/[\s=]/g, "").toUpperCase();
                 const base32Regex = /^[A-Z2-7]+$/;
-                if (!base32Regex.test(cleanSecret)) {
+                if (!isUri && !base32Regex.test(cleanSecret)) {
                     alert("Invalid TOTP secret key. Only Base32 characters are allowed (A-Z and 2-7).");
                     return;
                 }
                 // Also validate with backend
                 try {
-                    await invoke<string>("get_totp_token", { secret: currentEntry.totpSecret, token: sessionToken });
+                    await invoke<TotpCode>("get_totp_token", { secret: currentEntry.totpSecret, token: sessionToken });
                 } catch {
-                    alert("Invalid TOTP secret key. Please enter a valid Base32 encoded secret.");
+                    alert("Invalid TOTP secret key. Please enter a valid Base32 secret or otpauth:// URI.");
                     return;
                 }
             }
@@ -499,7 +520,14 @@ export default function Dashboard({ onLogout, sessionToken }: DashboardProps) {
                             ) : currentEntry.totpSecret ? (
                                 <>
                                     <div style={{ fontSize: "32px", color: "#8A2BE2", fontFamily: "monospace", marginBottom: "8px" }}>
-                                        {totpCode} <span style={{ fontSize: "14px", color: "#555" }}>({timeLeft}s)</span>
+                                        {totpCode}{" "}
+                                        {isHotp ? (
+                                            <button type="button" onClick={generateHotpCode} style={{ fontSize: "14px", cursor: "pointer" }}>
+                                                Generate code
+                                            </button>
+                                        ) : (
+                                            <span style={{ fontSize: "14px", color: "#555" }}>({timeLeft}s)</span>
+                                        )}
                                     </div>
                                     <button
                                         type="button"
@@ -8,6 +8,200 @@ export interface VaultEntry {
     notes?: string;
 }
 
+export interface CustomField {
+    name: string;
+    value: string;
+    hidden?: boolean;
+}
+
+/** A previous password of an item, newest first */
+export interface PasswordHistoryEntry {
+    password: string;
+    replacedAt: string;
+}
+
+/** A single-use two-factor backup code */
+export interface RecoveryCode {
+    code: string;
+    /** Absent while the code is unused */
+    usedAt?: string;
+}
+
+export interface RecoveryCodeStatus {
+    total: number;
+    remaining: number;
+    runningLow: boolean;
+}
+
+export type ItemType =
+    | 'login'
+    | 'card'
+    | 'identity'
+    | 'secureNote'
+    | 'sshKey'
+    | 'apiCredential'
+    | 'wifi';
+
+export interface CardDetails {
+    cardholderName?: string;
+    number: string;
+    expiryMonth: number;
+    expiryYear: number;
+    securityCode?: string;
+    brand?: string;
+}
+
+export interface IdentityDetails {
+    firstName?: string;
+    middleName?: string;
+    lastName?: string;
+    email?: string;
+    phone?: string;
+    company?: string;
+    addressLine1?: string;
+    addressLine2?: string;
+    city?: string;
+    region?: string;
+    postalCode?: string;
+    country?: string;
+}
+
+export interface SshKeyDetails {
+    privateKey?: string;
+    publicKey?: string;
+    passphrase?: string;
+}
+
+export interface ApiCredentialDetails {
+    key: string;
+    secret?: string;
+    endpoint?: string;
+    expiresAt?: string;
+}
+
+export type WifiSecurity = 'none' | 'wep' | 'wpaPersonal' | 'wpaEnterprise';
+
+export interface WifiDetails {
+    ssid: string;
+    security: WifiSecurity;
+    password?: string;
+    hidden?: boolean;
+}
+
+/** Typed entry content, validated by the Rust backend */
+export interface VaultItem {
+    version: number;
+    /** Defaults to 'login' when absent */
+    type?: ItemType;
+    title: string;
+    username?: string;
+    password?: string;
+    passwordHistory?: PasswordHistoryEntry[];
+    urls: string[];
+    totpSecret?: string;
+    recoveryCodes?: RecoveryCode[];
+    notes?: string;
+    customFields: CustomField[];
+    tags?: string[];
+    /** Folder ID; absent = profile root */
+    folderId?: string;
+    card?: CardDetails;
+    identity?: IdentityDetails;
+    sshKey?: SshKeyDetails;
+    apiCredential?: ApiCredentialDetails;
+    wifi?: WifiDetails;
+}
+
+/** A VaultItem together with its storage metadata */
+export interface StoredItem {
+    id: number;
+    entryUuid?: string;
+    profileId: number;
+    createdAt: string;
+    updatedAt: string;
+    item: VaultItem;
+}
+
+/** A folder of the active profile; parentId absent = top level */
+export interface Folder {
+    id: string;
+    name: string;
+    parentId?: string;
+    createdAt: string;
+    updatedAt: string;
+}
+
+/** A search result from `search_entries`, best match first */
+export interface SearchHit {
+    score: number;
+    entry: StoredItem;
+}
+
+/** Order of the paginated entry list */
+export type EntrySort = 'name' | 'updated' | 'lastUsed' | 'mostUsed';
+
+/** Display metadata of one entry; secrets are fetched with get_vault_item */
+export interface EntrySummary {
+    id: number;
+    entryUuid?: string;
+    type: ItemType;
+    title: string;
+    username?: string;
+    faviconDomain?: string;
+    createdAt: string;
+    updatedAt: string;
+    lastUsedAt?: string;
+    /** Uses summed over all synced devices */
+    useCount: number;
+    favorite: boolean;
+}
+
+/** One page of list_entry_summaries; pass nextCursor back for the next page */
+export interface EntryPage {
+    entries: EntrySummary[];
+    nextCursor?: string;
+    total: number;
+}
+
+/** A soft-deleted item in the trash view */
+export interface DeletedItem {
+    id: number;
+    entryUuid?: string;
+    deletedAt: string;
+    item: VaultItem;
+}
+
+/** Metadata of one stored revision of an entry, newest first */
+export interface EntryRevision {
+    id: number;
+    syncVersion: number;
+    title: string;
+    deviceId: string;
+    createdAt: string;
+    deleted: boolean;
+}
+
+export interface FieldChange {
+    field: string;
+    before?: string;
+    after?: string;
+}
+
+export interface RevisionRetention {
+    keepLatest: number;
+    maxAgeDays: number;
+}
+
+/** A file attached to an entry; download with readAttachmentChunk(0..chunkCount) */
+export interface AttachmentInfo {
+    id: number;
+    fileName: string;
+    mimeType?: string;
+    size: number;
+    chunkCount: number;
+    createdAt: string;
+}
+
 export interface Profile {
     id: number;
     name: string;
@@ -54,3 +248,244 @@ export interface SyncProgress {
     total_chunks: number;
     message: string;
 }
+
+/** Options of generate_password; omitted fields use the backend defaults */
+export interface PasswordOptions {
+    length?: number;
+    lowercase?: boolean;
+    uppercase?: boolean;
+    digits?: boolean;
+    symbols?: boolean;
+    minLowercase?: number;
+    minUppercase?: number;
+    minDigits?: number;
+    minSymbols?: number;
+    excludeAmbiguous?: boolean;
+    /** `a` lower, `A` upper, `9` digit, `#` symbol, `*` any, `\` escapes */
+    pattern?: string;
+    /** Symbols to draw from instead of the default set */
+    symbolSet?: string;
+    /** Longest run of one repeated character */
+    maxConsecutive?: number;
+}
+
+export type Capitalization = 'lowercase' | 'title' | 'uppercase' | 'random';
+
+/** Options of generate_passphrase; omitted fields use the backend defaults */
+export interface PassphraseOptions {
+    words?: number;
+    separator?: string;
+    capitalization?: Capitalization;
+    includeNumber?: boolean;
+}
+
+export interface GeneratedSecret {
+    value: string;
+    entropyBits: number;
+    /** Domain whose password rules were followed */
+    rulesDomain?: string;
+}
+
+/** A value from the generator history, newest first */
+export interface GeneratedValue {
+    id: number;
+    kind: 'password' | 'passphrase';
+    value: string;
+    createdAt: string;
+}
+
+export type PatternKind =
+    | 'password' | 'word' | 'userInput' | 'keyboard' | 'sequence'
+    | 'repeat' | 'year' | 'date' | 'bruteforce';
+
+export interface StrengthMatch {
+    pattern: PatternKind;
+    token: string;
+    guessesLog10: number;
+}
+
+/** Result of estimate_password_strength */
+export interface StrengthEstimate {
+    /** 0 (too guessable) to 4 (very unguessable) */
+    score: number;
+    guessesLog10: number;
+    crackTimeDisplay: string;
+    warning: string | null;
+    suggestions: string[];
+    sequence: StrengthMatch[];
+}
+
+export interface MasterPasswordPolicy {
+    minLength: number;
+    minScore: number;
+}
+
+/** A row that did not decrypt during a master password change */
+export type UnreadableRow =
+    | { kind: 'entry'; id: number }
+    | { kind: 'folder'; id: string }
+    | { kind: 'revision'; id: number }
+    | { kind: 'generated'; id: number }
+    | { kind: 'attachment'; id: number };
+
+export interface PasswordChangeReport {
+    skipped: UnreadableRow[];
+    /** Names of the paired devices that were forgotten */
+    forgottenDevices: string[];
+}
+
+export type HealthScope = 'activeProfile' | 'allProfiles';
+
+/** Options of get_health_report; omitted fields use the backend defaults */
+export interface HealthOptions {
+    scope?: HealthScope;
+    staleAfterMonths?: number;
+}
+
+export type Severity = 'critical' | 'high' | 'medium' | 'low';
+
+export type HealthIssueKind =
+    | 'reused'
+    | 'nearDuplicate'
+    | 'weak'
+    | 'stale'
+    | 'missingTotp'
+    | 'lowRecoveryCodes';
+
+export interface HealthEntryRef {
+    id: number;
+    entryUuid: string | null;
+    profileId: number;
+    title: string;
+    username: string | null;
+}
+
+export interface HealthIssue {
+    kind: HealthIssueKind;
+    severity: Severity;
+    entries: HealthEntryRef[];
+    detail: string;
+}
+
+export interface HealthReport {
+    generatedAt: string;
+    scope: HealthScope;
+    entriesChecked: number;
+    healthyEntries: number;
+    /** Non-empty groups, most severe first */
+    groups: { severity: Severity; issues: HealthIssue[] }[];
+}
+
+export interface BreachIndexInfo {
+    hashes: number;
+    indexSize: number;
+}
+
+export interface BreachedEntry {
+    entry: HealthEntryRef;
+    /** How often the password appears in the breach data */
+    count: number;
+}
+
+export interface BreachReport {
+    generatedAt: string;
+    scope: HealthScope;
+    passwordsChecked: number;
+    /** Most often seen first */
+    breached: BreachedEntry[];
+}
+
+export type RuleSource = 'bundled' | 'imported' | 'user';
+
+/** Password rules for a URL, in the password-rules.json syntax */
+export interface SiteRules {
+    domain: string;
+    rules: string;
+    source: RuleSource;
+}
+
+export type OtpAlgorithm = 'SHA1' | 'SHA256' | 'SHA512';
+
+export type OtpKind = 'totp' | 'hotp' | 'steam';
+
+/** Current TOTP code with what the frontend needs to count down */
+export interface TotpCode {
+    kind: OtpKind;
+    code: string;
+    /** Seconds until `code` expires; 0 for HOTP codes, which do not expire */
+    remainingSeconds: number;
+    nextCode: string;
+    /** 0 for HOTP */
+    period: number;
+    /** Counter `code` was generated with (HOTP) */
+    counter?: number | null;
+    digits: number;
+    algorithm: OtpAlgorithm;
+    issuer?: string | null;
+    label?: string | null;
+}
+
+/** TOTP codes of one entry, as returned by the batch command */
+export interface EntryTotp {
+    id: number;
+    entryUuid?: string | null;
+    title: string;
+    totp: TotpCode;
+}
+
+/** An account stored from a scanned QR code or authenticator backup */
+export interface ImportedOtp {
+    id: number;
+    entryUuid?: string | null;
+    title: string;
+    /** Whether a new entry was created rather than an existing one updated */
+    created: boolean;
+}
+
+export type AuthenticatorApp = 'aegis' | '2fas' | 'andotp';
+
+export interface SkippedOtp {
+    name: string;
+    reason: string;
+}
+
+export interface OtpImportReport {
+    app: AuthenticatorApp;
+    imported: ImportedOtp[];
+    /** Accounts matching an existing entry by issuer and account name */
+    duplicates: string[];
+    skipped: SkippedOtp[];
+}
+
+/** Where the folders of a Bitwarden export end up */
+export type BitwardenFolderMapping = 'folders' | 'profiles';
+
+export interface ImportedEntry {
+    id: number;
+    entryUuid: string;
+    profileId: number;
+    title: string;
+    itemType: ItemType;
+}
+
+export interface SkippedItem {
+    name: string;
+    reason: string;
+}
+
+/** Something of an imported item that could not be carried over as is */
+export interface ImportWarning {
+    title: string;
+    message: string;
+}
+
+export interface BitwardenImportReport {
+    /** Whether the export was password-protected */
+    encrypted: boolean;
+    imported: ImportedEntry[];
+    skipped: SkippedItem[];
+    warnings: ImportWarning[];
+    /** Paths of the folders created, e.g. "Work/Servers" */
+    foldersCreated: string[];
+    profilesCreated: string[];
+}
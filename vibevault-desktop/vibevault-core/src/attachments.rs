@@ -0,0 +1,419 @@
+// Encrypted file attachments.
+//
+// Files are uploaded and downloaded in chunks so the frontend never has to
+// hold a whole file in one IPC message. Every chunk is encrypted under the
+// vault key with its blob ID and index as associated data, so chunks cannot
+// be reordered or moved between files. Identical files share one blob,
+// matched by a keyed MAC of their content.
+
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::crypto::{
+    decrypt_aes256_gcm, decrypt_aes256_gcm_aad, encrypt_aes256_gcm, encrypt_aes256_gcm_aad,
+};
+use crate::store::{AttachmentBlobRecord, AttachmentRecord, RekeyBatch, VaultStore};
+use crate::vault::now_iso;
+use crate::Vault;
+
+/// Largest file that can be attached (25 MiB)
+pub const MAX_ATTACHMENT_SIZE: u64 = 25 * 1024 * 1024;
+/// Largest chunk accepted by `upload_attachment_chunk` (1 MiB)
+pub const MAX_CHUNK_SIZE: usize = 1024 * 1024;
+/// Most attachments a single entry can have
+pub const MAX_ATTACHMENTS_PER_ENTRY: usize = 32;
+
+const MAX_FILE_NAME_LEN: usize = 255;
+const DEDUP_KEY_INFO: &[u8] = b"vibevault-attachment-dedup";
+
+/// Attachment metadata as listed to the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentInfo {
+    pub id: i64,
+    pub file_name: String,
+    pub mime_type: Option<String>,
+    pub size: u64,
+    pub chunk_count: u32,
+    pub created_at: String,
+}
+
+/// Encrypted together in `AttachmentRecord::meta_blob`
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AttachmentMeta {
+    file_name: String,
+    mime_type: Option<String>,
+}
+
+/// An upload in progress; lives only as long as the session
+pub(crate) struct AttachmentUpload {
+    entry_uuid: String,
+    meta: AttachmentMeta,
+    blob_id: String,
+    declared_size: u64,
+    written: u64,
+    chunk_count: i64,
+    mac: Hmac<Sha256>,
+}
+
+fn chunk_aad(blob_id: &str, index: i64) -> Vec<u8> {
+    format!("{}:{}", blob_id, index).into_bytes()
+}
+
+/// MAC keyed by a subkey of the vault key, used to find duplicate content
+fn content_mac(key: &[u8; 32]) -> Result<Hmac<Sha256>, String> {
+    let mut dedup_key = [0u8; 32];
+    Hkdf::<Sha256>::new(None, key)
+        .expand(DEDUP_KEY_INFO, &mut dedup_key)
+        .map_err(|_| "Key derivation failed")?;
+    Hmac::<Sha256>::new_from_slice(&dedup_key).map_err(|_| "Key derivation failed".to_string())
+}
+
+fn to_info(key: &[u8; 32], record: &AttachmentRecord) -> Result<AttachmentInfo, String> {
+    let meta: AttachmentMeta = serde_json::from_slice(&decrypt_aes256_gcm(
+        key,
+        &record.meta_blob,
+        &record.meta_nonce,
+    )?)
+    .map_err(|e| e.to_string())?;
+    Ok(AttachmentInfo {
+        id: record.id,
+        file_name: meta.file_name,
+        mime_type: meta.mime_type,
+        size: record.size as u64,
+        chunk_count: record.chunk_count as u32,
+        created_at: record.created_at.clone(),
+    })
+}
+
+/// Delete the given blobs unless an attachment still references them
+fn delete_unreferenced_blobs(db: &mut dyn VaultStore, blob_ids: &[String]) -> Result<(), String> {
+    let attachments = db.list_all_attachments()?;
+    for blob_id in blob_ids {
+        if !attachments.iter().any(|a| &a.blob_id == blob_id) {
+            db.delete_attachment_blob(blob_id)?;
+        }
+    }
+    Ok(())
+}
+
+/// Remove attachments whose entry no longer exists, then every blob nothing
+/// references (including chunks of uploads that never finished). Only safe
+/// while no upload is in progress, i.e. at unlock.
+pub(crate) fn cleanup_attachments(db: &mut dyn VaultStore) -> Result<(), String> {
+    for attachment in db.list_all_attachments()? {
+        if db.find_entry_by_uuid(&attachment.entry_uuid)?.is_none() {
+            db.delete_attachment(attachment.id)?;
+        }
+    }
+    let blob_ids = db.attachment_blob_ids()?;
+    delete_unreferenced_blobs(db, &blob_ids)
+}
+
+/// Add every attachment, re-encrypted under `new_key`, to `batch`: names
+/// and types, chunks and the dedup hashes. Run `cleanup_attachments` first
+/// so that only finished, referenced blobs are left. Returns the IDs of
+/// attachments whose name or content does not decrypt with `old_key`;
+/// those rows are left as they are.
+pub(crate) fn rekey_attachments(
+    db: &dyn VaultStore,
+    old_key: &[u8; 32],
+    new_key: &[u8; 32],
+    batch: &mut RekeyBatch,
+) -> Result<Vec<i64>, String> {
+    let mut unreadable_blobs: Vec<String> = Vec::new();
+    let mut skipped = Vec::new();
+    for mut attachment in db.list_all_attachments()? {
+        // Identical files share a blob; re-encrypt its chunks only once
+        let blob_id = attachment.blob_id.clone();
+        if !unreadable_blobs.contains(&blob_id)
+            && !batch.blob_hashes.iter().any(|(id, _)| id == &blob_id)
+        {
+            match rekey_blob(db, old_key, new_key, &blob_id, attachment.chunk_count) {
+                Ok((content_hash, chunks)) => {
+                    batch.blob_hashes.push((blob_id.clone(), content_hash));
+                    batch.chunks.extend(chunks);
+                }
+                Err(_) => unreadable_blobs.push(blob_id.clone()),
+            }
+        }
+        if unreadable_blobs.contains(&blob_id) {
+            skipped.push(attachment.id);
+            continue;
+        }
+
+        match decrypt_aes256_gcm(old_key, &attachment.meta_blob, &attachment.meta_nonce) {
+            Ok(meta) => {
+                (attachment.meta_blob, attachment.meta_nonce) = encrypt_aes256_gcm(new_key, &meta)?;
+                batch.attachments.push(attachment);
+            }
+            Err(_) => skipped.push(attachment.id),
+        }
+    }
+    Ok(skipped)
+}
+
+/// The chunks of one blob under `new_key` and its new dedup hash
+type RekeyedBlob = (String, Vec<(String, i64, Vec<u8>, Vec<u8>)>);
+
+fn rekey_blob(
+    db: &dyn VaultStore,
+    old_key: &[u8; 32],
+    new_key: &[u8; 32],
+    blob_id: &str,
+    chunk_count: i64,
+) -> Result<RekeyedBlob, String> {
+    let mut mac = content_mac(new_key)?;
+    let mut chunks = Vec::new();
+    for index in 0..chunk_count {
+        let aad = chunk_aad(blob_id, index);
+        let (ciphertext, nonce) = db
+            .get_attachment_chunk(blob_id, index)?
+            .ok_or("Attachment data is missing")?;
+        let data = decrypt_aes256_gcm_aad(old_key, &ciphertext, &nonce, &aad)?;
+        mac.update(&data);
+        let (ciphertext, nonce) = encrypt_aes256_gcm_aad(new_key, &data, &aad)?;
+        chunks.push((blob_id.to_string(), index, ciphertext, nonce));
+    }
+    Ok((hex::encode(mac.finalize().into_bytes()), chunks))
+}
+
+/// Drop every attachment of a permanently deleted entry
+pub(crate) fn delete_entry_attachments(
+    db: &mut dyn VaultStore,
+    entry_uuid: &str,
+) -> Result<(), String> {
+    let mut blob_ids = Vec::new();
+    for attachment in db.list_attachments(entry_uuid)? {
+        db.delete_attachment(attachment.id)?;
+        blob_ids.push(attachment.blob_id);
+    }
+    delete_unreferenced_blobs(db, &blob_ids)
+}
+
+impl Vault {
+    /// Sync UUID of active entry `id` in the active profile
+    fn attachment_entry_uuid(
+        &mut self,
+        token: &str,
+        id: i64,
+    ) -> Result<([u8; 32], String), String> {
+        let (key, active_profile) = self.session_context(token)?;
+        let entry = self
+            .store
+            .list_entries(active_profile)?
+            .into_iter()
+            .find(|e| e.id == id)
+            .ok_or("Entry not found or belongs to different profile")?;
+        let entry_uuid = entry.entry_uuid.ok_or("Entry has no sync UUID")?;
+        Ok((key, entry_uuid))
+    }
+
+    /// Attachment `attachment_id`, provided its entry is active in the active profile
+    fn owned_attachment(
+        &mut self,
+        token: &str,
+        attachment_id: i64,
+    ) -> Result<([u8; 32], AttachmentRecord), String> {
+        let (key, active_profile) = self.session_context(token)?;
+        let record = self
+            .store
+            .find_attachment(attachment_id)?
+            .ok_or("Attachment not found")?;
+        let owned = self
+            .store
+            .find_entry_by_uuid(&record.entry_uuid)?
+            .is_some_and(|e| e.profile_id == active_profile && e.deleted_at.is_none());
+        if !owned {
+            return Err("Attachment not found".to_string());
+        }
+        Ok((key, record))
+    }
+
+    /// Attachments of entry `id`, oldest first
+    pub fn list_attachments(
+        &mut self,
+        token: &str,
+        id: i64,
+    ) -> Result<Vec<AttachmentInfo>, String> {
+        let (key, entry_uuid) = self.attachment_entry_uuid(token, id)?;
+        self.store
+            .list_attachments(&entry_uuid)?
+            .iter()
+            .map(|record| to_info(&key, record))
+            .collect()
+    }
+
+    /// Start uploading a file of `size` bytes to entry `id`. Returns an
+    /// upload ID for `upload_attachment_chunk` / `finish_attachment_upload`.
+    pub fn begin_attachment_upload(
+        &mut self,
+        token: &str,
+        id: i64,
+        file_name: &str,
+        mime_type: Option<&str>,
+        size: u64,
+    ) -> Result<String, String> {
+        let (key, entry_uuid) = self.attachment_entry_uuid(token, id)?;
+
+        let file_name = file_name.trim();
+        if file_name.is_empty() || file_name.chars().count() > MAX_FILE_NAME_LEN {
+            return Err(format!(
+                "File name must be 1 to {} characters",
+                MAX_FILE_NAME_LEN
+            ));
+        }
+        if size > MAX_ATTACHMENT_SIZE {
+            return Err(format!(
+                "Attachments must be at most {} MiB",
+                MAX_ATTACHMENT_SIZE / (1024 * 1024)
+            ));
+        }
+        if self.store.list_attachments(&entry_uuid)?.len() >= MAX_ATTACHMENTS_PER_ENTRY {
+            return Err(format!(
+                "An entry can have at most {} attachments",
+                MAX_ATTACHMENTS_PER_ENTRY
+            ));
+        }
+
+        let upload_id = Uuid::new_v4().to_string();
+        self.uploads.insert(
+            upload_id.clone(),
+            AttachmentUpload {
+                entry_uuid,
+                meta: AttachmentMeta {
+                    file_name: file_name.to_string(),
+                    mime_type: mime_type.map(str::to_string),
+                },
+                blob_id: Uuid::new_v4().to_string(),
+                declared_size: size,
+                written: 0,
+                chunk_count: 0,
+                mac: content_mac(&key)?,
+            },
+        );
+        Ok(upload_id)
+    }
+
+    /// Encrypt and store the next chunk of an upload
+    pub fn upload_attachment_chunk(
+        &mut self,
+        token: &str,
+        upload_id: &str,
+        data: &[u8],
+    ) -> Result<(), String> {
+        let key = self.validate_session(token)?;
+        if data.is_empty() || data.len() > MAX_CHUNK_SIZE {
+            return Err(format!("Chunks must be 1 to {} bytes", MAX_CHUNK_SIZE));
+        }
+        let upload = self.uploads.get_mut(upload_id).ok_or("Upload not found")?;
+        if upload.written + data.len() as u64 > upload.declared_size {
+            return Err("Upload is larger than its declared size".to_string());
+        }
+
+        let index = upload.chunk_count;
+        let (ciphertext, nonce) =
+            encrypt_aes256_gcm_aad(&key, data, &chunk_aad(&upload.blob_id, index))?;
+        self.store
+            .put_attachment_chunk(&upload.blob_id, index, &ciphertext, &nonce)?;
+
+        upload.mac.update(data);
+        upload.written += data.len() as u64;
+        upload.chunk_count += 1;
+        Ok(())
+    }
+
+    /// Complete an upload and attach the file. If identical content is
+    /// already stored, the new chunks are dropped and the existing blob reused.
+    pub fn finish_attachment_upload(
+        &mut self,
+        token: &str,
+        upload_id: &str,
+    ) -> Result<AttachmentInfo, String> {
+        let key = self.validate_session(token)?;
+        let upload = self.uploads.remove(upload_id).ok_or("Upload not found")?;
+        if upload.written != upload.declared_size {
+            self.store.delete_attachment_blob(&upload.blob_id)?;
+            return Err(format!(
+                "Upload incomplete: received {} of {} bytes",
+                upload.written, upload.declared_size
+            ));
+        }
+
+        let content_hash = hex::encode(upload.mac.finalize().into_bytes());
+        let now = now_iso();
+        let (blob_id, chunk_count) = match self.store.find_attachment_blob_by_hash(&content_hash)? {
+            Some(existing) => {
+                self.store.delete_attachment_blob(&upload.blob_id)?;
+                (existing.blob_id, existing.chunk_count)
+            }
+            None => {
+                self.store.insert_attachment_blob(&AttachmentBlobRecord {
+                    blob_id: upload.blob_id.clone(),
+                    content_hash,
+                    size: upload.written as i64,
+                    chunk_count: upload.chunk_count,
+                    created_at: now.clone(),
+                })?;
+                (upload.blob_id, upload.chunk_count)
+            }
+        };
+
+        let meta = serde_json::to_vec(&upload.meta).map_err(|e| e.to_string())?;
+        let (meta_blob, meta_nonce) = encrypt_aes256_gcm(&key, &meta)?;
+        let mut record = AttachmentRecord {
+            id: 0,
+            entry_uuid: upload.entry_uuid,
+            blob_id,
+            meta_blob,
+            meta_nonce,
+            size: upload.written as i64,
+            chunk_count,
+            created_at: now,
+        };
+        record.id = self.store.insert_attachment(&record)?;
+        to_info(&key, &record)
+    }
+
+    /// Abandon an upload and discard the chunks written so far
+    pub fn cancel_attachment_upload(&mut self, token: &str, upload_id: &str) -> Result<(), String> {
+        self.validate_session(token)?;
+        let upload = self.uploads.remove(upload_id).ok_or("Upload not found")?;
+        self.store.delete_attachment_blob(&upload.blob_id)
+    }
+
+    /// Decrypt chunk `index` (0-based, below `chunk_count`) of an attachment
+    pub fn read_attachment_chunk(
+        &mut self,
+        token: &str,
+        attachment_id: i64,
+        index: u32,
+    ) -> Result<Vec<u8>, String> {
+        let (key, record) = self.owned_attachment(token, attachment_id)?;
+        let index = index as i64;
+        if index >= record.chunk_count {
+            return Err("Chunk index out of range".to_string());
+        }
+        let (ciphertext, nonce) = self
+            .store
+            .get_attachment_chunk(&record.blob_id, index)?
+            .ok_or("Attachment data is missing")?;
+        decrypt_aes256_gcm_aad(
+            &key,
+            &ciphertext,
+            &nonce,
+            &chunk_aad(&record.blob_id, index),
+        )
+    }
+
+    /// Remove an attachment; its content goes too unless another attachment shares it
+    pub fn delete_attachment(&mut self, token: &str, attachment_id: i64) -> Result<(), String> {
+        let (_key, record) = self.owned_attachment(token, attachment_id)?;
+        self.store.delete_attachment(record.id)?;
+        delete_unreferenced_blobs(self.store.as_mut(), &[record.blob_id])
+    }
+}
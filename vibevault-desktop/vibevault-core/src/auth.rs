@@ -0,0 +1,357 @@
+use argon2::{
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use rand::RngCore;
+use base64::{Engine as _, engine::general_purpose};
+use zeroize::Zeroize;
+use std::time::{Duration, Instant};
+
+use crate::{SessionState, Vault};
+use crate::store::UserRecord;
+use crate::attachments::cleanup_attachments;
+use crate::history::stored_password_history_depth;
+use crate::revisions::{compact_revisions, stored_revision_retention};
+use crate::search::SearchIndex;
+use crate::vault::{migrate_legacy_items, migrate_plaintext_entries};
+
+const SESSION_EXPIRED: &str = "Session expired. Please log in again.";
+
+impl Vault {
+    /// Validate session token and return the encryption key.
+    /// Also enforces auto-lock timeout — if too much time has passed since
+    /// the last activity, the session is cleared and an error is returned.
+    pub fn validate_session(&mut self, token: &str) -> Result<[u8; 32], String> {
+        // Check auto-lock timeout
+        let timeout = self.auto_lock_seconds;
+        if timeout > 0 && self.last_activity.elapsed() > Duration::from_secs(timeout) {
+            // Clear the expired session
+            self.session = None;
+            self.search_index = None;
+            return Err(SESSION_EXPIRED.to_string());
+        }
+
+        let session = self.session.as_ref().ok_or(SESSION_EXPIRED)?;
+
+        // Constant-time comparison to prevent timing attacks
+        if token.len() != session.token.len() {
+            return Err(SESSION_EXPIRED.to_string());
+        }
+        let mut diff = 0u8;
+        for (a, b) in token.bytes().zip(session.token.bytes()) {
+            diff |= a ^ b;
+        }
+        if diff != 0 {
+            return Err(SESSION_EXPIRED.to_string());
+        }
+
+        let key = session.encryption_key;
+
+        // Update last activity timestamp
+        self.last_activity = Instant::now();
+
+        Ok(key)
+    }
+
+    /// Validate session and return (encryption_key, active_profile_id) in one call.
+    pub(crate) fn session_context(&mut self, token: &str) -> Result<([u8; 32], i64), String> {
+        let key = self.validate_session(token)?;
+        Ok((key, self.active_profile_id))
+    }
+
+    /// Whether a master user has been registered
+    pub fn is_registered(&self) -> Result<bool, String> {
+        let count = self.store.user_count().unwrap_or(0);
+        Ok(count > 0)
+    }
+
+    /// Register the master user with a freshly generated encryption salt.
+    /// The master password must meet the master password policy.
+    pub fn register(&mut self, username: &str, pass: &str) -> Result<(), String> {
+        self.check_master_password(username, pass)?;
+
+        // Generate encryption salt (separate from auth salt)
+        let mut enc_salt_bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut enc_salt_bytes);
+        let enc_salt_hex = hex::encode(enc_salt_bytes);
+
+        self.register_with_encryption_salt(username, pass, &enc_salt_hex)
+    }
+
+    /// Register the master user reusing an existing vault's encryption salt,
+    /// as received in the first sync payload, so that both devices derive the
+    /// same vault key from the same master password. The password was
+    /// chosen on the other device, so the local policy is not applied.
+    pub fn register_with_encryption_salt(
+        &mut self,
+        username: &str,
+        pass: &str,
+        encryption_salt: &str,
+    ) -> Result<(), String> {
+        hex::decode(encryption_salt).map_err(|_| "Invalid encryption salt")?;
+
+        let (password_hash, salt_str) = hash_master_password(pass)?;
+
+        // Save User
+        self.store
+            .insert_user(&UserRecord {
+                username: username.to_string(),
+                password_hash,
+                salt: salt_str,
+                encryption_salt: encryption_salt.to_string(),
+            })
+            .map_err(|_| "Registration failed")?;
+
+        Ok(())
+    }
+
+    /// Verify the master password, derive the vault key and open a session.
+    /// Returns the session token that every other call must present.
+    pub fn unlock(&mut self, username: &str, pass: &str) -> Result<String, String> {
+        self.uploads.clear();
+        let db = self.store.as_mut();
+
+        // Brute-force protection: read persisted attempt counter from DB
+        {
+            let (failed_count, last_failed_at) = db.login_attempts().unwrap_or((0, None));
+
+            if failed_count >= 3 {
+                if let Some(ref ts) = last_failed_at {
+                    if let Ok(last) = chrono::NaiveDateTime::parse_from_str(ts, "%Y-%m-%d %H:%M:%S") {
+                        let now = chrono::Utc::now().naive_utc();
+                        let delay_secs = 1i64 << (failed_count - 3).min(4);
+                        let elapsed = (now - last).num_seconds();
+                        if elapsed < delay_secs {
+                            let remaining = delay_secs - elapsed + 1;
+                            return Err(format!(
+                                "Too many failed attempts. Wait {} seconds.",
+                                remaining
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
+        // Authenticate
+        let auth_result: Result<String, String> = (|| {
+            let user = db
+                .find_user(username)
+                .ok()
+                .flatten()
+                .ok_or_else(|| "Invalid username or password".to_string())?;
+
+            let parsed_hash = PasswordHash::new(&user.password_hash)
+                .map_err(|_| "Invalid username or password".to_string())?;
+            Argon2::default()
+                .verify_password(pass.as_bytes(), &parsed_hash)
+                .map_err(|_| "Invalid username or password".to_string())?;
+
+            Ok(user.encryption_salt)
+        })();
+
+        let enc_salt_hex = match auth_result {
+            Ok(salt) => {
+                // Reset counter on success
+                let _ = db.reset_login_attempts();
+                salt
+            }
+            Err(e) => {
+                // Increment persisted counter
+                let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+                let _ = db.record_failed_login(&now);
+                return Err(e);
+            }
+        };
+
+        // Handle existing users who don't have an encryption_salt yet
+        let enc_salt_hex = if enc_salt_hex.is_empty() {
+            let mut enc_salt_bytes = [0u8; 32];
+            rand::thread_rng().fill_bytes(&mut enc_salt_bytes);
+            let new_hex = hex::encode(enc_salt_bytes);
+            db.set_encryption_salt(username, &new_hex)?;
+            new_hex
+        } else {
+            enc_salt_hex
+        };
+
+        let mut encryption_key = derive_vault_key(pass, &enc_salt_hex)?;
+
+        // Generate session token
+        let mut token_bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut token_bytes);
+        let session_token = hex::encode(token_bytes);
+        token_bytes.zeroize();
+
+        // Migrate any plaintext entries before storing session
+        migrate_plaintext_entries(db, &encryption_key)?;
+        migrate_legacy_items(db, &encryption_key)?;
+
+        // Purge tombstoned entries older than 90 days
+        let cutoff = (chrono::Utc::now() - chrono::Duration::days(90)).to_rfc3339();
+        let _ = db.purge_tombstones(&cutoff);
+
+        self.password_history_depth = stored_password_history_depth(db);
+
+        // Compact revision history (also drops revisions of purged entries)
+        let retention = stored_revision_retention(db);
+        self.revision_retention = retention;
+        let cutoff =
+            (chrono::Utc::now() - chrono::Duration::days(retention.max_age_days)).to_rfc3339();
+        let _ = compact_revisions(db, retention, &cutoff);
+
+        // Drop attachments of purged entries and chunks of abandoned uploads
+        let _ = cleanup_attachments(db);
+
+        // Build the in-memory search index from the decrypted entries
+        self.search_index = Some(SearchIndex::build(self.store.as_ref(), &encryption_key)?);
+
+        // Store session and reset activity timer
+        self.session = Some(SessionState {
+            token: session_token.clone(),
+            encryption_key,
+        });
+        self.last_activity = Instant::now();
+
+        // Zeroize local copy
+        encryption_key.zeroize();
+
+        Ok(session_token)
+    }
+
+    /// Drop the session key and the search index and fall back to the
+    /// default profile. Unfinished attachment uploads are abandoned.
+    pub fn lock(&mut self) {
+        self.session = None;
+        self.search_index = None;
+        self.active_profile_id = 1;
+        self.uploads.clear();
+    }
+
+    /// Whether a session is currently open
+    pub fn is_unlocked(&self) -> bool {
+        self.session.is_some()
+    }
+
+    /// Reset the inactivity timer (called on user interaction).
+    pub fn touch_activity(&mut self) {
+        self.last_activity = Instant::now();
+    }
+
+    pub fn auto_lock_seconds(&self) -> u64 {
+        self.auto_lock_seconds
+    }
+
+    /// Change the inactivity timeout; 0 disables auto-lock
+    pub fn set_auto_lock_seconds(&mut self, token: &str, seconds: u64) -> Result<(), String> {
+        self.validate_session(token)?;
+        self.auto_lock_seconds = seconds;
+        Ok(())
+    }
+}
+
+/// Argon2 PHC hash of a master password and the salt it was made with
+pub(crate) fn hash_master_password(pass: &str) -> Result<(String, String), String> {
+    let mut salt_bytes = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut salt_bytes);
+    let salt_str = general_purpose::STANDARD
+        .encode(salt_bytes)
+        .replace("=", "");
+
+    let salt = SaltString::from_b64(&salt_str).map_err(|_| "Salt Error")?;
+    let password_hash = Argon2::default()
+        .hash_password(pass.as_bytes(), &salt)
+        .map_err(|e| e.to_string())?
+        .to_string();
+    Ok((password_hash, salt_str))
+}
+
+/// Derive the vault key from the master password and the hex-encoded
+/// encryption salt using Argon2id
+pub(crate) fn derive_vault_key(pass: &str, encryption_salt: &str) -> Result<[u8; 32], String> {
+    let enc_salt_bytes = hex::decode(encryption_salt).map_err(|_| "Invalid encryption salt")?;
+    let mut encryption_key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(pass.as_bytes(), &enc_salt_bytes, &mut encryption_key)
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    Ok(encryption_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MasterPasswordPolicy, MemoryStore, Vault};
+
+    fn registered_vault() -> Vault {
+        let mut vault = Vault::new(Box::new(MemoryStore::new()));
+        vault.register("alice", "correct horse").unwrap();
+        vault
+    }
+
+    #[test]
+    fn test_unlock_rejects_wrong_password() {
+        let mut vault = registered_vault();
+        assert!(vault.unlock("alice", "wrong").is_err());
+        assert_eq!(vault.store().login_attempts().unwrap().0, 1);
+
+        let token = vault.unlock("alice", "correct horse").unwrap();
+        assert_eq!(vault.store().login_attempts().unwrap().0, 0);
+        assert!(vault.validate_session(&token).is_ok());
+    }
+
+    #[test]
+    fn test_register_enforces_policy() {
+        let mut vault = Vault::new(Box::new(MemoryStore::new()));
+        assert!(vault.register("alice", "").is_err());
+        assert!(vault.register("alice", "password1234").is_err());
+        assert!(vault.register("alice", "qwertyuiop123").is_err());
+        assert!(!vault.is_registered().unwrap());
+
+        // Configurable before registration, never below the floor
+        let lax = MasterPasswordPolicy {
+            min_length: 8,
+            min_score: 0,
+        };
+        assert!(vault
+            .set_master_password_policy(None, MasterPasswordPolicy { min_length: 4, ..lax })
+            .is_err());
+        vault.set_master_password_policy(None, lax).unwrap();
+        vault.register("alice", "password").unwrap();
+
+        // Once registered, changing it needs a session
+        assert!(vault
+            .set_master_password_policy(None, MasterPasswordPolicy::default())
+            .is_err());
+        let token = vault.unlock("alice", "password").unwrap();
+        vault
+            .set_master_password_policy(Some(&token), MasterPasswordPolicy::default())
+            .unwrap();
+        assert!(vault.check_master_password("alice", "password").is_err());
+    }
+
+    #[test]
+    fn test_lock_invalidates_token() {
+        let mut vault = registered_vault();
+        let token = vault.unlock("alice", "correct horse").unwrap();
+        vault.lock();
+        assert!(vault.validate_session(&token).is_err());
+        assert!(!vault.is_unlocked());
+    }
+
+    #[test]
+    fn test_same_salt_derives_same_key() {
+        let mut desktop = registered_vault();
+        let salt = desktop.store().first_encryption_salt().unwrap();
+        let desktop_token = desktop.unlock("alice", "correct horse").unwrap();
+
+        let mut other = Vault::new(Box::new(MemoryStore::new()));
+        other
+            .register_with_encryption_salt("alice", "correct horse", &salt)
+            .unwrap();
+        let other_token = other.unlock("alice", "correct horse").unwrap();
+
+        assert_eq!(
+            desktop.validate_session(&desktop_token).unwrap(),
+            other.validate_session(&other_token).unwrap()
+        );
+    }
+}
@@ -0,0 +1,805 @@
+// Importing a Bitwarden JSON export.
+//
+// Bitwarden exports either plain JSON or, when protected with a password,
+// the same JSON sealed in one "EncString": AES-256-CBC plus an
+// HMAC-SHA256 over IV and ciphertext, under two keys stretched with HKDF
+// from a PBKDF2 or Argon2id key of the password. Every item becomes a
+// `VaultItem` stored through `save_item`, so it is validated and encrypted
+// exactly like an entry created in the app. Whatever does not carry over
+// as is (passkeys, linked fields, an incomplete card) is named in the
+// report instead of being dropped silently.
+
+use std::collections::HashMap;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose, Engine as _};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroize;
+
+use crate::crypto::decrypt_aes256_cbc;
+use crate::folders::Folder;
+use crate::history::PasswordHistoryEntry;
+use crate::item::{validate_url, CustomField, VaultItem};
+use crate::item_types::{CardDetails, IdentityDetails, ItemType, SshKeyDetails};
+use crate::store::FavoriteRecord;
+use crate::totp::parse_totp;
+use crate::vault::now_iso;
+use crate::Vault;
+
+/// Title of an item that has no name in the export
+const UNTITLED_ITEM: &str = "Untitled";
+
+/// KDF work accepted from an export. Bitwarden's own settings stay well
+/// inside these; the caps keep a crafted file from stalling the app.
+const MAX_PBKDF2_ITERATIONS: u32 = 2_000_000;
+const MAX_ARGON2_MEMORY_MIB: u32 = 1024;
+const MAX_ARGON2_ITERATIONS: u32 = 10;
+const MAX_ARGON2_PARALLELISM: u32 = 16;
+
+/// Where the folders of a Bitwarden export end up
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BitwardenFolderMapping {
+    /// Folders of the active profile, nested along the `/` in their names
+    #[default]
+    Folders,
+    /// One profile per folder, reusing a profile of the same name. Items
+    /// without a folder go to the active profile.
+    Profiles,
+}
+
+/// An entry created by an import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedEntry {
+    pub id: i64,
+    pub entry_uuid: String,
+    pub profile_id: i64,
+    pub title: String,
+    pub item_type: ItemType,
+}
+
+/// An item of an export that was not imported
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedItem {
+    pub name: String,
+    pub reason: String,
+}
+
+/// Something of an imported item that could not be carried over as is
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportWarning {
+    pub title: String,
+    pub message: String,
+}
+
+/// Outcome of importing a Bitwarden export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BitwardenImportReport {
+    /// Whether the export was password-protected
+    pub encrypted: bool,
+    pub imported: Vec<ImportedEntry>,
+    pub skipped: Vec<SkippedItem>,
+    pub warnings: Vec<ImportWarning>,
+    /// Paths of the folders created for the export's folders
+    pub folders_created: Vec<String>,
+    /// Names of the profiles created for the export's folders
+    pub profiles_created: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BwExport {
+    #[serde(default)]
+    folders: Vec<BwFolder>,
+    /// Organization exports group items in collections instead of folders
+    #[serde(default)]
+    collections: Vec<BwFolder>,
+    #[serde(default)]
+    items: Vec<Value>,
+}
+
+#[derive(Deserialize)]
+struct BwFolder {
+    id: String,
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BwItem {
+    #[serde(rename = "type")]
+    item_type: u8,
+    name: Option<String>,
+    notes: Option<String>,
+    favorite: Option<bool>,
+    folder_id: Option<String>,
+    collection_ids: Option<Vec<String>>,
+    fields: Option<Vec<BwField>>,
+    login: Option<BwLogin>,
+    card: Option<BwCard>,
+    identity: Option<BwIdentity>,
+    ssh_key: Option<BwSshKey>,
+    password_history: Option<Vec<BwPasswordHistory>>,
+}
+
+#[derive(Deserialize)]
+struct BwField {
+    name: Option<String>,
+    value: Option<String>,
+    /// 0 text, 1 hidden, 2 boolean, 3 linked to a built-in field
+    #[serde(rename = "type")]
+    field_type: u8,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BwLogin {
+    uris: Option<Vec<BwUri>>,
+    username: Option<String>,
+    password: Option<String>,
+    totp: Option<String>,
+    fido2_credentials: Option<Vec<Value>>,
+}
+
+#[derive(Deserialize)]
+struct BwUri {
+    uri: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BwCard {
+    cardholder_name: Option<String>,
+    brand: Option<String>,
+    number: Option<String>,
+    exp_month: Option<String>,
+    exp_year: Option<String>,
+    code: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BwIdentity {
+    title: Option<String>,
+    first_name: Option<String>,
+    middle_name: Option<String>,
+    last_name: Option<String>,
+    address1: Option<String>,
+    address2: Option<String>,
+    address3: Option<String>,
+    city: Option<String>,
+    state: Option<String>,
+    postal_code: Option<String>,
+    country: Option<String>,
+    company: Option<String>,
+    email: Option<String>,
+    phone: Option<String>,
+    ssn: Option<String>,
+    username: Option<String>,
+    passport_number: Option<String>,
+    license_number: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BwSshKey {
+    private_key: Option<String>,
+    public_key: Option<String>,
+    key_fingerprint: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BwPasswordHistory {
+    last_used_date: Option<String>,
+    password: Option<String>,
+}
+
+/// The item built from a Bitwarden item, and what to keep instead of its
+/// card, identity or SSH key section should that not validate
+struct Converted {
+    item: VaultItem,
+    fallback_fields: Vec<CustomField>,
+    warnings: Vec<String>,
+}
+
+/// `value` unless it is missing or blank
+fn text(value: Option<String>) -> Option<String> {
+    value.filter(|v| !v.trim().is_empty())
+}
+
+fn field(name: &str, value: Option<String>, hidden: bool) -> Option<CustomField> {
+    text(value).map(|value| CustomField {
+        name: name.to_string(),
+        value,
+        hidden,
+    })
+}
+
+/// `2.iv|data|mac`, all three base64
+fn decrypt_enc_string(enc_key: &[u8], mac_key: &[u8], enc: &str) -> Result<Vec<u8>, String> {
+    let invalid = || "Invalid encrypted Bitwarden export".to_string();
+    let (enc_type, rest) = enc.split_once('.').ok_or_else(invalid)?;
+    if enc_type != "2" {
+        return Err(format!("Unsupported Bitwarden encryption type {}", enc_type));
+    }
+    let parts: Vec<Vec<u8>> = rest
+        .split('|')
+        .map(|p| general_purpose::STANDARD.decode(p).map_err(|_| invalid()))
+        .collect::<Result<_, _>>()?;
+    let (iv, ciphertext, mac) = match parts.as_slice() {
+        [iv, ciphertext, mac] => (iv, ciphertext, mac),
+        _ => return Err(invalid()),
+    };
+    let mut hmac = <Hmac<Sha256> as Mac>::new_from_slice(mac_key).map_err(|_| invalid())?;
+    hmac.update(iv);
+    hmac.update(ciphertext);
+    hmac.verify_slice(mac)
+        .map_err(|_| "Wrong export password".to_string())?;
+    decrypt_aes256_cbc(enc_key, iv, ciphertext)
+}
+
+/// The password's key, derived with the export's KDF settings
+fn derive_export_key(root: &Value, password: &str) -> Result<Vec<u8>, String> {
+    let salt = root
+        .get("salt")
+        .and_then(Value::as_str)
+        .ok_or("Encrypted Bitwarden export has no salt")?;
+    let param = |name: &str| {
+        root.get(name)
+            .and_then(Value::as_u64)
+            .and_then(|v| u32::try_from(v).ok())
+    };
+    let iterations =
+        param("kdfIterations").ok_or("Encrypted Bitwarden export has no KDF settings")?;
+    match param("kdfType").unwrap_or(0) {
+        0 => {
+            if !(1..=MAX_PBKDF2_ITERATIONS).contains(&iterations) {
+                return Err("Unsupported PBKDF2 iteration count".to_string());
+            }
+            let mut key = vec![0u8; 32];
+            pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), salt.as_bytes(), iterations, &mut key);
+            Ok(key)
+        }
+        1 => {
+            let memory = param("kdfMemory").unwrap_or(0);
+            let parallelism = param("kdfParallelism").unwrap_or(0);
+            if !(1..=MAX_ARGON2_MEMORY_MIB).contains(&memory)
+                || !(1..=MAX_ARGON2_ITERATIONS).contains(&iterations)
+                || !(1..=MAX_ARGON2_PARALLELISM).contains(&parallelism)
+            {
+                return Err("Unsupported Argon2 settings".to_string());
+            }
+            let params = Params::new(memory * 1024, iterations, parallelism, Some(32))
+                .map_err(|e| e.to_string())?;
+            // Bitwarden hashes the salt to the 32 bytes Argon2 expects
+            let salt = Sha256::digest(salt.as_bytes());
+            let mut key = vec![0u8; 32];
+            Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                .hash_password_into(password.as_bytes(), &salt, &mut key)
+                .map_err(|e| e.to_string())?;
+            Ok(key)
+        }
+        other => Err(format!("Unsupported Bitwarden KDF type {}", other)),
+    }
+}
+
+/// Decrypt the `data` of a password-protected export to the JSON of a
+/// plain export
+fn decrypt_export(root: &Value, password: &str) -> Result<Value, String> {
+    let mut key = derive_export_key(root, password)?;
+    let hkdf = Hkdf::<Sha256>::from_prk(&key).map_err(|_| "Invalid export key")?;
+    key.zeroize();
+    let mut enc_key = [0u8; 32];
+    let mut mac_key = [0u8; 32];
+    hkdf.expand(b"enc", &mut enc_key)
+        .and_then(|_| hkdf.expand(b"mac", &mut mac_key))
+        .map_err(|_| "Invalid export key")?;
+
+    let field = |name: &str| {
+        root.get(name)
+            .and_then(Value::as_str)
+            .ok_or_else(|| format!("Encrypted Bitwarden export has no {}", name))
+    };
+    let result = decrypt_enc_string(&enc_key, &mac_key, field("encKeyValidation_DO_NOT_EDIT")?)
+        .and_then(|_| decrypt_enc_string(&enc_key, &mac_key, field("data")?));
+    enc_key.zeroize();
+    mac_key.zeroize();
+    let mut plaintext = result?;
+    let data = serde_json::from_slice(&plaintext)
+        .map_err(|e| format!("Invalid Bitwarden export: {}", e));
+    plaintext.zeroize();
+    data
+}
+
+/// Parse an export, decrypting it first if it is password-protected.
+/// Returns the export and whether it was encrypted.
+fn read_export(json: &str, password: Option<&str>) -> Result<(BwExport, bool), String> {
+    let root: Value =
+        serde_json::from_str(json).map_err(|e| format!("Invalid Bitwarden export: {}", e))?;
+    let encrypted = root.get("encrypted").and_then(Value::as_bool) == Some(true);
+    let data = if !encrypted {
+        root
+    } else if root.get("passwordProtected").and_then(Value::as_bool) == Some(true) {
+        let password = password.ok_or("This export is encrypted; enter its password")?;
+        decrypt_export(&root, password)?
+    } else {
+        return Err("Account-restricted exports can only be read by Bitwarden; \
+                    export with a password instead"
+            .to_string());
+    };
+    if data.get("items").is_none() {
+        return Err("Not a Bitwarden export".to_string());
+    }
+    let export =
+        serde_json::from_value(data).map_err(|e| format!("Invalid Bitwarden export: {}", e))?;
+    Ok((export, encrypted))
+}
+
+fn convert_login(login: BwLogin, converted: &mut Converted) {
+    let item = &mut converted.item;
+    item.username = text(login.username);
+    item.password = text(login.password);
+    for uri in login.uris.unwrap_or_default().into_iter().filter_map(|u| text(u.uri)) {
+        let uri = uri.trim().to_string();
+        if validate_url(&uri).is_ok() {
+            item.urls.push(uri);
+        } else {
+            converted
+                .warnings
+                .push(format!("'{}' is not a valid URL and was kept as a field", uri));
+            item.custom_fields.extend(field("URL", Some(uri), false));
+        }
+    }
+    if let Some(totp) = text(login.totp) {
+        if parse_totp(&totp).is_ok() {
+            item.totp_secret = Some(totp);
+        } else {
+            converted
+                .warnings
+                .push("The TOTP secret is not valid and was kept as a hidden field".to_string());
+            item.custom_fields.extend(field("TOTP", Some(totp), true));
+        }
+    }
+    if login.fido2_credentials.is_some_and(|c| !c.is_empty()) {
+        converted
+            .warnings
+            .push("Passkeys cannot be imported and were left out".to_string());
+    }
+}
+
+fn convert_card(card: BwCard, converted: &mut Converted) {
+    let expiry = match (text(card.exp_month.clone()), text(card.exp_year.clone())) {
+        (Some(month), Some(year)) => Some(format!("{}/{}", month.trim(), year.trim())),
+        (month, year) => month.or(year),
+    };
+    converted.fallback_fields = [
+        field("Cardholder name", card.cardholder_name.clone(), false),
+        field("Brand", card.brand.clone(), false),
+        field("Number", card.number.clone(), true),
+        field("Expiry", expiry, false),
+        field("Security code", card.code.clone(), true),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    let expiry_year = card
+        .exp_year
+        .as_deref()
+        .and_then(|y| y.trim().parse::<u16>().ok())
+        .map(|y| if y < 100 { y + 2000 } else { y });
+    converted.item.card = Some(CardDetails {
+        cardholder_name: text(card.cardholder_name),
+        number: card.number.unwrap_or_default(),
+        expiry_month: card
+            .exp_month
+            .as_deref()
+            .and_then(|m| m.trim().parse().ok())
+            .unwrap_or(0),
+        expiry_year: expiry_year.unwrap_or(0),
+        security_code: text(card.code),
+        brand: text(card.brand),
+    });
+}
+
+fn convert_identity(identity: BwIdentity, converted: &mut Converted) {
+    let address_line2 = match (text(identity.address2), text(identity.address3)) {
+        (Some(line2), Some(line3)) => Some(format!("{}, {}", line2, line3)),
+        (line2, line3) => line2.or(line3),
+    };
+    let details = IdentityDetails {
+        first_name: text(identity.first_name),
+        middle_name: text(identity.middle_name),
+        last_name: text(identity.last_name),
+        email: text(identity.email),
+        phone: text(identity.phone),
+        company: text(identity.company),
+        address_line1: text(identity.address1),
+        address_line2,
+        city: text(identity.city),
+        region: text(identity.state),
+        postal_code: text(identity.postal_code),
+        country: text(identity.country),
+    };
+    converted.fallback_fields = [
+        ("First name", &details.first_name),
+        ("Middle name", &details.middle_name),
+        ("Last name", &details.last_name),
+        ("Email", &details.email),
+        ("Phone", &details.phone),
+        ("Company", &details.company),
+        ("Address", &details.address_line1),
+        ("Address", &details.address_line2),
+        ("City", &details.city),
+        ("Region", &details.region),
+        ("Postal code", &details.postal_code),
+        ("Country", &details.country),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| field(name, value.clone(), false))
+    .collect();
+    converted.item.identity = Some(details);
+
+    // Fields the identity type has no place for
+    converted.item.custom_fields.extend(
+        [
+            field("Title", identity.title, false),
+            field("Username", identity.username, false),
+            field("Social security number", identity.ssn, true),
+            field("Passport number", identity.passport_number, true),
+            field("License number", identity.license_number, true),
+        ]
+        .into_iter()
+        .flatten(),
+    );
+}
+
+fn convert_ssh_key(key: BwSshKey, converted: &mut Converted) {
+    converted.fallback_fields = [
+        field("Private key", key.private_key.clone(), true),
+        field("Public key", key.public_key.clone(), false),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    converted.item.ssh_key = Some(SshKeyDetails {
+        private_key: text(key.private_key),
+        public_key: text(key.public_key),
+        passphrase: None,
+    });
+    converted
+        .item
+        .custom_fields
+        .extend(field("Fingerprint", key.key_fingerprint, false));
+}
+
+/// Map one Bitwarden item onto a `VaultItem`, keeping at most
+/// `history_depth` previous passwords
+fn convert_item(bw: BwItem, history_depth: usize) -> Result<Converted, String> {
+    let title = text(bw.name).map_or_else(|| UNTITLED_ITEM.to_string(), |n| n.trim().to_string());
+    let item_type = match bw.item_type {
+        1 => ItemType::Login,
+        2 => ItemType::SecureNote,
+        3 => ItemType::Card,
+        4 => ItemType::Identity,
+        5 => ItemType::SshKey,
+        other => return Err(format!("Unsupported item type {}", other)),
+    };
+    let mut converted = Converted {
+        item: VaultItem::with_type(&title, item_type),
+        fallback_fields: Vec::new(),
+        warnings: Vec::new(),
+    };
+    let missing = || format!("{:?} item has no details", item_type);
+    match item_type {
+        ItemType::Login => {
+            if let Some(login) = bw.login {
+                convert_login(login, &mut converted);
+            }
+        }
+        ItemType::Card => convert_card(bw.card.ok_or_else(missing)?, &mut converted),
+        ItemType::Identity => convert_identity(bw.identity.ok_or_else(missing)?, &mut converted),
+        ItemType::SshKey => convert_ssh_key(bw.ssh_key.ok_or_else(missing)?, &mut converted),
+        _ => {}
+    }
+
+    let item = &mut converted.item;
+    item.notes = text(bw.notes);
+    for bw_field in bw.fields.unwrap_or_default() {
+        let name = text(bw_field.name).unwrap_or_else(|| "Field".to_string());
+        match bw_field.field_type {
+            0..=2 => item.custom_fields.push(CustomField {
+                name,
+                value: bw_field.value.unwrap_or_default(),
+                hidden: bw_field.field_type == 1,
+            }),
+            _ => converted
+                .warnings
+                .push(format!("Linked field '{}' was left out", name)),
+        }
+    }
+
+    let mut history: Vec<PasswordHistoryEntry> = bw
+        .password_history
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|h| {
+            Some(PasswordHistoryEntry {
+                password: text(h.password)?,
+                replaced_at: h.last_used_date.unwrap_or_else(now_iso),
+            })
+        })
+        .collect();
+    history.sort_by(|a, b| b.replaced_at.cmp(&a.replaced_at));
+    history.truncate(history_depth);
+    item.password_history = history;
+
+    Ok(converted)
+}
+
+impl Converted {
+    /// Validate the item; a card, identity or SSH key that does not pass is
+    /// kept as a secure note with its details as fields
+    fn into_valid_item(mut self) -> Result<(VaultItem, Vec<String>), String> {
+        let error = match self.item.validate() {
+            Ok(()) => return Ok((self.item, self.warnings)),
+            Err(error) => error,
+        };
+        if !matches!(
+            self.item.item_type,
+            ItemType::Card | ItemType::Identity | ItemType::SshKey
+        ) {
+            return Err(error);
+        }
+        self.warnings.push(format!(
+            "{:?} details are not valid ({}); imported as a secure note",
+            self.item.item_type, error
+        ));
+        let item = &mut self.item;
+        item.item_type = ItemType::SecureNote;
+        item.card = None;
+        item.identity = None;
+        item.ssh_key = None;
+        item.custom_fields.splice(0..0, self.fallback_fields);
+        item.validate()?;
+        Ok((self.item, self.warnings))
+    }
+}
+
+/// Where the items of one export folder go
+#[derive(Clone)]
+enum Destination {
+    Folder(String),
+    Profile(i64),
+}
+
+impl Vault {
+    /// Import a Bitwarden JSON export, plain or password-protected. Folders
+    /// (and the collections of an organization export) become folders of
+    /// the active profile or profiles of their own, depending on `mapping`.
+    /// Items that cannot be read or stored are skipped and listed in the
+    /// report; the rest of the export is still imported.
+    pub fn import_bitwarden(
+        &mut self,
+        token: &str,
+        json: &str,
+        password: Option<&str>,
+        mapping: BitwardenFolderMapping,
+    ) -> Result<BitwardenImportReport, String> {
+        let (_key, active_profile) = self.session_context(token)?;
+        let (export, encrypted) = read_export(json, password)?;
+
+        let mut report = BitwardenImportReport {
+            encrypted,
+            imported: Vec::new(),
+            skipped: Vec::new(),
+            warnings: Vec::new(),
+            folders_created: Vec::new(),
+            profiles_created: Vec::new(),
+        };
+        let folder_names: HashMap<String, String> = export
+            .folders
+            .into_iter()
+            .chain(export.collections)
+            .map(|f| (f.id, f.name))
+            .collect();
+        let mut destinations: HashMap<String, Option<Destination>> = HashMap::new();
+        let mut folders = self.list_folders(token)?;
+
+        for value in export.items {
+            let name = value
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or(UNTITLED_ITEM)
+                .to_string();
+            let skip = |report: &mut BitwardenImportReport, reason: String| {
+                report.skipped.push(SkippedItem {
+                    name: name.clone(),
+                    reason,
+                })
+            };
+            let bw: BwItem = match serde_json::from_value(value) {
+                Ok(bw) => bw,
+                Err(e) => {
+                    skip(&mut report, format!("Invalid item: {}", e));
+                    continue;
+                }
+            };
+            let favorite = bw.favorite == Some(true);
+            let folder_id = bw
+                .folder_id
+                .clone()
+                .or_else(|| bw.collection_ids.as_ref().and_then(|ids| ids.first().cloned()));
+
+            let converted = convert_item(bw, self.password_history_depth)
+                .and_then(Converted::into_valid_item);
+            let (mut item, warnings) = match converted {
+                Ok(converted) => converted,
+                Err(reason) => {
+                    skip(&mut report, reason);
+                    continue;
+                }
+            };
+            report
+                .warnings
+                .extend(warnings.into_iter().map(|message| ImportWarning {
+                    title: item.title.clone(),
+                    message,
+                }));
+
+            let destination = match folder_id {
+                Some(folder_id) => {
+                    if !destinations.contains_key(&folder_id) {
+                        let destination = match folder_names.get(&folder_id) {
+                            Some(folder_name) => self.bitwarden_destination(
+                                token,
+                                folder_name,
+                                mapping,
+                                &mut folders,
+                                &mut report,
+                            ),
+                            None => None,
+                        };
+                        destinations.insert(folder_id.clone(), destination);
+                    }
+                    destinations[&folder_id].clone()
+                }
+                None => None,
+            };
+            let profile_id = match destination {
+                Some(Destination::Folder(uuid)) => {
+                    item.folder_id = Some(uuid);
+                    active_profile
+                }
+                Some(Destination::Profile(id)) => id,
+                None => active_profile,
+            };
+
+            let entry_uuid = match self.save_item(token, &item, Some(profile_id)) {
+                Ok(uuid) => uuid,
+                Err(reason) => {
+                    skip(&mut report, reason);
+                    continue;
+                }
+            };
+            let id = self
+                .store
+                .find_entry_by_uuid(&entry_uuid)?
+                .ok_or("Saved entry not found")?
+                .id;
+            if favorite {
+                self.store.put_favorite(&FavoriteRecord {
+                    entry_uuid: entry_uuid.clone(),
+                    favorite: true,
+                    updated_at: now_iso(),
+                })?;
+            }
+            report.imported.push(ImportedEntry {
+                id,
+                entry_uuid,
+                profile_id,
+                title: item.title,
+                item_type: item.item_type,
+            });
+        }
+        Ok(report)
+    }
+
+    /// The folder or profile for an export folder, created if needed. A
+    /// folder that cannot be created is reported and its items go to the
+    /// profile root.
+    fn bitwarden_destination(
+        &mut self,
+        token: &str,
+        folder_name: &str,
+        mapping: BitwardenFolderMapping,
+        folders: &mut Vec<Folder>,
+        report: &mut BitwardenImportReport,
+    ) -> Option<Destination> {
+        let result = match mapping {
+            BitwardenFolderMapping::Folders => self
+                .ensure_folder_path(token, folder_name, folders, report)
+                .map(|uuid| uuid.map(Destination::Folder)),
+            BitwardenFolderMapping::Profiles => self
+                .ensure_profile(token, folder_name, report)
+                .map(|id| Some(Destination::Profile(id))),
+        };
+        match result {
+            Ok(destination) => destination,
+            Err(error) => {
+                report.warnings.push(ImportWarning {
+                    title: folder_name.to_string(),
+                    message: format!(
+                        "Folder could not be created ({}); its items were imported without it",
+                        error
+                    ),
+                });
+                None
+            }
+        }
+    }
+
+    /// Find or create the nested folders of a `Parent/Child` path in the
+    /// active profile. Returns the innermost folder's ID.
+    fn ensure_folder_path(
+        &mut self,
+        token: &str,
+        path: &str,
+        folders: &mut Vec<Folder>,
+        report: &mut BitwardenImportReport,
+    ) -> Result<Option<String>, String> {
+        let mut parent: Option<String> = None;
+        let mut created_path = Vec::new();
+        for name in path.split('/').map(str::trim).filter(|n| !n.is_empty()) {
+            created_path.push(name);
+            let existing = folders.iter().find(|f| {
+                f.parent_id == parent && f.name.to_lowercase() == name.to_lowercase()
+            });
+            let id = match existing {
+                Some(folder) => folder.id.clone(),
+                None => {
+                    let id = self.create_folder(token, name, parent.as_deref())?;
+                    let now = now_iso();
+                    folders.push(Folder {
+                        id: id.clone(),
+                        name: name.to_string(),
+                        parent_id: parent.clone(),
+                        created_at: now.clone(),
+                        updated_at: now,
+                    });
+                    report.folders_created.push(created_path.join("/"));
+                    id
+                }
+            };
+            parent = Some(id);
+        }
+        Ok(parent)
+    }
+
+    fn ensure_profile(
+        &mut self,
+        token: &str,
+        name: &str,
+        report: &mut BitwardenImportReport,
+    ) -> Result<i64, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Profile name is required".to_string());
+        }
+        if let Some(profile) = self.list_profiles(token)?.into_iter().find(|p| p.name == name) {
+            return Ok(profile.id);
+        }
+        let id = self.create_profile(token, name)?;
+        report.profiles_created.push(name.to_string());
+        Ok(id)
+    }
+}
@@ -0,0 +1,434 @@
+// Offline breach check against the Have I Been Pwned password hashes.
+//
+// The HIBP SHA-1 dump is imported once into a compact index file: a
+// 65536-entry fan-out table on the first two bytes of the hash, followed by
+// the remaining 18 bytes and the breach count of every hash, in hash order.
+// A lookup reads one fan-out slot and binary-searches a few hundred
+// records on disk, so the index never has to fit in memory. Vault
+// passwords are hashed in memory and only their hashes are looked up; the
+// index itself holds public data only.
+
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use zeroize::Zeroize;
+
+use crate::health::{HealthEntryRef, HealthScope};
+use crate::vault::{decrypt_item, now_iso};
+use crate::Vault;
+
+const MAGIC: &[u8; 8] = b"VVHIBP01";
+const FANOUT_SLOTS: usize = 1 << 16;
+const HEADER_LEN: u64 = 16 + FANOUT_SLOTS as u64 * 8;
+/// Hash bytes after the two covered by the fan-out table, plus a u32 count
+const RECORD_LEN: usize = 18 + 4;
+/// Hex digits in the file names of the range-split layout
+const RANGE_PREFIX_LEN: usize = 5;
+
+/// Result of `build_breach_index`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreachIndexInfo {
+    /// Number of distinct hashes in the index
+    pub hashes: u64,
+    pub index_size: u64,
+}
+
+/// A vault entry whose password appears in the breach data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreachedEntry {
+    pub entry: HealthEntryRef,
+    /// How often the password appears in the breach data
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreachReport {
+    pub generated_at: String,
+    pub scope: HealthScope,
+    /// Entries with a password that were checked
+    pub passwords_checked: usize,
+    /// Breached entries, most often seen first
+    pub breached: Vec<BreachedEntry>,
+}
+
+/// An opened breach index
+pub struct BreachIndex {
+    file: File,
+    fanout: Vec<u64>,
+}
+
+/// Writes records in hash order and fills in the fan-out table
+struct IndexWriter {
+    out: BufWriter<File>,
+    fanout: Vec<u64>,
+    last: Option<[u8; 20]>,
+    pending_count: u64,
+    written: u64,
+}
+
+impl IndexWriter {
+    fn create(path: &Path) -> Result<Self, String> {
+        let file = File::create(path).map_err(|e| format!("Failed to create index: {}", e))?;
+        let mut out = BufWriter::new(file);
+        // Header and fan-out are rewritten once all records are known
+        out.write_all(&vec![0u8; HEADER_LEN as usize])
+            .map_err(|e| e.to_string())?;
+        Ok(IndexWriter {
+            out,
+            fanout: vec![0; FANOUT_SLOTS],
+            last: None,
+            pending_count: 0,
+            written: 0,
+        })
+    }
+
+    fn push(&mut self, hash: [u8; 20], count: u64) -> Result<(), String> {
+        match self.last {
+            Some(last) if hash == last => {
+                self.pending_count = self.pending_count.saturating_add(count);
+                return Ok(());
+            }
+            Some(last) if hash < last => {
+                return Err("Breach data is not sorted by hash".to_string());
+            }
+            _ => {}
+        }
+        self.flush_pending()?;
+        self.last = Some(hash);
+        self.pending_count = count;
+        Ok(())
+    }
+
+    fn flush_pending(&mut self) -> Result<(), String> {
+        let hash = match self.last {
+            Some(hash) => hash,
+            None => return Ok(()),
+        };
+        let count = u32::try_from(self.pending_count).unwrap_or(u32::MAX);
+        self.out.write_all(&hash[2..]).map_err(|e| e.to_string())?;
+        self.out
+            .write_all(&count.to_le_bytes())
+            .map_err(|e| e.to_string())?;
+        self.written += 1;
+        let slot = u16::from_be_bytes([hash[0], hash[1]]) as usize;
+        self.fanout[slot] = self.written;
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<u64, String> {
+        self.flush_pending()?;
+        // Turn per-slot "last record" marks into cumulative end offsets
+        let mut end = 0;
+        for slot in self.fanout.iter_mut() {
+            if *slot == 0 {
+                *slot = end;
+            }
+            end = *slot;
+        }
+
+        let mut file = self.out.into_inner().map_err(|e| e.to_string())?;
+        file.seek(SeekFrom::Start(0)).map_err(|e| e.to_string())?;
+        let mut header = Vec::with_capacity(HEADER_LEN as usize);
+        header.extend_from_slice(MAGIC);
+        header.extend_from_slice(&self.written.to_le_bytes());
+        for end in &self.fanout {
+            header.extend_from_slice(&end.to_le_bytes());
+        }
+        file.write_all(&header).map_err(|e| e.to_string())?;
+        file.sync_all().map_err(|e| e.to_string())?;
+        Ok(self.written)
+    }
+}
+
+/// Parse one `HASH:COUNT` line whose hash is `prefix` followed by the
+/// hex digits before the colon. Empty lines give `None`.
+fn parse_line(prefix: &str, line: &str) -> Result<Option<([u8; 20], u64)>, String> {
+    let line = line.trim();
+    if line.is_empty() {
+        return Ok(None);
+    }
+    let invalid = || format!("Invalid breach data line: {}", line);
+    let (hash, count) = line.split_once(':').ok_or_else(invalid)?;
+    let hex_hash = format!("{}{}", prefix, hash);
+    if hex_hash.len() != 40 {
+        return Err(if hex_hash.len() == 32 {
+            "NTLM hashes are not supported; use the SHA-1 files".to_string()
+        } else {
+            invalid()
+        });
+    }
+    let mut bytes = [0u8; 20];
+    hex::decode_to_slice(&hex_hash, &mut bytes).map_err(|_| invalid())?;
+    let count = count.trim().parse::<u64>().map_err(|_| invalid())?;
+    Ok(Some((bytes, count)))
+}
+
+fn index_file(writer: &mut IndexWriter, path: &Path, prefix: &str) -> Result<(), String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        if let Some((hash, count)) = parse_line(prefix, &line)? {
+            writer.push(hash, count)?;
+        }
+    }
+    Ok(())
+}
+
+/// Range-split files (`00000.txt` … `FFFFF.txt`) of `dir`, in hash order
+fn range_files(dir: &Path) -> Result<Vec<(String, PathBuf)>, String> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).map_err(|e| e.to_string())? {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        let stem = match path.file_stem().and_then(|s| s.to_str()) {
+            Some(stem) => stem.to_uppercase(),
+            None => continue,
+        };
+        if stem.len() == RANGE_PREFIX_LEN && stem.chars().all(|c| c.is_ascii_hexdigit()) {
+            files.push((stem, path));
+        }
+    }
+    if files.is_empty() {
+        return Err("No range files (00000 … FFFFF) found".to_string());
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Build a breach index at `index` from `source`: either one file of
+/// `HASH:COUNT` lines sorted by hash, or a directory in the range-split
+/// layout of the HIBP downloader. The index is written next to its final
+/// path and moved into place only when complete.
+pub fn build_breach_index(source: &Path, index: &Path) -> Result<BreachIndexInfo, String> {
+    let partial = index.with_extension("partial");
+    let result = (|| {
+        let mut writer = IndexWriter::create(&partial)?;
+        if source.is_dir() {
+            for (prefix, path) in range_files(source)? {
+                index_file(&mut writer, &path, &prefix)?;
+            }
+        } else {
+            index_file(&mut writer, source, "")?;
+        }
+        writer.finish()
+    })();
+
+    let hashes = match result {
+        Ok(hashes) => hashes,
+        Err(e) => {
+            let _ = fs::remove_file(&partial);
+            return Err(e);
+        }
+    };
+    fs::rename(&partial, index).map_err(|e| format!("Failed to save index: {}", e))?;
+    let index_size = fs::metadata(index).map_err(|e| e.to_string())?.len();
+    Ok(BreachIndexInfo { hashes, index_size })
+}
+
+impl BreachIndex {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let mut file =
+            File::open(path).map_err(|_| "No breach index; import breach data first".to_string())?;
+        let mut header = vec![0u8; HEADER_LEN as usize];
+        file.read_exact(&mut header)
+            .map_err(|_| "Invalid breach index".to_string())?;
+        if &header[..8] != MAGIC {
+            return Err("Invalid breach index".to_string());
+        }
+        let fanout: Vec<u64> = header[16..]
+            .chunks_exact(8)
+            .map(|b| u64::from_le_bytes(b.try_into().unwrap_or_default()))
+            .collect();
+        Ok(BreachIndex { file, fanout })
+    }
+
+    /// Number of distinct hashes in the index
+    pub fn len(&self) -> u64 {
+        self.fanout.last().copied().unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn record(&mut self, position: u64) -> Result<[u8; RECORD_LEN], String> {
+        let mut record = [0u8; RECORD_LEN];
+        self.file
+            .seek(SeekFrom::Start(HEADER_LEN + position * RECORD_LEN as u64))
+            .map_err(|e| e.to_string())?;
+        self.file
+            .read_exact(&mut record)
+            .map_err(|_| "Invalid breach index".to_string())?;
+        Ok(record)
+    }
+
+    /// Breach count of a SHA-1 hash; 0 when it is not in the index
+    pub fn count(&mut self, hash: &[u8; 20]) -> Result<u64, String> {
+        let slot = u16::from_be_bytes([hash[0], hash[1]]) as usize;
+        let mut low = if slot == 0 { 0 } else { self.fanout[slot - 1] };
+        let mut high = self.fanout[slot];
+        while low < high {
+            let middle = low + (high - low) / 2;
+            let record = self.record(middle)?;
+            match record[..18].cmp(&hash[2..]) {
+                std::cmp::Ordering::Less => low = middle + 1,
+                std::cmp::Ordering::Greater => high = middle,
+                std::cmp::Ordering::Equal => {
+                    let count: [u8; 4] = record[18..].try_into().unwrap_or_default();
+                    return Ok(u32::from_le_bytes(count) as u64);
+                }
+            }
+        }
+        Ok(0)
+    }
+
+    /// Breach count of a password
+    pub fn password_count(&mut self, password: &str) -> Result<u64, String> {
+        let mut hash: [u8; 20] = Sha1::digest(password.as_bytes()).into();
+        let count = self.count(&hash);
+        hash.zeroize();
+        count
+    }
+}
+
+impl Vault {
+    /// Look up every password of the active profile, or of all profiles,
+    /// in the breach index at `index`
+    pub fn breach_report(
+        &mut self,
+        token: &str,
+        index: &Path,
+        scope: HealthScope,
+    ) -> Result<BreachReport, String> {
+        let (key, active_profile) = self.session_context(token)?;
+        let mut index = BreachIndex::open(index)?;
+
+        let profiles = match scope {
+            HealthScope::ActiveProfile => vec![active_profile],
+            HealthScope::AllProfiles => self.store.list_profiles()?.iter().map(|p| p.id).collect(),
+        };
+
+        let mut passwords_checked = 0;
+        let mut breached = Vec::new();
+        for profile_id in profiles {
+            for record in self.store.list_entries(profile_id)? {
+                let mut item = match decrypt_item(&key, &record) {
+                    Ok(item) => item,
+                    Err(_) => continue,
+                };
+                let count = match item.password.as_deref().filter(|p| !p.is_empty()) {
+                    Some(password) => index.password_count(password),
+                    None => continue,
+                };
+                if let Some(password) = item.password.as_mut() {
+                    password.zeroize();
+                }
+                passwords_checked += 1;
+                let count = count?;
+                if count > 0 {
+                    breached.push(BreachedEntry {
+                        entry: HealthEntryRef {
+                            id: record.id,
+                            entry_uuid: record.entry_uuid.clone(),
+                            profile_id: record.profile_id,
+                            title: item.title,
+                            username: item.username,
+                        },
+                        count,
+                    });
+                }
+            }
+        }
+        breached.sort_by_key(|b| std::cmp::Reverse(b.count));
+
+        Ok(BreachReport {
+            generated_at: now_iso(),
+            scope,
+            passwords_checked,
+            breached,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sha1_hex(password: &str) -> String {
+        hex::encode_upper(Sha1::digest(password.as_bytes()))
+    }
+
+    fn scratch_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("vibevault-hibp-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn sorted_lines(passwords: &[(&str, u64)]) -> Vec<(String, u64)> {
+        let mut lines: Vec<(String, u64)> =
+            passwords.iter().map(|(p, c)| (sha1_hex(p), *c)).collect();
+        lines.sort();
+        lines
+    }
+
+    #[test]
+    fn test_single_file_index() {
+        let dir = scratch_dir();
+        let lines = sorted_lines(&[("password", 9_000_000), ("123456", 40_000_000), ("hunter2", 24)]);
+        let source = dir.join("pwned-passwords-sha1-ordered-by-hash.txt");
+        let text: String = lines.iter().map(|(h, c)| format!("{}:{}\r\n", h, c)).collect();
+        fs::write(&source, text).unwrap();
+
+        let info = build_breach_index(&source, &dir.join("hibp.idx")).unwrap();
+        assert_eq!(info.hashes, 3);
+        assert_eq!(info.index_size, HEADER_LEN + 3 * RECORD_LEN as u64);
+
+        let mut index = BreachIndex::open(&dir.join("hibp.idx")).unwrap();
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.password_count("password").unwrap(), 9_000_000);
+        assert_eq!(index.password_count("hunter2").unwrap(), 24);
+        assert_eq!(index.password_count("not breached").unwrap(), 0);
+
+        // Unsorted input is rejected and leaves no partial index behind
+        let unsorted: String = lines.iter().rev().map(|(h, c)| format!("{}:{}\n", h, c)).collect();
+        fs::write(&source, unsorted).unwrap();
+        assert!(build_breach_index(&source, &dir.join("other.idx")).is_err());
+        assert!(!dir.join("other.partial").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_range_layout_index() {
+        let dir = scratch_dir();
+        let ranges = dir.join("ranges");
+        fs::create_dir_all(&ranges).unwrap();
+        for (hash, count) in sorted_lines(&[("letmein", 7), ("qwerty", 11), ("dragon", 3)]) {
+            let (prefix, suffix) = hash.split_at(RANGE_PREFIX_LEN);
+            let path = ranges.join(format!("{}.txt", prefix));
+            let mut text = fs::read_to_string(&path).unwrap_or_default();
+            text.push_str(&format!("{}:{}\n", suffix, count));
+            fs::write(path, text).unwrap();
+        }
+
+        let info = build_breach_index(&ranges, &dir.join("hibp.idx")).unwrap();
+        assert_eq!(info.hashes, 3);
+        let mut index = BreachIndex::open(&dir.join("hibp.idx")).unwrap();
+        assert_eq!(index.password_count("qwerty").unwrap(), 11);
+        assert_eq!(index.password_count("dragon").unwrap(), 3);
+        assert_eq!(index.password_count("Dragon").unwrap(), 0);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(parse_line("", "8846F7EAEE8FB117AD06BDD830B7586C:5").is_err());
+        assert!(parse_line("", "not a hash").is_err());
+        assert!(parse_line("", "  ").unwrap().is_none());
+        assert!(BreachIndex::open(Path::new("/nonexistent/hibp.idx")).is_err());
+    }
+}
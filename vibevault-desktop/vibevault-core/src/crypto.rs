@@ -0,0 +1,99 @@
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    aes::Aes256,
+    Aes256Gcm, Nonce,
+};
+use cbc::cipher::{block_padding::Pkcs7, BlockDecryptMut, KeyIvInit};
+use rand::RngCore;
+
+/// Encrypt data with AES-256-GCM, returns (ciphertext, nonce)
+pub fn encrypt_aes256_gcm(key: &[u8; 32], plaintext: &[u8]) -> Result<(Vec<u8>, Vec<u8>), String> {
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| "Encryption init failed")?;
+    let mut nonce_bytes = [0u8; 12];
+    rand::thread_rng().fill_bytes(&mut nonce_bytes);
+    let nonce = Nonce::from_slice(&nonce_bytes);
+    let ciphertext = cipher
+        .encrypt(nonce, plaintext)
+        .map_err(|_| "Encryption failed")?;
+    Ok((ciphertext, nonce_bytes.to_vec()))
+}
+
+/// Decrypt data with AES-256-GCM
+pub fn decrypt_aes256_gcm(
+    key: &[u8; 32],
+    ciphertext: &[u8],
+    nonce_bytes: &[u8],
+) -> Result<Vec<u8>, String> {
+    if nonce_bytes.len() != 12 {
+        return Err("Invalid nonce length".to_string());
+    }
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| "Decryption init failed")?;
+    let nonce = Nonce::from_slice(nonce_bytes);
+    cipher
+        .decrypt(nonce, ciphertext)
+        .map_err(|_| "Decryption failed — wrong password or corrupted data".to_string())
+}
+
+/// Encrypt with AES-256-GCM, authenticating `aad` alongside the ciphertext.
+/// Returns (ciphertext, nonce).
+pub fn encrypt_aes256_gcm_aad(
+    key: &[u8; 32],
+    plaintext: &[u8],
+    aad: &[u8],
+) -> Result<(Vec<u8>, Vec<u8>), String> {
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| "Encryption init failed")?;
+    let mut nonce_bytes = [0u8; 12];
+    rand::thread_rng().fill_bytes(&mut nonce_bytes);
+    let nonce = Nonce::from_slice(&nonce_bytes);
+    let ciphertext = cipher
+        .encrypt(nonce, Payload { msg: plaintext, aad })
+        .map_err(|_| "Encryption failed")?;
+    Ok((ciphertext, nonce_bytes.to_vec()))
+}
+
+/// Decrypt AES-256-GCM data that was encrypted with `aad`
+pub fn decrypt_aes256_gcm_aad(
+    key: &[u8; 32],
+    ciphertext: &[u8],
+    nonce_bytes: &[u8],
+    aad: &[u8],
+) -> Result<Vec<u8>, String> {
+    if nonce_bytes.len() != 12 {
+        return Err("Invalid nonce length".to_string());
+    }
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| "Decryption init failed")?;
+    let nonce = Nonce::from_slice(nonce_bytes);
+    cipher
+        .decrypt(nonce, Payload { msg: ciphertext, aad })
+        .map_err(|_| "Decryption failed — wrong password or corrupted data".to_string())
+}
+
+/// AES-256-CBC with PKCS#7 padding, for files made by other apps. CBC is
+/// not authenticated: check the file's MAC before calling this.
+pub fn decrypt_aes256_cbc(key: &[u8], iv: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, String> {
+    cbc::Decryptor::<Aes256>::new_from_slices(key, iv)
+        .map_err(|_| "Invalid key or IV length")?
+        .decrypt_padded_vec_mut::<Pkcs7>(ciphertext)
+        .map_err(|_| "Decryption failed — wrong password or corrupted data".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aes_cbc_vector() {
+        // SP 800-38A F.2.5, first block, plus the PKCS#7 padding block
+        let key = hex::decode("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4")
+            .unwrap();
+        let iv: Vec<u8> = (0..16).collect();
+        let ciphertext =
+            hex::decode("f58c4c04d6e5f1ba779eabfb5f7bfbd6485a5c81519cf378fa36d42b8547edc0").unwrap();
+        assert_eq!(
+            hex::encode(decrypt_aes256_cbc(&key, &iv, &ciphertext).unwrap()),
+            "6bc1bee22e409f96e93d7e117393172a"
+        );
+        assert!(decrypt_aes256_cbc(&key, &iv, &ciphertext[..16]).is_err());
+        assert!(decrypt_aes256_cbc(&key[..16], &iv, &ciphertext).is_err());
+    }
+}
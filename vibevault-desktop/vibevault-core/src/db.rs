@@ -1,4 +1,5 @@
 use rusqlite::{params, Connection};
+use std::path::Path;
 use uuid::Uuid;
 
 pub struct DatabaseManager {
@@ -6,18 +7,19 @@ pub struct DatabaseManager {
 }
 
 impl DatabaseManager {
-    pub fn new(app_handle: &tauri::AppHandle) -> Result<Self, String> {
-        use tauri::Manager;
-        let app_dir = app_handle
-            .path()
-            .app_data_dir()
-            .map_err(|e| format!("Failed to get app data dir: {}", e))?;
-        std::fs::create_dir_all(&app_dir)
-            .map_err(|e| format!("Failed to create app data dir: {}", e))?;
-        let db_path = app_dir.join("vibevault.db");
+    /// Open (or create) the database at `path` and bring its schema up to date.
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let conn = Connection::open(path).map_err(|e| format!("Failed to open DB: {}", e))?;
 
+        Self::run_migrations(&conn)?;
+
+        Ok(DatabaseManager { conn })
+    }
+
+    /// Open a throwaway in-memory database with the full schema (used by tests)
+    pub fn open_in_memory() -> Result<Self, String> {
         let conn =
-            Connection::open(db_path).map_err(|e| format!("Failed to open DB: {}", e))?;
+            Connection::open_in_memory().map_err(|e| format!("Failed to open DB: {}", e))?;
 
         Self::run_migrations(&conn)?;
 
@@ -204,7 +206,139 @@ impl DatabaseManager {
         )
         .map_err(|e| format!("Failed to seed login_attempts: {}", e))?;
 
-        // 8. Create indexes for common queries
+        // 8. Create entry_revisions table (encrypted snapshots per sync_version)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS entry_revisions (
+                id INTEGER PRIMARY KEY,
+                entry_uuid TEXT NOT NULL,
+                sync_version INTEGER NOT NULL,
+                uuid TEXT NOT NULL,
+                data_blob BLOB NOT NULL,
+                nonce BLOB NOT NULL,
+                deleted_at TEXT,
+                device_id TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )
+        .map_err(|e| format!("Failed to create entry_revisions table: {}", e))?;
+
+        // 9. Create settings table for device-local key/value settings
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            )",
+            [],
+        )
+        .map_err(|e| format!("Failed to create settings table: {}", e))?;
+
+        // 10. Create attachment tables: per-entry metadata, deduplicated
+        // blobs and their encrypted chunks
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS attachments (
+                id INTEGER PRIMARY KEY,
+                entry_uuid TEXT NOT NULL,
+                blob_id TEXT NOT NULL,
+                meta_blob BLOB NOT NULL,
+                meta_nonce BLOB NOT NULL,
+                size INTEGER NOT NULL,
+                chunk_count INTEGER NOT NULL,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )
+        .map_err(|e| format!("Failed to create attachments table: {}", e))?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS attachment_blobs (
+                blob_id TEXT PRIMARY KEY,
+                content_hash TEXT NOT NULL UNIQUE,
+                size INTEGER NOT NULL,
+                chunk_count INTEGER NOT NULL,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )
+        .map_err(|e| format!("Failed to create attachment_blobs table: {}", e))?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS attachment_chunks (
+                blob_id TEXT NOT NULL,
+                chunk_index INTEGER NOT NULL,
+                data BLOB NOT NULL,
+                nonce BLOB NOT NULL,
+                PRIMARY KEY (blob_id, chunk_index)
+            )",
+            [],
+        )
+        .map_err(|e| format!("Failed to create attachment_chunks table: {}", e))?;
+
+        // 11. Create folders table (name and parent encrypted in data_blob)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS folders (
+                id INTEGER PRIMARY KEY,
+                folder_uuid TEXT NOT NULL UNIQUE,
+                profile_id INTEGER NOT NULL,
+                data_blob BLOB NOT NULL,
+                nonce BLOB NOT NULL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                deleted_at TEXT,
+                sync_version INTEGER NOT NULL DEFAULT 1
+            )",
+            [],
+        )
+        .map_err(|e| format!("Failed to create folders table: {}", e))?;
+
+        // 12. Create entry_usage table (per-device usage counters)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS entry_usage (
+                entry_uuid TEXT NOT NULL,
+                device_id TEXT NOT NULL,
+                last_used_at TEXT NOT NULL,
+                use_count INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (entry_uuid, device_id)
+            )",
+            [],
+        )
+        .map_err(|e| format!("Failed to create entry_usage table: {}", e))?;
+
+        // 13. Create entry_favorites table
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS entry_favorites (
+                entry_uuid TEXT PRIMARY KEY,
+                favorite INTEGER NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
+            [],
+        )
+        .map_err(|e| format!("Failed to create entry_favorites table: {}", e))?;
+
+        // 14. Create generator_history table (encrypted generated values)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS generator_history (
+                id INTEGER PRIMARY KEY,
+                data_blob BLOB NOT NULL,
+                nonce BLOB NOT NULL,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )
+        .map_err(|e| format!("Failed to create generator_history table: {}", e))?;
+
+        // 15. Create hotp_counters table (next unused counter per HOTP entry)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS hotp_counters (
+                entry_uuid TEXT PRIMARY KEY,
+                counter INTEGER NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
+            [],
+        )
+        .map_err(|e| format!("Failed to create hotp_counters table: {}", e))?;
+
+        // 16. Create indexes for common queries
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_vault_entry_uuid ON vault_entries (entry_uuid)",
             [],
@@ -217,6 +351,18 @@ impl DatabaseManager {
         )
         .map_err(|e| format!("Failed to create profile/deleted index: {}", e))?;
 
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_revisions_entry_uuid ON entry_revisions (entry_uuid)",
+            [],
+        )
+        .map_err(|e| format!("Failed to create revisions index: {}", e))?;
+
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_attachments_entry_uuid ON attachments (entry_uuid)",
+            [],
+        )
+        .map_err(|e| format!("Failed to create attachments index: {}", e))?;
+
         Ok(())
     }
 
@@ -251,15 +397,4 @@ impl DatabaseManager {
 
         Ok(())
     }
-
-    /// Purge tombstoned entries older than 90 days
-    pub fn cleanup_tombstones(&self) -> Result<usize, String> {
-        self.conn
-            .execute(
-                "DELETE FROM vault_entries WHERE deleted_at IS NOT NULL
-                 AND deleted_at < datetime('now', '-90 days')",
-                [],
-            )
-            .map_err(|e| e.to_string())
-    }
 }
@@ -0,0 +1,391 @@
+// Nested folders and bulk tagging within a profile.
+//
+// A folder's name and parent are encrypted together under the vault key.
+// Entries point at their folder through `VaultItem::folder_id`, and carry
+// their tags in `VaultItem::tags`, both inside the entry's own encrypted
+// blob, so assignments travel with the entry in `SyncEntry`. Folders
+// themselves sync like entries: last write wins, tombstones propagate
+// deletes. Items or folders whose parent no longer exists show up at the
+// profile root.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::crypto::{decrypt_aes256_gcm, encrypt_aes256_gcm};
+use crate::item::VaultItem;
+use crate::store::FolderRecord;
+use crate::vault::now_iso;
+use crate::Vault;
+
+/// Deepest allowed nesting (a top-level folder has depth 1)
+pub const MAX_FOLDER_DEPTH: usize = 16;
+
+const MAX_FOLDER_NAME_LEN: usize = 128;
+
+/// A folder as shown in the sidebar tree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Folder {
+    pub id: String,
+    pub name: String,
+    /// `None` = top-level folder
+    pub parent_id: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Encrypted together in `FolderRecord::data_blob`
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FolderData {
+    name: String,
+    #[serde(default)]
+    parent_id: Option<String>,
+}
+
+fn decrypt_folder(key: &[u8; 32], record: &FolderRecord) -> Result<Folder, String> {
+    let plaintext = decrypt_aes256_gcm(key, &record.data_blob, &record.nonce)?;
+    let data: FolderData = serde_json::from_slice(&plaintext).map_err(|e| e.to_string())?;
+    Ok(Folder {
+        id: record.folder_uuid.clone(),
+        name: data.name,
+        parent_id: data.parent_id,
+        created_at: record.created_at.clone(),
+        updated_at: record.updated_at.clone(),
+    })
+}
+
+fn encrypt_folder(
+    key: &[u8; 32],
+    name: &str,
+    parent_id: Option<&str>,
+) -> Result<(Vec<u8>, Vec<u8>), String> {
+    let data = FolderData {
+        name: name.to_string(),
+        parent_id: parent_id.map(str::to_string),
+    };
+    encrypt_aes256_gcm(key, &serde_json::to_vec(&data).map_err(|e| e.to_string())?)
+}
+
+fn validate_folder_name(name: &str) -> Result<&str, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Folder name is required".to_string());
+    }
+    if name.chars().count() > MAX_FOLDER_NAME_LEN {
+        return Err(format!(
+            "Folder name must be at most {} characters",
+            MAX_FOLDER_NAME_LEN
+        ));
+    }
+    Ok(name)
+}
+
+/// The active folders of a profile, with dangling parents cleared
+struct FolderTree {
+    folders: Vec<(FolderRecord, Folder)>,
+}
+
+impl FolderTree {
+    fn get(&self, id: &str) -> Option<&(FolderRecord, Folder)> {
+        self.folders.iter().find(|(_, f)| f.id == id)
+    }
+
+    fn require(&self, id: &str) -> Result<&(FolderRecord, Folder), String> {
+        self.get(id)
+            .ok_or_else(|| "Folder not found or belongs to different profile".to_string())
+    }
+
+    /// Levels from the root down to `id`; `None` (the root) has depth 0
+    fn depth(&self, id: Option<&str>) -> usize {
+        let mut depth = 0;
+        let mut current = id;
+        while let Some(id) = current {
+            depth += 1;
+            // Guard against cycles that concurrent moves on two devices can create
+            if depth > self.folders.len() {
+                break;
+            }
+            current = self.get(id).and_then(|(_, f)| f.parent_id.as_deref());
+        }
+        depth
+    }
+
+    /// `id` and every folder below it, each with its level (1 for `id`)
+    fn subtree(&self, id: &str) -> Vec<(String, usize)> {
+        let mut found = vec![(id.to_string(), 1)];
+        let mut next = 0;
+        while next < found.len() {
+            let (parent, level) = found[next].clone();
+            for (_, folder) in &self.folders {
+                if folder.parent_id.as_deref() == Some(parent.as_str())
+                    && !found.iter().any(|(id, _)| *id == folder.id)
+                {
+                    found.push((folder.id.clone(), level + 1));
+                }
+            }
+            next += 1;
+        }
+        found
+    }
+
+    /// Folder names are unique among siblings, ignoring case
+    fn check_sibling_name(
+        &self,
+        parent_id: Option<&str>,
+        name: &str,
+        except: Option<&str>,
+    ) -> Result<(), String> {
+        let taken = self.folders.iter().any(|(_, f)| {
+            f.parent_id.as_deref() == parent_id
+                && Some(f.id.as_str()) != except
+                && f.name.to_lowercase() == name.to_lowercase()
+        });
+        if taken {
+            return Err(format!("A folder named '{}' already exists here", name));
+        }
+        Ok(())
+    }
+}
+
+impl Vault {
+    fn folder_tree(&self, key: &[u8; 32], profile_id: i64) -> Result<FolderTree, String> {
+        let mut folders = Vec::new();
+        for record in self.store.list_folders(profile_id)? {
+            let folder = decrypt_folder(key, &record)?;
+            folders.push((record, folder));
+        }
+
+        let ids: Vec<String> = folders.iter().map(|(_, f)| f.id.clone()).collect();
+        for (_, folder) in folders.iter_mut() {
+            if folder.parent_id.as_ref().is_some_and(|p| !ids.contains(p)) {
+                folder.parent_id = None;
+            }
+        }
+        Ok(FolderTree { folders })
+    }
+
+    /// Re-encrypt a folder with new content and bump its sync version
+    fn write_folder(
+        &mut self,
+        key: &[u8; 32],
+        record: &FolderRecord,
+        name: &str,
+        parent_id: Option<&str>,
+        now: &str,
+    ) -> Result<(), String> {
+        let (data_blob, nonce) = encrypt_folder(key, name, parent_id)?;
+        self.store.overwrite_folder(&FolderRecord {
+            data_blob,
+            nonce,
+            updated_at: now.to_string(),
+            sync_version: record.sync_version + 1,
+            ..record.clone()
+        })
+    }
+
+    /// Folders of the active profile, sorted by name
+    pub fn list_folders(&mut self, token: &str) -> Result<Vec<Folder>, String> {
+        let (key, active_profile) = self.session_context(token)?;
+        let mut folders: Vec<Folder> = self
+            .folder_tree(&key, active_profile)?
+            .folders
+            .into_iter()
+            .map(|(_, f)| f)
+            .collect();
+        folders.sort_by_key(|f| f.name.to_lowercase());
+        Ok(folders)
+    }
+
+    /// Create a folder in the active profile, under `parent_id` or at the
+    /// top level. Returns the new folder's ID.
+    pub fn create_folder(
+        &mut self,
+        token: &str,
+        name: &str,
+        parent_id: Option<&str>,
+    ) -> Result<String, String> {
+        let (key, active_profile) = self.session_context(token)?;
+        let name = validate_folder_name(name)?;
+        let tree = self.folder_tree(&key, active_profile)?;
+
+        if let Some(parent_id) = parent_id {
+            tree.require(parent_id)?;
+        }
+        if tree.depth(parent_id) + 1 > MAX_FOLDER_DEPTH {
+            return Err(format!("Folders can be nested at most {} levels deep", MAX_FOLDER_DEPTH));
+        }
+        tree.check_sibling_name(parent_id, name, None)?;
+
+        let (data_blob, nonce) = encrypt_folder(&key, name, parent_id)?;
+        let folder_uuid = Uuid::new_v4().to_string();
+        let now = now_iso();
+        self.store.insert_folder(&FolderRecord {
+            id: 0,
+            folder_uuid: folder_uuid.clone(),
+            profile_id: active_profile,
+            data_blob,
+            nonce,
+            created_at: now.clone(),
+            updated_at: now,
+            deleted_at: None,
+            sync_version: 1,
+        })?;
+        Ok(folder_uuid)
+    }
+
+    pub fn rename_folder(&mut self, token: &str, id: &str, name: &str) -> Result<(), String> {
+        let (key, active_profile) = self.session_context(token)?;
+        let name = validate_folder_name(name)?;
+        let tree = self.folder_tree(&key, active_profile)?;
+
+        let (record, folder) = tree.require(id)?;
+        tree.check_sibling_name(folder.parent_id.as_deref(), name, Some(id))?;
+        self.write_folder(&key, record, name, folder.parent_id.as_deref(), &now_iso())
+    }
+
+    /// Move a folder (with everything in it) under `parent_id`, or to the
+    /// top level. A folder cannot be moved into its own subtree.
+    pub fn move_folder(
+        &mut self,
+        token: &str,
+        id: &str,
+        parent_id: Option<&str>,
+    ) -> Result<(), String> {
+        let (key, active_profile) = self.session_context(token)?;
+        let tree = self.folder_tree(&key, active_profile)?;
+
+        let (record, folder) = tree.require(id)?;
+        let subtree = tree.subtree(id);
+        if let Some(parent_id) = parent_id {
+            tree.require(parent_id)?;
+            if subtree.iter().any(|(sub, _)| sub == parent_id) {
+                return Err(
+                    "A folder cannot be moved into itself or one of its subfolders".to_string(),
+                );
+            }
+        }
+        let height = subtree.iter().map(|(_, level)| *level).max().unwrap_or(1);
+        if tree.depth(parent_id) + height > MAX_FOLDER_DEPTH {
+            return Err(format!("Folders can be nested at most {} levels deep", MAX_FOLDER_DEPTH));
+        }
+        tree.check_sibling_name(parent_id, &folder.name, Some(id))?;
+
+        self.write_folder(&key, record, &folder.name, parent_id, &now_iso())
+    }
+
+    /// Delete a folder and its subfolders. Entries inside them are not
+    /// deleted; they move up to the deleted folder's parent.
+    pub fn delete_folder(&mut self, token: &str, id: &str) -> Result<(), String> {
+        let (key, active_profile) = self.session_context(token)?;
+        let tree = self.folder_tree(&key, active_profile)?;
+
+        let parent_id = tree.require(id)?.1.parent_id.clone();
+        let removed: Vec<String> = tree.subtree(id).into_iter().map(|(id, _)| id).collect();
+
+        let entry_ids: Vec<i64> = self
+            .list_items(token, None)?
+            .into_iter()
+            .filter(|s| s.item.folder_id.as_ref().is_some_and(|f| removed.contains(f)))
+            .map(|s| s.id)
+            .collect();
+        self.rewrite_items(token, &entry_ids, |item| item.folder_id = parent_id.clone())?;
+
+        let now = now_iso();
+        for folder_id in &removed {
+            if let Some((record, _)) = tree.get(folder_id) {
+                self.store.overwrite_folder(&FolderRecord {
+                    deleted_at: Some(now.clone()),
+                    updated_at: now.clone(),
+                    sync_version: record.sync_version + 1,
+                    ..record.clone()
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Put entries of the active profile into `folder_id`, or at the profile root
+    pub fn move_entries_to_folder(
+        &mut self,
+        token: &str,
+        entry_ids: &[i64],
+        folder_id: Option<&str>,
+    ) -> Result<(), String> {
+        let (key, active_profile) = self.session_context(token)?;
+        if let Some(folder_id) = folder_id {
+            self.folder_tree(&key, active_profile)?.require(folder_id)?;
+        }
+        let folder_id = folder_id.map(str::to_string);
+        self.rewrite_items(token, entry_ids, |item| item.folder_id = folder_id.clone())
+    }
+
+    /// Add and remove tags on many entries of the active profile at once.
+    /// Tags compare case-insensitively; existing spellings are kept.
+    pub fn apply_tags(
+        &mut self,
+        token: &str,
+        entry_ids: &[i64],
+        add: &[String],
+        remove: &[String],
+    ) -> Result<(), String> {
+        let add: Vec<&str> = add.iter().map(|t| t.trim()).filter(|t| !t.is_empty()).collect();
+        let remove: Vec<String> = remove.iter().map(|t| t.trim().to_lowercase()).collect();
+
+        self.rewrite_items(token, entry_ids, |item| {
+            item.tags.retain(|t| !remove.contains(&t.trim().to_lowercase()));
+            for tag in &add {
+                if !item.tags.iter().any(|t| t.trim().to_lowercase() == tag.to_lowercase()) {
+                    item.tags.push(tag.to_string());
+                }
+            }
+        })
+    }
+
+    /// Every tag used in the active profile, sorted, without duplicates
+    pub fn list_tags(&mut self, token: &str) -> Result<Vec<String>, String> {
+        let mut tags: Vec<String> = Vec::new();
+        for stored in self.list_items(token, None)? {
+            for tag in stored.item.tags {
+                let tag = tag.trim().to_string();
+                if !tags.iter().any(|t| t.to_lowercase() == tag.to_lowercase()) {
+                    tags.push(tag);
+                }
+            }
+        }
+        tags.sort_by_key(|t| t.to_lowercase());
+        Ok(tags)
+    }
+
+    /// Apply `change` to each entry and write back the ones that changed.
+    /// Every entry is checked before the first one is written, so a bad ID
+    /// or an invalid result leaves all of them untouched.
+    fn rewrite_items(
+        &mut self,
+        token: &str,
+        entry_ids: &[i64],
+        mut change: impl FnMut(&mut VaultItem),
+    ) -> Result<(), String> {
+        let (key, active_profile) = self.session_context(token)?;
+
+        let mut changed: HashMap<i64, VaultItem> = HashMap::new();
+        for &id in entry_ids {
+            let mut item = self.get_item(token, id)?.item;
+            let before = item.clone();
+            change(&mut item);
+            if item != before {
+                item.validate()?;
+                changed.insert(id, item);
+            }
+        }
+
+        let now = now_iso();
+        for (id, item) in changed {
+            let blob = item.to_blob()?;
+            self.write_entry_content(&key, active_profile, id, item.title.trim(), &blob, &now)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,641 @@
+// Password and passphrase generator.
+//
+// Randomness comes from `rand::thread_rng`, a CSPRNG seeded from the OS, and
+// every pick is a uniform `gen_range`, so the reported entropy follows from
+// the options alone. Each generated value is appended to an encrypted,
+// device-local history so that a password shown in the generator is not lost
+// if the entry is never saved.
+
+use std::sync::OnceLock;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::crypto::{decrypt_aes256_gcm, encrypt_aes256_gcm};
+use crate::store::GeneratedRecord;
+use crate::vault::now_iso;
+use crate::Vault;
+
+pub const MIN_PASSWORD_LENGTH: usize = 4;
+pub const MAX_PASSWORD_LENGTH: usize = 256;
+pub const MIN_PASSPHRASE_WORDS: usize = 3;
+pub const MAX_PASSPHRASE_WORDS: usize = 32;
+const MAX_SEPARATOR_LEN: usize = 8;
+/// Tries to meet `max_consecutive` before giving up
+const MAX_CONSECUTIVE_ATTEMPTS: usize = 1000;
+
+/// Number of generated values kept in the history
+pub const MAX_GENERATOR_HISTORY: usize = 100;
+
+const LOWERCASE: &str = "abcdefghijklmnopqrstuvwxyz";
+const UPPERCASE: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &str = "0123456789";
+const SYMBOLS: &str = "!#$%&()*+,-./:;<=>?@[]^_{|}~";
+/// Characters that are easily confused with each other in many fonts
+const AMBIGUOUS: &str = "Il1|O0o";
+
+/// EFF large wordlist in its dice format (`11111<TAB>abacus`)
+const WORDLIST: &str = include_str!("../data/eff_large_wordlist.txt");
+
+/// Options for `generate_password`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PasswordOptions {
+    pub length: usize,
+    pub lowercase: bool,
+    pub uppercase: bool,
+    pub digits: bool,
+    pub symbols: bool,
+    /// Minimum characters per class; ignored for disabled classes
+    pub min_lowercase: usize,
+    pub min_uppercase: usize,
+    pub min_digits: usize,
+    pub min_symbols: usize,
+    /// Leave out characters such as `l`, `1` and `O` that are easy to misread
+    pub exclude_ambiguous: bool,
+    /// Template such as `Aaaaa-99-##`: `a` lowercase, `A` uppercase, `9`
+    /// digit, `#` symbol, `*` any enabled class, `\` makes the next character
+    /// literal and anything else is copied as is. Overrides `length` and the
+    /// minimum counts.
+    pub pattern: Option<String>,
+    /// Symbols to draw from instead of the default set, for sites that
+    /// accept only some
+    pub symbol_set: Option<String>,
+    /// Longest run of one repeated character; `None` means no limit
+    pub max_consecutive: Option<usize>,
+}
+
+impl Default for PasswordOptions {
+    fn default() -> Self {
+        PasswordOptions {
+            length: 20,
+            lowercase: true,
+            uppercase: true,
+            digits: true,
+            symbols: true,
+            min_lowercase: 1,
+            min_uppercase: 1,
+            min_digits: 1,
+            min_symbols: 1,
+            exclude_ambiguous: false,
+            pattern: None,
+            symbol_set: None,
+            max_consecutive: None,
+        }
+    }
+}
+
+/// Letter case of passphrase words
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Capitalization {
+    #[default]
+    Lowercase,
+    /// First letter of every word
+    Title,
+    Uppercase,
+    /// First letter of each word upper or lower at random (one bit per word)
+    Random,
+}
+
+/// Options for `generate_passphrase`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PassphraseOptions {
+    pub words: usize,
+    pub separator: String,
+    pub capitalization: Capitalization,
+    /// Append a random digit to one random word
+    pub include_number: bool,
+}
+
+impl Default for PassphraseOptions {
+    fn default() -> Self {
+        PassphraseOptions {
+            words: 6,
+            separator: "-".to_string(),
+            capitalization: Capitalization::Lowercase,
+            include_number: false,
+        }
+    }
+}
+
+/// A generated password or passphrase
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeneratedSecret {
+    pub value: String,
+    /// Bits of entropy given the options, assuming an attacker knows them
+    pub entropy_bits: f64,
+    /// Domain whose password rules were followed, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rules_domain: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GeneratedKind {
+    Password,
+    Passphrase,
+}
+
+/// One value from the generator history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeneratedValue {
+    pub id: i64,
+    pub kind: GeneratedKind,
+    pub value: String,
+    pub created_at: String,
+}
+
+/// Encrypted payload of a `generator_history` row
+#[derive(Serialize, Deserialize)]
+struct HistoryData {
+    kind: GeneratedKind,
+    value: String,
+}
+
+impl Drop for HistoryData {
+    fn drop(&mut self) {
+        self.value.zeroize();
+    }
+}
+
+/// Words of the embedded wordlist, parsed on first use
+pub fn wordlist() -> &'static [&'static str] {
+    static WORDS: OnceLock<Vec<&'static str>> = OnceLock::new();
+    WORDS.get_or_init(|| {
+        WORDLIST
+            .lines()
+            .filter_map(|line| line.split('\t').nth(1))
+            .map(str::trim)
+            .filter(|word| !word.is_empty())
+            .collect()
+    })
+}
+
+fn class_chars(set: &str, exclude_ambiguous: bool) -> Vec<char> {
+    set.chars()
+        .filter(|c| !exclude_ambiguous || !AMBIGUOUS.contains(*c))
+        .collect()
+}
+
+/// Generate a random password
+pub fn password(options: &PasswordOptions) -> Result<GeneratedSecret, String> {
+    let lowercase = class_chars(LOWERCASE, options.exclude_ambiguous);
+    let uppercase = class_chars(UPPERCASE, options.exclude_ambiguous);
+    let digits = class_chars(DIGITS, options.exclude_ambiguous);
+    let mut symbols = class_chars(
+        options.symbol_set.as_deref().unwrap_or(SYMBOLS),
+        options.exclude_ambiguous,
+    );
+    symbols.sort_unstable();
+    symbols.dedup();
+    if symbols
+        .iter()
+        .any(|c| c.is_alphanumeric() || c.is_control())
+    {
+        return Err("Symbol set may only contain symbols".to_string());
+    }
+    if options.symbols && symbols.is_empty() {
+        return Err("Symbol set is empty".to_string());
+    }
+    if options.max_consecutive == Some(0) {
+        return Err("Consecutive character limit must be at least 1".to_string());
+    }
+
+    let classes = [
+        (options.lowercase, options.min_lowercase, &lowercase),
+        (options.uppercase, options.min_uppercase, &uppercase),
+        (options.digits, options.min_digits, &digits),
+        (options.symbols, options.min_symbols, &symbols),
+    ];
+    let pool: Vec<char> = classes
+        .iter()
+        .filter(|(enabled, _, _)| *enabled)
+        .flat_map(|(_, _, chars)| chars.iter().copied())
+        .collect();
+    if pool.is_empty() {
+        return Err("Enable at least one character class".to_string());
+    }
+
+    if let Some(pattern) = &options.pattern {
+        return from_pattern(pattern, &lowercase, &uppercase, &digits, &symbols, &pool);
+    }
+
+    if !(MIN_PASSWORD_LENGTH..=MAX_PASSWORD_LENGTH).contains(&options.length) {
+        return Err(format!(
+            "Password length must be between {} and {}",
+            MIN_PASSWORD_LENGTH, MAX_PASSWORD_LENGTH
+        ));
+    }
+    // Minimums of disabled classes are ignored
+    let required: usize = classes
+        .iter()
+        .filter(|(enabled, _, _)| *enabled)
+        .map(|(_, min, _)| min)
+        .sum();
+    if required > options.length {
+        return Err("Minimum counts exceed the password length".to_string());
+    }
+
+    let mut rng = rand::thread_rng();
+    let mut chars: Vec<char> = Vec::with_capacity(options.length);
+    for attempt in 0.. {
+        chars.zeroize();
+        chars.clear();
+        for (_, min, class) in classes.iter().filter(|(enabled, _, _)| *enabled) {
+            for _ in 0..*min {
+                chars.push(class[rng.gen_range(0..class.len())]);
+            }
+        }
+        while chars.len() < options.length {
+            chars.push(pool[rng.gen_range(0..pool.len())]);
+        }
+        chars.shuffle(&mut rng);
+
+        let within_limit = options
+            .max_consecutive
+            .is_none_or(|max| longest_run(&chars) <= max);
+        if within_limit {
+            break;
+        }
+        if attempt + 1 == MAX_CONSECUTIVE_ATTEMPTS {
+            chars.zeroize();
+            return Err("Cannot meet the consecutive character limit".to_string());
+        }
+    }
+
+    let value: String = chars.iter().collect();
+    chars.zeroize();
+    Ok(GeneratedSecret {
+        value,
+        // Minimum counts lower this only marginally
+        entropy_bits: options.length as f64 * (pool.len() as f64).log2(),
+        rules_domain: None,
+    })
+}
+
+/// Length of the longest run of one repeated character
+fn longest_run(chars: &[char]) -> usize {
+    chars
+        .chunk_by(|a, b| a == b)
+        .map(<[char]>::len)
+        .max()
+        .unwrap_or(0)
+}
+
+fn from_pattern(
+    pattern: &str,
+    lowercase: &[char],
+    uppercase: &[char],
+    digits: &[char],
+    symbols: &[char],
+    pool: &[char],
+) -> Result<GeneratedSecret, String> {
+    if pattern.chars().count() > MAX_PASSWORD_LENGTH {
+        return Err(format!(
+            "Pattern must be at most {} characters",
+            MAX_PASSWORD_LENGTH
+        ));
+    }
+
+    let mut rng = rand::thread_rng();
+    let mut value = String::new();
+    let mut entropy_bits = 0.0;
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        let (name, class) = match c {
+            'a' => ("Lowercase", lowercase),
+            'A' => ("Uppercase", uppercase),
+            '9' => ("Digit", digits),
+            '#' => ("Symbol", symbols),
+            '*' => ("Pattern", pool),
+            '\\' => {
+                value.push(chars.next().ok_or("Pattern ends with an escape")?);
+                continue;
+            }
+            literal => {
+                value.push(literal);
+                continue;
+            }
+        };
+        if class.is_empty() {
+            return Err(format!("{} character set is empty", name));
+        }
+        value.push(class[rng.gen_range(0..class.len())]);
+        entropy_bits += (class.len() as f64).log2();
+    }
+
+    if entropy_bits == 0.0 {
+        return Err("Pattern has no random characters".to_string());
+    }
+    Ok(GeneratedSecret {
+        value,
+        entropy_bits,
+        rules_domain: None,
+    })
+}
+
+/// Generate a passphrase from the embedded wordlist
+pub fn passphrase(options: &PassphraseOptions) -> Result<GeneratedSecret, String> {
+    if !(MIN_PASSPHRASE_WORDS..=MAX_PASSPHRASE_WORDS).contains(&options.words) {
+        return Err(format!(
+            "Passphrase must have between {} and {} words",
+            MIN_PASSPHRASE_WORDS, MAX_PASSPHRASE_WORDS
+        ));
+    }
+    if options.separator.chars().count() > MAX_SEPARATOR_LEN {
+        return Err(format!(
+            "Separator must be at most {} characters",
+            MAX_SEPARATOR_LEN
+        ));
+    }
+
+    let list = wordlist();
+    let mut rng = rand::thread_rng();
+    let mut words: Vec<String> = (0..options.words)
+        .map(|_| {
+            let word = list[rng.gen_range(0..list.len())];
+            match options.capitalization {
+                Capitalization::Lowercase => word.to_string(),
+                Capitalization::Uppercase => word.to_uppercase(),
+                Capitalization::Title => capitalize(word),
+                Capitalization::Random if rng.gen_bool(0.5) => capitalize(word),
+                Capitalization::Random => word.to_string(),
+            }
+        })
+        .collect();
+
+    let mut entropy_bits = options.words as f64 * (list.len() as f64).log2();
+    if options.capitalization == Capitalization::Random {
+        entropy_bits += options.words as f64;
+    }
+    if options.include_number {
+        let index = rng.gen_range(0..words.len());
+        let digit = rng.gen_range(0..10u8);
+        words[index].push(char::from(b'0' + digit));
+        entropy_bits += (10.0 * options.words as f64).log2();
+    }
+
+    let value = words.join(&options.separator);
+    words.zeroize();
+    Ok(GeneratedSecret {
+        value,
+        entropy_bits,
+        rules_domain: None,
+    })
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+impl Vault {
+    /// Generate a password and remember it in the generator history
+    pub fn generate_password(
+        &mut self,
+        token: &str,
+        options: &PasswordOptions,
+    ) -> Result<GeneratedSecret, String> {
+        let key = self.validate_session(token)?;
+        let generated = password(options)?;
+        self.remember_generated(&key, GeneratedKind::Password, &generated.value)?;
+        Ok(generated)
+    }
+
+    /// Generate a passphrase and remember it in the generator history
+    pub fn generate_passphrase(
+        &mut self,
+        token: &str,
+        options: &PassphraseOptions,
+    ) -> Result<GeneratedSecret, String> {
+        let key = self.validate_session(token)?;
+        let generated = passphrase(options)?;
+        self.remember_generated(&key, GeneratedKind::Passphrase, &generated.value)?;
+        Ok(generated)
+    }
+
+    /// Previously generated values, newest first. Rows that no longer
+    /// decrypt are skipped.
+    pub fn generator_history(&mut self, token: &str) -> Result<Vec<GeneratedValue>, String> {
+        let key = self.validate_session(token)?;
+        Ok(self
+            .store
+            .list_generated()?
+            .into_iter()
+            .filter_map(|record| {
+                let mut plaintext =
+                    decrypt_aes256_gcm(&key, &record.data_blob, &record.nonce).ok()?;
+                let data: Option<HistoryData> = serde_json::from_slice(&plaintext).ok();
+                plaintext.zeroize();
+                let data = data?;
+                Some(GeneratedValue {
+                    id: record.id,
+                    kind: data.kind,
+                    value: data.value.clone(),
+                    created_at: record.created_at,
+                })
+            })
+            .collect())
+    }
+
+    pub fn clear_generator_history(&mut self, token: &str) -> Result<(), String> {
+        self.validate_session(token)?;
+        let ids: Vec<i64> = self.store.list_generated()?.iter().map(|r| r.id).collect();
+        self.store.delete_generated(&ids)?;
+        Ok(())
+    }
+
+    fn remember_generated(
+        &mut self,
+        key: &[u8; 32],
+        kind: GeneratedKind,
+        value: &str,
+    ) -> Result<(), String> {
+        let data = HistoryData {
+            kind,
+            value: value.to_string(),
+        };
+        let mut plaintext = serde_json::to_vec(&data).map_err(|e| e.to_string())?;
+        let encrypted = encrypt_aes256_gcm(key, &plaintext);
+        plaintext.zeroize();
+        let (data_blob, nonce) = encrypted?;
+
+        self.store.append_generated(&GeneratedRecord {
+            id: 0,
+            data_blob,
+            nonce,
+            created_at: now_iso(),
+        })?;
+
+        let expired: Vec<i64> = self
+            .store
+            .list_generated()?
+            .iter()
+            .skip(MAX_GENERATOR_HISTORY)
+            .map(|r| r.id)
+            .collect();
+        if !expired.is_empty() {
+            self.store.delete_generated(&expired)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wordlist() {
+        let words = wordlist();
+        assert_eq!(words.len(), 7776);
+        let unique: std::collections::HashSet<_> = words.iter().collect();
+        assert_eq!(unique.len(), words.len());
+        assert!(words
+            .iter()
+            .all(|w| w.chars().all(|c| c.is_ascii_lowercase() || c == '-')));
+    }
+
+    #[test]
+    fn test_password_classes_and_minimums() {
+        let options = PasswordOptions {
+            length: 12,
+            symbols: false,
+            min_symbols: 0,
+            min_digits: 4,
+            exclude_ambiguous: true,
+            ..PasswordOptions::default()
+        };
+        for _ in 0..50 {
+            let generated = password(&options).unwrap();
+            let value = &generated.value;
+            assert_eq!(value.chars().count(), 12);
+            assert!(value.chars().all(|c| c.is_ascii_alphanumeric()));
+            assert!(value.chars().filter(|c| c.is_ascii_digit()).count() >= 4);
+            assert!(value.chars().any(|c| c.is_ascii_lowercase()));
+            assert!(value.chars().any(|c| c.is_ascii_uppercase()));
+            assert!(!value.chars().any(|c| AMBIGUOUS.contains(c)));
+        }
+
+        let too_many = PasswordOptions {
+            length: 4,
+            min_digits: 4,
+            ..PasswordOptions::default()
+        };
+        assert!(password(&too_many).is_err());
+        let no_digits = PasswordOptions {
+            digits: false,
+            ..PasswordOptions::default()
+        };
+        let value = password(&no_digits).unwrap().value;
+        assert!(!value.chars().any(|c| c.is_ascii_digit()));
+    }
+
+    #[test]
+    fn test_password_entropy() {
+        let options = PasswordOptions {
+            length: 10,
+            uppercase: false,
+            digits: false,
+            symbols: false,
+            min_uppercase: 0,
+            min_digits: 0,
+            min_symbols: 0,
+            ..PasswordOptions::default()
+        };
+        let generated = password(&options).unwrap();
+        assert!((generated.entropy_bits - 10.0 * 26f64.log2()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_pattern() {
+        let options = PasswordOptions {
+            pattern: Some(r"Aaaa-99\#\9".to_string()),
+            ..PasswordOptions::default()
+        };
+        let generated = password(&options).unwrap();
+        let chars: Vec<char> = generated.value.chars().collect();
+        assert_eq!(chars.len(), 9);
+        assert!(chars[0].is_ascii_uppercase());
+        assert!(chars[1..4].iter().all(|c| c.is_ascii_lowercase()));
+        assert_eq!(chars[4], '-');
+        assert!(chars[5..7].iter().all(|c| c.is_ascii_digit()));
+        assert_eq!(&generated.value[7..], "#9");
+        let expected = 26f64.log2() * 4.0 + 10f64.log2() * 2.0;
+        assert!((generated.entropy_bits - expected).abs() < 1e-9);
+
+        let literal_only = PasswordOptions {
+            pattern: Some("--".to_string()),
+            ..PasswordOptions::default()
+        };
+        assert!(password(&literal_only).is_err());
+    }
+
+    #[test]
+    fn test_pattern_with_empty_symbol_set() {
+        let empty_set = PasswordOptions {
+            symbols: false,
+            symbol_set: Some(String::new()),
+            pattern: Some("aa#".to_string()),
+            ..PasswordOptions::default()
+        };
+        assert_eq!(
+            password(&empty_set).unwrap_err(),
+            "Symbol character set is empty"
+        );
+
+        // '|' is ambiguous, so nothing is left of the set
+        let ambiguous_only = PasswordOptions {
+            symbols: false,
+            symbol_set: Some("|".to_string()),
+            exclude_ambiguous: true,
+            pattern: Some("aa#".to_string()),
+            ..PasswordOptions::default()
+        };
+        assert_eq!(
+            password(&ambiguous_only).unwrap_err(),
+            "Symbol character set is empty"
+        );
+    }
+
+    #[test]
+    fn test_passphrase() {
+        let options = PassphraseOptions {
+            words: 5,
+            separator: ".".to_string(),
+            capitalization: Capitalization::Title,
+            include_number: true,
+        };
+        let generated = passphrase(&options).unwrap();
+        let words: Vec<&str> = generated.value.split('.').collect();
+        assert_eq!(words.len(), 5);
+        assert!(words
+            .iter()
+            .all(|w| w.chars().next().unwrap().is_ascii_uppercase()));
+        assert_eq!(
+            generated
+                .value
+                .chars()
+                .filter(|c| c.is_ascii_digit())
+                .count(),
+            1
+        );
+        let expected = 5.0 * 7776f64.log2() + 50f64.log2();
+        assert!((generated.entropy_bits - expected).abs() < 1e-9);
+
+        let too_short = PassphraseOptions {
+            words: 2,
+            ..PassphraseOptions::default()
+        };
+        assert!(passphrase(&too_short).is_err());
+    }
+}
@@ -0,0 +1,475 @@
+// Vault health report ("watchtower").
+//
+// The report decrypts the entries in memory, compares their passwords and
+// returns only entry references and findings: no password, not even a
+// hash of one, is part of the result, and nothing of it is stored. The
+// frontend asks for a fresh report whenever it shows one.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Months, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::item::VaultItem;
+use crate::item_types::ItemType;
+use crate::listing::favicon_domain;
+use crate::recovery_codes::RecoveryCodeStatus;
+use crate::strength::estimate_strength;
+use crate::vault::{decrypt_item, now_iso};
+use crate::Vault;
+
+/// Passwords this many months old or older are reported as stale
+pub const DEFAULT_STALE_AFTER_MONTHS: u32 = 12;
+pub const MAX_STALE_AFTER_MONTHS: u32 = 120;
+
+/// Passwords scoring below this are reported as weak
+const WEAK_SCORE: u8 = 3;
+/// Largest edit distance between two passwords that still counts as a
+/// near-duplicate
+const NEAR_DUPLICATE_DISTANCE: usize = 2;
+/// Passwords shorter than this are never near-duplicates of each other;
+/// any two short passwords are only a few edits apart
+const NEAR_DUPLICATE_MIN_LENGTH: usize = 8;
+
+/// Which entries `health_report` checks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HealthScope {
+    #[default]
+    ActiveProfile,
+    AllProfiles,
+}
+
+/// Options for `health_report`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HealthOptions {
+    pub scope: HealthScope,
+    pub stale_after_months: u32,
+}
+
+impl Default for HealthOptions {
+    fn default() -> Self {
+        HealthOptions {
+            scope: HealthScope::ActiveProfile,
+            stale_after_months: DEFAULT_STALE_AFTER_MONTHS,
+        }
+    }
+}
+
+/// How urgent a finding is, most urgent first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    Critical,
+    High,
+    Medium,
+    Low,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HealthIssueKind {
+    /// The same password is used by several entries
+    Reused,
+    /// Passwords of several entries differ only slightly
+    NearDuplicate,
+    Weak,
+    /// Not changed for `stale_after_months`
+    Stale,
+    /// A login without a TOTP secret
+    MissingTotp,
+    /// At most `LOW_RECOVERY_CODES` unused recovery codes are left
+    LowRecoveryCodes,
+}
+
+/// An entry a finding refers to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthEntryRef {
+    pub id: i64,
+    pub entry_uuid: Option<String>,
+    pub profile_id: i64,
+    pub title: String,
+    pub username: Option<String>,
+}
+
+/// One finding about one or more entries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthIssue {
+    pub kind: HealthIssueKind,
+    pub severity: Severity,
+    pub entries: Vec<HealthEntryRef>,
+    /// Human-readable explanation, e.g. "Used by 3 entries"
+    pub detail: String,
+}
+
+/// The findings of one severity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthGroup {
+    pub severity: Severity,
+    pub issues: Vec<HealthIssue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReport {
+    pub generated_at: String,
+    pub scope: HealthScope,
+    pub entries_checked: usize,
+    /// Entries without any finding
+    pub healthy_entries: usize,
+    /// Non-empty groups, most severe first
+    pub groups: Vec<HealthGroup>,
+}
+
+/// A decrypted entry with a password, as far as the report needs it
+struct Checked {
+    entry: HealthEntryRef,
+    item: VaultItem,
+    /// When the current password was set
+    password_set_at: String,
+}
+
+impl Drop for Checked {
+    fn drop(&mut self) {
+        if let Some(password) = self.item.password.as_mut() {
+            password.zeroize();
+        }
+    }
+}
+
+fn parse_timestamp(ts: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(ts)
+        .map(|t| t.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(ts, "%Y-%m-%d %H:%M:%S")
+                .ok()
+                .map(|t| t.and_utc())
+        })
+}
+
+/// Edit distance of `a` and `b`, or `None` once it exceeds `max`
+fn bounded_distance(a: &[char], b: &[char], max: usize) -> Option<usize> {
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        if current.iter().min().is_some_and(|m| *m > max) {
+            return None;
+        }
+        previous = current;
+    }
+    previous.last().copied().filter(|d| *d <= max)
+}
+
+/// Whether two different passwords are close variants of each other:
+/// equal ignoring case, or a few edits apart
+fn near_duplicates(a: &str, b: &str) -> bool {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    if a.len().min(b.len()) < NEAR_DUPLICATE_MIN_LENGTH {
+        return false;
+    }
+    let lower = |s: &[char]| -> Vec<char> { s.iter().flat_map(|c| c.to_lowercase()).collect() };
+    lower(&a) == lower(&b) || bounded_distance(&a, &b, NEAR_DUPLICATE_DISTANCE).is_some()
+}
+
+fn find(parent: &mut [usize], i: usize) -> usize {
+    let mut root = i;
+    while parent[root] != root {
+        root = parent[root];
+    }
+    parent[i] = root;
+    root
+}
+
+fn reused_issues(checked: &[Checked]) -> Vec<HealthIssue> {
+    let mut by_password: HashMap<&str, Vec<&HealthEntryRef>> = HashMap::new();
+    for c in checked {
+        if let Some(password) = c.item.password.as_deref() {
+            by_password.entry(password).or_default().push(&c.entry);
+        }
+    }
+    let mut issues: Vec<HealthIssue> = by_password
+        .into_values()
+        .filter(|entries| entries.len() > 1)
+        .map(|entries| HealthIssue {
+            kind: HealthIssueKind::Reused,
+            severity: Severity::High,
+            detail: format!("Used by {} entries", entries.len()),
+            entries: entries.into_iter().cloned().collect(),
+        })
+        .collect();
+    issues.sort_by_key(|issue| std::cmp::Reverse(issue.entries.len()));
+    issues
+}
+
+fn near_duplicate_issues(checked: &[Checked]) -> Vec<HealthIssue> {
+    // Compare distinct passwords only; identical ones are reported as reused
+    let mut distinct: Vec<&str> = checked
+        .iter()
+        .filter_map(|c| c.item.password.as_deref())
+        .collect();
+    distinct.sort_unstable();
+    distinct.dedup();
+
+    let mut parent: Vec<usize> = (0..distinct.len()).collect();
+    for i in 0..distinct.len() {
+        for j in i + 1..distinct.len() {
+            if near_duplicates(distinct[i], distinct[j]) {
+                let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                parent[a] = b;
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..distinct.len() {
+        let root = find(&mut parent, i);
+        groups.entry(root).or_default().push(i);
+    }
+
+    let mut issues: Vec<HealthIssue> = groups
+        .into_values()
+        .filter(|members| members.len() > 1)
+        .map(|members| {
+            let passwords: Vec<&str> = members.iter().map(|i| distinct[*i]).collect();
+            let entries: Vec<HealthEntryRef> = checked
+                .iter()
+                .filter(|c| c.item.password.as_deref().is_some_and(|p| passwords.contains(&p)))
+                .map(|c| c.entry.clone())
+                .collect();
+            HealthIssue {
+                kind: HealthIssueKind::NearDuplicate,
+                severity: Severity::Medium,
+                detail: format!("{} entries use variations of the same password", entries.len()),
+                entries,
+            }
+        })
+        .collect();
+    issues.sort_by_key(|issue| std::cmp::Reverse(issue.entries.len()));
+    issues
+}
+
+fn weak_issue(c: &Checked) -> Option<HealthIssue> {
+    let password = c.item.password.as_deref()?;
+    let domains: Vec<String> = c.item.urls.iter().filter_map(|u| favicon_domain(u)).collect();
+    let mut inputs: Vec<&str> = vec![c.item.title.as_str()];
+    inputs.extend(c.item.username.as_deref());
+    inputs.extend(domains.iter().map(String::as_str));
+
+    let estimate = estimate_strength(password, &inputs);
+    if estimate.score >= WEAK_SCORE {
+        return None;
+    }
+    Some(HealthIssue {
+        kind: HealthIssueKind::Weak,
+        severity: if estimate.score == 0 {
+            Severity::Critical
+        } else {
+            Severity::High
+        },
+        entries: vec![c.entry.clone()],
+        detail: match estimate.warning {
+            Some(warning) => format!("Cracked in {}: {}", estimate.crack_time_display, warning),
+            None => format!("Cracked in {}", estimate.crack_time_display),
+        },
+    })
+}
+
+fn stale_issue(c: &Checked, cutoff: DateTime<Utc>, months: u32) -> Option<HealthIssue> {
+    let set_at = parse_timestamp(&c.password_set_at)?;
+    if set_at > cutoff {
+        return None;
+    }
+    Some(HealthIssue {
+        kind: HealthIssueKind::Stale,
+        severity: Severity::Medium,
+        entries: vec![c.entry.clone()],
+        detail: format!(
+            "Not changed for more than {} months (since {})",
+            months,
+            set_at.format("%Y-%m-%d")
+        ),
+    })
+}
+
+fn missing_totp_issue(c: &Checked) -> Option<HealthIssue> {
+    let has_totp = c
+        .item
+        .totp_secret
+        .as_deref()
+        .is_some_and(|s| !s.trim().is_empty());
+    if c.item.item_type != ItemType::Login || has_totp {
+        return None;
+    }
+    Some(HealthIssue {
+        kind: HealthIssueKind::MissingTotp,
+        severity: Severity::Low,
+        entries: vec![c.entry.clone()],
+        detail: "Two-factor authentication is not set up".to_string(),
+    })
+}
+
+fn low_recovery_codes_issue(c: &Checked) -> Option<HealthIssue> {
+    let status = RecoveryCodeStatus::of(&c.item);
+    if !status.running_low {
+        return None;
+    }
+    Some(HealthIssue {
+        kind: HealthIssueKind::LowRecoveryCodes,
+        severity: if status.remaining == 0 {
+            Severity::High
+        } else {
+            Severity::Medium
+        },
+        entries: vec![c.entry.clone()],
+        detail: match status.remaining {
+            0 => "All recovery codes have been used".to_string(),
+            1 => format!("Only 1 of {} recovery codes left", status.total),
+            n => format!("Only {} of {} recovery codes left", n, status.total),
+        },
+    })
+}
+
+impl Vault {
+    /// Check the passwords of the active profile, or of all profiles, for
+    /// reuse, near-duplicates, weakness and age, and logins for a missing
+    /// TOTP secret. Entries that are running out of recovery codes are
+    /// reported too. Reuse is detected across every profile in scope.
+    pub fn health_report(
+        &mut self,
+        token: &str,
+        options: &HealthOptions,
+    ) -> Result<HealthReport, String> {
+        let (key, active_profile) = self.session_context(token)?;
+        if !(1..=MAX_STALE_AFTER_MONTHS).contains(&options.stale_after_months) {
+            return Err(format!(
+                "Stale age must be between 1 and {} months",
+                MAX_STALE_AFTER_MONTHS
+            ));
+        }
+
+        let profiles = match options.scope {
+            HealthScope::ActiveProfile => vec![active_profile],
+            HealthScope::AllProfiles => self.store.list_profiles()?.iter().map(|p| p.id).collect(),
+        };
+
+        let mut checked = Vec::new();
+        for profile_id in profiles {
+            for record in self.store.list_entries(profile_id)? {
+                // Entries that do not decrypt are left out rather than
+                // failing the whole report
+                let item = match decrypt_item(&key, &record) {
+                    Ok(item) => item,
+                    Err(_) => continue,
+                };
+                let password_set_at = item
+                    .password_history
+                    .first()
+                    .map_or_else(|| record.created_at.clone(), |h| h.replaced_at.clone());
+                checked.push(Checked {
+                    entry: HealthEntryRef {
+                        id: record.id,
+                        entry_uuid: record.entry_uuid.clone(),
+                        profile_id: record.profile_id,
+                        title: item.title.clone(),
+                        username: item.username.clone(),
+                    },
+                    item,
+                    password_set_at,
+                });
+            }
+        }
+        let entries_checked = checked.len();
+
+        // Recovery codes are checked on every entry; only entries with a
+        // password take part in the password checks
+        let low_recovery_codes: Vec<HealthIssue> =
+            checked.iter().filter_map(low_recovery_codes_issue).collect();
+        let mut missing_totp = Vec::new();
+        for c in &checked {
+            if c.item.password.as_deref().is_none_or(str::is_empty) {
+                continue;
+            }
+            missing_totp.extend(missing_totp_issue(c));
+        }
+        checked.retain(|c| c.item.password.as_deref().is_some_and(|p| !p.is_empty()));
+
+        let cutoff = Utc::now()
+            .checked_sub_months(Months::new(options.stale_after_months))
+            .unwrap_or_else(Utc::now);
+        let mut issues = reused_issues(&checked);
+        issues.extend(near_duplicate_issues(&checked));
+        issues.extend(checked.iter().filter_map(weak_issue));
+        issues.extend(
+            checked
+                .iter()
+                .filter_map(|c| stale_issue(c, cutoff, options.stale_after_months)),
+        );
+        issues.extend(missing_totp);
+        issues.extend(low_recovery_codes);
+
+        let mut flagged: Vec<i64> = issues
+            .iter()
+            .flat_map(|issue| issue.entries.iter().map(|e| e.id))
+            .collect();
+        flagged.sort_unstable();
+        flagged.dedup();
+
+        let mut groups: Vec<HealthGroup> = Vec::new();
+        for severity in [Severity::Critical, Severity::High, Severity::Medium, Severity::Low] {
+            let of_severity: Vec<HealthIssue> = issues
+                .iter()
+                .filter(|issue| issue.severity == severity)
+                .cloned()
+                .collect();
+            if !of_severity.is_empty() {
+                groups.push(HealthGroup {
+                    severity,
+                    issues: of_severity,
+                });
+            }
+        }
+
+        Ok(HealthReport {
+            generated_at: now_iso(),
+            scope: options.scope,
+            entries_checked,
+            healthy_entries: entries_checked - flagged.len(),
+            groups,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_near_duplicates() {
+        assert!(near_duplicates("Summer2023!xy", "Summer2024!xy"));
+        assert!(near_duplicates("correcthorse", "CorrectHorse"));
+        assert!(near_duplicates("tr0ub4dor&3", "tr0ub4dor&3!!"));
+        assert!(!near_duplicates("abc123", "abc124"));
+        assert!(!near_duplicates("kT9#vq2$Lm8!", "pX3&wz7^Rn1@"));
+        assert_eq!(bounded_distance(&['a', 'b'], &['a', 'b', 'c', 'd', 'e'], 2), None);
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert!(parse_timestamp("2024-05-01T10:00:00+00:00").is_some());
+        assert!(parse_timestamp("2024-05-01 10:00:00").is_some());
+        assert!(parse_timestamp("yesterday").is_none());
+    }
+}
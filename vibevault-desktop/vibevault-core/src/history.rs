@@ -0,0 +1,171 @@
+use serde::{Deserialize, Serialize};
+
+use crate::item::VaultItem;
+use crate::store::VaultStore;
+use crate::vault::now_iso;
+use crate::Vault;
+
+/// Default number of previous passwords kept per entry
+pub const DEFAULT_PASSWORD_HISTORY_DEPTH: usize = 10;
+
+/// Upper bound for the configurable depth
+pub const MAX_PASSWORD_HISTORY_DEPTH: usize = 100;
+
+const PASSWORD_HISTORY_DEPTH_SETTING: &str = "password_history_depth";
+
+/// A password an entry used before, newest first in `VaultItem::password_history`.
+///
+/// History lives inside the item's encrypted blob, so it is encrypted with
+/// the vault key and travels with the entry during sync.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasswordHistoryEntry {
+    pub password: String,
+    /// When this password was replaced (RFC 3339)
+    pub replaced_at: String,
+}
+
+/// Carry `old`'s history over to `new` and, if the password changed, push
+/// the old password on top. Callers that round-trip the history keep it as
+/// sent; callers that omit it (legacy blobs) inherit the stored one.
+pub(crate) fn record_password_change(
+    old: &VaultItem,
+    new: &mut VaultItem,
+    now: &str,
+    depth: usize,
+) {
+    if new.password_history.is_empty() {
+        new.password_history = old.password_history.clone();
+    }
+
+    if let Some(previous) = old.password.as_deref().filter(|p| !p.is_empty()) {
+        if new.password.as_deref() != Some(previous) {
+            new.password_history.insert(
+                0,
+                PasswordHistoryEntry {
+                    password: previous.to_string(),
+                    replaced_at: now.to_string(),
+                },
+            );
+        }
+    }
+
+    new.password_history.truncate(depth);
+}
+
+/// The depth chosen on this device, or the default if none was chosen
+pub(crate) fn stored_password_history_depth(db: &dyn VaultStore) -> usize {
+    db.get_setting(PASSWORD_HISTORY_DEPTH_SETTING)
+        .ok()
+        .flatten()
+        .and_then(|depth| depth.parse().ok())
+        .filter(|depth| *depth <= MAX_PASSWORD_HISTORY_DEPTH)
+        .unwrap_or(DEFAULT_PASSWORD_HISTORY_DEPTH)
+}
+
+impl Vault {
+    pub fn password_history_depth(&self) -> usize {
+        self.password_history_depth
+    }
+
+    /// Change how many previous passwords are kept per entry; 0 disables
+    /// history. Existing histories are trimmed on their next update. The
+    /// depth is stored with the vault and read back on unlock.
+    pub fn set_password_history_depth(&mut self, token: &str, depth: usize) -> Result<(), String> {
+        self.validate_session(token)?;
+        if depth > MAX_PASSWORD_HISTORY_DEPTH {
+            return Err(format!(
+                "History depth must be at most {}",
+                MAX_PASSWORD_HISTORY_DEPTH
+            ));
+        }
+        self.store
+            .set_setting(PASSWORD_HISTORY_DEPTH_SETTING, &depth.to_string())?;
+        self.password_history_depth = depth;
+        Ok(())
+    }
+
+    /// Previous passwords of an entry of the active profile, newest first
+    pub fn password_history(
+        &mut self,
+        token: &str,
+        id: i64,
+    ) -> Result<Vec<PasswordHistoryEntry>, String> {
+        Ok(self.get_item(token, id)?.item.password_history)
+    }
+
+    /// Make history item `index` the current password again. The password
+    /// being replaced goes to the top of the history, so a restore can
+    /// itself be undone.
+    pub fn restore_password(&mut self, token: &str, id: i64, index: usize) -> Result<(), String> {
+        let (key, active_profile) = self.session_context(token)?;
+        let mut item = self.get_item(token, id)?.item;
+        if index >= item.password_history.len() {
+            return Err("Password history item not found".to_string());
+        }
+
+        let now = now_iso();
+        let restored = item.password_history.remove(index);
+        if let Some(current) = item.password.take().filter(|p| !p.is_empty()) {
+            item.password_history.insert(
+                0,
+                PasswordHistoryEntry {
+                    password: current,
+                    replaced_at: now.clone(),
+                },
+            );
+        }
+        item.password = Some(restored.password);
+        item.password_history.truncate(self.password_history_depth);
+
+        item.validate()?;
+        let blob = item.to_blob()?;
+        self.write_entry_content(&key, active_profile, id, item.title.trim(), &blob, &now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn login(password: &str) -> VaultItem {
+        let mut item = VaultItem::new("GitHub");
+        item.password = Some(password.to_string());
+        item
+    }
+
+    #[test]
+    fn test_change_pushes_previous_password() {
+        let old = login("first");
+        let mut new = login("second");
+        record_password_change(&old, &mut new, "t1", 10);
+        assert_eq!(new.password_history.len(), 1);
+        assert_eq!(new.password_history[0].password, "first");
+
+        // Unchanged password leaves history alone
+        let old = new.clone();
+        let mut same = login("second");
+        record_password_change(&old, &mut same, "t2", 10);
+        assert_eq!(same.password_history, old.password_history);
+    }
+
+    #[test]
+    fn test_depth_trims_oldest() {
+        let mut item = login("p0");
+        for i in 1..=5 {
+            let mut next = login(&format!("p{}", i));
+            record_password_change(&item, &mut next, "t", 3);
+            item = next;
+        }
+        let kept: Vec<_> = item
+            .password_history
+            .iter()
+            .map(|h| h.password.as_str())
+            .collect();
+        assert_eq!(kept, ["p4", "p3", "p2"]);
+
+        let mut disabled = login("p6");
+        record_password_change(&item, &mut disabled, "t", 0);
+        assert!(disabled.password_history.is_empty());
+    }
+}
@@ -0,0 +1,402 @@
+use serde::{Deserialize, Serialize};
+
+use crate::history::{PasswordHistoryEntry, MAX_PASSWORD_HISTORY_DEPTH};
+use crate::item_types::{
+    ApiCredentialDetails, CardDetails, IdentityDetails, ItemType, SshKeyDetails, WifiDetails,
+};
+use crate::recovery_codes::{validate_recovery_codes, RecoveryCode};
+
+/// Current schema version written into every encrypted item
+pub const VAULT_ITEM_VERSION: u32 = 1;
+
+const MAX_TITLE_LEN: usize = 256;
+const MAX_FIELD_LEN: usize = 4096;
+const MAX_NOTES_LEN: usize = 65536;
+const MAX_URLS: usize = 32;
+const MAX_CUSTOM_FIELDS: usize = 64;
+const MAX_TAGS: usize = 64;
+const MAX_TAG_LEN: usize = 64;
+const MAX_FOLDER_ID_LEN: usize = 64;
+
+/// A user-defined extra field on an item
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomField {
+    pub name: String,
+    pub value: String,
+    /// Masked in the UI like a password
+    #[serde(default)]
+    pub hidden: bool,
+}
+
+/// The decrypted content of a vault entry.
+///
+/// Serialized as JSON into the encrypted `data_blob`. Field names stay
+/// compatible with the legacy `{username, password, totpSecret, notes}`
+/// blobs the frontend and iOS app already read. Non-login types carry
+/// their own fields in the section matching `item_type`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultItem {
+    #[serde(default = "current_version")]
+    pub version: u32,
+    #[serde(rename = "type", default)]
+    pub item_type: ItemType,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    /// Previous passwords, newest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub password_history: Vec<PasswordHistoryEntry>,
+    #[serde(default)]
+    pub urls: Vec<String>,
+    /// Base32 TOTP secret or `otpauth://` URI
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub totp_secret: Option<String>,
+    /// Single-use two-factor backup codes, in the order they were added
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recovery_codes: Vec<RecoveryCode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub custom_fields: Vec<CustomField>,
+    /// Free-form labels used for filtering and search
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Sync UUID of the folder holding this item; `None` = profile root
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub folder_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub card: Option<CardDetails>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity: Option<IdentityDetails>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh_key: Option<SshKeyDetails>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_credential: Option<ApiCredentialDetails>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wifi: Option<WifiDetails>,
+}
+
+fn current_version() -> u32 {
+    VAULT_ITEM_VERSION
+}
+
+/// A typed item together with its storage metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredItem {
+    pub id: i64,
+    pub entry_uuid: Option<String>,
+    pub profile_id: i64,
+    pub created_at: String,
+    pub updated_at: String,
+    pub item: VaultItem,
+}
+
+impl VaultItem {
+    /// An empty login item with the current schema version
+    pub fn new(title: &str) -> Self {
+        Self::with_type(title, ItemType::Login)
+    }
+
+    /// An empty item of `item_type`; the caller fills in its section
+    pub fn with_type(title: &str, item_type: ItemType) -> Self {
+        VaultItem {
+            version: VAULT_ITEM_VERSION,
+            item_type,
+            title: title.to_string(),
+            username: None,
+            password: None,
+            password_history: Vec::new(),
+            urls: Vec::new(),
+            totp_secret: None,
+            recovery_codes: Vec::new(),
+            notes: None,
+            custom_fields: Vec::new(),
+            tags: Vec::new(),
+            folder_id: None,
+            card: None,
+            identity: None,
+            ssh_key: None,
+            api_credential: None,
+            wifi: None,
+        }
+    }
+
+    /// Parse a decrypted blob. Legacy blobs (no `version`) are upgraded in
+    /// place, taking their title from the entry's `uuid` column; blobs that
+    /// are not JSON at all are kept as notes so nothing is lost.
+    pub fn from_blob(title: &str, blob: &[u8]) -> Result<Self, String> {
+        if let Ok(mut item) = serde_json::from_slice::<VaultItem>(blob) {
+            if item.version > VAULT_ITEM_VERSION {
+                return Err(format!(
+                    "Item version {} is newer than supported version {}",
+                    item.version, VAULT_ITEM_VERSION
+                ));
+            }
+            if item.version == 0 {
+                item.version = VAULT_ITEM_VERSION;
+            }
+            if item.title.is_empty() {
+                item.title = title.to_string();
+            }
+            return Ok(item);
+        }
+
+        let text = std::str::from_utf8(blob).map_err(|_| "Entry is not valid UTF-8")?;
+        let mut item = VaultItem::new(title);
+        if !text.trim().is_empty() {
+            item.notes = Some(text.to_string());
+        }
+        Ok(item)
+    }
+
+    /// Serialize for encryption
+    pub fn to_blob(&self) -> Result<Vec<u8>, String> {
+        serde_json::to_vec(self).map_err(|e| e.to_string())
+    }
+
+    /// Check field lengths and formats before the item is stored
+    pub fn validate(&self) -> Result<(), String> {
+        if self.version != VAULT_ITEM_VERSION {
+            return Err(format!("Unsupported item version {}", self.version));
+        }
+
+        let title = self.title.trim();
+        if title.is_empty() {
+            return Err("Title is required".to_string());
+        }
+        if title.chars().count() > MAX_TITLE_LEN {
+            return Err(format!("Title must be at most {} characters", MAX_TITLE_LEN));
+        }
+
+        for (name, value) in [("Username", &self.username), ("Password", &self.password)] {
+            if let Some(v) = value {
+                if v.chars().count() > MAX_FIELD_LEN {
+                    return Err(format!("{} must be at most {} characters", name, MAX_FIELD_LEN));
+                }
+            }
+        }
+
+        if self.password_history.len() > MAX_PASSWORD_HISTORY_DEPTH {
+            return Err(format!(
+                "At most {} history items are allowed",
+                MAX_PASSWORD_HISTORY_DEPTH
+            ));
+        }
+        if self
+            .password_history
+            .iter()
+            .any(|h| h.password.chars().count() > MAX_FIELD_LEN)
+        {
+            return Err(format!("Password must be at most {} characters", MAX_FIELD_LEN));
+        }
+
+        if self.urls.len() > MAX_URLS {
+            return Err(format!("At most {} URLs are allowed", MAX_URLS));
+        }
+        for url in &self.urls {
+            validate_url(url)?;
+        }
+
+        if let Some(secret) = &self.totp_secret {
+            if !secret.trim().is_empty() {
+                crate::totp::parse_totp(secret)?;
+            }
+        }
+        validate_recovery_codes(&self.recovery_codes)?;
+
+        if let Some(notes) = &self.notes {
+            if notes.chars().count() > MAX_NOTES_LEN {
+                return Err(format!("Notes must be at most {} characters", MAX_NOTES_LEN));
+            }
+        }
+
+        if self.custom_fields.len() > MAX_CUSTOM_FIELDS {
+            return Err(format!("At most {} custom fields are allowed", MAX_CUSTOM_FIELDS));
+        }
+        for field in &self.custom_fields {
+            if field.name.trim().is_empty() {
+                return Err("Custom field name is required".to_string());
+            }
+            if field.value.chars().count() > MAX_FIELD_LEN {
+                return Err(format!(
+                    "Custom field '{}' must be at most {} characters",
+                    field.name, MAX_FIELD_LEN
+                ));
+            }
+        }
+
+        if self.tags.len() > MAX_TAGS {
+            return Err(format!("At most {} tags are allowed", MAX_TAGS));
+        }
+        for tag in &self.tags {
+            let tag = tag.trim();
+            if tag.is_empty() {
+                return Err("Tag cannot be empty".to_string());
+            }
+            if tag.chars().count() > MAX_TAG_LEN {
+                return Err(format!("Tags must be at most {} characters", MAX_TAG_LEN));
+            }
+        }
+
+        if let Some(folder_id) = &self.folder_id {
+            if folder_id.trim().is_empty() || folder_id.len() > MAX_FOLDER_ID_LEN {
+                return Err("Invalid folder".to_string());
+            }
+        }
+
+        self.validate_type_section()
+    }
+
+    /// The section for `item_type` must be present and valid, and no other
+    /// type's section may be set
+    fn validate_type_section(&self) -> Result<(), String> {
+        let sections = [
+            (ItemType::Card, self.card.is_some()),
+            (ItemType::Identity, self.identity.is_some()),
+            (ItemType::SshKey, self.ssh_key.is_some()),
+            (ItemType::ApiCredential, self.api_credential.is_some()),
+            (ItemType::Wifi, self.wifi.is_some()),
+        ];
+        if let Some((other, _)) = sections
+            .iter()
+            .find(|(t, present)| *present && *t != self.item_type)
+        {
+            return Err(format!(
+                "{:?} fields are not allowed on a {:?} item",
+                other, self.item_type
+            ));
+        }
+
+        let missing = || format!("{:?} item is missing its details", self.item_type);
+        match self.item_type {
+            ItemType::Login | ItemType::SecureNote => Ok(()),
+            ItemType::Card => self.card.as_ref().ok_or_else(missing)?.validate(),
+            ItemType::Identity => self.identity.as_ref().ok_or_else(missing)?.validate(),
+            ItemType::SshKey => self.ssh_key.as_ref().ok_or_else(missing)?.validate(),
+            ItemType::ApiCredential => self.api_credential.as_ref().ok_or_else(missing)?.validate(),
+            ItemType::Wifi => self.wifi.as_ref().ok_or_else(missing)?.validate(),
+        }
+    }
+}
+
+/// Accept `scheme://host...` URLs and bare hostnames such as `github.com`
+pub(crate) fn validate_url(url: &str) -> Result<(), String> {
+    let url = url.trim();
+    if url.is_empty() {
+        return Err("URL cannot be empty".to_string());
+    }
+    if url.len() > 2048 || url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(format!("Invalid URL: {}", url));
+    }
+    let host = match url.split_once("://") {
+        Some((scheme, rest)) => {
+            if scheme.is_empty()
+                || !scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+            {
+                return Err(format!("Invalid URL: {}", url));
+            }
+            rest.split(['/', '?', '#']).next().unwrap_or("")
+        }
+        None => url.split(['/', '?', '#']).next().unwrap_or(""),
+    };
+    if host.is_empty() {
+        return Err(format!("Invalid URL: {}", url));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_blob_upgrades() {
+        let blob = br#"{"username":"alice","password":"hunter2","totpSecret":"JBSWY3DPEHPK3PXP","notes":"n"}"#;
+        let item = VaultItem::from_blob("github", blob).unwrap();
+        assert_eq!(item.version, VAULT_ITEM_VERSION);
+        assert_eq!(item.title, "github");
+        assert_eq!(item.username.as_deref(), Some("alice"));
+        assert_eq!(item.password.as_deref(), Some("hunter2"));
+        assert_eq!(item.totp_secret.as_deref(), Some("JBSWY3DPEHPK3PXP"));
+        assert!(item.validate().is_ok());
+
+        // Upgraded blobs keep the legacy keys readable
+        let json: serde_json::Value = serde_json::from_slice(&item.to_blob().unwrap()).unwrap();
+        assert_eq!(json["totpSecret"], "JBSWY3DPEHPK3PXP");
+        assert_eq!(json["username"], "alice");
+    }
+
+    #[test]
+    fn test_plain_text_blob_becomes_notes() {
+        let item = VaultItem::from_blob("wifi", b"router password is 1234").unwrap();
+        assert_eq!(item.notes.as_deref(), Some("router password is 1234"));
+        assert!(VaultItem::from_blob("bin", &[0xff, 0xfe]).is_err());
+    }
+
+    #[test]
+    fn test_future_version_rejected() {
+        assert!(VaultItem::from_blob("x", br#"{"version":99,"title":"x"}"#).is_err());
+    }
+
+    #[test]
+    fn test_validation() {
+        let mut item = VaultItem::new("GitHub");
+        item.urls = vec!["https://github.com/login".to_string(), "github.com".to_string()];
+        assert!(item.validate().is_ok());
+
+        item.title = "   ".to_string();
+        assert!(item.validate().is_err());
+        item.title = "GitHub".to_string();
+
+        item.urls.push("not a url".to_string());
+        assert!(item.validate().is_err());
+        item.urls.pop();
+
+        item.totp_secret = Some("not base32!".to_string());
+        assert!(item.validate().is_err());
+        item.totp_secret = Some("otpauth://totp/GitHub:alice?secret=JBSWY3DPEHPK3PXP".to_string());
+        assert!(item.validate().is_ok());
+        item.totp_secret = Some("otpauth://totp/GitHub:alice?secret=JBSWY3DPEHPK3PXP&digits=10".to_string());
+        assert!(item.validate().is_err());
+        item.totp_secret = None;
+
+        item.custom_fields.push(CustomField {
+            name: "".to_string(),
+            value: "x".to_string(),
+            hidden: false,
+        });
+        assert!(item.validate().is_err());
+    }
+
+    #[test]
+    fn test_type_sections() {
+        let mut item = VaultItem::with_type("Home", ItemType::Wifi);
+        assert!(item.validate().is_err());
+
+        item.wifi = Some(crate::item_types::WifiDetails {
+            ssid: "home".to_string(),
+            password: Some("correct horse".to_string()),
+            ..Default::default()
+        });
+        assert!(item.validate().is_ok());
+
+        let json: serde_json::Value = serde_json::from_slice(&item.to_blob().unwrap()).unwrap();
+        assert_eq!(json["type"], "wifi");
+        assert_eq!(json["wifi"]["ssid"], "home");
+
+        item.card = Some(Default::default());
+        assert!(item.validate().is_err());
+
+        // Legacy blobs without a type are logins
+        let legacy = VaultItem::from_blob("github", br#"{"username":"alice"}"#).unwrap();
+        assert_eq!(legacy.item_type, ItemType::Login);
+    }
+}
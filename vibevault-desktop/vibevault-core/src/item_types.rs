@@ -0,0 +1,376 @@
+use serde::{Deserialize, Serialize};
+
+const MAX_FIELD_LEN: usize = 4096;
+const MAX_KEY_LEN: usize = 16384;
+
+/// What kind of secret an item holds. Blobs written before item types
+/// existed have no `type` key and are logins.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ItemType {
+    #[default]
+    Login,
+    Card,
+    Identity,
+    SecureNote,
+    SshKey,
+    ApiCredential,
+    Wifi,
+}
+
+/// Payment card
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CardDetails {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cardholder_name: Option<String>,
+    /// Digits only once validated; spaces and dashes are accepted on input
+    pub number: String,
+    /// 1-12
+    pub expiry_month: u8,
+    /// Four-digit year
+    pub expiry_year: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub security_code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub brand: Option<String>,
+}
+
+/// Personal identity and postal address
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdentityDetails {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub middle_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub company: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address_line1: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address_line2: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub city: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub postal_code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+}
+
+/// SSH key pair in OpenSSH/PEM text form
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SshKeyDetails {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+    /// Passphrase protecting the private key, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passphrase: Option<String>,
+}
+
+/// API key or client credentials for a service
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiCredentialDetails {
+    pub key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    /// RFC 3339 expiry, if the credential has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WifiSecurity {
+    None,
+    Wep,
+    #[default]
+    WpaPersonal,
+    WpaEnterprise,
+}
+
+/// Wi-Fi network
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WifiDetails {
+    pub ssid: String,
+    #[serde(default)]
+    pub security: WifiSecurity,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    /// Network does not broadcast its SSID
+    #[serde(default)]
+    pub hidden: bool,
+}
+
+fn check_len(name: &str, value: &Option<String>, max: usize) -> Result<(), String> {
+    if let Some(v) = value {
+        if v.chars().count() > max {
+            return Err(format!("{} must be at most {} characters", name, max));
+        }
+    }
+    Ok(())
+}
+
+fn is_blank(value: &Option<String>) -> bool {
+    value.as_deref().is_none_or(|v| v.trim().is_empty())
+}
+
+/// Luhn checksum over a string of ASCII digits
+pub fn luhn_valid(digits: &str) -> bool {
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return false;
+    }
+    let sum: u32 = digits
+        .bytes()
+        .rev()
+        .enumerate()
+        .map(|(i, b)| {
+            let d = (b - b'0') as u32;
+            if i % 2 == 1 {
+                let d = d * 2;
+                if d > 9 {
+                    d - 9
+                } else {
+                    d
+                }
+            } else {
+                d
+            }
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+impl CardDetails {
+    /// Card number with spaces and dashes removed
+    pub fn normalized_number(&self) -> String {
+        self.number
+            .chars()
+            .filter(|c| !matches!(c, ' ' | '-'))
+            .collect()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let number = self.normalized_number();
+        if !(12..=19).contains(&number.len()) || !luhn_valid(&number) {
+            return Err("Invalid card number".to_string());
+        }
+        if !(1..=12).contains(&self.expiry_month) {
+            return Err("Expiry month must be between 1 and 12".to_string());
+        }
+        if !(2000..=2099).contains(&self.expiry_year) {
+            return Err("Expiry year must be a four-digit year".to_string());
+        }
+        if let Some(code) = &self.security_code {
+            if !code.is_empty()
+                && (!(3..=4).contains(&code.len()) || !code.bytes().all(|b| b.is_ascii_digit()))
+            {
+                return Err("Security code must be 3 or 4 digits".to_string());
+            }
+        }
+        check_len("Cardholder name", &self.cardholder_name, MAX_FIELD_LEN)?;
+        check_len("Brand", &self.brand, MAX_FIELD_LEN)
+    }
+}
+
+impl IdentityDetails {
+    pub fn validate(&self) -> Result<(), String> {
+        let fields = [
+            ("First name", &self.first_name),
+            ("Middle name", &self.middle_name),
+            ("Last name", &self.last_name),
+            ("Email", &self.email),
+            ("Phone", &self.phone),
+            ("Company", &self.company),
+            ("Address", &self.address_line1),
+            ("Address", &self.address_line2),
+            ("City", &self.city),
+            ("Region", &self.region),
+            ("Postal code", &self.postal_code),
+            ("Country", &self.country),
+        ];
+        if fields.iter().all(|(_, v)| is_blank(v)) {
+            return Err("Identity must have at least one field".to_string());
+        }
+        for (name, value) in fields {
+            check_len(name, value, MAX_FIELD_LEN)?;
+        }
+        if let Some(email) = self.email.as_deref().filter(|e| !e.trim().is_empty()) {
+            let valid = email
+                .split_once('@')
+                .is_some_and(|(user, domain)| !user.is_empty() && domain.contains('.'));
+            if !valid {
+                return Err(format!("Invalid email address: {}", email));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SshKeyDetails {
+    pub fn validate(&self) -> Result<(), String> {
+        if is_blank(&self.private_key) && is_blank(&self.public_key) {
+            return Err("SSH key must have a private or public key".to_string());
+        }
+        check_len("Private key", &self.private_key, MAX_KEY_LEN)?;
+        check_len("Public key", &self.public_key, MAX_KEY_LEN)?;
+        check_len("Passphrase", &self.passphrase, MAX_FIELD_LEN)?;
+
+        if let Some(private) = self.private_key.as_deref().filter(|k| !k.trim().is_empty()) {
+            let private = private.trim();
+            if !private.starts_with("-----BEGIN ") || !private.ends_with("PRIVATE KEY-----") {
+                return Err("Private key must be in PEM or OpenSSH format".to_string());
+            }
+        }
+        if let Some(public) = self.public_key.as_deref().filter(|k| !k.trim().is_empty()) {
+            let mut parts = public.split_whitespace();
+            let algorithm = parts.next().unwrap_or("");
+            let body = parts.next().unwrap_or("");
+            let known = algorithm.starts_with("ssh-")
+                || algorithm.starts_with("ecdsa-")
+                || algorithm.starts_with("sk-");
+            if !known || body.is_empty() {
+                return Err("Public key must be in OpenSSH format".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ApiCredentialDetails {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.key.trim().is_empty() {
+            return Err("API key is required".to_string());
+        }
+        if self.key.chars().count() > MAX_KEY_LEN {
+            return Err(format!(
+                "API key must be at most {} characters",
+                MAX_KEY_LEN
+            ));
+        }
+        check_len("API secret", &self.secret, MAX_KEY_LEN)?;
+        if let Some(endpoint) = self.endpoint.as_deref().filter(|e| !e.trim().is_empty()) {
+            crate::item::validate_url(endpoint)?;
+        }
+        if let Some(expires) = self.expires_at.as_deref().filter(|e| !e.trim().is_empty()) {
+            chrono::DateTime::parse_from_rfc3339(expires)
+                .map_err(|_| format!("Invalid expiry date: {}", expires))?;
+        }
+        Ok(())
+    }
+}
+
+impl WifiDetails {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.ssid.is_empty() || self.ssid.len() > 32 {
+            return Err("SSID must be 1 to 32 bytes".to_string());
+        }
+        let password = self.password.as_deref().unwrap_or("");
+        let is_hex = |s: &str| s.bytes().all(|b| b.is_ascii_hexdigit());
+        match self.security {
+            WifiSecurity::None => {
+                if !password.is_empty() {
+                    return Err("Open networks have no password".to_string());
+                }
+            }
+            WifiSecurity::Wep => {
+                let valid = matches!(password.len(), 5 | 13)
+                    || (matches!(password.len(), 10 | 26) && is_hex(password));
+                if !valid {
+                    return Err(
+                        "WEP keys are 5 or 13 characters, or 10 or 26 hex digits".to_string()
+                    );
+                }
+            }
+            WifiSecurity::WpaPersonal => {
+                let valid = (8..=63).contains(&password.len())
+                    || (password.len() == 64 && is_hex(password));
+                if !valid {
+                    return Err("WPA passwords are 8 to 63 characters".to_string());
+                }
+            }
+            WifiSecurity::WpaEnterprise => check_len("Password", &self.password, MAX_FIELD_LEN)?,
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_luhn() {
+        assert!(luhn_valid("4111111111111111"));
+        assert!(luhn_valid("79927398713"));
+        assert!(!luhn_valid("4111111111111112"));
+        assert!(!luhn_valid("4111a11111111111"));
+    }
+
+    #[test]
+    fn test_card_validation() {
+        let mut card = CardDetails {
+            number: "4111 1111-1111 1111".to_string(),
+            expiry_month: 12,
+            expiry_year: 2030,
+            security_code: Some("123".to_string()),
+            ..Default::default()
+        };
+        assert!(card.validate().is_ok());
+        assert_eq!(card.normalized_number(), "4111111111111111");
+
+        card.expiry_month = 13;
+        assert!(card.validate().is_err());
+        card.expiry_month = 1;
+        card.expiry_year = 30;
+        assert!(card.validate().is_err());
+        card.expiry_year = 2030;
+        card.security_code = Some("12a".to_string());
+        assert!(card.validate().is_err());
+    }
+
+    #[test]
+    fn test_wifi_validation() {
+        let mut wifi = WifiDetails {
+            ssid: "home".to_string(),
+            password: Some("short".to_string()),
+            ..Default::default()
+        };
+        assert!(wifi.validate().is_err());
+        wifi.password = Some("long enough".to_string());
+        assert!(wifi.validate().is_ok());
+        wifi.security = WifiSecurity::None;
+        assert!(wifi.validate().is_err());
+        wifi.password = None;
+        assert!(wifi.validate().is_ok());
+    }
+
+    #[test]
+    fn test_ssh_key_validation() {
+        let key = SshKeyDetails {
+            public_key: Some("ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAI alice@laptop".to_string()),
+            ..Default::default()
+        };
+        assert!(key.validate().is_ok());
+        let bad = SshKeyDetails {
+            private_key: Some("not a key".to_string()),
+            ..Default::default()
+        };
+        assert!(bad.validate().is_err());
+    }
+}
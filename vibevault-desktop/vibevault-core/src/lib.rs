@@ -0,0 +1,126 @@
+// VibeVault core: session, vault, profile, crypto and sync logic with no
+// dependency on Tauri. The desktop app wraps a `Vault` in its Tauri state;
+// CLIs, daemons and integration tests can drive one directly.
+
+pub mod attachments;
+pub mod auth;
+pub mod bitwarden;
+pub mod breach;
+pub mod crypto;
+pub mod db;
+pub mod folders;
+pub mod generator;
+pub mod health;
+pub mod history;
+pub mod item;
+pub mod item_types;
+pub mod listing;
+pub mod master_password;
+pub mod otp_import;
+pub mod password_rules;
+pub mod profiles;
+pub mod recovery_codes;
+pub mod revisions;
+pub mod search;
+pub mod store;
+pub mod strength;
+pub mod sync;
+pub mod totp;
+pub mod trash;
+pub mod usage;
+pub mod vault;
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Instant;
+use zeroize::Zeroize;
+
+pub use attachments::AttachmentInfo;
+pub use bitwarden::{
+    BitwardenFolderMapping, BitwardenImportReport, ImportWarning, ImportedEntry, SkippedItem,
+};
+pub use breach::{BreachIndexInfo, BreachReport};
+pub use db::DatabaseManager;
+pub use folders::Folder;
+pub use generator::{
+    Capitalization, GeneratedSecret, GeneratedValue, PassphraseOptions, PasswordOptions,
+};
+pub use health::{HealthOptions, HealthReport};
+pub use history::PasswordHistoryEntry;
+pub use item::{CustomField, StoredItem, VaultItem};
+pub use item_types::{
+    ApiCredentialDetails, CardDetails, IdentityDetails, ItemType, SshKeyDetails, WifiDetails,
+    WifiSecurity,
+};
+pub use listing::{EntryPage, EntrySort, EntrySummary};
+pub use master_password::{PasswordChangeReport, UnreadableRow};
+pub use otp_import::{AuthenticatorApp, ImportedOtp, OtpImportReport, SkippedOtp};
+pub use password_rules::{RuleSource, SiteRules};
+pub use profiles::Profile;
+pub use recovery_codes::{RecoveryCode, RecoveryCodeStatus};
+pub use revisions::{EntryRevision, FieldChange, RevisionRetention};
+pub use search::SearchHit;
+pub use store::{MemoryStore, VaultStore};
+pub use strength::{MasterPasswordPolicy, StrengthEstimate};
+pub use totp::{EntryTotp, OtpAlgorithm, OtpKind, TotpCode, TotpParams};
+pub use trash::DeletedItem;
+pub use vault::VaultEntry;
+
+/// Default inactivity timeout before the session auto-locks (15 minutes)
+pub const DEFAULT_AUTO_LOCK_SECONDS: u64 = 900;
+
+// --- SESSION STATE ---
+pub struct SessionState {
+    pub token: String,
+    pub encryption_key: [u8; 32],
+}
+
+impl Drop for SessionState {
+    fn drop(&mut self) {
+        self.encryption_key.zeroize();
+        self.token.zeroize();
+    }
+}
+
+// --- VAULT ---
+
+/// An open vault: the storage backend plus the unlocked session (if any),
+/// the active profile and the auto-lock timer.
+pub struct Vault {
+    store: Box<dyn VaultStore>,
+    session: Option<SessionState>,
+    active_profile_id: i64,
+    last_activity: Instant,
+    auto_lock_seconds: u64,
+    password_history_depth: usize,
+    revision_retention: RevisionRetention,
+    uploads: HashMap<String, attachments::AttachmentUpload>,
+    search_index: Option<search::SearchIndex>,
+}
+
+impl Vault {
+    /// Wrap an already-initialized storage backend
+    pub fn new(store: Box<dyn VaultStore>) -> Self {
+        Vault {
+            store,
+            session: None,
+            active_profile_id: 1,
+            last_activity: Instant::now(),
+            auto_lock_seconds: DEFAULT_AUTO_LOCK_SECONDS,
+            password_history_depth: history::DEFAULT_PASSWORD_HISTORY_DEPTH,
+            revision_retention: RevisionRetention::default(),
+            uploads: HashMap::new(),
+            search_index: None,
+        }
+    }
+
+    /// Open the SQLite database at `path`, running migrations as needed
+    pub fn open(path: &Path) -> Result<Self, String> {
+        Ok(Self::new(Box::new(DatabaseManager::open(path)?)))
+    }
+
+    /// Direct access to the storage backend (no session required)
+    pub fn store(&self) -> &dyn VaultStore {
+        self.store.as_ref()
+    }
+}
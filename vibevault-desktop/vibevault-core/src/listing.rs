@@ -0,0 +1,258 @@
+// Lightweight, paginated entry listing.
+//
+// List rows carry only what the entry list shows: title, username, the
+// domain to fetch a favicon for, timestamps and usage. They come from the
+// in-memory search index and the plaintext row metadata, so paging through
+// a profile decrypts nothing and no secret leaves the backend; the full
+// item is fetched per entry with `get_item` when the user opens it.
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::net::IpAddr;
+
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+
+use crate::item_types::ItemType;
+use crate::usage::{favorite_uuids, usage_by_entry};
+use crate::Vault;
+
+/// Page size the frontend uses when it does not ask for one
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+/// Largest page `list_entry_summaries` returns
+pub const MAX_PAGE_SIZE: usize = 500;
+
+/// Order of `list_entry_summaries`; ties are broken by entry ID
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EntrySort {
+    /// Title A–Z, ignoring case
+    #[default]
+    Name,
+    /// Most recently changed first
+    Updated,
+    /// Most recently used on any device first; never-used entries last
+    LastUsed,
+    /// Most often used first
+    MostUsed,
+}
+
+/// Display metadata of one entry, without any secret
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntrySummary {
+    pub id: i64,
+    pub entry_uuid: Option<String>,
+    #[serde(rename = "type")]
+    pub item_type: ItemType,
+    pub title: String,
+    pub username: Option<String>,
+    /// Host of the first URL, for the favicon
+    pub favicon_domain: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub last_used_at: Option<String>,
+    /// Uses summed over all synced devices
+    pub use_count: i64,
+    pub favorite: bool,
+}
+
+/// One page of entry summaries. Pass `next_cursor` back to get the page
+/// after it; `None` means this was the last page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryPage {
+    pub entries: Vec<EntrySummary>,
+    pub next_cursor: Option<String>,
+    /// Number of entries in the profile across all pages
+    pub total: usize,
+}
+
+/// Position after the last row of a page: its sort key and ID
+#[derive(Serialize, Deserialize)]
+struct Cursor {
+    key: String,
+    id: i64,
+}
+
+impl Cursor {
+    fn encode(&self) -> Result<String, String> {
+        let json = serde_json::to_vec(self).map_err(|e| e.to_string())?;
+        Ok(general_purpose::URL_SAFE_NO_PAD.encode(json))
+    }
+
+    fn decode(cursor: &str) -> Result<Self, String> {
+        general_purpose::URL_SAFE_NO_PAD
+            .decode(cursor)
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .ok_or_else(|| "Invalid cursor".to_string())
+    }
+}
+
+/// Host of `url` without scheme, credentials, port or a leading `www.`.
+/// IP addresses have no favicon worth fetching and give `None`.
+pub fn favicon_domain(url: &str) -> Option<String> {
+    let url = url.trim();
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    if host.starts_with('[') {
+        return None;
+    }
+    let host = host.split(':').next()?.trim_end_matches('.').to_lowercase();
+    if host.is_empty() || host.parse::<IpAddr>().is_ok() {
+        return None;
+    }
+    Some(host.strip_prefix("www.").unwrap_or(&host).to_string())
+}
+
+impl EntrySort {
+    fn key(self, summary: &EntrySummary) -> String {
+        match self {
+            EntrySort::Name => summary.title.to_lowercase(),
+            EntrySort::Updated => summary.updated_at.clone(),
+            EntrySort::LastUsed => summary.last_used_at.clone().unwrap_or_default(),
+            // Zero-padded so that string order is numeric order
+            EntrySort::MostUsed => format!("{:020}", summary.use_count.max(0)),
+        }
+    }
+
+    fn compare(self, a: (&str, i64), b: (&str, i64)) -> Ordering {
+        let keys = match self {
+            EntrySort::Name => a.0.cmp(b.0),
+            EntrySort::Updated | EntrySort::LastUsed | EntrySort::MostUsed => b.0.cmp(a.0),
+        };
+        keys.then(a.1.cmp(&b.1))
+    }
+}
+
+impl Vault {
+    /// One page of display metadata for the active profile's entries.
+    /// `limit` must be between 1 and `MAX_PAGE_SIZE`.
+    pub fn list_entry_summaries(
+        &mut self,
+        token: &str,
+        sort: EntrySort,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<EntryPage, String> {
+        let (key, active_profile) = self.session_context(token)?;
+        if limit == 0 || limit > MAX_PAGE_SIZE {
+            return Err(format!("Page size must be between 1 and {}", MAX_PAGE_SIZE));
+        }
+        let cursor = cursor.map(Cursor::decode).transpose()?;
+
+        let rows: Vec<(String, EntrySummary)> = self
+            .entry_summaries(&key, active_profile, sort)?
+            .into_iter()
+            .map(|summary| (sort.key(&summary), summary))
+            .collect();
+        let total = rows.len();
+
+        let start = match &cursor {
+            Some(cursor) => rows.partition_point(|(key, summary)| {
+                sort.compare((key, summary.id), (&cursor.key, cursor.id)) != Ordering::Greater
+            }),
+            None => 0,
+        };
+        let mut page: Vec<(String, EntrySummary)> =
+            rows.into_iter().skip(start).take(limit + 1).collect();
+
+        let next_cursor = if page.len() > limit {
+            page.truncate(limit);
+            match page.last() {
+                Some((key, summary)) => Some(
+                    Cursor {
+                        key: key.clone(),
+                        id: summary.id,
+                    }
+                    .encode()?,
+                ),
+                None => None,
+            }
+        } else {
+            None
+        };
+
+        Ok(EntryPage {
+            entries: page.into_iter().map(|(_, summary)| summary).collect(),
+            next_cursor,
+            total,
+        })
+    }
+
+    /// Summaries of every active entry of `profile_id` in `sort` order
+    pub(crate) fn entry_summaries(
+        &mut self,
+        key: &[u8; 32],
+        profile_id: i64,
+        sort: EntrySort,
+    ) -> Result<Vec<EntrySummary>, String> {
+        let records = self.store.list_entries(profile_id)?;
+        let usage = usage_by_entry(self.store.as_ref())?;
+        let favorites: HashSet<String> = favorite_uuids(self.store.as_ref())?.into_iter().collect();
+        let index = self.search_index(key)?;
+
+        let mut rows: Vec<(String, EntrySummary)> = records
+            .into_iter()
+            .filter_map(|record| {
+                // Entries the index could not decrypt are left out
+                let indexed = index.get(record.id)?;
+                let used = record.entry_uuid.as_ref().and_then(|u| usage.get(u));
+                let summary = EntrySummary {
+                    id: record.id,
+                    entry_uuid: record.entry_uuid.clone(),
+                    item_type: indexed.item_type,
+                    title: indexed.display_title.clone(),
+                    username: indexed.display_username.clone(),
+                    favicon_domain: indexed.favicon_domain.clone(),
+                    created_at: record.created_at,
+                    updated_at: record.updated_at,
+                    last_used_at: used.and_then(|u| u.last_used_at.clone()),
+                    use_count: used.map_or(0, |u| u.use_count),
+                    favorite: record
+                        .entry_uuid
+                        .as_ref()
+                        .is_some_and(|u| favorites.contains(u)),
+                };
+                Some((sort.key(&summary), summary))
+            })
+            .collect();
+        rows.sort_by(|a, b| sort.compare((&a.0, a.1.id), (&b.0, b.1.id)));
+        Ok(rows.into_iter().map(|(_, summary)| summary).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_favicon_domain() {
+        assert_eq!(
+            favicon_domain("https://www.GitHub.com/login").as_deref(),
+            Some("github.com")
+        );
+        assert_eq!(favicon_domain("github.com").as_deref(), Some("github.com"));
+        assert_eq!(
+            favicon_domain("ssh://git@gitlab.example.org:2222/repo").as_deref(),
+            Some("gitlab.example.org")
+        );
+        assert_eq!(favicon_domain("http://192.168.1.1/admin"), None);
+        assert_eq!(favicon_domain("http://[::1]:8080"), None);
+    }
+
+    #[test]
+    fn test_cursor_roundtrip() {
+        let cursor = Cursor {
+            key: "github".to_string(),
+            id: 7,
+        };
+        let decoded = Cursor::decode(&cursor.encode().unwrap()).unwrap();
+        assert_eq!((decoded.key.as_str(), decoded.id), ("github", 7));
+        assert!(Cursor::decode("not a cursor").is_err());
+    }
+}
@@ -0,0 +1,178 @@
+// Changing the master password.
+//
+// The vault key is derived from the master password, so a new password
+// means a new key: every row encrypted with the vault key is re-encrypted
+// and written together with the new password hash in one store
+// transaction. Sync payloads carry rows still encrypted with the vault
+// key, so paired devices cannot read them afterwards and are forgotten.
+
+use argon2::{
+    password_hash::{PasswordHash, PasswordVerifier},
+    Argon2,
+};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::attachments::{cleanup_attachments, rekey_attachments};
+use crate::auth::{derive_vault_key, hash_master_password};
+use crate::crypto::{decrypt_aes256_gcm, encrypt_aes256_gcm};
+use crate::store::{RekeyBatch, UserRecord, VaultStore};
+use crate::Vault;
+
+/// A row that did not decrypt with the old key. It is left as it was:
+/// it could not be read before the change either.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "id", rename_all = "camelCase")]
+pub enum UnreadableRow {
+    Entry(i64),
+    Folder(String),
+    Revision(i64),
+    Generated(i64),
+    Attachment(i64),
+}
+
+/// Outcome of a master password change
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasswordChangeReport {
+    pub skipped: Vec<UnreadableRow>,
+    /// Names of the paired devices that were forgotten
+    pub forgotten_devices: Vec<String>,
+}
+
+impl Vault {
+    /// Change the master password and re-encrypt the vault under the key
+    /// it derives. The new password must meet the master password policy.
+    /// Rows that do not decrypt with the current key are skipped and
+    /// reported. Every paired device is forgotten and has to be paired
+    /// again. The session stays open.
+    pub fn change_master_password(
+        &mut self,
+        token: &str,
+        username: &str,
+        current_pass: &str,
+        new_pass: &str,
+    ) -> Result<PasswordChangeReport, String> {
+        let old_key = self.validate_session(token)?;
+        let user = self.store.find_user(username)?.ok_or("User not found")?;
+        let parsed_hash = PasswordHash::new(&user.password_hash)
+            .map_err(|_| "Current master password is incorrect")?;
+        Argon2::default()
+            .verify_password(current_pass.as_bytes(), &parsed_hash)
+            .map_err(|_| "Current master password is incorrect")?;
+        self.check_master_password(username, new_pass)?;
+
+        let mut enc_salt_bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut enc_salt_bytes);
+        let encryption_salt = hex::encode(enc_salt_bytes);
+        let (password_hash, salt) = hash_master_password(new_pass)?;
+        let mut new_key = derive_vault_key(new_pass, &encryption_salt)?;
+
+        // Unfinished uploads are encrypted under the old key; drop them so
+        // only finished attachments are left to re-encrypt
+        self.uploads.clear();
+        cleanup_attachments(self.store.as_mut())?;
+
+        let user = UserRecord {
+            password_hash,
+            salt,
+            encryption_salt,
+            ..user
+        };
+        let result = rekey_batch(self.store.as_ref(), &old_key, &new_key, user).and_then(
+            |(batch, skipped)| {
+                self.store.replace_vault_key(&batch)?;
+                Ok(skipped)
+            },
+        );
+        if result.is_ok() {
+            if let Some(session) = self.session.as_mut() {
+                session.encryption_key = new_key;
+            }
+        }
+        new_key.zeroize();
+        let mut report = PasswordChangeReport {
+            skipped: result?,
+            forgotten_devices: Vec::new(),
+        };
+
+        for device in self.store.list_paired_devices()? {
+            self.store.forget_device(&device.device_id)?;
+            report.forgotten_devices.push(device.device_name);
+        }
+        Ok(report)
+    }
+}
+
+/// Every row encrypted with `old_key`, re-encrypted under `new_key`,
+/// and the rows that did not decrypt
+fn rekey_batch(
+    db: &dyn VaultStore,
+    old_key: &[u8; 32],
+    new_key: &[u8; 32],
+    user: UserRecord,
+) -> Result<(RekeyBatch, Vec<UnreadableRow>), String> {
+    let reencrypt = |blob: &[u8], nonce: &[u8]| -> Option<(Vec<u8>, Vec<u8>)> {
+        let mut plaintext = decrypt_aes256_gcm(old_key, blob, nonce).ok()?;
+        let encrypted = encrypt_aes256_gcm(new_key, &plaintext);
+        plaintext.zeroize();
+        encrypted.ok()
+    };
+
+    let mut batch = RekeyBatch {
+        user,
+        entries: Vec::new(),
+        folders: Vec::new(),
+        revisions: Vec::new(),
+        generated: Vec::new(),
+        attachments: Vec::new(),
+        blob_hashes: Vec::new(),
+        chunks: Vec::new(),
+    };
+    let mut skipped = Vec::new();
+    for mut entry in db.entries_updated_since(None)? {
+        match reencrypt(&entry.data_blob, &entry.nonce) {
+            Some((data_blob, nonce)) => {
+                (entry.data_blob, entry.nonce) = (data_blob, nonce);
+                batch.entries.push(entry);
+            }
+            None => skipped.push(UnreadableRow::Entry(entry.id)),
+        }
+    }
+    for mut folder in db.folders_updated_since(None)? {
+        match reencrypt(&folder.data_blob, &folder.nonce) {
+            Some((data_blob, nonce)) => {
+                (folder.data_blob, folder.nonce) = (data_blob, nonce);
+                batch.folders.push(folder);
+            }
+            None => skipped.push(UnreadableRow::Folder(folder.folder_uuid)),
+        }
+    }
+    for entry_uuid in db.revisioned_entry_uuids()? {
+        for mut revision in db.list_revisions(&entry_uuid)? {
+            match reencrypt(&revision.data_blob, &revision.nonce) {
+                Some((data_blob, nonce)) => {
+                    (revision.data_blob, revision.nonce) = (data_blob, nonce);
+                    batch.revisions.push(revision);
+                }
+                None => skipped.push(UnreadableRow::Revision(revision.id)),
+            }
+        }
+    }
+    for mut record in db.list_generated()? {
+        match reencrypt(&record.data_blob, &record.nonce) {
+            Some((data_blob, nonce)) => {
+                (record.data_blob, record.nonce) = (data_blob, nonce);
+                batch.generated.push(record);
+            }
+            None => skipped.push(UnreadableRow::Generated(record.id)),
+        }
+    }
+    skipped.extend(
+        rekey_attachments(db, old_key, new_key, &mut batch)?
+            .into_iter()
+            .map(UnreadableRow::Attachment),
+    );
+    Ok((batch, skipped))
+}
@@ -0,0 +1,734 @@
+// Importing one-time password accounts from outside the vault.
+//
+// QR codes are decoded to text by the app, or from PNG and JPEG images with
+// the `qr-image` feature; this module turns that text, an `otpauth://` URI
+// or a Google Authenticator `otpauth-migration://` export, into
+// `TotpParams` and stores each account on an entry. Backups of the Aegis,
+// 2FAS and andOTP apps are read the same way. Accounts are stored as
+// canonical `otpauth://` URIs so no parameter is lost.
+
+use std::collections::HashSet;
+
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::crypto::{decrypt_aes256_gcm, encrypt_aes256_gcm};
+use crate::item::VaultItem;
+use crate::store::EntryRecord;
+use crate::totp::{parse_totp, percent_decode, percent_encode, OtpAlgorithm, OtpKind, TotpParams};
+use crate::vault::now_iso;
+use crate::Vault;
+
+/// Google Authenticator's "Transfer accounts" export, possibly split over
+/// several QR codes
+const MIGRATION_SCHEME: &str = "otpauth-migration://";
+
+/// Title of a new entry when an account has neither issuer nor name
+const UNTITLED_ACCOUNT: &str = "Imported 2FA";
+
+/// An account stored by an import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedOtp {
+    pub id: i64,
+    pub entry_uuid: Option<String>,
+    pub title: String,
+    /// Whether a new entry was created rather than an existing one updated
+    pub created: bool,
+}
+
+/// Authenticator app a backup was made by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuthenticatorApp {
+    #[serde(rename = "aegis")]
+    Aegis,
+    #[serde(rename = "2fas")]
+    TwoFas,
+    #[serde(rename = "andotp")]
+    AndOtp,
+}
+
+/// An account of a backup that was not imported
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedOtp {
+    pub name: String,
+    pub reason: String,
+}
+
+/// Outcome of importing an authenticator backup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OtpImportReport {
+    pub app: AuthenticatorApp,
+    pub imported: Vec<ImportedOtp>,
+    /// Accounts whose issuer and account name match an existing entry or an
+    /// earlier account of the backup
+    pub duplicates: Vec<String>,
+    /// Accounts of an unsupported type or with invalid parameters
+    pub skipped: Vec<SkippedOtp>,
+}
+
+/// One account read from a backup
+struct BackupAccount {
+    name: String,
+    params: Result<TotpParams, String>,
+    notes: Option<String>,
+}
+
+/// Largest scrypt working set accepted from an Aegis backup (1 GiB)
+const MAX_SCRYPT_MEMORY: u64 = 1 << 30;
+
+/// 2FAS derives the key of encrypted backups with PBKDF2-SHA256
+const TWOFAS_PBKDF2_ITERATIONS: u32 = 10_000;
+
+/// Read a protobuf varint at `pos`
+fn read_varint(buf: &[u8], pos: &mut usize) -> Result<u64, String> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *buf.get(*pos).ok_or("Truncated migration payload")?;
+        *pos += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err("Invalid varint in migration payload".to_string())
+}
+
+/// A decoded protobuf field: its number and either a varint or bytes
+enum Field<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+}
+
+/// Split a protobuf message into its fields, skipping fixed-width ones
+fn read_fields(buf: &[u8]) -> Result<Vec<(u64, Field<'_>)>, String> {
+    let mut fields = Vec::new();
+    let mut pos = 0;
+    while pos < buf.len() {
+        let tag = read_varint(buf, &mut pos)?;
+        match tag & 7 {
+            0 => fields.push((tag >> 3, Field::Varint(read_varint(buf, &mut pos)?))),
+            2 => {
+                let len = read_varint(buf, &mut pos)? as usize;
+                let bytes = pos
+                    .checked_add(len)
+                    .and_then(|end| buf.get(pos..end))
+                    .ok_or("Truncated migration payload")?;
+                pos += len;
+                fields.push((tag >> 3, Field::Bytes(bytes)));
+            }
+            1 => pos += 8,
+            5 => pos += 4,
+            _ => return Err("Unsupported field in migration payload".to_string()),
+        }
+    }
+    Ok(fields)
+}
+
+/// One `OtpParameters` message of a migration payload
+fn migration_account(buf: &[u8]) -> Result<TotpParams, String> {
+    let mut secret = Vec::new();
+    let mut name = String::new();
+    let mut issuer = String::new();
+    let mut algorithm = OtpAlgorithm::Sha1;
+    let mut digits = 6;
+    let mut kind = OtpKind::Totp;
+    let mut counter = 0;
+    for (number, field) in read_fields(buf)? {
+        match (number, field) {
+            (1, Field::Bytes(b)) => secret = b.to_vec(),
+            (2, Field::Bytes(b)) => name = String::from_utf8_lossy(b).into_owned(),
+            (3, Field::Bytes(b)) => issuer = String::from_utf8_lossy(b).into_owned(),
+            (4, Field::Varint(v)) => {
+                algorithm = match v {
+                    0 | 1 => OtpAlgorithm::Sha1,
+                    2 => OtpAlgorithm::Sha256,
+                    3 => OtpAlgorithm::Sha512,
+                    _ => return Err(format!("Unsupported algorithm for account {}", name)),
+                }
+            }
+            (5, Field::Varint(v)) => digits = if v == 2 { 8 } else { 6 },
+            (6, Field::Varint(v)) => kind = if v == 1 { OtpKind::Hotp } else { OtpKind::Totp },
+            (7, Field::Varint(v)) => counter = v,
+            _ => {}
+        }
+    }
+    if secret.is_empty() {
+        return Err("Migration account has no secret".to_string());
+    }
+
+    // Names are usually `Issuer:account`, like otpauth labels
+    let label = match name.split_once(':') {
+        Some((prefix, account)) if issuer.is_empty() || prefix.trim() == issuer => {
+            if issuer.is_empty() {
+                issuer = prefix.trim().to_string();
+            }
+            account.trim().to_string()
+        }
+        _ => name.trim().to_string(),
+    };
+    Ok(TotpParams {
+        kind,
+        secret,
+        algorithm,
+        digits,
+        period: crate::totp::DEFAULT_PERIOD,
+        counter,
+        issuer: Some(issuer).filter(|i| !i.is_empty()),
+        label: Some(label).filter(|l| !l.is_empty()),
+    })
+}
+
+/// Every account of an `otpauth-migration://offline?data=...` URI
+pub fn parse_migration_uri(uri: &str) -> Result<Vec<TotpParams>, String> {
+    let query = uri
+        .trim()
+        .get(..MIGRATION_SCHEME.len())
+        .filter(|scheme| scheme.eq_ignore_ascii_case(MIGRATION_SCHEME))
+        .and_then(|_| uri.trim().split_once('?'))
+        .map(|(_, query)| query)
+        .ok_or("Not an otpauth-migration URI")?;
+    let data = query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("data="))
+        .ok_or("Migration URI has no data")?;
+    let data = percent_decode(data, false)?.replace(' ', "+");
+    let payload = general_purpose::STANDARD
+        .decode(&data)
+        .or_else(|_| general_purpose::URL_SAFE.decode(&data))
+        .map_err(|_| "Invalid migration data".to_string())?;
+
+    let accounts = read_fields(&payload)?
+        .into_iter()
+        .filter_map(|(number, field)| match (number, field) {
+            (1, Field::Bytes(account)) => Some(migration_account(account)),
+            _ => None,
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if accounts.is_empty() {
+        return Err("Migration QR code holds no accounts".to_string());
+    }
+    Ok(accounts)
+}
+
+/// The accounts held by the text of a scanned QR code
+pub fn parse_otp_qr_text(text: &str) -> Result<Vec<TotpParams>, String> {
+    let text = text.trim();
+    if text
+        .get(..MIGRATION_SCHEME.len())
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case(MIGRATION_SCHEME))
+    {
+        return parse_migration_uri(text);
+    }
+    if !text.contains("://") {
+        return Err("QR code does not hold a one-time password URI".to_string());
+    }
+    Ok(vec![parse_totp(text)?])
+}
+
+/// Text of every QR code found in a PNG or JPEG image
+#[cfg(feature = "qr-image")]
+pub fn decode_qr_image(bytes: &[u8]) -> Result<Vec<String>, String> {
+    let image = image::load_from_memory(bytes)
+        .map_err(|e| format!("Failed to read image: {}", e))?
+        .to_luma8();
+    let mut prepared = rqrr::PreparedImage::prepare_from_greyscale(
+        image.width() as usize,
+        image.height() as usize,
+        |x, y| image.get_pixel(x as u32, y as u32)[0],
+    );
+    let codes: Vec<String> = prepared
+        .detect_grids()
+        .into_iter()
+        .filter_map(|grid| grid.decode().ok().map(|(_, text)| text))
+        .collect();
+    if codes.is_empty() {
+        return Err("No QR code found in the image".to_string());
+    }
+    Ok(codes)
+}
+
+/// The accounts held by every QR code found in a PNG or JPEG image. Needs
+/// no vault, so the app decodes the image before taking the vault lock.
+#[cfg(feature = "qr-image")]
+pub fn parse_otp_qr_image(bytes: &[u8]) -> Result<Vec<TotpParams>, String> {
+    let mut accounts = Vec::new();
+    for text in decode_qr_image(bytes)? {
+        accounts.extend(parse_otp_qr_text(&text)?);
+    }
+    Ok(accounts)
+}
+
+fn str_field<'a>(value: &'a Value, name: &str) -> &'a str {
+    value.get(name).and_then(Value::as_str).unwrap_or("").trim()
+}
+
+fn display_name(issuer: &str, account: &str) -> String {
+    match (issuer.is_empty(), account.is_empty()) {
+        (false, false) => format!("{} ({})", issuer, account),
+        (false, true) => issuer.to_string(),
+        _ => account.to_string(),
+    }
+}
+
+/// Build the parameters of a backup account by way of an `otpauth://` URI,
+/// so they are validated exactly like a scanned code
+#[allow(clippy::too_many_arguments)]
+fn backup_params(
+    kind: &str,
+    secret: &str,
+    algorithm: &str,
+    digits: Option<u64>,
+    period: Option<u64>,
+    counter: Option<u64>,
+    issuer: &str,
+    account: &str,
+) -> Result<TotpParams, String> {
+    let (uri_kind, steam) = match kind.to_lowercase().as_str() {
+        "totp" | "" => ("totp", false),
+        "hotp" => ("hotp", false),
+        "steam" => ("totp", true),
+        other => return Err(format!("Unsupported OTP type: {}", other)),
+    };
+    let label = if issuer.is_empty() {
+        percent_encode(account)
+    } else {
+        format!("{}:{}", percent_encode(issuer), percent_encode(account))
+    };
+    let mut uri = format!(
+        "otpauth://{}/{}?secret={}",
+        uri_kind,
+        label,
+        percent_encode(secret)
+    );
+    if !issuer.is_empty() {
+        uri.push_str(&format!("&issuer={}", percent_encode(issuer)));
+    }
+    if steam {
+        uri.push_str("&encoder=steam");
+    } else {
+        if !algorithm.is_empty() {
+            uri.push_str(&format!("&algorithm={}", percent_encode(algorithm)));
+        }
+        if let Some(digits) = digits {
+            uri.push_str(&format!("&digits={}", digits));
+        }
+        if let (Some(period), "totp") = (period, uri_kind) {
+            uri.push_str(&format!("&period={}", period));
+        }
+        if let (Some(counter), "hotp") = (counter, uri_kind) {
+            uri.push_str(&format!("&counter={}", counter));
+        }
+    }
+    parse_totp(&uri)
+}
+
+fn hex_field(value: &Value, name: &str) -> Result<Vec<u8>, String> {
+    hex::decode(str_field(value, name)).map_err(|_| format!("Invalid {} in backup header", name))
+}
+
+/// Decrypt the `db` of a password-protected Aegis vault: a password slot
+/// holds the master key encrypted under an scrypt-derived key
+fn decrypt_aegis_db(header: &Value, db: &str, password: &str) -> Result<Value, String> {
+    let slots = header
+        .get("slots")
+        .and_then(Value::as_array)
+        .ok_or("Aegis backup has no key slots")?;
+    let mut master_key = None;
+    for slot in slots.iter().filter(|s| s.get("type").and_then(Value::as_u64) == Some(1)) {
+        let param = |name: &str| slot.get(name).and_then(Value::as_u64).unwrap_or(0);
+        let (n, r, p) = (param("n"), param("r"), param("p"));
+        let memory = n.saturating_mul(r).saturating_mul(128);
+        if n < 2 || !n.is_power_of_two() || r == 0 || p == 0 || memory > MAX_SCRYPT_MEMORY {
+            return Err("Unsupported Aegis key parameters".to_string());
+        }
+        let params = scrypt::Params::new(n.trailing_zeros() as u8, r as u32, p as u32, 32)
+            .map_err(|_| "Unsupported Aegis key parameters")?;
+        let mut derived = [0u8; 32];
+        scrypt::scrypt(password.as_bytes(), &hex_field(slot, "salt")?, &params, &mut derived)
+            .map_err(|_| "Invalid derived key")?;
+        let key_params = slot.get("key_params").unwrap_or(&Value::Null);
+        let sealed = [hex_field(slot, "key")?, hex_field(key_params, "tag")?].concat();
+        if let Ok(key) = decrypt_aes256_gcm(&derived, &sealed, &hex_field(key_params, "nonce")?) {
+            master_key = Some(key);
+            break;
+        }
+    }
+    let master_key: [u8; 32] = master_key
+        .ok_or("Wrong backup password")?
+        .try_into()
+        .map_err(|_| "Invalid Aegis master key")?;
+
+    let params = header.get("params").unwrap_or(&Value::Null);
+    let ciphertext = general_purpose::STANDARD
+        .decode(db)
+        .map_err(|_| "Invalid Aegis vault data")?;
+    let plaintext = decrypt_aes256_gcm(
+        &master_key,
+        &[ciphertext, hex_field(params, "tag")?].concat(),
+        &hex_field(params, "nonce")?,
+    )?;
+    serde_json::from_slice(&plaintext).map_err(|e| format!("Invalid Aegis vault: {}", e))
+}
+
+fn aegis_accounts(root: &Value, password: Option<&str>) -> Result<Vec<BackupAccount>, String> {
+    let db = match root.get("db") {
+        Some(Value::String(db)) => {
+            let password = password.ok_or("This backup is encrypted; enter its password")?;
+            decrypt_aegis_db(root.get("header").unwrap_or(&Value::Null), db, password)?
+        }
+        Some(db) => db.clone(),
+        None => return Err("Aegis backup has no vault".to_string()),
+    };
+    let entries = db
+        .get("entries")
+        .and_then(Value::as_array)
+        .ok_or("Aegis vault has no entries")?;
+    Ok(entries
+        .iter()
+        .map(|e| {
+            let info = e.get("info").unwrap_or(&Value::Null);
+            let (issuer, account) = (str_field(e, "issuer"), str_field(e, "name"));
+            BackupAccount {
+                name: display_name(issuer, account),
+                params: backup_params(
+                    str_field(e, "type"),
+                    str_field(info, "secret"),
+                    str_field(info, "algo"),
+                    info.get("digits").and_then(Value::as_u64),
+                    info.get("period").and_then(Value::as_u64),
+                    info.get("counter").and_then(Value::as_u64),
+                    issuer,
+                    account,
+                ),
+                notes: Some(str_field(e, "note").to_string()).filter(|n| !n.is_empty()),
+            }
+        })
+        .collect())
+}
+
+/// Decrypt `servicesEncrypted` of a password-protected 2FAS backup:
+/// base64 `ciphertext:salt:iv`, AES-GCM under a PBKDF2-derived key
+fn decrypt_twofas_services(encrypted: &str, password: &str) -> Result<Value, String> {
+    let parts: Vec<Vec<u8>> = encrypted
+        .split(':')
+        .map(|part| general_purpose::STANDARD.decode(part))
+        .collect::<Result<_, _>>()
+        .map_err(|_| "Invalid 2FAS encrypted data")?;
+    let (ciphertext, salt, iv) = match parts.as_slice() {
+        [ciphertext, salt, iv] => (ciphertext, salt, iv),
+        _ => return Err("Invalid 2FAS encrypted data".to_string()),
+    };
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, TWOFAS_PBKDF2_ITERATIONS, &mut key);
+    let plaintext = decrypt_aes256_gcm(&key, ciphertext, iv).map_err(|_| "Wrong backup password")?;
+    serde_json::from_slice(&plaintext).map_err(|e| format!("Invalid 2FAS backup: {}", e))
+}
+
+fn twofas_accounts(root: &Value, password: Option<&str>) -> Result<Vec<BackupAccount>, String> {
+    let services = match root.get("servicesEncrypted").and_then(Value::as_str) {
+        Some(encrypted) if !encrypted.is_empty() => {
+            let password = password.ok_or("This backup is encrypted; enter its password")?;
+            decrypt_twofas_services(encrypted, password)?
+        }
+        _ => root.get("services").cloned().unwrap_or(Value::Null),
+    };
+    let services = services.as_array().ok_or("2FAS backup has no services")?;
+    Ok(services
+        .iter()
+        .map(|s| {
+            let otp = s.get("otp").unwrap_or(&Value::Null);
+            let issuer = match str_field(otp, "issuer") {
+                "" => str_field(s, "name"),
+                issuer => issuer,
+            };
+            let account = match str_field(otp, "account") {
+                "" => str_field(otp, "label"),
+                account => account,
+            };
+            BackupAccount {
+                name: display_name(issuer, account),
+                params: backup_params(
+                    str_field(otp, "tokenType"),
+                    str_field(s, "secret"),
+                    str_field(otp, "algorithm"),
+                    otp.get("digits").and_then(Value::as_u64),
+                    otp.get("period").and_then(Value::as_u64),
+                    otp.get("counter").and_then(Value::as_u64),
+                    issuer,
+                    account,
+                ),
+                notes: None,
+            }
+        })
+        .collect())
+}
+
+fn andotp_accounts(entries: &[Value]) -> Vec<BackupAccount> {
+    entries
+        .iter()
+        .map(|e| {
+            let (issuer, account) = (str_field(e, "issuer"), str_field(e, "label"));
+            BackupAccount {
+                name: display_name(issuer, account),
+                params: backup_params(
+                    str_field(e, "type"),
+                    str_field(e, "secret"),
+                    str_field(e, "algorithm"),
+                    e.get("digits").and_then(Value::as_u64),
+                    e.get("period").and_then(Value::as_u64),
+                    e.get("counter").and_then(Value::as_u64),
+                    issuer,
+                    account,
+                ),
+                notes: None,
+            }
+        })
+        .collect()
+}
+
+/// Recognize the app a JSON backup comes from and read its accounts
+fn read_backup(
+    json: &str,
+    password: Option<&str>,
+) -> Result<(AuthenticatorApp, Vec<BackupAccount>), String> {
+    let root: Value =
+        serde_json::from_str(json).map_err(|e| format!("Backup is not valid JSON: {}", e))?;
+    if let Some(entries) = root.as_array() {
+        return Ok((AuthenticatorApp::AndOtp, andotp_accounts(entries)));
+    }
+    if root.get("db").is_some() {
+        return Ok((AuthenticatorApp::Aegis, aegis_accounts(&root, password)?));
+    }
+    if root.get("services").is_some() || root.get("servicesEncrypted").is_some() {
+        return Ok((AuthenticatorApp::TwoFas, twofas_accounts(&root, password)?));
+    }
+    Err("Not an Aegis, 2FAS or andOTP backup".to_string())
+}
+
+/// Duplicate key of an account: issuer (or entry title) and account name
+/// (or username), ignoring case
+fn duplicate_key(issuer: Option<&str>, account: Option<&str>) -> (String, String) {
+    (
+        issuer.unwrap_or("").trim().to_lowercase(),
+        account.unwrap_or("").trim().to_lowercase(),
+    )
+}
+
+impl Vault {
+    /// Store the accounts of a scanned QR code as `import_otp_accounts`
+    /// does
+    pub fn import_otp_qr_text(
+        &mut self,
+        token: &str,
+        text: &str,
+        entry_id: Option<i64>,
+    ) -> Result<Vec<ImportedOtp>, String> {
+        self.validate_session(token)?;
+        let accounts = parse_otp_qr_text(text)?;
+        self.import_otp_accounts(token, &accounts, entry_id)
+    }
+
+    /// Store parsed accounts. With `entry_id` the single account becomes the
+    /// TOTP secret of that entry of the active profile; otherwise every
+    /// account gets a new login entry titled after its issuer, all written
+    /// in one store transaction.
+    pub fn import_otp_accounts(
+        &mut self,
+        token: &str,
+        accounts: &[TotpParams],
+        entry_id: Option<i64>,
+    ) -> Result<Vec<ImportedOtp>, String> {
+        let (key, active_profile) = self.session_context(token)?;
+        if accounts.is_empty() {
+            return Err("No accounts to import".to_string());
+        }
+
+        if let Some(id) = entry_id {
+            if accounts.len() != 1 {
+                return Err(format!(
+                    "QR code holds {} accounts; only one can be attached to an entry",
+                    accounts.len()
+                ));
+            }
+            let stored = self.get_item(token, id)?;
+            let mut item = stored.item;
+            item.totp_secret = Some(accounts[0].to_uri());
+            self.update_item(token, id, &item)?;
+            return Ok(vec![ImportedOtp {
+                id,
+                entry_uuid: stored.entry_uuid,
+                title: item.title,
+                created: false,
+            }]);
+        }
+
+        let now = now_iso();
+        let mut records = Vec::new();
+        for account in accounts {
+            let item = otp_item(account, None);
+            item.validate()?;
+            let (data_blob, nonce) = encrypt_aes256_gcm(&key, &item.to_blob()?)?;
+            records.push(EntryRecord {
+                id: 0,
+                uuid: item.title.trim().to_string(),
+                data_blob,
+                nonce,
+                profile_id: active_profile,
+                entry_uuid: Some(Uuid::new_v4().to_string()),
+                created_at: now.clone(),
+                updated_at: now.clone(),
+                deleted_at: None,
+                sync_version: 1,
+            });
+        }
+        let written = self.store.write_entries(&records)?;
+
+        let mut imported = Vec::new();
+        for (id, record) in written.into_iter().zip(records) {
+            self.record_local_revision(id)?;
+            self.reindex_entry(&key, id)?;
+            imported.push(ImportedOtp {
+                id,
+                entry_uuid: record.entry_uuid,
+                title: record.uuid,
+                created: true,
+            });
+        }
+        Ok(imported)
+    }
+
+    /// Import an Aegis (plain or encrypted), 2FAS or andOTP JSON backup into
+    /// the active profile, one login entry per account. Accounts whose
+    /// issuer and account name match an existing entry are not imported
+    /// again.
+    pub fn import_authenticator_backup(
+        &mut self,
+        token: &str,
+        json: &str,
+        password: Option<&str>,
+    ) -> Result<OtpImportReport, String> {
+        self.validate_session(token)?;
+        let (app, accounts) = read_backup(json, password)?;
+
+        let mut seen: HashSet<(String, String)> = HashSet::new();
+        for stored in self.list_items(token, None)? {
+            let item = stored.item;
+            if let Some(Ok(params)) = item.totp_secret.as_deref().map(parse_totp) {
+                seen.insert(duplicate_key(
+                    params.issuer.as_deref().or(Some(item.title.as_str())),
+                    params.label.as_deref().or(item.username.as_deref()),
+                ));
+            }
+        }
+
+        let mut report = OtpImportReport {
+            app,
+            imported: Vec::new(),
+            duplicates: Vec::new(),
+            skipped: Vec::new(),
+        };
+        for account in accounts {
+            let params = match account.params {
+                Ok(params) => params,
+                Err(reason) => {
+                    report.skipped.push(SkippedOtp {
+                        name: account.name,
+                        reason,
+                    });
+                    continue;
+                }
+            };
+            let title = params.issuer.as_deref().or(params.label.as_deref());
+            if !seen.insert(duplicate_key(title, params.label.as_deref())) {
+                report.duplicates.push(account.name);
+                continue;
+            }
+            report
+                .imported
+                .push(self.save_otp_account(token, &params, account.notes.as_deref())?);
+        }
+        Ok(report)
+    }
+
+    /// Save one account as a new login entry of the active profile
+    pub(crate) fn save_otp_account(
+        &mut self,
+        token: &str,
+        account: &TotpParams,
+        notes: Option<&str>,
+    ) -> Result<ImportedOtp, String> {
+        let item = otp_item(account, notes);
+        let title = item.title.clone();
+        let entry_uuid = self.save_item(token, &item, None)?;
+        let id = self
+            .store
+            .find_entry_by_uuid(&entry_uuid)?
+            .ok_or("Saved entry not found")?
+            .id;
+        Ok(ImportedOtp {
+            id,
+            entry_uuid: Some(entry_uuid),
+            title,
+            created: true,
+        })
+    }
+}
+
+/// A new login entry holding one account, titled after its issuer
+fn otp_item(account: &TotpParams, notes: Option<&str>) -> VaultItem {
+    let title = account
+        .issuer
+        .clone()
+        .or_else(|| account.label.clone())
+        .unwrap_or_else(|| UNTITLED_ACCOUNT.to_string());
+    let mut item = VaultItem::new(&title);
+    item.username = account.label.clone();
+    item.totp_secret = Some(account.to_uri());
+    item.notes = notes.map(str::to_string);
+    item
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_migration_uri() {
+        let accounts = parse_migration_uri(
+            "otpauth-migration://offline?data=CjEKCkhlbGxvId6tvu8SGEV4YW1wbGU6YWxpY2VAZ29vZ2xlLmNvbRoHRXhhbXBsZTAC",
+        )
+        .unwrap();
+        assert_eq!(accounts.len(), 1);
+        let account = &accounts[0];
+        assert_eq!(account.kind, OtpKind::Totp);
+        assert_eq!(account.issuer.as_deref(), Some("Example"));
+        assert_eq!(account.label.as_deref(), Some("alice@google.com"));
+        assert_eq!(account.secret, parse_totp("JBSWY3DPEHPK3PXP").unwrap().secret);
+
+        // Percent-encoded data, as most QR generators write it
+        let encoded = "otpauth-migration://offline?data=CjEKCkhlbGxvId6tvu8SGEV4YW1wbGU6YWxpY2VAZ29vZ2xlLmNvbRoHRXhhbXBsZTAC"
+            .replace('+', "%2B");
+        assert_eq!(parse_migration_uri(&encoded).unwrap()[0].digits, 6);
+
+        assert!(parse_migration_uri("otpauth-migration://offline?data=").is_err());
+        assert!(parse_migration_uri("otpauth-migration://offline?data=CgM%3D").is_err());
+    }
+
+    #[test]
+    fn test_parse_qr_text() {
+        assert_eq!(
+            parse_otp_qr_text("otpauth://totp/GitHub:alice?secret=JBSWY3DPEHPK3PXP")
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(parse_otp_qr_text("https://example.com").is_err());
+        assert!(parse_otp_qr_text("JBSWY3DPEHPK3PXP").is_err());
+    }
+}
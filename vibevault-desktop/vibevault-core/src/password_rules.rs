@@ -0,0 +1,468 @@
+// Per-site password rules for the generator.
+//
+// Rules use the syntax of Apple's password-manager-resources
+// (`minlength: 8; maxlength: 20; required: lower; required: digit;
+// allowed: [-_!]; max-consecutive: 2;`) and the layout of its
+// `password-rules.json`, keyed by domain. A copy of that file is bundled;
+// a newer one can be imported and replaces it on this device. Users can
+// also set their own rules per domain, which win over both. All of it is
+// device-local settings; nothing here is secret or synced.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::generator::{GeneratedSecret, PasswordOptions};
+use crate::listing::favicon_domain;
+use crate::Vault;
+
+const BUNDLED_RULES: &str = include_str!("../data/password-rules.json");
+const IMPORTED_RULES_SETTING: &str = "password_rules";
+const OVERRIDES_SETTING: &str = "password_rule_overrides";
+
+/// Symbols of Apple's `special` class
+const SPECIAL: &str = "-~!@#$%^&*_+=`|(){}[:;\"'<>,.?] ";
+/// Members of `SPECIAL` the generator leaves out: quotes, backtick and
+/// space trip up too many forms and shells
+const UNGENERATED_SPECIAL: &str = "\"'` ";
+
+/// A set of characters in a `required` or `allowed` rule
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleCharset {
+    pub lower: bool,
+    pub upper: bool,
+    pub digit: bool,
+    pub special: bool,
+    /// Characters listed in brackets
+    pub custom: String,
+}
+
+/// Parsed password rules of a site
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PasswordRules {
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    pub max_consecutive: Option<usize>,
+    /// At least one character from each of these sets
+    pub required: Vec<RuleCharset>,
+    /// Further characters the site accepts
+    pub allowed: Vec<RuleCharset>,
+}
+
+/// Where the rules for a domain came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RuleSource {
+    Bundled,
+    Imported,
+    User,
+}
+
+/// The rules that apply to a URL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteRules {
+    /// Domain the rules are listed under, e.g. `apple.com` for
+    /// `https://appleid.apple.com`
+    pub domain: String,
+    pub rules: String,
+    pub source: RuleSource,
+}
+
+#[derive(Deserialize)]
+struct RulesEntry {
+    #[serde(rename = "password-rules")]
+    password_rules: Option<String>,
+}
+
+fn parse_charset(value: &str) -> Result<RuleCharset, String> {
+    let mut charset = RuleCharset::default();
+    let chars: Vec<char> = value.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            ',' | ' ' => i += 1,
+            '[' => {
+                // `]` closes the class only where the class may end: before
+                // a comma or at the end of the value
+                let mut j = i + 1;
+                loop {
+                    if j >= chars.len() {
+                        return Err("Unterminated character class".to_string());
+                    }
+                    let at_end = chars[j + 1..]
+                        .iter()
+                        .find(|c| **c != ' ')
+                        .is_none_or(|c| *c == ',');
+                    if chars[j] == ']' && at_end {
+                        break;
+                    }
+                    j += 1;
+                }
+                charset.custom.extend(&chars[i + 1..j]);
+                i = j + 1;
+            }
+            _ => {
+                let end = chars[i..]
+                    .iter()
+                    .position(|c| *c == ',')
+                    .map_or(chars.len(), |p| i + p);
+                let name: String = chars[i..end].iter().collect();
+                match name.trim().to_lowercase().as_str() {
+                    "lower" => charset.lower = true,
+                    "upper" => charset.upper = true,
+                    "digit" => charset.digit = true,
+                    "special" => charset.special = true,
+                    "ascii-printable" | "unicode" => {
+                        charset.lower = true;
+                        charset.upper = true;
+                        charset.digit = true;
+                        charset.special = true;
+                    }
+                    other => return Err(format!("Unknown character class: {}", other)),
+                }
+                i = end;
+            }
+        }
+    }
+    Ok(charset)
+}
+
+/// Parse rules such as `minlength: 8; required: lower, upper; allowed: [-_];`
+pub fn parse_rules(text: &str) -> Result<PasswordRules, String> {
+    let mut rules = PasswordRules::default();
+    let number = |value: &str, name: &str| {
+        value
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| format!("Invalid {}: {}", name, value.trim()))
+    };
+
+    // `;` may also appear inside a bracketed class, so split by hand
+    let mut rest = text;
+    while !rest.trim().is_empty() {
+        let (name, after) = rest
+            .split_once(':')
+            .ok_or_else(|| format!("Invalid password rule: {}", rest.trim()))?;
+        let mut depth = false;
+        let end = after
+            .char_indices()
+            .find(|(_, c)| {
+                match c {
+                    '[' => depth = true,
+                    ']' => depth = false,
+                    ';' if !depth => return true,
+                    _ => {}
+                }
+                false
+            })
+            .map_or(after.len(), |(i, _)| i);
+        let value = &after[..end];
+        rest = after.get(end + 1..).unwrap_or("");
+
+        match name.trim().to_lowercase().as_str() {
+            "minlength" => rules.min_length = Some(number(value, "minlength")?),
+            "maxlength" => rules.max_length = Some(number(value, "maxlength")?),
+            "max-consecutive" => rules.max_consecutive = Some(number(value, "max-consecutive")?),
+            "required" => rules.required.push(parse_charset(value)?),
+            "allowed" => rules.allowed.push(parse_charset(value)?),
+            other => return Err(format!("Unknown password rule: {}", other)),
+        }
+    }
+
+    if let (Some(min), Some(max)) = (rules.min_length, rules.max_length) {
+        if min > max {
+            return Err("minlength is larger than maxlength".to_string());
+        }
+    }
+    if rules.max_consecutive == Some(0) {
+        return Err("max-consecutive must be at least 1".to_string());
+    }
+    Ok(rules)
+}
+
+/// Generator options that follow `rules`, starting from the user's
+/// `options`. Classes the site requires are turned on, classes it does
+/// not accept are turned off, and the length is clamped to the site's
+/// range. Rules without any character sets keep the user's classes.
+pub fn apply_rules(rules: &PasswordRules, options: &PasswordOptions) -> PasswordOptions {
+    let mut applied = options.clone();
+    applied.pattern = None;
+    if let Some(min) = rules.min_length {
+        applied.length = applied.length.max(min);
+    }
+    if let Some(max) = rules.max_length {
+        applied.length = applied.length.min(max);
+    }
+    applied.max_consecutive = match (applied.max_consecutive, rules.max_consecutive) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+
+    let sets: Vec<&RuleCharset> = rules.required.iter().chain(&rules.allowed).collect();
+    if sets.is_empty() {
+        return applied;
+    }
+    let any = |f: fn(&RuleCharset) -> bool| sets.iter().any(|s| f(s));
+    applied.lowercase = any(|s| s.lower);
+    applied.uppercase = any(|s| s.upper);
+    applied.digits = any(|s| s.digit);
+
+    // Bracketed symbols (letters and digits in brackets are left to the
+    // classes), plus most of `special` when the site accepts all of it
+    let mut symbols: Vec<char> = sets
+        .iter()
+        .flat_map(|s| s.custom.chars())
+        .filter(|c| !c.is_alphanumeric() && !c.is_whitespace() && !c.is_control())
+        .collect();
+    if any(|s| s.special) {
+        symbols.extend(SPECIAL.chars().filter(|c| !UNGENERATED_SPECIAL.contains(*c)));
+    }
+    symbols.sort_unstable();
+    symbols.dedup();
+    applied.symbols = !symbols.is_empty();
+    applied.symbol_set = applied.symbols.then(|| symbols.into_iter().collect());
+
+    // A required set is met by one character of its first class; classes
+    // that are only allowed need none
+    let (mut lower, mut upper, mut digit, mut symbol) = (0, 0, 0, 0);
+    for set in &rules.required {
+        if set.lower {
+            lower = 1;
+        } else if set.upper {
+            upper = 1;
+        } else if set.digit {
+            digit = 1;
+        } else if set.special || !set.custom.is_empty() {
+            symbol = 1;
+        }
+    }
+    applied.min_lowercase = if applied.lowercase { options.min_lowercase.max(lower) } else { 0 };
+    applied.min_uppercase = if applied.uppercase { options.min_uppercase.max(upper) } else { 0 };
+    applied.min_digits = if applied.digits { options.min_digits.max(digit) } else { 0 };
+    applied.min_symbols = if applied.symbols { options.min_symbols.max(symbol) } else { 0 };
+    applied
+}
+
+/// Domain and its parents down to two labels: `a.b.example.com`,
+/// `b.example.com`, `example.com`
+fn candidate_domains(host: &str) -> Vec<String> {
+    let labels: Vec<&str> = host.split('.').collect();
+    (0..labels.len().saturating_sub(1))
+        .map(|i| labels[i..].join("."))
+        .collect()
+}
+
+fn parse_rules_file(json: &str) -> Result<HashMap<String, String>, String> {
+    let entries: HashMap<String, RulesEntry> =
+        serde_json::from_str(json).map_err(|e| format!("Invalid password rules file: {}", e))?;
+    Ok(entries
+        .into_iter()
+        .filter_map(|(domain, entry)| Some((domain.to_lowercase(), entry.password_rules?)))
+        .collect())
+}
+
+impl Vault {
+    fn rule_overrides(&self) -> Result<BTreeMap<String, String>, String> {
+        match self.store.get_setting(OVERRIDES_SETTING)? {
+            Some(json) => serde_json::from_str(&json).map_err(|e| e.to_string()),
+            None => Ok(BTreeMap::new()),
+        }
+    }
+
+    /// The rules that apply to `url`: the user's own rules, else those of
+    /// the imported or bundled rules file
+    pub fn password_rules_for_url(
+        &mut self,
+        token: &str,
+        url: &str,
+    ) -> Result<Option<SiteRules>, String> {
+        self.validate_session(token)?;
+        let host = match favicon_domain(url) {
+            Some(host) => host,
+            None => return Ok(None),
+        };
+        let overrides = self.rule_overrides()?;
+        let (file, source) = match self.store.get_setting(IMPORTED_RULES_SETTING)? {
+            Some(json) => (parse_rules_file(&json)?, RuleSource::Imported),
+            None => (parse_rules_file(BUNDLED_RULES)?, RuleSource::Bundled),
+        };
+
+        for domain in candidate_domains(&host) {
+            if let Some(rules) = overrides.get(&domain) {
+                return Ok(Some(SiteRules {
+                    domain,
+                    rules: rules.clone(),
+                    source: RuleSource::User,
+                }));
+            }
+            if let Some(rules) = file.get(&domain) {
+                return Ok(Some(SiteRules {
+                    domain,
+                    rules: rules.clone(),
+                    source,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Replace the bundled rules file on this device with a newer
+    /// `password-rules.json`. Returns the number of domains with rules.
+    /// Every rule must parse, so a broken file is rejected as a whole.
+    pub fn import_password_rules(&mut self, token: &str, json: &str) -> Result<usize, String> {
+        self.validate_session(token)?;
+        let rules = parse_rules_file(json)?;
+        for (domain, text) in &rules {
+            parse_rules(text).map_err(|e| format!("{}: {}", domain, e))?;
+        }
+        self.store.set_setting(IMPORTED_RULES_SETTING, json)?;
+        Ok(rules.len())
+    }
+
+    /// The user's own rules by domain
+    pub fn password_rule_overrides(
+        &mut self,
+        token: &str,
+    ) -> Result<BTreeMap<String, String>, String> {
+        self.validate_session(token)?;
+        self.rule_overrides()
+    }
+
+    /// Set the user's own rules for `domain` (and its subdomains), or
+    /// remove them with `None`
+    pub fn set_password_rule_override(
+        &mut self,
+        token: &str,
+        domain: &str,
+        rules: Option<&str>,
+    ) -> Result<(), String> {
+        self.validate_session(token)?;
+        let domain = favicon_domain(domain).ok_or("Invalid domain")?;
+        let mut overrides = self.rule_overrides()?;
+        match rules {
+            Some(rules) => {
+                parse_rules(rules)?;
+                overrides.insert(domain, rules.trim().to_string());
+            }
+            None => {
+                overrides.remove(&domain);
+            }
+        }
+        let json = serde_json::to_string(&overrides).map_err(|e| e.to_string())?;
+        self.store.set_setting(OVERRIDES_SETTING, &json)
+    }
+
+    /// Generate a password for a site: the rules for `url`, if there are
+    /// any, are applied on top of `options`
+    pub fn generate_password_for_url(
+        &mut self,
+        token: &str,
+        url: &str,
+        options: &PasswordOptions,
+    ) -> Result<GeneratedSecret, String> {
+        let site = match self.password_rules_for_url(token, url)? {
+            Some(site) => site,
+            None => return self.generate_password(token, options),
+        };
+        let rules = parse_rules(&site.rules).map_err(|e| format!("{}: {}", site.domain, e))?;
+        let mut generated = self.generate_password(token, &apply_rules(&rules, options))?;
+        generated.rules_domain = Some(site.domain);
+        Ok(generated)
+    }
+}
+
+/// Whether `value` meets `rules`
+#[cfg(test)]
+fn satisfies(rules: &PasswordRules, value: &str) -> bool {
+    let fits = |c: char, set: &RuleCharset| {
+        (set.lower && c.is_ascii_lowercase())
+            || (set.upper && c.is_ascii_uppercase())
+            || (set.digit && c.is_ascii_digit())
+            || (set.special && SPECIAL.contains(c))
+            || set.custom.contains(c)
+    };
+    let length = value.chars().count();
+    let any_set = !rules.required.is_empty() || !rules.allowed.is_empty();
+    rules.min_length.is_none_or(|min| length >= min)
+        && rules.max_length.is_none_or(|max| length <= max)
+        && rules
+            .required
+            .iter()
+            .all(|set| value.chars().any(|c| fits(c, set)))
+        && (!any_set
+            || value
+                .chars()
+                .all(|c| rules.required.iter().chain(&rules.allowed).any(|set| fits(c, set))))
+        && rules.max_consecutive.is_none_or(|max| {
+            let chars: Vec<char> = value.chars().collect();
+            chars.chunk_by(|a, b| a == b).all(|run| run.len() <= max)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::password;
+
+    #[test]
+    fn test_parse_rules() {
+        let rules = parse_rules(
+            "minlength: 8; maxlength: 12; max-consecutive: 2; required: lower, upper; \
+             required: digit; allowed: [-!\"#$%&'()*+,./:;<=>?@[^_`{|}~]];",
+        )
+        .unwrap();
+        assert_eq!(rules.min_length, Some(8));
+        assert_eq!(rules.max_length, Some(12));
+        assert_eq!(rules.max_consecutive, Some(2));
+        assert_eq!(rules.required.len(), 2);
+        assert!(rules.required[0].lower && rules.required[0].upper);
+        assert!(rules.allowed[0].custom.starts_with("-!\""));
+        assert!(rules.allowed[0].custom.ends_with("~]"));
+        assert!(rules.allowed[0].custom.contains(';'));
+
+        assert!(parse_rules("minlength: 20; maxlength: 8;").is_err());
+        assert!(parse_rules("required: emoji;").is_err());
+        assert!(parse_rules("colour: blue;").is_err());
+        assert!(parse_rules("allowed: [abc").is_err());
+        assert_eq!(parse_rules("").unwrap(), PasswordRules::default());
+    }
+
+    #[test]
+    fn test_bundled_rules_parse() {
+        let rules = parse_rules_file(BUNDLED_RULES).unwrap();
+        assert!(!rules.is_empty());
+        for (domain, text) in rules {
+            parse_rules(&text).unwrap_or_else(|e| panic!("{}: {}", domain, e));
+        }
+    }
+
+    #[test]
+    fn test_generated_passwords_follow_rules() {
+        for text in [
+            "minlength: 8; maxlength: 12; max-consecutive: 2; required: lower; required: upper; \
+             required: digit; allowed: [-!$*.=?@_'];",
+            "minlength: 6; maxlength: 16;",
+            "minlength: 8; required: digit; required: [- #$%&'()*+,./:;<=>?@[^_`{|}~]]; \
+             allowed: lower, upper;",
+            "maxlength: 10; required: digit;",
+            "minlength: 30; required: lower, upper; required: special;",
+        ] {
+            let rules = parse_rules(text).unwrap();
+            let options = apply_rules(&rules, &PasswordOptions::default());
+            for _ in 0..30 {
+                let value = password(&options).unwrap().value;
+                assert!(satisfies(&rules, &value), "{} does not satisfy {}", value, text);
+            }
+        }
+    }
+
+    #[test]
+    fn test_candidate_domains() {
+        assert_eq!(
+            candidate_domains("appleid.apple.com"),
+            ["appleid.apple.com", "apple.com"]
+        );
+        assert_eq!(candidate_domains("localhost"), Vec::<String>::new());
+    }
+}
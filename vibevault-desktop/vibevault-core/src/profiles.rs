@@ -0,0 +1,268 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::crypto::encrypt_aes256_gcm;
+use crate::revisions::ensure_baseline_revision;
+use crate::store::{EntryRecord, FolderRecord};
+use crate::vault::{decrypt_item, now_iso};
+use crate::Vault;
+
+/// A profile as shown in the profile switcher
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    pub id: i64,
+    pub name: String,
+    pub created_at: String,
+    pub entry_count: i64,
+}
+
+impl Vault {
+    pub fn create_profile(&mut self, token: &str, name: &str) -> Result<i64, String> {
+        self.validate_session(token)?;
+        self.store.create_profile(name)
+    }
+
+    pub fn list_profiles(&mut self, token: &str) -> Result<Vec<Profile>, String> {
+        self.validate_session(token)?;
+        let profiles = self
+            .store
+            .list_profiles()?
+            .into_iter()
+            .map(|p| Profile {
+                id: p.id,
+                name: p.name,
+                created_at: p.created_at,
+                entry_count: p.entry_count,
+            })
+            .collect();
+        Ok(profiles)
+    }
+
+    pub fn rename_profile(&mut self, token: &str, id: i64, name: &str) -> Result<(), String> {
+        self.validate_session(token)?;
+        self.store.rename_profile(id, name)
+    }
+
+    /// Delete a profile. A profile with entries is only deleted when
+    /// `move_entries_to` names another profile to move them into first; its
+    /// folders are deleted. The last remaining profile cannot be deleted.
+    pub fn delete_profile(
+        &mut self,
+        token: &str,
+        id: i64,
+        move_entries_to: Option<i64>,
+    ) -> Result<(), String> {
+        let key = self.validate_session(token)?;
+
+        let profiles = self.store.list_profiles()?;
+
+        // Check if profile has any active entries
+        let entry_count = profiles
+            .iter()
+            .find(|p| p.id == id)
+            .map(|p| p.entry_count)
+            .unwrap_or(0);
+
+        if entry_count > 0 && move_entries_to.is_none() {
+            return Err(
+                "Cannot delete profile with entries. Move or delete entries first.".to_string(),
+            );
+        }
+
+        // Check if it's the last profile
+        if profiles.len() <= 1 {
+            return Err("Cannot delete the last profile.".to_string());
+        }
+
+        let mut records = match move_entries_to {
+            Some(target) => {
+                if target == id {
+                    return Err("Cannot move entries into the profile being deleted".to_string());
+                }
+                let ids: Vec<i64> = self.store.list_entries(id)?.iter().map(|e| e.id).collect();
+                self.transfer_records(&key, id, &ids, target, false)?
+            }
+            None => Vec::new(),
+        };
+        let moved: Vec<i64> = records.iter().map(|e| e.id).collect();
+
+        // Tombstones must outlive the profile so deletes still sync; they
+        // follow the entries (or go to any remaining profile), as do the
+        // tombstones of the profile's folders below
+        let heir = move_entries_to
+            .or_else(|| profiles.iter().map(|p| p.id).find(|p| *p != id))
+            .unwrap_or(1);
+        records.extend(
+            self.store
+                .list_deleted_entries(id)?
+                .into_iter()
+                .map(|e| EntryRecord { profile_id: heir, ..e }),
+        );
+
+        let now = now_iso();
+        let folders: Vec<FolderRecord> = self
+            .store
+            .list_folders(id)?
+            .into_iter()
+            .map(|folder| FolderRecord {
+                profile_id: heir,
+                deleted_at: Some(now.clone()),
+                updated_at: now.clone(),
+                sync_version: folder.sync_version + 1,
+                ..folder
+            })
+            .collect();
+
+        // One transaction, so a failure never leaves a half-emptied profile
+        self.store.delete_profile(id, &records, &folders)?;
+        self.finish_transfer(&key, &moved)?;
+
+        if self.active_profile_id == id {
+            self.active_profile_id = heir;
+        }
+        Ok(())
+    }
+
+    /// Move entries of the active profile into `target_profile`. Their
+    /// sync version is bumped so the move reaches other devices. Folder
+    /// assignments are dropped since folders belong to one profile.
+    pub fn move_entries(
+        &mut self,
+        token: &str,
+        ids: &[i64],
+        target_profile: i64,
+    ) -> Result<(), String> {
+        let (key, active_profile) = self.session_context(token)?;
+        self.transfer_entries(&key, active_profile, ids, target_profile, false)?;
+        Ok(())
+    }
+
+    /// Copy entries of the active profile into `target_profile` as new
+    /// entries with their own sync UUIDs, which are returned in order.
+    /// Attachments and folder assignments are not copied.
+    pub fn copy_entries(
+        &mut self,
+        token: &str,
+        ids: &[i64],
+        target_profile: i64,
+    ) -> Result<Vec<String>, String> {
+        let (key, active_profile) = self.session_context(token)?;
+        let new_ids = self.transfer_entries(&key, active_profile, ids, target_profile, true)?;
+
+        let mut entry_uuids = Vec::new();
+        for id in new_ids {
+            if let Some(entry_uuid) = self.store.find_entry(id)?.and_then(|e| e.entry_uuid) {
+                entry_uuids.push(entry_uuid);
+            }
+        }
+        Ok(entry_uuids)
+    }
+
+    /// Move or copy entries of `source` into `target` in one store
+    /// transaction: if any entry is missing, nothing is written.
+    /// Returns the row IDs written.
+    fn transfer_entries(
+        &mut self,
+        key: &[u8; 32],
+        source: i64,
+        ids: &[i64],
+        target: i64,
+        copy: bool,
+    ) -> Result<Vec<i64>, String> {
+        let records = self.transfer_records(key, source, ids, target, copy)?;
+        let written = self.store.write_entries(&records)?;
+        self.finish_transfer(key, &written)?;
+        Ok(written)
+    }
+
+    /// The rows `transfer_entries` writes: entries of `source` re-encrypted
+    /// for `target`, or new copies of them
+    fn transfer_records(
+        &mut self,
+        key: &[u8; 32],
+        source: i64,
+        ids: &[i64],
+        target: i64,
+        copy: bool,
+    ) -> Result<Vec<EntryRecord>, String> {
+        if !self.store.list_profiles()?.iter().any(|p| p.id == target) {
+            return Err("Target profile not found".to_string());
+        }
+        if !copy && source == target {
+            return Err("Entries are already in this profile".to_string());
+        }
+
+        let mut entries: HashMap<i64, EntryRecord> = self
+            .store
+            .list_entries(source)?
+            .into_iter()
+            .map(|e| (e.id, e))
+            .collect();
+
+        let now = now_iso();
+        let mut records = Vec::new();
+        let mut seen = HashSet::new();
+        for id in ids {
+            if !seen.insert(*id) {
+                continue;
+            }
+            let entry = entries
+                .remove(id)
+                .ok_or("Entry not found or belongs to different profile")?;
+
+            let mut item = decrypt_item(key, &entry)?;
+            item.folder_id = None;
+            let (data_blob, nonce) = encrypt_aes256_gcm(key, &item.to_blob()?)?;
+
+            records.push(if copy {
+                EntryRecord {
+                    id: 0,
+                    uuid: entry.uuid,
+                    data_blob,
+                    nonce,
+                    profile_id: target,
+                    entry_uuid: Some(Uuid::new_v4().to_string()),
+                    created_at: now.clone(),
+                    updated_at: now.clone(),
+                    deleted_at: None,
+                    sync_version: 1,
+                }
+            } else {
+                ensure_baseline_revision(self.store.as_mut(), &entry)?;
+                EntryRecord {
+                    data_blob,
+                    nonce,
+                    profile_id: target,
+                    updated_at: now.clone(),
+                    sync_version: entry.sync_version + 1,
+                    ..entry
+                }
+            });
+        }
+
+        Ok(records)
+    }
+
+    /// Record a revision of each transferred row and refresh its search entry
+    fn finish_transfer(&mut self, key: &[u8; 32], written: &[i64]) -> Result<(), String> {
+        for id in written {
+            self.record_local_revision(*id)?;
+            self.reindex_entry(key, *id)?;
+        }
+        Ok(())
+    }
+
+    pub fn active_profile(&mut self, token: &str) -> Result<i64, String> {
+        self.validate_session(token)?;
+        Ok(self.active_profile_id)
+    }
+
+    pub fn set_active_profile(&mut self, token: &str, id: i64) -> Result<(), String> {
+        self.validate_session(token)?;
+        self.active_profile_id = id;
+        Ok(())
+    }
+}
@@ -0,0 +1,203 @@
+use serde::{Deserialize, Serialize};
+
+use crate::item::VaultItem;
+use crate::vault::now_iso;
+use crate::Vault;
+
+pub const MAX_RECOVERY_CODES: usize = 100;
+pub const MAX_RECOVERY_CODE_LEN: usize = 128;
+
+/// An entry with this many unused codes or fewer is running low and shows
+/// up in the health report
+pub const LOW_RECOVERY_CODES: usize = 2;
+
+/// A single-use backup code for a site's two-factor login.
+///
+/// Like the password history, codes live inside the item's encrypted blob
+/// and travel with the entry during sync.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryCode {
+    pub code: String,
+    /// When the code was marked as used (RFC 3339); `None` = still unused
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub used_at: Option<String>,
+}
+
+/// How many of an entry's recovery codes are left
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryCodeStatus {
+    pub total: usize,
+    pub remaining: usize,
+    /// `remaining` is at most `LOW_RECOVERY_CODES`
+    pub running_low: bool,
+}
+
+impl RecoveryCodeStatus {
+    pub(crate) fn of(item: &VaultItem) -> Self {
+        let remaining = item
+            .recovery_codes
+            .iter()
+            .filter(|c| c.used_at.is_none())
+            .count();
+        RecoveryCodeStatus {
+            total: item.recovery_codes.len(),
+            remaining,
+            running_low: !item.recovery_codes.is_empty() && remaining <= LOW_RECOVERY_CODES,
+        }
+    }
+}
+
+/// Sites print codes as `1234 5678`, `abcd-efgh` or `ABCDEFGH`; all three
+/// are the same code when typed back in
+pub(crate) fn normalize_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+pub(crate) fn validate_recovery_codes(codes: &[RecoveryCode]) -> Result<(), String> {
+    if codes.len() > MAX_RECOVERY_CODES {
+        return Err(format!(
+            "At most {} recovery codes are allowed",
+            MAX_RECOVERY_CODES
+        ));
+    }
+    let mut seen: Vec<String> = Vec::with_capacity(codes.len());
+    for code in codes {
+        let normalized = normalize_code(&code.code);
+        if normalized.is_empty() {
+            return Err("Recovery code cannot be empty".to_string());
+        }
+        if code.code.chars().count() > MAX_RECOVERY_CODE_LEN {
+            return Err(format!(
+                "Recovery codes must be at most {} characters",
+                MAX_RECOVERY_CODE_LEN
+            ));
+        }
+        if seen.contains(&normalized) {
+            return Err(format!("Duplicate recovery code: {}", code.code.trim()));
+        }
+        seen.push(normalized);
+    }
+    Ok(())
+}
+
+impl Vault {
+    /// Recovery codes of an entry of the active profile, in the order they
+    /// were added
+    pub fn recovery_codes(&mut self, token: &str, id: i64) -> Result<Vec<RecoveryCode>, String> {
+        Ok(self.get_item(token, id)?.item.recovery_codes)
+    }
+
+    pub fn recovery_code_status(
+        &mut self,
+        token: &str,
+        id: i64,
+    ) -> Result<RecoveryCodeStatus, String> {
+        Ok(RecoveryCodeStatus::of(&self.get_item(token, id)?.item))
+    }
+
+    /// Append a batch of codes, e.g. the list a site shows when two-factor
+    /// login is set up. Blank lines and codes the entry already has are
+    /// skipped, so pasting the same list twice changes nothing.
+    pub fn add_recovery_codes(
+        &mut self,
+        token: &str,
+        id: i64,
+        codes: &[String],
+    ) -> Result<RecoveryCodeStatus, String> {
+        let mut item = self.get_item(token, id)?.item;
+        let mut known: Vec<String> = item
+            .recovery_codes
+            .iter()
+            .map(|c| normalize_code(&c.code))
+            .collect();
+        let mut added = false;
+        for code in codes {
+            let normalized = normalize_code(code);
+            if normalized.is_empty() || known.contains(&normalized) {
+                continue;
+            }
+            known.push(normalized);
+            item.recovery_codes.push(RecoveryCode {
+                code: code.trim().to_string(),
+                used_at: None,
+            });
+            added = true;
+        }
+        if added {
+            self.update_item(token, id, &item)?;
+        }
+        Ok(RecoveryCodeStatus::of(&item))
+    }
+
+    /// Record that `code` has been used to log in. Spacing, dashes and case
+    /// do not matter; a code can only be used once.
+    pub fn mark_recovery_code_used(
+        &mut self,
+        token: &str,
+        id: i64,
+        code: &str,
+    ) -> Result<RecoveryCodeStatus, String> {
+        let mut item = self.get_item(token, id)?.item;
+        let normalized = normalize_code(code);
+        let entry = item
+            .recovery_codes
+            .iter_mut()
+            .find(|c| normalize_code(&c.code) == normalized)
+            .ok_or("Recovery code not found")?;
+        if entry.used_at.is_some() {
+            return Err("Recovery code has already been used".to_string());
+        }
+        entry.used_at = Some(now_iso());
+        self.update_item(token, id, &item)?;
+        Ok(RecoveryCodeStatus::of(&item))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(code: &str, used: bool) -> RecoveryCode {
+        RecoveryCode {
+            code: code.to_string(),
+            used_at: used.then(|| "2026-01-01T00:00:00Z".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_normalize_ignores_spacing_and_case() {
+        assert_eq!(normalize_code("ABCD-efgh"), "abcdefgh");
+        assert_eq!(normalize_code(" 1234 5678 "), "12345678");
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(validate_recovery_codes(&[code("1234 5678", false), code("abcd", true)]).is_ok());
+        assert!(validate_recovery_codes(&[code(" - ", false)]).is_err());
+        assert!(validate_recovery_codes(&[code("1234-5678", false), code("12345678", true)]).is_err());
+        assert!(validate_recovery_codes(&[code(&"a".repeat(MAX_RECOVERY_CODE_LEN + 1), false)]).is_err());
+        let many: Vec<RecoveryCode> = (0..=MAX_RECOVERY_CODES)
+            .map(|i| code(&format!("code{}", i), false))
+            .collect();
+        assert!(validate_recovery_codes(&many).is_err());
+    }
+
+    #[test]
+    fn test_status_counts_unused_codes() {
+        let mut item = VaultItem::new("GitHub");
+        let status = RecoveryCodeStatus::of(&item);
+        assert_eq!((status.total, status.remaining, status.running_low), (0, 0, false));
+
+        item.recovery_codes = vec![code("a", true), code("b", false), code("c", false)];
+        let status = RecoveryCodeStatus::of(&item);
+        assert_eq!((status.total, status.remaining, status.running_low), (3, 2, true));
+
+        item.recovery_codes.push(code("d", false));
+        assert!(!RecoveryCodeStatus::of(&item).running_low);
+    }
+}
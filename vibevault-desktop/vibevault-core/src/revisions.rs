@@ -0,0 +1,398 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::crypto::decrypt_aes256_gcm;
+use crate::item::VaultItem;
+use crate::store::{EntryRecord, RevisionRecord, VaultStore};
+use crate::Vault;
+
+/// Device ID recorded for revisions whose origin is not known (entries
+/// that predate revision tracking, or peers that do not send `modified_by`)
+pub const UNKNOWN_DEVICE: &str = "unknown";
+
+const DEVICE_ID_SETTING: &str = "device_id";
+const REVISION_RETENTION_SETTING: &str = "revision_retention";
+
+/// Revision metadata as listed to the frontend (content stays encrypted)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryRevision {
+    pub id: i64,
+    pub sync_version: i64,
+    pub title: String,
+    pub device_id: String,
+    pub created_at: String,
+    pub deleted: bool,
+}
+
+/// One field that differs between two revisions. Nested sections are
+/// reported with dotted names (`card.number`); lists as JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// How many revisions survive compaction: the `keep_latest` newest of each
+/// entry are always kept, older ones only while younger than `max_age_days`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevisionRetention {
+    pub keep_latest: usize,
+    pub max_age_days: i64,
+}
+
+impl Default for RevisionRetention {
+    fn default() -> Self {
+        RevisionRetention {
+            keep_latest: 20,
+            max_age_days: 365,
+        }
+    }
+}
+
+/// The retention chosen on this device, or the default if none was chosen
+pub(crate) fn stored_revision_retention(db: &dyn VaultStore) -> RevisionRetention {
+    db.get_setting(REVISION_RETENTION_SETTING)
+        .ok()
+        .flatten()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// This device's persistent ID, created on first use
+pub(crate) fn local_device_id(db: &mut dyn VaultStore) -> Result<String, String> {
+    if let Some(id) = db.get_setting(DEVICE_ID_SETTING)? {
+        return Ok(id);
+    }
+    let id = Uuid::new_v4().to_string();
+    db.set_setting(DEVICE_ID_SETTING, &id)?;
+    Ok(id)
+}
+
+/// Snapshot `entry` as a revision made on `device_id`
+pub(crate) fn record_revision(
+    db: &mut dyn VaultStore,
+    entry: &EntryRecord,
+    device_id: &str,
+) -> Result<(), String> {
+    let entry_uuid = match &entry.entry_uuid {
+        Some(entry_uuid) => entry_uuid.clone(),
+        None => return Ok(()),
+    };
+    db.append_revision(&RevisionRecord {
+        id: 0,
+        entry_uuid,
+        sync_version: entry.sync_version,
+        uuid: entry.uuid.clone(),
+        data_blob: entry.data_blob.clone(),
+        nonce: entry.nonce.clone(),
+        deleted_at: entry.deleted_at.clone(),
+        device_id: device_id.to_string(),
+        created_at: entry.updated_at.clone(),
+    })?;
+    Ok(())
+}
+
+/// Entries written before revision tracking have no history; snapshot their
+/// current state before it is overwritten so the first edit can be undone.
+pub(crate) fn ensure_baseline_revision(
+    db: &mut dyn VaultStore,
+    entry: &EntryRecord,
+) -> Result<(), String> {
+    if let Some(entry_uuid) = &entry.entry_uuid {
+        if db.list_revisions(entry_uuid)?.is_empty() {
+            record_revision(db, entry, UNKNOWN_DEVICE)?;
+        }
+    }
+    Ok(())
+}
+
+/// Device that produced the entry's current `sync_version`, if recorded
+pub(crate) fn modified_by(
+    db: &dyn VaultStore,
+    entry: &EntryRecord,
+) -> Result<Option<String>, String> {
+    let entry_uuid = match &entry.entry_uuid {
+        Some(entry_uuid) => entry_uuid,
+        None => return Ok(None),
+    };
+    Ok(db
+        .list_revisions(entry_uuid)?
+        .into_iter()
+        .find(|r| r.sync_version == entry.sync_version && r.device_id != UNKNOWN_DEVICE)
+        .map(|r| r.device_id))
+}
+
+/// Apply `retention` to every entry's revisions. Revisions of entries that
+/// no longer exist (purged tombstones) are removed entirely.
+pub(crate) fn compact_revisions(
+    db: &mut dyn VaultStore,
+    retention: RevisionRetention,
+    cutoff: &str,
+) -> Result<usize, String> {
+    let mut doomed = Vec::new();
+    for entry_uuid in db.revisioned_entry_uuids()? {
+        let revisions = db.list_revisions(&entry_uuid)?;
+        if db.find_entry_by_uuid(&entry_uuid)?.is_none() {
+            doomed.extend(revisions.iter().map(|r| r.id));
+            continue;
+        }
+        doomed.extend(
+            revisions
+                .iter()
+                .skip(retention.keep_latest.max(1))
+                .filter(|r| r.created_at.as_str() < cutoff)
+                .map(|r| r.id),
+        );
+    }
+    db.delete_revisions(&doomed)
+}
+
+/// Flatten an item into `field -> value` pairs for diffing
+fn flatten(prefix: &str, value: &Value, out: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map {
+                let name = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten(&name, v, out);
+            }
+        }
+        Value::Null => {}
+        Value::String(s) => out.push((prefix.to_string(), s.clone())),
+        other => out.push((prefix.to_string(), other.to_string())),
+    }
+}
+
+/// Field-by-field differences between two items, in field-name order
+pub fn diff_items(before: &VaultItem, after: &VaultItem) -> Result<Vec<FieldChange>, String> {
+    let mut old = Vec::new();
+    let mut new = Vec::new();
+    flatten(
+        "",
+        &serde_json::to_value(before).map_err(|e| e.to_string())?,
+        &mut old,
+    );
+    flatten(
+        "",
+        &serde_json::to_value(after).map_err(|e| e.to_string())?,
+        &mut new,
+    );
+
+    let mut fields: Vec<&String> = old.iter().chain(new.iter()).map(|(k, _)| k).collect();
+    fields.sort();
+    fields.dedup();
+
+    let lookup = |pairs: &[(String, String)], field: &str| {
+        pairs
+            .iter()
+            .find(|(k, _)| k == field)
+            .map(|(_, v)| v.clone())
+    };
+    Ok(fields
+        .into_iter()
+        .filter_map(|field| {
+            let before = lookup(&old, field);
+            let after = lookup(&new, field);
+            (before != after).then(|| FieldChange {
+                field: field.clone(),
+                before,
+                after,
+            })
+        })
+        .collect())
+}
+
+fn decrypt_revision(key: &[u8; 32], revision: &RevisionRecord) -> Result<Vec<u8>, String> {
+    if revision.nonce.is_empty() {
+        Ok(revision.data_blob.clone())
+    } else {
+        decrypt_aes256_gcm(key, &revision.data_blob, &revision.nonce)
+    }
+}
+
+impl Vault {
+    /// This device's persistent ID, as recorded on its revisions
+    pub fn device_id(&mut self) -> Result<String, String> {
+        local_device_id(self.store.as_mut())
+    }
+
+    pub fn revision_retention(&self) -> RevisionRetention {
+        self.revision_retention
+    }
+
+    /// Change how many revisions are kept. The retention is stored with the
+    /// vault, so the compaction on the next unlock already follows it.
+    pub fn set_revision_retention(
+        &mut self,
+        token: &str,
+        retention: RevisionRetention,
+    ) -> Result<(), String> {
+        self.validate_session(token)?;
+        if retention.keep_latest == 0 || retention.max_age_days < 0 {
+            return Err("Retention must keep at least one revision".to_string());
+        }
+        let json = serde_json::to_string(&retention).map_err(|e| e.to_string())?;
+        self.store.set_setting(REVISION_RETENTION_SETTING, &json)?;
+        self.revision_retention = retention;
+        Ok(())
+    }
+
+    /// Entry `id` of the active profile and its revisions, newest first
+    fn revisions_of(
+        &mut self,
+        token: &str,
+        id: i64,
+    ) -> Result<([u8; 32], EntryRecord, Vec<RevisionRecord>), String> {
+        let (key, active_profile) = self.session_context(token)?;
+        let entry = self
+            .store
+            .list_entries(active_profile)?
+            .into_iter()
+            .find(|e| e.id == id)
+            .ok_or("Entry not found or belongs to different profile")?;
+        let entry_uuid = entry.entry_uuid.clone().ok_or("Entry has no sync UUID")?;
+        let revisions = self.store.list_revisions(&entry_uuid)?;
+        Ok((key, entry, revisions))
+    }
+
+    /// Revisions of an entry of the active profile, newest first
+    pub fn entry_revisions(&mut self, token: &str, id: i64) -> Result<Vec<EntryRevision>, String> {
+        let (_key, _entry, revisions) = self.revisions_of(token, id)?;
+        Ok(revisions
+            .into_iter()
+            .map(|r| EntryRevision {
+                id: r.id,
+                sync_version: r.sync_version,
+                title: r.uuid,
+                device_id: r.device_id,
+                created_at: r.created_at,
+                deleted: r.deleted_at.is_some(),
+            })
+            .collect())
+    }
+
+    /// Field-by-field changes going from revision `from` to revision `to`
+    /// of the same entry
+    pub fn diff_revisions(
+        &mut self,
+        token: &str,
+        id: i64,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<FieldChange>, String> {
+        let (key, _entry, revisions) = self.revisions_of(token, id)?;
+        let find = |revision_id: i64| {
+            revisions
+                .iter()
+                .find(|r| r.id == revision_id)
+                .ok_or_else(|| "Revision not found".to_string())
+        };
+        let (from, to) = (find(from)?, find(to)?);
+        let before = VaultItem::from_blob(&from.uuid, &decrypt_revision(&key, from)?)?;
+        let after = VaultItem::from_blob(&to.uuid, &decrypt_revision(&key, to)?)?;
+        diff_items(&before, &after)
+    }
+
+    /// Make an old revision the entry's current content. This is a new edit:
+    /// the sync version is bumped so the rollback reaches other devices.
+    pub fn rollback_entry(&mut self, token: &str, id: i64, revision_id: i64) -> Result<(), String> {
+        let (key, _entry, revisions) = self.revisions_of(token, id)?;
+        let revision = revisions
+            .iter()
+            .find(|r| r.id == revision_id)
+            .ok_or("Revision not found")?;
+        let plaintext = decrypt_revision(&key, revision)?;
+        self.update_entry(token, id, &revision.uuid, &plaintext)
+    }
+
+    /// Drop revisions outside the retention policy; returns how many were removed
+    pub fn compact_revisions(&mut self, token: &str) -> Result<usize, String> {
+        self.validate_session(token)?;
+        let cutoff = (chrono::Utc::now()
+            - chrono::Duration::days(self.revision_retention.max_age_days))
+        .to_rfc3339();
+        compact_revisions(self.store.as_mut(), self.revision_retention, &cutoff)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MemoryStore;
+
+    #[test]
+    fn test_diff_items() {
+        let mut before = VaultItem::new("GitHub");
+        before.username = Some("alice".to_string());
+        before.password = Some("old".to_string());
+        let mut after = before.clone();
+        after.password = Some("new".to_string());
+        after.notes = Some("rotated".to_string());
+        after.urls = vec!["github.com".to_string()];
+
+        let changes = diff_items(&before, &after).unwrap();
+        let fields: Vec<_> = changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, ["notes", "password", "urls"]);
+        assert_eq!(changes[0].before, None);
+        assert_eq!(changes[1].after.as_deref(), Some("new"));
+    }
+
+    #[test]
+    fn test_compaction_keeps_latest_and_recent() {
+        let mut store = MemoryStore::new();
+        let mut entry = EntryRecord {
+            id: 0,
+            uuid: "github".to_string(),
+            data_blob: vec![1],
+            nonce: vec![],
+            profile_id: 1,
+            entry_uuid: Some("e-1".to_string()),
+            created_at: "2020-01-01T00:00:00+00:00".to_string(),
+            updated_at: String::new(),
+            deleted_at: None,
+            sync_version: 0,
+        };
+        store.insert_entry(&entry).unwrap();
+        for (version, day) in ["2020-01-01", "2020-06-01", "2021-01-01", "2024-01-01"]
+            .iter()
+            .enumerate()
+        {
+            entry.sync_version = version as i64 + 1;
+            entry.updated_at = format!("{}T00:00:00+00:00", day);
+            record_revision(&mut store, &entry, "desktop").unwrap();
+        }
+        // An orphaned history (entry already purged)
+        entry.entry_uuid = Some("gone".to_string());
+        record_revision(&mut store, &entry, "desktop").unwrap();
+
+        let retention = RevisionRetention {
+            keep_latest: 2,
+            max_age_days: 0,
+        };
+        let removed =
+            compact_revisions(&mut store, retention, "2020-12-01T00:00:00+00:00").unwrap();
+        assert_eq!(removed, 3);
+        let left: Vec<_> = store
+            .list_revisions("e-1")
+            .unwrap()
+            .iter()
+            .map(|r| r.sync_version)
+            .collect();
+        assert_eq!(left, [4, 3]);
+    }
+
+    #[test]
+    fn test_device_id_is_stable() {
+        let mut store = MemoryStore::new();
+        let id = local_device_id(&mut store).unwrap();
+        assert_eq!(local_device_id(&mut store).unwrap(), id);
+    }
+}
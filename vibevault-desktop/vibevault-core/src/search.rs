@@ -0,0 +1,464 @@
+// Full-text search over decrypted entries.
+//
+// The index is built from the decrypted items when the vault is unlocked
+// and dropped again when it locks, so plaintext never reaches disk. Every
+// write path (save, update, delete, restore, purge, sync import) keeps it
+// current. Queries are plain words matched fuzzily against title, username,
+// URLs, tags and notes, optionally narrowed with `field:value` filters such
+// as `user:alice tag:infra`.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::item::{StoredItem, VaultItem};
+use crate::item_types::ItemType;
+use crate::listing::favicon_domain;
+use crate::store::{EntryRecord, VaultStore};
+use crate::vault::{decrypt_item, to_stored_item};
+use crate::Vault;
+
+const MAX_QUERY_LEN: usize = 512;
+
+/// A ranked search result, best match first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub score: u32,
+    pub entry: StoredItem,
+}
+
+/// The entry field a query term is restricted to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchField {
+    Title,
+    Username,
+    Url,
+    Tag,
+    Notes,
+}
+
+impl SearchField {
+    const ALL: [SearchField; 5] = [
+        SearchField::Title,
+        SearchField::Username,
+        SearchField::Url,
+        SearchField::Tag,
+        SearchField::Notes,
+    ];
+
+    /// How much a match in this field counts towards the entry's score
+    fn weight(self) -> u32 {
+        match self {
+            SearchField::Title => 3,
+            SearchField::Username | SearchField::Url | SearchField::Tag => 2,
+            SearchField::Notes => 1,
+        }
+    }
+}
+
+/// One word of a query, optionally bound to a field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchTerm {
+    pub field: Option<SearchField>,
+    pub text: String,
+}
+
+/// A parsed query: every term must match; `type:` narrows the item type
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchQuery {
+    pub terms: Vec<SearchTerm>,
+    pub item_type: Option<ItemType>,
+}
+
+/// Parse `github user:alice tag:"prod infra" type:login`. Double quotes
+/// group words; unknown `prefix:` tokens (such as URLs) are searched as
+/// plain text.
+pub fn parse_query(query: &str) -> Result<SearchQuery, String> {
+    if query.chars().count() > MAX_QUERY_LEN {
+        return Err(format!("Search query must be at most {} characters", MAX_QUERY_LEN));
+    }
+
+    let mut parsed = SearchQuery::default();
+    for token in tokenize(query) {
+        let (field, value) = match token.split_once(':') {
+            Some((prefix, value)) => match prefix.to_lowercase().as_str() {
+                "title" => (Some(SearchField::Title), value),
+                "user" | "username" => (Some(SearchField::Username), value),
+                "url" => (Some(SearchField::Url), value),
+                "tag" => (Some(SearchField::Tag), value),
+                "note" | "notes" => (Some(SearchField::Notes), value),
+                "type" => {
+                    parsed.item_type = Some(parse_item_type(value)?);
+                    continue;
+                }
+                _ => (None, token.as_str()),
+            },
+            None => (None, token.as_str()),
+        };
+
+        let text = value.trim().to_lowercase();
+        if text.is_empty() {
+            return Err(format!("Missing value in search filter '{}'", token));
+        }
+        parsed.terms.push(SearchTerm { field, text });
+    }
+    Ok(parsed)
+}
+
+/// Split on whitespace, keeping double-quoted runs together (quotes removed)
+fn tokenize(query: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in query.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// Accept `sshKey`, `ssh_key`, `ssh-key` and `SSHKEY` alike
+fn parse_item_type(value: &str) -> Result<ItemType, String> {
+    let wanted: String = value
+        .chars()
+        .filter(|c| !matches!(c, '_' | '-'))
+        .collect::<String>()
+        .to_lowercase();
+    [
+        ItemType::Login,
+        ItemType::Card,
+        ItemType::Identity,
+        ItemType::SecureNote,
+        ItemType::SshKey,
+        ItemType::ApiCredential,
+        ItemType::Wifi,
+    ]
+    .into_iter()
+    .find(|t| format!("{:?}", t).to_lowercase() == wanted)
+    .ok_or_else(|| format!("Unknown item type '{}'", value))
+}
+
+/// Lowercased searchable text of one entry, plus what a list row shows
+pub(crate) struct IndexedEntry {
+    profile_id: i64,
+    pub(crate) item_type: ItemType,
+    pub(crate) display_title: String,
+    pub(crate) display_username: Option<String>,
+    pub(crate) favicon_domain: Option<String>,
+    title: String,
+    username: String,
+    urls: Vec<String>,
+    tags: Vec<String>,
+    notes: String,
+}
+
+impl IndexedEntry {
+    fn new(profile_id: i64, item: &VaultItem) -> Self {
+        let lower = |s: &Option<String>| s.as_deref().unwrap_or("").to_lowercase();
+        IndexedEntry {
+            profile_id,
+            item_type: item.item_type,
+            display_title: item.title.clone(),
+            display_username: item.username.clone().filter(|u| !u.is_empty()),
+            favicon_domain: item.urls.iter().find_map(|u| favicon_domain(u)),
+            title: item.title.to_lowercase(),
+            username: lower(&item.username),
+            urls: item.urls.iter().map(|u| u.to_lowercase()).collect(),
+            tags: item.tags.iter().map(|t| t.trim().to_lowercase()).collect(),
+            notes: lower(&item.notes),
+        }
+    }
+
+    fn field_texts(&self, field: SearchField) -> Vec<&str> {
+        match field {
+            SearchField::Title => vec![&self.title],
+            SearchField::Username => vec![&self.username],
+            SearchField::Url => self.urls.iter().map(String::as_str).collect(),
+            SearchField::Tag => self.tags.iter().map(String::as_str).collect(),
+            SearchField::Notes => vec![&self.notes],
+        }
+    }
+
+    /// Best weighted score of `term` over the fields it may match; 0 = no match
+    fn term_score(&self, term: &SearchTerm) -> u32 {
+        let fields = match term.field {
+            Some(field) => vec![field],
+            None => SearchField::ALL.to_vec(),
+        };
+        fields
+            .into_iter()
+            .flat_map(|field| {
+                // Subsequence matches are noise in long free text
+                let loose = field != SearchField::Notes;
+                self.field_texts(field)
+                    .into_iter()
+                    .map(move |text| field.weight() * match_score(&term.text, text, loose))
+            })
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl Drop for IndexedEntry {
+    fn drop(&mut self) {
+        self.display_title.zeroize();
+        self.display_username.zeroize();
+        self.favicon_domain.zeroize();
+        self.title.zeroize();
+        self.username.zeroize();
+        self.urls.zeroize();
+        self.tags.zeroize();
+        self.notes.zeroize();
+    }
+}
+
+/// How well `term` matches `text` (both lowercase): exact, word prefix,
+/// substring, a word within typo distance, or (when `loose`) the term's
+/// characters in order. 0 = no match.
+fn match_score(term: &str, text: &str, loose: bool) -> u32 {
+    if text.is_empty() {
+        return 0;
+    }
+    if text == term {
+        return 100;
+    }
+    if let Some(pos) = text.find(term) {
+        let at_word_start = text[..pos]
+            .chars()
+            .next_back()
+            .is_none_or(|c| !c.is_alphanumeric());
+        return if at_word_start { 80 } else { 60 };
+    }
+
+    let term_len = term.chars().count();
+    let max_typos = match term_len {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    };
+    if max_typos > 0 {
+        let close = text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .any(|word| {
+                // Compare against the whole word and its same-length prefix,
+                // so partially typed words still match
+                let prefix: String = word.chars().take(term_len).collect();
+                edit_distance(term, word) <= max_typos || edit_distance(term, &prefix) <= max_typos
+            });
+        if close {
+            return 40;
+        }
+    }
+
+    if loose && term_len >= 2 && is_subsequence(term, text) {
+        return 20;
+    }
+    0
+}
+
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut chars = haystack.chars();
+    needle.chars().all(|n| chars.any(|h| h == n))
+}
+
+/// Edit distance counting insertions, deletions, substitutions and
+/// transpositions of adjacent characters as one edit each
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// Searchable text of every active entry, keyed by entry ID.
+/// Lives only while the vault is unlocked.
+#[derive(Default)]
+pub(crate) struct SearchIndex {
+    entries: HashMap<i64, IndexedEntry>,
+}
+
+impl SearchIndex {
+    /// Index every active entry that decrypts with `key`
+    pub(crate) fn build(db: &dyn VaultStore, key: &[u8; 32]) -> Result<Self, String> {
+        let mut index = SearchIndex::default();
+        for entry in db.entries_updated_since(None)? {
+            index.upsert(key, &entry);
+        }
+        Ok(index)
+    }
+
+    /// (Re)index one entry; deleted or undecryptable entries are dropped
+    pub(crate) fn upsert(&mut self, key: &[u8; 32], entry: &EntryRecord) {
+        if entry.deleted_at.is_some() {
+            self.entries.remove(&entry.id);
+            return;
+        }
+        match decrypt_item(key, entry) {
+            Ok(item) => {
+                self.entries
+                    .insert(entry.id, IndexedEntry::new(entry.profile_id, &item));
+            }
+            Err(_) => {
+                self.entries.remove(&entry.id);
+            }
+        }
+    }
+
+    pub(crate) fn remove(&mut self, id: i64) {
+        self.entries.remove(&id);
+    }
+
+    /// IDs and scores of the entries of `profile_id` matching every term
+    fn search(&self, profile_id: i64, query: &SearchQuery) -> Vec<(i64, u32)> {
+        self.entries
+            .iter()
+            .filter(|(_, e)| e.profile_id == profile_id)
+            .filter(|(_, e)| query.item_type.is_none_or(|t| e.item_type == t))
+            .filter_map(|(id, e)| {
+                let mut total = 0;
+                for term in &query.terms {
+                    match e.term_score(term) {
+                        0 => return None,
+                        score => total += score,
+                    }
+                }
+                Some((*id, total))
+            })
+            .collect()
+    }
+
+    pub(crate) fn get(&self, id: i64) -> Option<&IndexedEntry> {
+        self.entries.get(&id)
+    }
+
+    fn title(&self, id: i64) -> &str {
+        self.entries.get(&id).map_or("", |e| e.title.as_str())
+    }
+}
+
+impl Vault {
+    /// Search the active profile. Results are ranked by score, then title;
+    /// a query with no words lists every entry (of the `type:`, if given).
+    pub fn search_entries(&mut self, token: &str, query: &str) -> Result<Vec<SearchHit>, String> {
+        let (key, active_profile) = self.session_context(token)?;
+        let query = parse_query(query)?;
+
+        let index = self.search_index(&key)?;
+
+        let mut matches = index.search(active_profile, &query);
+        matches.sort_by(|a, b| {
+            b.1.cmp(&a.1)
+                .then_with(|| index.title(a.0).cmp(index.title(b.0)))
+                .then_with(|| a.0.cmp(&b.0))
+        });
+
+        let mut entries: HashMap<i64, EntryRecord> = self
+            .store
+            .list_entries(active_profile)?
+            .into_iter()
+            .map(|e| (e.id, e))
+            .collect();
+        matches
+            .into_iter()
+            .filter_map(|(id, score)| entries.remove(&id).map(|entry| (entry, score)))
+            .map(|(entry, score)| {
+                Ok(SearchHit {
+                    score,
+                    entry: to_stored_item(&key, &entry)?,
+                })
+            })
+            .collect()
+    }
+
+    /// The index of the open session, built on first use if unlock did not
+    pub(crate) fn search_index(&mut self, key: &[u8; 32]) -> Result<&SearchIndex, String> {
+        if self.search_index.is_none() {
+            self.search_index = Some(SearchIndex::build(self.store.as_ref(), key)?);
+        }
+        self.search_index.as_ref().ok_or_else(|| "Search index unavailable".to_string())
+    }
+
+    /// Refresh entry `id` in the search index after it changed
+    pub(crate) fn reindex_entry(&mut self, key: &[u8; 32], id: i64) -> Result<(), String> {
+        if let Some(index) = self.search_index.as_mut() {
+            match self.store.find_entry(id)? {
+                Some(entry) => index.upsert(key, &entry),
+                None => index.remove(id),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_query_filters() {
+        let query = parse_query(r#"github User:alice tag:"prod infra" type:ssh_key"#).unwrap();
+        assert_eq!(query.item_type, Some(ItemType::SshKey));
+        assert_eq!(
+            query.terms,
+            vec![
+                SearchTerm { field: None, text: "github".to_string() },
+                SearchTerm { field: Some(SearchField::Username), text: "alice".to_string() },
+                SearchTerm { field: Some(SearchField::Tag), text: "prod infra".to_string() },
+            ]
+        );
+
+        // URLs are plain text, not filters
+        let query = parse_query("https://github.com").unwrap();
+        assert_eq!(query.terms[0].field, None);
+
+        assert!(parse_query("user:").is_err());
+        assert!(parse_query("type:spaceship").is_err());
+        assert!(parse_query("   ").unwrap().terms.is_empty());
+    }
+
+    #[test]
+    fn test_match_score_ranking() {
+        assert_eq!(match_score("github", "github", true), 100);
+        assert_eq!(match_score("hub", "git hub", true), 80);
+        assert_eq!(match_score("hub", "github", true), 60);
+        // One typo, one transposition, partially typed
+        assert_eq!(match_score("githbu", "github", true), 40);
+        assert_eq!(match_score("gihtub", "github", true), 40);
+        assert_eq!(match_score("gtihu", "github enterprise", true), 40);
+        // Subsequence only where loose matching is allowed
+        assert_eq!(match_score("ghb", "github", true), 20);
+        assert_eq!(match_score("ghb", "github", false), 0);
+        assert_eq!(match_score("xyz", "github", true), 0);
+    }
+}
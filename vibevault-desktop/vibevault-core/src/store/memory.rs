@@ -0,0 +1,766 @@
+use std::collections::HashMap;
+
+use super::{
+    AttachmentBlobRecord, AttachmentRecord, EncryptedChunk, EntryRecord, FavoriteRecord, FolderRecord,
+    GeneratedRecord, HotpCounterRecord, ProfileRecord, RekeyBatch, RevisionRecord, UsageRecord, UserRecord,
+    VaultStore,
+};
+use crate::sync::{PairedDevice, SyncHistoryEntry};
+
+/// Volatile `VaultStore` that mirrors the SQLite schema's behavior.
+/// Nothing is written to disk; intended for tests and tooling.
+pub struct MemoryStore {
+    users: Vec<UserRecord>,
+    failed_logins: (u32, Option<String>),
+    entries: Vec<EntryRecord>,
+    folders: Vec<FolderRecord>,
+    usage: HashMap<(String, String), UsageRecord>,
+    favorites: HashMap<String, FavoriteRecord>,
+    hotp_counters: HashMap<String, HotpCounterRecord>,
+    generated: Vec<GeneratedRecord>,
+    revisions: Vec<RevisionRecord>,
+    attachments: Vec<AttachmentRecord>,
+    attachment_blobs: Vec<AttachmentBlobRecord>,
+    attachment_chunks: HashMap<(String, i64), EncryptedChunk>,
+    profiles: Vec<(i64, String, String)>, // (id, name, created_at)
+    devices: Vec<PairedDevice>,
+    sync_log: Vec<SyncHistoryEntry>,
+    settings: HashMap<String, String>,
+    next_entry_id: i64,
+    next_folder_id: i64,
+    next_generated_id: i64,
+    next_revision_id: i64,
+    next_attachment_id: i64,
+    next_profile_id: i64,
+    next_device_id: i64,
+    next_log_id: i64,
+}
+
+fn sqlite_now() -> String {
+    chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+impl MemoryStore {
+    /// Create an empty store seeded with the default 'Personal' profile
+    pub fn new() -> Self {
+        MemoryStore {
+            users: Vec::new(),
+            failed_logins: (0, None),
+            entries: Vec::new(),
+            folders: Vec::new(),
+            usage: HashMap::new(),
+            favorites: HashMap::new(),
+            hotp_counters: HashMap::new(),
+            generated: Vec::new(),
+            revisions: Vec::new(),
+            attachments: Vec::new(),
+            attachment_blobs: Vec::new(),
+            attachment_chunks: HashMap::new(),
+            profiles: vec![(1, "Personal".to_string(), sqlite_now())],
+            devices: Vec::new(),
+            sync_log: Vec::new(),
+            settings: HashMap::new(),
+            next_entry_id: 1,
+            next_folder_id: 1,
+            next_generated_id: 1,
+            next_revision_id: 1,
+            next_attachment_id: 1,
+            next_profile_id: 2,
+            next_device_id: 1,
+            next_log_id: 1,
+        }
+    }
+
+    fn active_entry_mut(&mut self, id: i64, profile_id: i64) -> Option<&mut EntryRecord> {
+        self.entries
+            .iter_mut()
+            .find(|e| e.id == id && e.profile_id == profile_id && e.deleted_at.is_none())
+    }
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VaultStore for MemoryStore {
+    // --- Users ---
+
+    fn user_count(&self) -> Result<i64, String> {
+        Ok(self.users.len() as i64)
+    }
+
+    fn find_user(&self, username: &str) -> Result<Option<UserRecord>, String> {
+        Ok(self.users.iter().find(|u| u.username == username).cloned())
+    }
+
+    fn insert_user(&mut self, user: &UserRecord) -> Result<(), String> {
+        if self.users.iter().any(|u| u.username == user.username) {
+            return Err("UNIQUE constraint failed: users.username".to_string());
+        }
+        self.users.push(user.clone());
+        Ok(())
+    }
+
+    fn set_encryption_salt(&mut self, username: &str, encryption_salt: &str) -> Result<(), String> {
+        if let Some(user) = self.users.iter_mut().find(|u| u.username == username) {
+            user.encryption_salt = encryption_salt.to_string();
+        }
+        Ok(())
+    }
+
+    fn first_encryption_salt(&self) -> Result<String, String> {
+        self.users
+            .first()
+            .map(|u| u.encryption_salt.clone())
+            .ok_or_else(|| "Failed to get encryption_salt: no user registered".to_string())
+    }
+
+    fn login_attempts(&self) -> Result<(u32, Option<String>), String> {
+        Ok(self.failed_logins.clone())
+    }
+
+    fn record_failed_login(&mut self, at: &str) -> Result<(), String> {
+        self.failed_logins.0 += 1;
+        self.failed_logins.1 = Some(at.to_string());
+        Ok(())
+    }
+
+    fn reset_login_attempts(&mut self) -> Result<(), String> {
+        self.failed_logins = (0, None);
+        Ok(())
+    }
+
+    // --- Entries ---
+
+    fn list_entries(&self, profile_id: i64) -> Result<Vec<EntryRecord>, String> {
+        Ok(self
+            .entries
+            .iter()
+            .filter(|e| e.profile_id == profile_id && e.deleted_at.is_none())
+            .cloned()
+            .collect())
+    }
+
+    fn entries_updated_since(&self, since: Option<&str>) -> Result<Vec<EntryRecord>, String> {
+        Ok(self
+            .entries
+            .iter()
+            .filter(|e| since.is_none_or(|ts| e.updated_at.as_str() > ts))
+            .cloned()
+            .collect())
+    }
+
+    fn find_entry_by_uuid(&self, entry_uuid: &str) -> Result<Option<EntryRecord>, String> {
+        Ok(self
+            .entries
+            .iter()
+            .find(|e| e.entry_uuid.as_deref() == Some(entry_uuid))
+            .cloned())
+    }
+
+    fn find_entry(&self, id: i64) -> Result<Option<EntryRecord>, String> {
+        Ok(self.entries.iter().find(|e| e.id == id).cloned())
+    }
+
+    fn insert_entry(&mut self, entry: &EntryRecord) -> Result<i64, String> {
+        let id = self.next_entry_id;
+        self.next_entry_id += 1;
+        self.entries.push(EntryRecord {
+            id,
+            ..entry.clone()
+        });
+        Ok(id)
+    }
+
+    fn update_entry_content(
+        &mut self,
+        id: i64,
+        profile_id: i64,
+        uuid: &str,
+        data_blob: &[u8],
+        nonce: &[u8],
+        updated_at: &str,
+    ) -> Result<bool, String> {
+        match self.active_entry_mut(id, profile_id) {
+            Some(entry) => {
+                entry.uuid = uuid.to_string();
+                entry.data_blob = data_blob.to_vec();
+                entry.nonce = nonce.to_vec();
+                entry.updated_at = updated_at.to_string();
+                entry.sync_version += 1;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn soft_delete_entry(&mut self, id: i64, profile_id: i64, deleted_at: &str) -> Result<bool, String> {
+        match self.active_entry_mut(id, profile_id) {
+            Some(entry) => {
+                entry.deleted_at = Some(deleted_at.to_string());
+                entry.updated_at = deleted_at.to_string();
+                entry.sync_version += 1;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn list_deleted_entries(&self, profile_id: i64) -> Result<Vec<EntryRecord>, String> {
+        let mut entries: Vec<EntryRecord> = self
+            .entries
+            .iter()
+            .filter(|e| e.profile_id == profile_id && e.deleted_at.is_some())
+            .cloned()
+            .collect();
+        entries.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+        Ok(entries)
+    }
+
+    fn restore_entry(&mut self, id: i64, profile_id: i64, updated_at: &str) -> Result<bool, String> {
+        match self
+            .entries
+            .iter_mut()
+            .find(|e| e.id == id && e.profile_id == profile_id && e.deleted_at.is_some())
+        {
+            Some(entry) => {
+                entry.deleted_at = None;
+                entry.updated_at = updated_at.to_string();
+                entry.sync_version += 1;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn purge_entry(&mut self, id: i64, profile_id: i64) -> Result<bool, String> {
+        let before = self.entries.len();
+        self.entries
+            .retain(|e| !(e.id == id && e.profile_id == profile_id && e.deleted_at.is_some()));
+        Ok(self.entries.len() < before)
+    }
+
+    fn overwrite_entry(&mut self, entry: &EntryRecord) -> Result<(), String> {
+        if let Some(existing) = self.entries.iter_mut().find(|e| e.id == entry.id) {
+            *existing = entry.clone();
+        }
+        Ok(())
+    }
+
+    fn write_entries(&mut self, entries: &[EntryRecord]) -> Result<Vec<i64>, String> {
+        // Check every overwrite first so that a failure writes nothing
+        if let Some(missing) = entries
+            .iter()
+            .find(|e| e.id != 0 && !self.entries.iter().any(|existing| existing.id == e.id))
+        {
+            return Err(format!("Entry {} not found", missing.id));
+        }
+
+        let mut ids = Vec::with_capacity(entries.len());
+        for entry in entries {
+            if entry.id == 0 {
+                ids.push(self.insert_entry(entry)?);
+            } else {
+                self.overwrite_entry(entry)?;
+                ids.push(entry.id);
+            }
+        }
+        Ok(ids)
+    }
+
+    fn purge_tombstones(&mut self, cutoff: &str) -> Result<usize, String> {
+        let before = self.entries.len();
+        self.entries
+            .retain(|e| e.deleted_at.as_deref().is_none_or(|ts| ts >= cutoff));
+        Ok(before - self.entries.len())
+    }
+
+    // --- Folders ---
+
+    fn list_folders(&self, profile_id: i64) -> Result<Vec<FolderRecord>, String> {
+        Ok(self
+            .folders
+            .iter()
+            .filter(|f| f.profile_id == profile_id && f.deleted_at.is_none())
+            .cloned()
+            .collect())
+    }
+
+    fn folders_updated_since(&self, since: Option<&str>) -> Result<Vec<FolderRecord>, String> {
+        Ok(self
+            .folders
+            .iter()
+            .filter(|f| since.is_none_or(|ts| f.updated_at.as_str() > ts))
+            .cloned()
+            .collect())
+    }
+
+    fn find_folder_by_uuid(&self, folder_uuid: &str) -> Result<Option<FolderRecord>, String> {
+        Ok(self
+            .folders
+            .iter()
+            .find(|f| f.folder_uuid == folder_uuid)
+            .cloned())
+    }
+
+    fn insert_folder(&mut self, folder: &FolderRecord) -> Result<i64, String> {
+        if self.folders.iter().any(|f| f.folder_uuid == folder.folder_uuid) {
+            return Err("UNIQUE constraint failed: folders.folder_uuid".to_string());
+        }
+        let id = self.next_folder_id;
+        self.next_folder_id += 1;
+        self.folders.push(FolderRecord {
+            id,
+            ..folder.clone()
+        });
+        Ok(id)
+    }
+
+    fn overwrite_folder(&mut self, folder: &FolderRecord) -> Result<(), String> {
+        if let Some(existing) = self.folders.iter_mut().find(|f| f.id == folder.id) {
+            *existing = folder.clone();
+        }
+        Ok(())
+    }
+
+    // --- Usage ---
+
+    fn record_entry_use(
+        &mut self,
+        entry_uuid: &str,
+        device_id: &str,
+        at: &str,
+    ) -> Result<(), String> {
+        let usage = self
+            .usage
+            .entry((entry_uuid.to_string(), device_id.to_string()))
+            .or_insert_with(|| UsageRecord {
+                entry_uuid: entry_uuid.to_string(),
+                device_id: device_id.to_string(),
+                last_used_at: at.to_string(),
+                use_count: 0,
+            });
+        if at > usage.last_used_at.as_str() {
+            usage.last_used_at = at.to_string();
+        }
+        usage.use_count += 1;
+        Ok(())
+    }
+
+    fn merge_entry_usage(&mut self, usage: &UsageRecord) -> Result<(), String> {
+        let local = self
+            .usage
+            .entry((usage.entry_uuid.clone(), usage.device_id.clone()))
+            .or_insert_with(|| usage.clone());
+        if usage.last_used_at > local.last_used_at {
+            local.last_used_at = usage.last_used_at.clone();
+        }
+        local.use_count = local.use_count.max(usage.use_count);
+        Ok(())
+    }
+
+    fn list_entry_usage(&self) -> Result<Vec<UsageRecord>, String> {
+        Ok(self.usage.values().cloned().collect())
+    }
+
+    fn delete_entry_usage(&mut self, entry_uuid: &str) -> Result<(), String> {
+        self.usage.retain(|(uuid, _), _| uuid != entry_uuid);
+        Ok(())
+    }
+
+    // --- Favorites ---
+
+    fn find_favorite(&self, entry_uuid: &str) -> Result<Option<FavoriteRecord>, String> {
+        Ok(self.favorites.get(entry_uuid).cloned())
+    }
+
+    fn list_favorites(&self) -> Result<Vec<FavoriteRecord>, String> {
+        Ok(self.favorites.values().cloned().collect())
+    }
+
+    fn put_favorite(&mut self, favorite: &FavoriteRecord) -> Result<(), String> {
+        self.favorites
+            .insert(favorite.entry_uuid.clone(), favorite.clone());
+        Ok(())
+    }
+
+    fn delete_favorite(&mut self, entry_uuid: &str) -> Result<(), String> {
+        self.favorites.remove(entry_uuid);
+        Ok(())
+    }
+
+    // --- HOTP counters ---
+
+    fn find_hotp_counter(&self, entry_uuid: &str) -> Result<Option<HotpCounterRecord>, String> {
+        Ok(self.hotp_counters.get(entry_uuid).cloned())
+    }
+
+    fn list_hotp_counters(&self) -> Result<Vec<HotpCounterRecord>, String> {
+        Ok(self.hotp_counters.values().cloned().collect())
+    }
+
+    fn merge_hotp_counter(&mut self, counter: &HotpCounterRecord) -> Result<(), String> {
+        let local = self
+            .hotp_counters
+            .entry(counter.entry_uuid.clone())
+            .or_insert_with(|| counter.clone());
+        if counter.updated_at > local.updated_at {
+            local.updated_at = counter.updated_at.clone();
+        }
+        local.counter = local.counter.max(counter.counter);
+        Ok(())
+    }
+
+    fn delete_hotp_counter(&mut self, entry_uuid: &str) -> Result<(), String> {
+        self.hotp_counters.remove(entry_uuid);
+        Ok(())
+    }
+
+    // --- Generator history ---
+
+    fn append_generated(&mut self, record: &GeneratedRecord) -> Result<i64, String> {
+        let id = self.next_generated_id;
+        self.next_generated_id += 1;
+        self.generated.push(GeneratedRecord {
+            id,
+            ..record.clone()
+        });
+        Ok(id)
+    }
+
+    fn list_generated(&self) -> Result<Vec<GeneratedRecord>, String> {
+        Ok(self.generated.iter().rev().cloned().collect())
+    }
+
+    fn delete_generated(&mut self, ids: &[i64]) -> Result<usize, String> {
+        let before = self.generated.len();
+        self.generated.retain(|r| !ids.contains(&r.id));
+        Ok(before - self.generated.len())
+    }
+
+    // --- Revisions ---
+
+    fn append_revision(&mut self, revision: &RevisionRecord) -> Result<i64, String> {
+        let id = self.next_revision_id;
+        self.next_revision_id += 1;
+        self.revisions.push(RevisionRecord {
+            id,
+            ..revision.clone()
+        });
+        Ok(id)
+    }
+
+    fn list_revisions(&self, entry_uuid: &str) -> Result<Vec<RevisionRecord>, String> {
+        Ok(self
+            .revisions
+            .iter()
+            .rev()
+            .filter(|r| r.entry_uuid == entry_uuid)
+            .cloned()
+            .collect())
+    }
+
+    fn find_revision(&self, id: i64) -> Result<Option<RevisionRecord>, String> {
+        Ok(self.revisions.iter().find(|r| r.id == id).cloned())
+    }
+
+    fn revisioned_entry_uuids(&self) -> Result<Vec<String>, String> {
+        let mut uuids: Vec<String> = self.revisions.iter().map(|r| r.entry_uuid.clone()).collect();
+        uuids.sort();
+        uuids.dedup();
+        Ok(uuids)
+    }
+
+    fn delete_revisions(&mut self, ids: &[i64]) -> Result<usize, String> {
+        let before = self.revisions.len();
+        self.revisions.retain(|r| !ids.contains(&r.id));
+        Ok(before - self.revisions.len())
+    }
+
+    // --- Attachments ---
+
+    fn put_attachment_chunk(
+        &mut self,
+        blob_id: &str,
+        index: i64,
+        data: &[u8],
+        nonce: &[u8],
+    ) -> Result<(), String> {
+        self.attachment_chunks
+            .insert((blob_id.to_string(), index), (data.to_vec(), nonce.to_vec()));
+        Ok(())
+    }
+
+    fn get_attachment_chunk(
+        &self,
+        blob_id: &str,
+        index: i64,
+    ) -> Result<Option<EncryptedChunk>, String> {
+        Ok(self
+            .attachment_chunks
+            .get(&(blob_id.to_string(), index))
+            .cloned())
+    }
+
+    fn insert_attachment_blob(&mut self, blob: &AttachmentBlobRecord) -> Result<(), String> {
+        if self.attachment_blobs.iter().any(|b| b.blob_id == blob.blob_id) {
+            return Err("UNIQUE constraint failed: attachment_blobs.blob_id".to_string());
+        }
+        self.attachment_blobs.push(blob.clone());
+        Ok(())
+    }
+
+    fn find_attachment_blob_by_hash(
+        &self,
+        content_hash: &str,
+    ) -> Result<Option<AttachmentBlobRecord>, String> {
+        Ok(self
+            .attachment_blobs
+            .iter()
+            .find(|b| b.content_hash == content_hash)
+            .cloned())
+    }
+
+    fn delete_attachment_blob(&mut self, blob_id: &str) -> Result<(), String> {
+        self.attachment_chunks.retain(|(id, _), _| id != blob_id);
+        self.attachment_blobs.retain(|b| b.blob_id != blob_id);
+        Ok(())
+    }
+
+    fn attachment_blob_ids(&self) -> Result<Vec<String>, String> {
+        let mut ids: Vec<String> = self
+            .attachment_chunks
+            .keys()
+            .map(|(id, _)| id.clone())
+            .chain(self.attachment_blobs.iter().map(|b| b.blob_id.clone()))
+            .collect();
+        ids.sort();
+        ids.dedup();
+        Ok(ids)
+    }
+
+    fn insert_attachment(&mut self, attachment: &AttachmentRecord) -> Result<i64, String> {
+        let id = self.next_attachment_id;
+        self.next_attachment_id += 1;
+        self.attachments.push(AttachmentRecord {
+            id,
+            ..attachment.clone()
+        });
+        Ok(id)
+    }
+
+    fn list_attachments(&self, entry_uuid: &str) -> Result<Vec<AttachmentRecord>, String> {
+        Ok(self
+            .attachments
+            .iter()
+            .filter(|a| a.entry_uuid == entry_uuid)
+            .cloned()
+            .collect())
+    }
+
+    fn list_all_attachments(&self) -> Result<Vec<AttachmentRecord>, String> {
+        Ok(self.attachments.clone())
+    }
+
+    fn find_attachment(&self, id: i64) -> Result<Option<AttachmentRecord>, String> {
+        Ok(self.attachments.iter().find(|a| a.id == id).cloned())
+    }
+
+    fn delete_attachment(&mut self, id: i64) -> Result<bool, String> {
+        let before = self.attachments.len();
+        self.attachments.retain(|a| a.id != id);
+        Ok(self.attachments.len() < before)
+    }
+
+    // --- Vault key ---
+
+    fn replace_vault_key(&mut self, batch: &RekeyBatch) -> Result<(), String> {
+        let user = self
+            .users
+            .iter()
+            .position(|u| u.username == batch.user.username)
+            .ok_or("User not found")?;
+        // write_entries checks its rows before writing any of them
+        self.write_entries(&batch.entries)?;
+        self.users[user] = batch.user.clone();
+        for folder in &batch.folders {
+            self.overwrite_folder(folder)?;
+        }
+        for revision in &batch.revisions {
+            if let Some(existing) = self.revisions.iter_mut().find(|r| r.id == revision.id) {
+                existing.data_blob = revision.data_blob.clone();
+                existing.nonce = revision.nonce.clone();
+            }
+        }
+        for record in &batch.generated {
+            if let Some(existing) = self.generated.iter_mut().find(|r| r.id == record.id) {
+                existing.data_blob = record.data_blob.clone();
+                existing.nonce = record.nonce.clone();
+            }
+        }
+        for attachment in &batch.attachments {
+            if let Some(existing) = self.attachments.iter_mut().find(|a| a.id == attachment.id) {
+                existing.meta_blob = attachment.meta_blob.clone();
+                existing.meta_nonce = attachment.meta_nonce.clone();
+            }
+        }
+        for (blob_id, content_hash) in &batch.blob_hashes {
+            if let Some(existing) = self.attachment_blobs.iter_mut().find(|b| &b.blob_id == blob_id) {
+                existing.content_hash = content_hash.clone();
+            }
+        }
+        for (blob_id, index, data, nonce) in &batch.chunks {
+            if let Some(existing) = self.attachment_chunks.get_mut(&(blob_id.clone(), *index)) {
+                *existing = (data.clone(), nonce.clone());
+            }
+        }
+        Ok(())
+    }
+
+    // --- Profiles ---
+
+    fn list_profiles(&self) -> Result<Vec<ProfileRecord>, String> {
+        let mut profiles: Vec<ProfileRecord> = self
+            .profiles
+            .iter()
+            .map(|(id, name, created_at)| ProfileRecord {
+                id: *id,
+                name: name.clone(),
+                created_at: created_at.clone(),
+                entry_count: self
+                    .entries
+                    .iter()
+                    .filter(|e| e.profile_id == *id && e.deleted_at.is_none())
+                    .count() as i64,
+            })
+            .collect();
+        profiles.sort_by_key(|p| p.id);
+        Ok(profiles)
+    }
+
+    fn find_profile_by_name(&self, name: &str) -> Result<Option<i64>, String> {
+        Ok(self
+            .profiles
+            .iter()
+            .find(|(_, n, _)| n == name)
+            .map(|(id, _, _)| *id))
+    }
+
+    fn create_profile(&mut self, name: &str) -> Result<i64, String> {
+        if self.profiles.iter().any(|(_, n, _)| n == name) {
+            return Err("UNIQUE constraint failed: profiles.name".to_string());
+        }
+        let id = self.next_profile_id;
+        self.next_profile_id += 1;
+        self.profiles.push((id, name.to_string(), sqlite_now()));
+        Ok(id)
+    }
+
+    fn rename_profile(&mut self, id: i64, name: &str) -> Result<(), String> {
+        if self.profiles.iter().any(|(pid, n, _)| n == name && *pid != id) {
+            return Err("UNIQUE constraint failed: profiles.name".to_string());
+        }
+        if let Some(profile) = self.profiles.iter_mut().find(|(pid, _, _)| *pid == id) {
+            profile.1 = name.to_string();
+        }
+        Ok(())
+    }
+
+    fn delete_profile(
+        &mut self,
+        id: i64,
+        entries: &[EntryRecord],
+        folders: &[FolderRecord],
+    ) -> Result<(), String> {
+        // write_entries checks its rows before writing any of them
+        self.write_entries(entries)?;
+        for folder in folders {
+            self.overwrite_folder(folder)?;
+        }
+        self.profiles.retain(|(pid, _, _)| *pid != id);
+        Ok(())
+    }
+
+    // --- Paired devices ---
+
+    fn list_paired_devices(&self) -> Result<Vec<PairedDevice>, String> {
+        let mut devices = self.devices.clone();
+        devices.sort_by(|a, b| b.paired_at.cmp(&a.paired_at));
+        Ok(devices)
+    }
+
+    fn add_paired_device(
+        &mut self,
+        device_name: &str,
+        device_id: &str,
+        _public_key: &[u8],
+        _shared_secret: &[u8],
+    ) -> Result<i64, String> {
+        // Key material is only needed for re-pairing, which tests never exercise
+        if self.devices.iter().any(|d| d.device_id == device_id) {
+            return Err("UNIQUE constraint failed: paired_devices.device_id".to_string());
+        }
+        let id = self.next_device_id;
+        self.next_device_id += 1;
+        self.devices.push(PairedDevice {
+            id,
+            device_name: device_name.to_string(),
+            device_id: device_id.to_string(),
+            paired_at: sqlite_now(),
+            last_sync_at: None,
+        });
+        Ok(id)
+    }
+
+    fn forget_device(&mut self, device_id: &str) -> Result<(), String> {
+        self.devices.retain(|d| d.device_id != device_id);
+        Ok(())
+    }
+
+    fn get_last_sync_at(&self, device_id: &str) -> Result<Option<String>, String> {
+        self.devices
+            .iter()
+            .find(|d| d.device_id == device_id)
+            .map(|d| d.last_sync_at.clone())
+            .ok_or_else(|| "Query returned no rows".to_string())
+    }
+
+    fn set_last_sync_at(&mut self, device_id: &str, at: &str) -> Result<(), String> {
+        if let Some(d) = self.devices.iter_mut().find(|d| d.device_id == device_id) {
+            d.last_sync_at = Some(at.to_string());
+        }
+        Ok(())
+    }
+
+    // --- Sync log ---
+
+    fn append_sync_log(&mut self, entry: &SyncHistoryEntry) -> Result<(), String> {
+        let id = self.next_log_id;
+        self.next_log_id += 1;
+        self.sync_log.push(SyncHistoryEntry {
+            id,
+            ..entry.clone()
+        });
+        Ok(())
+    }
+
+    fn sync_history(&self, limit: usize) -> Result<Vec<SyncHistoryEntry>, String> {
+        let mut history = self.sync_log.clone();
+        history.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+        history.truncate(limit);
+        Ok(history)
+    }
+
+    // --- Settings ---
+
+    fn get_setting(&self, key: &str) -> Result<Option<String>, String> {
+        Ok(self.settings.get(key).cloned())
+    }
+
+    fn set_setting(&mut self, key: &str, value: &str) -> Result<(), String> {
+        self.settings.insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+}
@@ -0,0 +1,848 @@
+// Storage abstraction for everything the app persists.
+//
+// Commands and the sync engine talk to a `VaultStore` instead of reaching
+// into `rusqlite` directly, so the same logic runs against the on-disk
+// SQLite database in the app and against `MemoryStore` in tests.
+
+pub mod memory;
+pub mod sqlite;
+
+pub use memory::MemoryStore;
+
+use crate::sync::{PairedDevice, SyncHistoryEntry};
+
+/// A row of the `users` table
+#[derive(Debug, Clone)]
+pub struct UserRecord {
+    pub username: String,
+    pub password_hash: String,
+    pub salt: String,
+    /// Hex-encoded salt for vault key derivation (empty for legacy users)
+    pub encryption_salt: String,
+}
+
+/// A stored vault entry. `data_blob` is ciphertext unless `nonce` is empty
+/// (legacy plaintext rows awaiting migration).
+#[derive(Debug, Clone)]
+pub struct EntryRecord {
+    pub id: i64,
+    pub uuid: String, // site name
+    pub data_blob: Vec<u8>,
+    pub nonce: Vec<u8>,
+    pub profile_id: i64,
+    pub entry_uuid: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub deleted_at: Option<String>,
+    pub sync_version: i64,
+}
+
+/// A folder of one profile. Its name and parent folder are encrypted
+/// together in `data_blob`, so neither the names nor the shape of the tree
+/// are stored in the clear.
+#[derive(Debug, Clone)]
+pub struct FolderRecord {
+    pub id: i64,
+    pub folder_uuid: String,
+    pub profile_id: i64,
+    pub data_blob: Vec<u8>,
+    pub nonce: Vec<u8>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub deleted_at: Option<String>,
+    pub sync_version: i64,
+}
+
+/// How often and how recently one device used an entry. Each device only
+/// counts its own uses, so rows merge across devices without conflicts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageRecord {
+    pub entry_uuid: String,
+    pub device_id: String,
+    pub last_used_at: String,
+    pub use_count: i64,
+}
+
+/// Favorite flag of an entry, kept apart from the encrypted blob
+#[derive(Debug, Clone, PartialEq)]
+pub struct FavoriteRecord {
+    pub entry_uuid: String,
+    pub favorite: bool,
+    pub updated_at: String,
+}
+
+/// The next unused counter of an HOTP entry. Counters only ever move
+/// forward, so copies from different devices merge by taking the larger.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HotpCounterRecord {
+    pub entry_uuid: String,
+    pub counter: i64,
+    pub updated_at: String,
+}
+
+/// A value produced by the password generator, encrypted with the vault key
+#[derive(Debug, Clone)]
+pub struct GeneratedRecord {
+    pub id: i64,
+    pub data_blob: Vec<u8>,
+    pub nonce: Vec<u8>,
+    pub created_at: String,
+}
+
+/// An encrypted snapshot of an entry as of one `sync_version`
+#[derive(Debug, Clone)]
+pub struct RevisionRecord {
+    pub id: i64,
+    pub entry_uuid: String,
+    pub sync_version: i64,
+    pub uuid: String, // site name at this revision
+    pub data_blob: Vec<u8>,
+    pub nonce: Vec<u8>,
+    pub deleted_at: Option<String>,
+    /// Device the change was made on
+    pub device_id: String,
+    pub created_at: String,
+}
+
+/// A file attached to an entry. The name and MIME type are encrypted
+/// together in `meta_blob`; the content lives in the blob `blob_id`, which
+/// identical files share.
+#[derive(Debug, Clone)]
+pub struct AttachmentRecord {
+    pub id: i64,
+    pub entry_uuid: String,
+    pub blob_id: String,
+    pub meta_blob: Vec<u8>,
+    pub meta_nonce: Vec<u8>,
+    pub size: i64,
+    pub chunk_count: i64,
+    pub created_at: String,
+}
+
+/// One stored attachment chunk: (ciphertext, nonce)
+pub type EncryptedChunk = (Vec<u8>, Vec<u8>);
+
+/// Deduplicated attachment content: `content_hash` is a keyed MAC of the
+/// plaintext, so equal files can be matched without revealing their content.
+#[derive(Debug, Clone)]
+pub struct AttachmentBlobRecord {
+    pub blob_id: String,
+    pub content_hash: String,
+    pub size: i64,
+    pub chunk_count: i64,
+    pub created_at: String,
+}
+
+/// Everything encrypted with the vault key, re-encrypted under a new one,
+/// together with the user record that derives the new key
+#[derive(Debug, Clone)]
+pub struct RekeyBatch {
+    pub user: UserRecord,
+    pub entries: Vec<EntryRecord>,
+    pub folders: Vec<FolderRecord>,
+    pub revisions: Vec<RevisionRecord>,
+    pub generated: Vec<GeneratedRecord>,
+    pub attachments: Vec<AttachmentRecord>,
+    /// (blob_id, content_hash) with the hash keyed by the new key
+    pub blob_hashes: Vec<(String, String)>,
+    /// (blob_id, chunk index, ciphertext, nonce)
+    pub chunks: Vec<(String, i64, Vec<u8>, Vec<u8>)>,
+}
+
+/// A profile together with its number of active (non-deleted) entries
+#[derive(Debug, Clone)]
+pub struct ProfileRecord {
+    pub id: i64,
+    pub name: String,
+    pub created_at: String,
+    pub entry_count: i64,
+}
+
+/// Persistence backend for users, entries, folders, usage, favorites, generator
+/// history, revisions, profiles, paired devices, the sync log and
+/// device-local settings.
+///
+/// Timestamps are passed in by the caller so that backends never read the clock.
+pub trait VaultStore: Send {
+    // --- Users ---
+
+    fn user_count(&self) -> Result<i64, String>;
+    fn find_user(&self, username: &str) -> Result<Option<UserRecord>, String>;
+    fn insert_user(&mut self, user: &UserRecord) -> Result<(), String>;
+    fn set_encryption_salt(&mut self, username: &str, encryption_salt: &str) -> Result<(), String>;
+    /// Encryption salt of the registered user (needed for first sync)
+    fn first_encryption_salt(&self) -> Result<String, String>;
+
+    /// Persisted brute-force counter: (failed_count, last_failed_at)
+    fn login_attempts(&self) -> Result<(u32, Option<String>), String>;
+    fn record_failed_login(&mut self, at: &str) -> Result<(), String>;
+    fn reset_login_attempts(&mut self) -> Result<(), String>;
+
+    // --- Entries ---
+
+    /// Active entries of one profile
+    fn list_entries(&self, profile_id: i64) -> Result<Vec<EntryRecord>, String>;
+    /// Entries of every profile, tombstones included, optionally only those updated after `since`
+    fn entries_updated_since(&self, since: Option<&str>) -> Result<Vec<EntryRecord>, String>;
+    fn find_entry_by_uuid(&self, entry_uuid: &str) -> Result<Option<EntryRecord>, String>;
+    /// Any entry by row ID, tombstones included
+    fn find_entry(&self, id: i64) -> Result<Option<EntryRecord>, String>;
+    /// Insert a new row; `entry.id` is ignored and the new row ID is returned
+    fn insert_entry(&mut self, entry: &EntryRecord) -> Result<i64, String>;
+    /// Replace the content of an active entry in `profile_id` and bump its sync_version.
+    /// Returns false if no such entry exists.
+    fn update_entry_content(
+        &mut self,
+        id: i64,
+        profile_id: i64,
+        uuid: &str,
+        data_blob: &[u8],
+        nonce: &[u8],
+        updated_at: &str,
+    ) -> Result<bool, String>;
+    /// Tombstone an active entry in `profile_id` and bump its sync_version.
+    /// Returns false if no such entry exists.
+    fn soft_delete_entry(&mut self, id: i64, profile_id: i64, deleted_at: &str) -> Result<bool, String>;
+    /// Tombstoned entries of one profile, most recently deleted first
+    fn list_deleted_entries(&self, profile_id: i64) -> Result<Vec<EntryRecord>, String>;
+    /// Clear the tombstone of an entry in `profile_id` and bump its sync_version.
+    /// Returns false if no such deleted entry exists.
+    fn restore_entry(&mut self, id: i64, profile_id: i64, updated_at: &str) -> Result<bool, String>;
+    /// Permanently remove a tombstoned entry of `profile_id`.
+    /// Returns false if no such deleted entry exists.
+    fn purge_entry(&mut self, id: i64, profile_id: i64) -> Result<bool, String>;
+    /// Overwrite every stored column of the row identified by `entry.id`
+    fn overwrite_entry(&mut self, entry: &EntryRecord) -> Result<(), String>;
+    /// Insert (`id == 0`) or overwrite several entries in one transaction:
+    /// either all of them are written or none. Returns each row's ID, in order.
+    fn write_entries(&mut self, entries: &[EntryRecord]) -> Result<Vec<i64>, String>;
+    /// Permanently remove tombstones deleted before `cutoff`
+    fn purge_tombstones(&mut self, cutoff: &str) -> Result<usize, String>;
+
+    // --- Folders ---
+
+    /// Active folders of one profile
+    fn list_folders(&self, profile_id: i64) -> Result<Vec<FolderRecord>, String>;
+    /// Folders of every profile, tombstones included, optionally only those updated after `since`
+    fn folders_updated_since(&self, since: Option<&str>) -> Result<Vec<FolderRecord>, String>;
+    /// Any folder by sync UUID, tombstones included
+    fn find_folder_by_uuid(&self, folder_uuid: &str) -> Result<Option<FolderRecord>, String>;
+    /// Insert a new row; `folder.id` is ignored and the new row ID is returned
+    fn insert_folder(&mut self, folder: &FolderRecord) -> Result<i64, String>;
+    /// Overwrite every stored column of the row identified by `folder.id`
+    fn overwrite_folder(&mut self, folder: &FolderRecord) -> Result<(), String>;
+
+    // --- Usage ---
+
+    /// Count one use of an entry on `device_id` at `at`
+    fn record_entry_use(
+        &mut self,
+        entry_uuid: &str,
+        device_id: &str,
+        at: &str,
+    ) -> Result<(), String>;
+    /// Merge another copy of a device's counter, keeping the larger count and
+    /// the later timestamp
+    fn merge_entry_usage(&mut self, usage: &UsageRecord) -> Result<(), String>;
+    /// Every counter of every device
+    fn list_entry_usage(&self) -> Result<Vec<UsageRecord>, String>;
+    /// Drop the counters of all devices for an entry
+    fn delete_entry_usage(&mut self, entry_uuid: &str) -> Result<(), String>;
+
+    // --- Favorites ---
+
+    fn find_favorite(&self, entry_uuid: &str) -> Result<Option<FavoriteRecord>, String>;
+    /// Every favorite flag, including ones that were cleared
+    fn list_favorites(&self) -> Result<Vec<FavoriteRecord>, String>;
+    /// Insert or replace the flag of `favorite.entry_uuid`
+    fn put_favorite(&mut self, favorite: &FavoriteRecord) -> Result<(), String>;
+    fn delete_favorite(&mut self, entry_uuid: &str) -> Result<(), String>;
+
+    // --- HOTP counters ---
+
+    fn find_hotp_counter(&self, entry_uuid: &str) -> Result<Option<HotpCounterRecord>, String>;
+    fn list_hotp_counters(&self) -> Result<Vec<HotpCounterRecord>, String>;
+    /// Store a counter unless a larger one is already stored; the later
+    /// timestamp is kept either way
+    fn merge_hotp_counter(&mut self, counter: &HotpCounterRecord) -> Result<(), String>;
+    fn delete_hotp_counter(&mut self, entry_uuid: &str) -> Result<(), String>;
+
+    // --- Generator history ---
+
+    /// Append a generated value; `record.id` is ignored and the new row ID is returned
+    fn append_generated(&mut self, record: &GeneratedRecord) -> Result<i64, String>;
+    /// Every generated value, newest first
+    fn list_generated(&self) -> Result<Vec<GeneratedRecord>, String>;
+    /// Delete the given rows, returning how many existed
+    fn delete_generated(&mut self, ids: &[i64]) -> Result<usize, String>;
+
+    // --- Revisions ---
+
+    /// Append a revision; `revision.id` is ignored and the new row ID is returned
+    fn append_revision(&mut self, revision: &RevisionRecord) -> Result<i64, String>;
+    /// Revisions of one entry, newest first
+    fn list_revisions(&self, entry_uuid: &str) -> Result<Vec<RevisionRecord>, String>;
+    fn find_revision(&self, id: i64) -> Result<Option<RevisionRecord>, String>;
+    /// Every entry_uuid that has at least one revision
+    fn revisioned_entry_uuids(&self) -> Result<Vec<String>, String>;
+    fn delete_revisions(&mut self, ids: &[i64]) -> Result<usize, String>;
+
+    // --- Attachments ---
+
+    /// Store one encrypted chunk of blob `blob_id`
+    fn put_attachment_chunk(
+        &mut self,
+        blob_id: &str,
+        index: i64,
+        data: &[u8],
+        nonce: &[u8],
+    ) -> Result<(), String>;
+    fn get_attachment_chunk(
+        &self,
+        blob_id: &str,
+        index: i64,
+    ) -> Result<Option<EncryptedChunk>, String>;
+    /// Register a fully written blob
+    fn insert_attachment_blob(&mut self, blob: &AttachmentBlobRecord) -> Result<(), String>;
+    fn find_attachment_blob_by_hash(
+        &self,
+        content_hash: &str,
+    ) -> Result<Option<AttachmentBlobRecord>, String>;
+    /// Remove a blob and all of its chunks
+    fn delete_attachment_blob(&mut self, blob_id: &str) -> Result<(), String>;
+    /// Every blob ID that has chunks or a blob row (including unfinished uploads)
+    fn attachment_blob_ids(&self) -> Result<Vec<String>, String>;
+    /// Insert an attachment; `attachment.id` is ignored and the new row ID is returned
+    fn insert_attachment(&mut self, attachment: &AttachmentRecord) -> Result<i64, String>;
+    /// Attachments of one entry, oldest first
+    fn list_attachments(&self, entry_uuid: &str) -> Result<Vec<AttachmentRecord>, String>;
+    fn list_all_attachments(&self) -> Result<Vec<AttachmentRecord>, String>;
+    fn find_attachment(&self, id: i64) -> Result<Option<AttachmentRecord>, String>;
+    fn delete_attachment(&mut self, id: i64) -> Result<bool, String>;
+
+    // --- Vault key ---
+
+    /// Overwrite the user's password hash and salts and every row of `batch`
+    /// in one transaction, so the vault is never left half re-encrypted
+    fn replace_vault_key(&mut self, batch: &RekeyBatch) -> Result<(), String>;
+
+    // --- Profiles ---
+
+    fn list_profiles(&self) -> Result<Vec<ProfileRecord>, String>;
+    fn find_profile_by_name(&self, name: &str) -> Result<Option<i64>, String>;
+    fn create_profile(&mut self, name: &str) -> Result<i64, String>;
+    fn rename_profile(&mut self, id: i64, name: &str) -> Result<(), String>;
+    /// Write `entries` and overwrite `folders` (the profile's rows, handed
+    /// on to another profile) and delete the profile, all in one
+    /// transaction: if any entry is missing, nothing is written.
+    fn delete_profile(
+        &mut self,
+        id: i64,
+        entries: &[EntryRecord],
+        folders: &[FolderRecord],
+    ) -> Result<(), String>;
+
+    // --- Paired devices ---
+
+    fn list_paired_devices(&self) -> Result<Vec<PairedDevice>, String>;
+    fn add_paired_device(
+        &mut self,
+        device_name: &str,
+        device_id: &str,
+        public_key: &[u8],
+        shared_secret: &[u8],
+    ) -> Result<i64, String>;
+    fn forget_device(&mut self, device_id: &str) -> Result<(), String>;
+    fn get_last_sync_at(&self, device_id: &str) -> Result<Option<String>, String>;
+    fn set_last_sync_at(&mut self, device_id: &str, at: &str) -> Result<(), String>;
+
+    // --- Sync log ---
+
+    /// Append a sync log row; `entry.id` is ignored
+    fn append_sync_log(&mut self, entry: &SyncHistoryEntry) -> Result<(), String>;
+    /// Most recent sync log rows, newest first
+    fn sync_history(&self, limit: usize) -> Result<Vec<SyncHistoryEntry>, String>;
+
+    // --- Settings ---
+
+    /// Device-local key/value settings; never synced
+    fn get_setting(&self, key: &str) -> Result<Option<String>, String>;
+    fn set_setting(&mut self, key: &str, value: &str) -> Result<(), String>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::DatabaseManager;
+
+    fn entry(uuid: &str, entry_uuid: &str, profile_id: i64, updated_at: &str) -> EntryRecord {
+        EntryRecord {
+            id: 0,
+            uuid: uuid.to_string(),
+            data_blob: vec![1, 2, 3],
+            nonce: vec![0u8; 12],
+            profile_id,
+            entry_uuid: Some(entry_uuid.to_string()),
+            created_at: updated_at.to_string(),
+            updated_at: updated_at.to_string(),
+            deleted_at: None,
+            sync_version: 1,
+        }
+    }
+
+    /// Run the same behavioral checks against every backend
+    fn backends() -> Vec<Box<dyn VaultStore>> {
+        vec![
+            Box::new(DatabaseManager::open_in_memory().unwrap()),
+            Box::new(MemoryStore::new()),
+        ]
+    }
+
+    #[test]
+    fn test_default_profile_exists() {
+        for store in backends() {
+            let profiles = store.list_profiles().unwrap();
+            assert_eq!(profiles.len(), 1);
+            assert_eq!(profiles[0].id, 1);
+            assert_eq!(profiles[0].name, "Personal");
+        }
+    }
+
+    #[test]
+    fn test_entry_lifecycle() {
+        for mut store in backends() {
+            let id = store
+                .insert_entry(&entry("github", "e-1", 1, "2024-01-01T00:00:00+00:00"))
+                .unwrap();
+            assert_eq!(store.list_entries(1).unwrap().len(), 1);
+            assert_eq!(store.list_profiles().unwrap()[0].entry_count, 1);
+
+            // Updates are scoped to the owning profile
+            assert!(!store
+                .update_entry_content(id, 2, "x", &[9], &[0u8; 12], "2024-01-02T00:00:00+00:00")
+                .unwrap());
+            assert!(store
+                .update_entry_content(id, 1, "gitlab", &[9], &[0u8; 12], "2024-01-02T00:00:00+00:00")
+                .unwrap());
+            let updated = store.find_entry_by_uuid("e-1").unwrap().unwrap();
+            assert_eq!(updated.uuid, "gitlab");
+            assert_eq!(updated.sync_version, 2);
+
+            assert!(store
+                .soft_delete_entry(id, 1, "2024-01-03T00:00:00+00:00")
+                .unwrap());
+            assert!(store.list_entries(1).unwrap().is_empty());
+            let tombstone = store.find_entry_by_uuid("e-1").unwrap().unwrap();
+            assert_eq!(tombstone.sync_version, 3);
+            assert_eq!(store.find_entry(id).unwrap().unwrap().entry_uuid, tombstone.entry_uuid);
+            assert!(tombstone.deleted_at.is_some());
+
+            // Tombstones are still exported for sync until purged
+            assert_eq!(store.entries_updated_since(None).unwrap().len(), 1);
+            assert_eq!(
+                store
+                    .entries_updated_since(Some("2024-01-03T00:00:00+00:00"))
+                    .unwrap()
+                    .len(),
+                0
+            );
+            assert_eq!(store.purge_tombstones("2024-02-01T00:00:00+00:00").unwrap(), 1);
+            assert!(store.find_entry_by_uuid("e-1").unwrap().is_none());
+        }
+    }
+
+    #[test]
+    fn test_trash_restore_and_purge() {
+        for mut store in backends() {
+            let keep = store
+                .insert_entry(&entry("github", "e-1", 1, "2024-01-01T00:00:00+00:00"))
+                .unwrap();
+            let drop = store
+                .insert_entry(&entry("gitlab", "e-2", 1, "2024-01-01T00:00:00+00:00"))
+                .unwrap();
+            store.soft_delete_entry(keep, 1, "2024-01-02T00:00:00+00:00").unwrap();
+            store.soft_delete_entry(drop, 1, "2024-01-03T00:00:00+00:00").unwrap();
+
+            let trash = store.list_deleted_entries(1).unwrap();
+            assert_eq!(trash.len(), 2);
+            assert_eq!(trash[0].uuid, "gitlab");
+            assert!(store.list_deleted_entries(2).unwrap().is_empty());
+
+            // Restore is scoped to the profile and only applies to tombstones
+            assert!(!store.restore_entry(keep, 2, "2024-01-04T00:00:00+00:00").unwrap());
+            assert!(store.restore_entry(keep, 1, "2024-01-04T00:00:00+00:00").unwrap());
+            assert!(!store.restore_entry(keep, 1, "2024-01-04T00:00:00+00:00").unwrap());
+            let restored = store.find_entry(keep).unwrap().unwrap();
+            assert!(restored.deleted_at.is_none());
+            assert_eq!(restored.sync_version, 3);
+            assert_eq!(restored.updated_at, "2024-01-04T00:00:00+00:00");
+
+            assert!(!store.purge_entry(keep, 1).unwrap());
+            assert!(store.purge_entry(drop, 1).unwrap());
+            assert!(store.find_entry(drop).unwrap().is_none());
+            assert!(store.list_deleted_entries(1).unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_profile_names_are_unique() {
+        for mut store in backends() {
+            let id = store.create_profile("Work").unwrap();
+            assert!(store.create_profile("Work").is_err());
+            assert_eq!(store.find_profile_by_name("Work").unwrap(), Some(id));
+            store.rename_profile(id, "Office").unwrap();
+            assert_eq!(store.find_profile_by_name("Work").unwrap(), None);
+            store.delete_profile(id, &[], &[]).unwrap();
+            assert_eq!(store.list_profiles().unwrap().len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_devices_and_sync_log() {
+        for mut store in backends() {
+            store
+                .add_paired_device("iPhone", "dev-1", &[1; 33], &[2; 32])
+                .unwrap();
+            assert_eq!(store.get_last_sync_at("dev-1").unwrap(), None);
+            store
+                .set_last_sync_at("dev-1", "2024-01-01T00:00:00+00:00")
+                .unwrap();
+            assert_eq!(
+                store.list_paired_devices().unwrap()[0].last_sync_at.as_deref(),
+                Some("2024-01-01T00:00:00+00:00")
+            );
+
+            for started_at in ["2024-01-01T00:00:00+00:00", "2024-01-02T00:00:00+00:00"] {
+                store
+                    .append_sync_log(&SyncHistoryEntry {
+                        id: 0,
+                        device_id: "dev-1".to_string(),
+                        direction: "push".to_string(),
+                        entries_sent: 3,
+                        entries_received: 0,
+                        status: "success".to_string(),
+                        started_at: started_at.to_string(),
+                        completed_at: Some(started_at.to_string()),
+                        error_message: None,
+                    })
+                    .unwrap();
+            }
+            let history = store.sync_history(1).unwrap();
+            assert_eq!(history.len(), 1);
+            assert_eq!(history[0].started_at, "2024-01-02T00:00:00+00:00");
+
+            store.forget_device("dev-1").unwrap();
+            assert!(store.list_paired_devices().unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_write_entries_is_atomic() {
+        for mut store in backends() {
+            let work = store.create_profile("Work").unwrap();
+            let mut existing = entry("github", "e-1", 1, "2024-01-01T00:00:00+00:00");
+            existing.id = store.insert_entry(&existing).unwrap();
+
+            // A missing row aborts the whole batch
+            let mut moved = existing.clone();
+            moved.profile_id = work;
+            let mut missing = existing.clone();
+            missing.id = 999;
+            let copy = entry("github", "e-2", work, "2024-01-02T00:00:00+00:00");
+            assert!(store
+                .write_entries(&[copy.clone(), moved.clone(), missing])
+                .is_err());
+            assert_eq!(store.entries_updated_since(None).unwrap().len(), 1);
+            assert_eq!(store.find_entry(existing.id).unwrap().unwrap().profile_id, 1);
+
+            let ids = store.write_entries(&[copy, moved]).unwrap();
+            assert_eq!(ids[1], existing.id);
+            assert_eq!(store.find_entry_by_uuid("e-2").unwrap().unwrap().id, ids[0]);
+            assert_eq!(store.find_entry(existing.id).unwrap().unwrap().profile_id, work);
+        }
+    }
+
+    #[test]
+    fn test_delete_profile_is_atomic() {
+        for mut store in backends() {
+            let work = store.create_profile("Work").unwrap();
+            let mut existing = entry("github", "e-1", work, "2024-01-01T00:00:00+00:00");
+            existing.id = store.insert_entry(&existing).unwrap();
+            let moved = EntryRecord {
+                profile_id: 1,
+                ..existing.clone()
+            };
+
+            // A missing row keeps both the entries and the profile
+            let missing = EntryRecord {
+                id: 999,
+                ..moved.clone()
+            };
+            assert!(store.delete_profile(work, &[moved.clone(), missing], &[]).is_err());
+            assert_eq!(store.find_entry(existing.id).unwrap().unwrap().profile_id, work);
+            assert_eq!(store.list_profiles().unwrap().len(), 2);
+
+            store.delete_profile(work, &[moved], &[]).unwrap();
+            assert_eq!(store.find_entry(existing.id).unwrap().unwrap().profile_id, 1);
+            assert_eq!(store.list_profiles().unwrap().len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_folders() {
+        for mut store in backends() {
+            let mut folder = FolderRecord {
+                id: 0,
+                folder_uuid: "f-1".to_string(),
+                profile_id: 1,
+                data_blob: vec![1],
+                nonce: vec![0u8; 12],
+                created_at: "2024-01-01T00:00:00+00:00".to_string(),
+                updated_at: "2024-01-01T00:00:00+00:00".to_string(),
+                deleted_at: None,
+                sync_version: 1,
+            };
+            folder.id = store.insert_folder(&folder).unwrap();
+            assert_eq!(store.list_folders(1).unwrap().len(), 1);
+            assert!(store.list_folders(2).unwrap().is_empty());
+
+            folder.deleted_at = Some("2024-01-02T00:00:00+00:00".to_string());
+            folder.updated_at = "2024-01-02T00:00:00+00:00".to_string();
+            folder.sync_version = 2;
+            store.overwrite_folder(&folder).unwrap();
+            assert!(store.list_folders(1).unwrap().is_empty());
+
+            let found = store.find_folder_by_uuid("f-1").unwrap().unwrap();
+            assert_eq!(found.sync_version, 2);
+            assert!(found.deleted_at.is_some());
+            assert_eq!(store.folders_updated_since(None).unwrap().len(), 1);
+            assert!(store
+                .folders_updated_since(Some("2024-01-02T00:00:00+00:00"))
+                .unwrap()
+                .is_empty());
+        }
+    }
+
+    #[test]
+    fn test_entry_usage() {
+        for mut store in backends() {
+            store.record_entry_use("e-1", "desk", "2024-01-01T00:00:00+00:00").unwrap();
+            store.record_entry_use("e-1", "desk", "2024-01-03T00:00:00+00:00").unwrap();
+            store.record_entry_use("e-2", "desk", "2024-01-02T00:00:00+00:00").unwrap();
+
+            let usage = store.list_entry_usage().unwrap();
+            let first = usage.iter().find(|u| u.entry_uuid == "e-1").unwrap();
+            assert_eq!(first.use_count, 2);
+            assert_eq!(first.last_used_at, "2024-01-03T00:00:00+00:00");
+
+            // Merging keeps the larger count and the later use
+            let stale = UsageRecord {
+                entry_uuid: "e-1".to_string(),
+                device_id: "desk".to_string(),
+                last_used_at: "2024-01-05T00:00:00+00:00".to_string(),
+                use_count: 1,
+            };
+            store.merge_entry_usage(&stale).unwrap();
+            store
+                .merge_entry_usage(&UsageRecord {
+                    device_id: "phone".to_string(),
+                    ..stale
+                })
+                .unwrap();
+            let usage = store.list_entry_usage().unwrap();
+            assert_eq!(usage.len(), 3);
+            let desk = usage
+                .iter()
+                .find(|u| u.entry_uuid == "e-1" && u.device_id == "desk")
+                .unwrap();
+            assert_eq!(desk.use_count, 2);
+            assert_eq!(desk.last_used_at, "2024-01-05T00:00:00+00:00");
+
+            store.delete_entry_usage("e-1").unwrap();
+            assert_eq!(store.list_entry_usage().unwrap().len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_favorites() {
+        for mut store in backends() {
+            let mut favorite = FavoriteRecord {
+                entry_uuid: "e-1".to_string(),
+                favorite: true,
+                updated_at: "2024-01-01T00:00:00+00:00".to_string(),
+            };
+            store.put_favorite(&favorite).unwrap();
+            favorite.favorite = false;
+            store.put_favorite(&favorite).unwrap();
+
+            assert_eq!(store.find_favorite("e-1").unwrap(), Some(favorite));
+            assert_eq!(store.list_favorites().unwrap().len(), 1);
+            store.delete_favorite("e-1").unwrap();
+            assert!(store.find_favorite("e-1").unwrap().is_none());
+        }
+    }
+
+    #[test]
+    fn test_hotp_counters() {
+        for mut store in backends() {
+            let counter = HotpCounterRecord {
+                entry_uuid: "e-1".to_string(),
+                counter: 5,
+                updated_at: "2024-01-01T00:00:00+00:00".to_string(),
+            };
+            store.merge_hotp_counter(&counter).unwrap();
+            // A smaller counter never rolls the stored one back
+            store
+                .merge_hotp_counter(&HotpCounterRecord {
+                    counter: 3,
+                    updated_at: "2024-01-02T00:00:00+00:00".to_string(),
+                    ..counter.clone()
+                })
+                .unwrap();
+            let stored = store.find_hotp_counter("e-1").unwrap().unwrap();
+            assert_eq!(stored.counter, 5);
+            assert_eq!(stored.updated_at, "2024-01-02T00:00:00+00:00");
+
+            store
+                .merge_hotp_counter(&HotpCounterRecord {
+                    counter: 9,
+                    ..counter
+                })
+                .unwrap();
+            assert_eq!(store.find_hotp_counter("e-1").unwrap().unwrap().counter, 9);
+            assert_eq!(store.list_hotp_counters().unwrap().len(), 1);
+            store.delete_hotp_counter("e-1").unwrap();
+            assert!(store.find_hotp_counter("e-1").unwrap().is_none());
+        }
+    }
+
+    #[test]
+    fn test_generator_history() {
+        for mut store in backends() {
+            let first = store
+                .append_generated(&GeneratedRecord {
+                    id: 0,
+                    data_blob: vec![1],
+                    nonce: vec![0u8; 12],
+                    created_at: "2024-01-01T00:00:00+00:00".to_string(),
+                })
+                .unwrap();
+            let second = store
+                .append_generated(&GeneratedRecord {
+                    id: 0,
+                    data_blob: vec![2],
+                    nonce: vec![0u8; 12],
+                    created_at: "2024-01-01T00:00:00+00:00".to_string(),
+                })
+                .unwrap();
+
+            let history = store.list_generated().unwrap();
+            assert_eq!(history.iter().map(|r| r.id).collect::<Vec<_>>(), [second, first]);
+            assert_eq!(store.delete_generated(&[first, 999]).unwrap(), 1);
+            assert_eq!(store.list_generated().unwrap()[0].data_blob, [2]);
+        }
+    }
+
+    #[test]
+    fn test_revisions() {
+        for mut store in backends() {
+            for version in 1..=3 {
+                store
+                    .append_revision(&RevisionRecord {
+                        id: 0,
+                        entry_uuid: "e-1".to_string(),
+                        sync_version: version,
+                        uuid: "github".to_string(),
+                        data_blob: vec![version as u8],
+                        nonce: vec![0u8; 12],
+                        deleted_at: None,
+                        device_id: "desktop".to_string(),
+                        created_at: format!("2024-01-0{}T00:00:00+00:00", version),
+                    })
+                    .unwrap();
+            }
+            let revisions = store.list_revisions("e-1").unwrap();
+            assert_eq!(revisions.len(), 3);
+            assert_eq!(revisions[0].sync_version, 3);
+            assert_eq!(store.find_revision(revisions[2].id).unwrap().unwrap().data_blob, vec![1]);
+            assert_eq!(store.revisioned_entry_uuids().unwrap(), vec!["e-1".to_string()]);
+
+            assert_eq!(store.delete_revisions(&[revisions[2].id]).unwrap(), 1);
+            assert_eq!(store.list_revisions("e-1").unwrap().len(), 2);
+            assert!(store.list_revisions("e-2").unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_attachments() {
+        for mut store in backends() {
+            store.put_attachment_chunk("b-1", 0, &[1], &[0u8; 12]).unwrap();
+            store.put_attachment_chunk("b-1", 1, &[2], &[0u8; 12]).unwrap();
+            store.put_attachment_chunk("b-2", 0, &[3], &[0u8; 12]).unwrap();
+            store
+                .insert_attachment_blob(&AttachmentBlobRecord {
+                    blob_id: "b-1".to_string(),
+                    content_hash: "h".to_string(),
+                    size: 2,
+                    chunk_count: 2,
+                    created_at: "2024-01-01T00:00:00+00:00".to_string(),
+                })
+                .unwrap();
+            assert_eq!(store.get_attachment_chunk("b-1", 1).unwrap().unwrap().0, vec![2]);
+            assert!(store.get_attachment_chunk("b-1", 2).unwrap().is_none());
+            assert_eq!(
+                store.find_attachment_blob_by_hash("h").unwrap().unwrap().blob_id,
+                "b-1"
+            );
+            assert_eq!(store.attachment_blob_ids().unwrap(), vec!["b-1", "b-2"]);
+
+            let id = store
+                .insert_attachment(&AttachmentRecord {
+                    id: 0,
+                    entry_uuid: "e-1".to_string(),
+                    blob_id: "b-1".to_string(),
+                    meta_blob: vec![9],
+                    meta_nonce: vec![0u8; 12],
+                    size: 2,
+                    chunk_count: 2,
+                    created_at: "2024-01-01T00:00:00+00:00".to_string(),
+                })
+                .unwrap();
+            assert_eq!(store.list_attachments("e-1").unwrap().len(), 1);
+            assert_eq!(store.find_attachment(id).unwrap().unwrap().blob_id, "b-1");
+            assert_eq!(store.list_all_attachments().unwrap().len(), 1);
+            assert!(store.delete_attachment(id).unwrap());
+            assert!(!store.delete_attachment(id).unwrap());
+
+            store.delete_attachment_blob("b-1").unwrap();
+            assert!(store.get_attachment_chunk("b-1", 0).unwrap().is_none());
+            assert!(store.find_attachment_blob_by_hash("h").unwrap().is_none());
+            assert_eq!(store.attachment_blob_ids().unwrap(), vec!["b-2"]);
+        }
+    }
+
+    #[test]
+    fn test_settings() {
+        for mut store in backends() {
+            assert_eq!(store.get_setting("device_id").unwrap(), None);
+            store.set_setting("device_id", "a").unwrap();
+            store.set_setting("device_id", "b").unwrap();
+            assert_eq!(store.get_setting("device_id").unwrap().as_deref(), Some("b"));
+        }
+    }
+
+    #[test]
+    fn test_login_attempts() {
+        for mut store in backends() {
+            assert_eq!(store.login_attempts().unwrap(), (0, None));
+            store.record_failed_login("2024-01-01 00:00:00").unwrap();
+            store.record_failed_login("2024-01-01 00:00:05").unwrap();
+            assert_eq!(
+                store.login_attempts().unwrap(),
+                (2, Some("2024-01-01 00:00:05".to_string()))
+            );
+            store.reset_login_attempts().unwrap();
+            assert_eq!(store.login_attempts().unwrap(), (0, None));
+        }
+    }
+}
@@ -0,0 +1,1219 @@
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::{
+    AttachmentBlobRecord, AttachmentRecord, EncryptedChunk, EntryRecord, FavoriteRecord, FolderRecord,
+    GeneratedRecord, HotpCounterRecord, ProfileRecord, RekeyBatch, RevisionRecord, UsageRecord, UserRecord,
+    VaultStore,
+};
+use crate::db::DatabaseManager;
+use crate::sync::{PairedDevice, SyncHistoryEntry};
+
+const ENTRY_COLUMNS: &str = "id, uuid, data_blob, nonce, profile_id, entry_uuid,
+                             created_at, updated_at, deleted_at, sync_version";
+
+fn map_entry(row: &rusqlite::Row) -> rusqlite::Result<EntryRecord> {
+    Ok(EntryRecord {
+        id: row.get(0)?,
+        uuid: row.get(1)?,
+        data_blob: row.get(2)?,
+        nonce: row.get(3)?,
+        profile_id: row.get(4)?,
+        entry_uuid: row.get(5)?,
+        created_at: row.get(6)?,
+        updated_at: row.get(7)?,
+        deleted_at: row.get(8)?,
+        sync_version: row.get(9)?,
+    })
+}
+
+const FOLDER_COLUMNS: &str = "id, folder_uuid, profile_id, data_blob, nonce,
+                              created_at, updated_at, deleted_at, sync_version";
+
+fn map_folder(row: &rusqlite::Row) -> rusqlite::Result<FolderRecord> {
+    Ok(FolderRecord {
+        id: row.get(0)?,
+        folder_uuid: row.get(1)?,
+        profile_id: row.get(2)?,
+        data_blob: row.get(3)?,
+        nonce: row.get(4)?,
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+        deleted_at: row.get(7)?,
+        sync_version: row.get(8)?,
+    })
+}
+
+fn map_favorite(row: &rusqlite::Row) -> rusqlite::Result<FavoriteRecord> {
+    Ok(FavoriteRecord {
+        entry_uuid: row.get(0)?,
+        favorite: row.get(1)?,
+        updated_at: row.get(2)?,
+    })
+}
+
+fn map_hotp_counter(row: &rusqlite::Row) -> rusqlite::Result<HotpCounterRecord> {
+    Ok(HotpCounterRecord {
+        entry_uuid: row.get(0)?,
+        counter: row.get(1)?,
+        updated_at: row.get(2)?,
+    })
+}
+
+const REVISION_COLUMNS: &str = "id, entry_uuid, sync_version, uuid, data_blob, nonce,
+                                deleted_at, device_id, created_at";
+
+fn map_revision(row: &rusqlite::Row) -> rusqlite::Result<RevisionRecord> {
+    Ok(RevisionRecord {
+        id: row.get(0)?,
+        entry_uuid: row.get(1)?,
+        sync_version: row.get(2)?,
+        uuid: row.get(3)?,
+        data_blob: row.get(4)?,
+        nonce: row.get(5)?,
+        deleted_at: row.get(6)?,
+        device_id: row.get(7)?,
+        created_at: row.get(8)?,
+    })
+}
+
+const ATTACHMENT_COLUMNS: &str = "id, entry_uuid, blob_id, meta_blob, meta_nonce, size,
+                                  chunk_count, created_at";
+
+fn map_attachment(row: &rusqlite::Row) -> rusqlite::Result<AttachmentRecord> {
+    Ok(AttachmentRecord {
+        id: row.get(0)?,
+        entry_uuid: row.get(1)?,
+        blob_id: row.get(2)?,
+        meta_blob: row.get(3)?,
+        meta_nonce: row.get(4)?,
+        size: row.get(5)?,
+        chunk_count: row.get(6)?,
+        created_at: row.get(7)?,
+    })
+}
+
+fn collect<T>(rows: impl Iterator<Item = rusqlite::Result<T>>) -> Result<Vec<T>, String> {
+    let mut out = Vec::new();
+    for row in rows {
+        out.push(row.map_err(|e| e.to_string())?);
+    }
+    Ok(out)
+}
+
+/// Insert (`id == 0`) or overwrite entries inside the caller's transaction.
+/// Returns each row's ID, in order.
+fn write_entries_in(tx: &Connection, entries: &[EntryRecord]) -> Result<Vec<i64>, String> {
+    let mut ids = Vec::with_capacity(entries.len());
+    for entry in entries {
+        if entry.id == 0 {
+            tx.execute(
+                "INSERT INTO vault_entries
+                 (uuid, data_blob, nonce, profile_id, entry_uuid, created_at, updated_at, deleted_at, sync_version)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    entry.uuid,
+                    entry.data_blob,
+                    entry.nonce,
+                    entry.profile_id,
+                    entry.entry_uuid,
+                    entry.created_at,
+                    entry.updated_at,
+                    entry.deleted_at,
+                    entry.sync_version,
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+            ids.push(tx.last_insert_rowid());
+        } else {
+            let rows_updated = tx
+                .execute(
+                    "UPDATE vault_entries
+                     SET uuid = ?1, data_blob = ?2, nonce = ?3, profile_id = ?4, entry_uuid = ?5,
+                         created_at = ?6, updated_at = ?7, deleted_at = ?8, sync_version = ?9
+                     WHERE id = ?10",
+                    params![
+                        entry.uuid,
+                        entry.data_blob,
+                        entry.nonce,
+                        entry.profile_id,
+                        entry.entry_uuid,
+                        entry.created_at,
+                        entry.updated_at,
+                        entry.deleted_at,
+                        entry.sync_version,
+                        entry.id,
+                    ],
+                )
+                .map_err(|e| e.to_string())?;
+            if rows_updated == 0 {
+                // Dropping the caller's transaction rolls back the rows written so far
+                return Err(format!("Entry {} not found", entry.id));
+            }
+            ids.push(entry.id);
+        }
+    }
+    Ok(ids)
+}
+
+fn overwrite_folder_in(conn: &Connection, folder: &FolderRecord) -> Result<(), String> {
+    conn
+        .execute(
+            "UPDATE folders
+             SET folder_uuid = ?1, profile_id = ?2, data_blob = ?3, nonce = ?4,
+                 created_at = ?5, updated_at = ?6, deleted_at = ?7, sync_version = ?8
+             WHERE id = ?9",
+            params![
+                folder.folder_uuid,
+                folder.profile_id,
+                folder.data_blob,
+                folder.nonce,
+                folder.created_at,
+                folder.updated_at,
+                folder.deleted_at,
+                folder.sync_version,
+                folder.id,
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+impl VaultStore for DatabaseManager {
+    // --- Users ---
+
+    fn user_count(&self) -> Result<i64, String> {
+        self.conn
+            .query_row("SELECT COUNT(*) FROM users", [], |row| row.get(0))
+            .map_err(|e| e.to_string())
+    }
+
+    fn find_user(&self, username: &str) -> Result<Option<UserRecord>, String> {
+        self.conn
+            .query_row(
+                "SELECT username, password_hash, salt, encryption_salt FROM users WHERE username = ?1",
+                params![username],
+                |row| {
+                    Ok(UserRecord {
+                        username: row.get(0)?,
+                        password_hash: row.get(1)?,
+                        salt: row.get(2)?,
+                        encryption_salt: row.get(3)?,
+                    })
+                },
+            )
+            .optional()
+            .map_err(|e| e.to_string())
+    }
+
+    fn insert_user(&mut self, user: &UserRecord) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT INTO users (username, password_hash, salt, encryption_salt) VALUES (?1, ?2, ?3, ?4)",
+                params![user.username, user.password_hash, user.salt, user.encryption_salt],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn set_encryption_salt(&mut self, username: &str, encryption_salt: &str) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE users SET encryption_salt = ?1 WHERE username = ?2",
+                params![encryption_salt, username],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn first_encryption_salt(&self) -> Result<String, String> {
+        self.conn
+            .query_row("SELECT encryption_salt FROM users LIMIT 1", [], |row| {
+                row.get::<_, String>(0)
+            })
+            .map_err(|e| format!("Failed to get encryption_salt: {}", e))
+    }
+
+    fn login_attempts(&self) -> Result<(u32, Option<String>), String> {
+        self.conn
+            .query_row(
+                "SELECT failed_count, last_failed_at FROM login_attempts WHERE id = 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|e| e.to_string())
+    }
+
+    fn record_failed_login(&mut self, at: &str) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE login_attempts SET failed_count = failed_count + 1, last_failed_at = ?1 WHERE id = 1",
+                params![at],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn reset_login_attempts(&mut self) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE login_attempts SET failed_count = 0, last_failed_at = NULL WHERE id = 1",
+                [],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    // --- Entries ---
+
+    fn list_entries(&self, profile_id: i64) -> Result<Vec<EntryRecord>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT {} FROM vault_entries WHERE profile_id = ?1 AND deleted_at IS NULL",
+                ENTRY_COLUMNS
+            ))
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![profile_id], map_entry)
+            .map_err(|e| e.to_string())?;
+        collect(rows)
+    }
+
+    fn entries_updated_since(&self, since: Option<&str>) -> Result<Vec<EntryRecord>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT {} FROM vault_entries WHERE ?1 IS NULL OR updated_at > ?1",
+                ENTRY_COLUMNS
+            ))
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![since], map_entry)
+            .map_err(|e| e.to_string())?;
+        collect(rows)
+    }
+
+    fn find_entry_by_uuid(&self, entry_uuid: &str) -> Result<Option<EntryRecord>, String> {
+        self.conn
+            .query_row(
+                &format!("SELECT {} FROM vault_entries WHERE entry_uuid = ?1", ENTRY_COLUMNS),
+                params![entry_uuid],
+                map_entry,
+            )
+            .optional()
+            .map_err(|e| e.to_string())
+    }
+
+    fn find_entry(&self, id: i64) -> Result<Option<EntryRecord>, String> {
+        self.conn
+            .query_row(
+                &format!("SELECT {} FROM vault_entries WHERE id = ?1", ENTRY_COLUMNS),
+                params![id],
+                map_entry,
+            )
+            .optional()
+            .map_err(|e| e.to_string())
+    }
+
+    fn insert_entry(&mut self, entry: &EntryRecord) -> Result<i64, String> {
+        self.conn
+            .execute(
+                "INSERT INTO vault_entries
+                 (uuid, data_blob, nonce, profile_id, entry_uuid, created_at, updated_at, deleted_at, sync_version)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    entry.uuid,
+                    entry.data_blob,
+                    entry.nonce,
+                    entry.profile_id,
+                    entry.entry_uuid,
+                    entry.created_at,
+                    entry.updated_at,
+                    entry.deleted_at,
+                    entry.sync_version,
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    fn update_entry_content(
+        &mut self,
+        id: i64,
+        profile_id: i64,
+        uuid: &str,
+        data_blob: &[u8],
+        nonce: &[u8],
+        updated_at: &str,
+    ) -> Result<bool, String> {
+        let rows_updated = self
+            .conn
+            .execute(
+                "UPDATE vault_entries
+                 SET uuid = ?1, data_blob = ?2, nonce = ?3, updated_at = ?4, sync_version = sync_version + 1
+                 WHERE id = ?5 AND profile_id = ?6 AND deleted_at IS NULL",
+                params![uuid, data_blob, nonce, updated_at, id, profile_id],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(rows_updated > 0)
+    }
+
+    fn soft_delete_entry(&mut self, id: i64, profile_id: i64, deleted_at: &str) -> Result<bool, String> {
+        let rows_updated = self
+            .conn
+            .execute(
+                "UPDATE vault_entries
+                 SET deleted_at = ?1, updated_at = ?1, sync_version = sync_version + 1
+                 WHERE id = ?2 AND profile_id = ?3 AND deleted_at IS NULL",
+                params![deleted_at, id, profile_id],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(rows_updated > 0)
+    }
+
+    fn list_deleted_entries(&self, profile_id: i64) -> Result<Vec<EntryRecord>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT {} FROM vault_entries
+                 WHERE profile_id = ?1 AND deleted_at IS NOT NULL
+                 ORDER BY deleted_at DESC",
+                ENTRY_COLUMNS
+            ))
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![profile_id], map_entry)
+            .map_err(|e| e.to_string())?;
+        collect(rows)
+    }
+
+    fn restore_entry(&mut self, id: i64, profile_id: i64, updated_at: &str) -> Result<bool, String> {
+        let rows_updated = self
+            .conn
+            .execute(
+                "UPDATE vault_entries
+                 SET deleted_at = NULL, updated_at = ?1, sync_version = sync_version + 1
+                 WHERE id = ?2 AND profile_id = ?3 AND deleted_at IS NOT NULL",
+                params![updated_at, id, profile_id],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(rows_updated > 0)
+    }
+
+    fn purge_entry(&mut self, id: i64, profile_id: i64) -> Result<bool, String> {
+        let rows_deleted = self
+            .conn
+            .execute(
+                "DELETE FROM vault_entries
+                 WHERE id = ?1 AND profile_id = ?2 AND deleted_at IS NOT NULL",
+                params![id, profile_id],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(rows_deleted > 0)
+    }
+
+    fn overwrite_entry(&mut self, entry: &EntryRecord) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE vault_entries
+                 SET uuid = ?1, data_blob = ?2, nonce = ?3, profile_id = ?4, entry_uuid = ?5,
+                     created_at = ?6, updated_at = ?7, deleted_at = ?8, sync_version = ?9
+                 WHERE id = ?10",
+                params![
+                    entry.uuid,
+                    entry.data_blob,
+                    entry.nonce,
+                    entry.profile_id,
+                    entry.entry_uuid,
+                    entry.created_at,
+                    entry.updated_at,
+                    entry.deleted_at,
+                    entry.sync_version,
+                    entry.id,
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn write_entries(&mut self, entries: &[EntryRecord]) -> Result<Vec<i64>, String> {
+        let tx = self.conn.transaction().map_err(|e| e.to_string())?;
+        let ids = write_entries_in(&tx, entries)?;
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok(ids)
+    }
+
+    fn purge_tombstones(&mut self, cutoff: &str) -> Result<usize, String> {
+        self.conn
+            .execute(
+                "DELETE FROM vault_entries WHERE deleted_at IS NOT NULL AND deleted_at < ?1",
+                params![cutoff],
+            )
+            .map_err(|e| e.to_string())
+    }
+
+    // --- Folders ---
+
+    fn list_folders(&self, profile_id: i64) -> Result<Vec<FolderRecord>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT {} FROM folders WHERE profile_id = ?1 AND deleted_at IS NULL",
+                FOLDER_COLUMNS
+            ))
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![profile_id], map_folder)
+            .map_err(|e| e.to_string())?;
+        collect(rows)
+    }
+
+    fn folders_updated_since(&self, since: Option<&str>) -> Result<Vec<FolderRecord>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT {} FROM folders WHERE ?1 IS NULL OR updated_at > ?1",
+                FOLDER_COLUMNS
+            ))
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![since], map_folder)
+            .map_err(|e| e.to_string())?;
+        collect(rows)
+    }
+
+    fn find_folder_by_uuid(&self, folder_uuid: &str) -> Result<Option<FolderRecord>, String> {
+        self.conn
+            .query_row(
+                &format!("SELECT {} FROM folders WHERE folder_uuid = ?1", FOLDER_COLUMNS),
+                params![folder_uuid],
+                map_folder,
+            )
+            .optional()
+            .map_err(|e| e.to_string())
+    }
+
+    fn insert_folder(&mut self, folder: &FolderRecord) -> Result<i64, String> {
+        self.conn
+            .execute(
+                "INSERT INTO folders
+                 (folder_uuid, profile_id, data_blob, nonce, created_at, updated_at, deleted_at, sync_version)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    folder.folder_uuid,
+                    folder.profile_id,
+                    folder.data_blob,
+                    folder.nonce,
+                    folder.created_at,
+                    folder.updated_at,
+                    folder.deleted_at,
+                    folder.sync_version,
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    fn overwrite_folder(&mut self, folder: &FolderRecord) -> Result<(), String> {
+        overwrite_folder_in(&self.conn, folder)
+    }
+
+    // --- Usage ---
+
+    fn record_entry_use(
+        &mut self,
+        entry_uuid: &str,
+        device_id: &str,
+        at: &str,
+    ) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT INTO entry_usage (entry_uuid, device_id, last_used_at, use_count)
+                 VALUES (?1, ?2, ?3, 1)
+                 ON CONFLICT(entry_uuid, device_id) DO UPDATE
+                 SET last_used_at = MAX(last_used_at, excluded.last_used_at),
+                     use_count = use_count + 1",
+                params![entry_uuid, device_id, at],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn merge_entry_usage(&mut self, usage: &UsageRecord) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT INTO entry_usage (entry_uuid, device_id, last_used_at, use_count)
+                 VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(entry_uuid, device_id) DO UPDATE
+                 SET last_used_at = MAX(last_used_at, excluded.last_used_at),
+                     use_count = MAX(use_count, excluded.use_count)",
+                params![
+                    usage.entry_uuid,
+                    usage.device_id,
+                    usage.last_used_at,
+                    usage.use_count
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn list_entry_usage(&self) -> Result<Vec<UsageRecord>, String> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT entry_uuid, device_id, last_used_at, use_count FROM entry_usage")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(UsageRecord {
+                    entry_uuid: row.get(0)?,
+                    device_id: row.get(1)?,
+                    last_used_at: row.get(2)?,
+                    use_count: row.get(3)?,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        collect(rows)
+    }
+
+    fn delete_entry_usage(&mut self, entry_uuid: &str) -> Result<(), String> {
+        self.conn
+            .execute("DELETE FROM entry_usage WHERE entry_uuid = ?1", params![entry_uuid])
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    // --- Favorites ---
+
+    fn find_favorite(&self, entry_uuid: &str) -> Result<Option<FavoriteRecord>, String> {
+        self.conn
+            .query_row(
+                "SELECT entry_uuid, favorite, updated_at FROM entry_favorites WHERE entry_uuid = ?1",
+                params![entry_uuid],
+                map_favorite,
+            )
+            .optional()
+            .map_err(|e| e.to_string())
+    }
+
+    fn list_favorites(&self) -> Result<Vec<FavoriteRecord>, String> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT entry_uuid, favorite, updated_at FROM entry_favorites")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt.query_map([], map_favorite).map_err(|e| e.to_string())?;
+        collect(rows)
+    }
+
+    fn put_favorite(&mut self, favorite: &FavoriteRecord) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO entry_favorites (entry_uuid, favorite, updated_at)
+                 VALUES (?1, ?2, ?3)",
+                params![favorite.entry_uuid, favorite.favorite, favorite.updated_at],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn delete_favorite(&mut self, entry_uuid: &str) -> Result<(), String> {
+        self.conn
+            .execute("DELETE FROM entry_favorites WHERE entry_uuid = ?1", params![entry_uuid])
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    // --- HOTP counters ---
+
+    fn find_hotp_counter(&self, entry_uuid: &str) -> Result<Option<HotpCounterRecord>, String> {
+        self.conn
+            .query_row(
+                "SELECT entry_uuid, counter, updated_at FROM hotp_counters WHERE entry_uuid = ?1",
+                params![entry_uuid],
+                map_hotp_counter,
+            )
+            .optional()
+            .map_err(|e| e.to_string())
+    }
+
+    fn list_hotp_counters(&self) -> Result<Vec<HotpCounterRecord>, String> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT entry_uuid, counter, updated_at FROM hotp_counters")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt.query_map([], map_hotp_counter).map_err(|e| e.to_string())?;
+        collect(rows)
+    }
+
+    fn merge_hotp_counter(&mut self, counter: &HotpCounterRecord) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT INTO hotp_counters (entry_uuid, counter, updated_at)
+                 VALUES (?1, ?2, ?3)
+                 ON CONFLICT(entry_uuid) DO UPDATE
+                 SET counter = MAX(counter, excluded.counter),
+                     updated_at = MAX(updated_at, excluded.updated_at)",
+                params![counter.entry_uuid, counter.counter, counter.updated_at],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn delete_hotp_counter(&mut self, entry_uuid: &str) -> Result<(), String> {
+        self.conn
+            .execute("DELETE FROM hotp_counters WHERE entry_uuid = ?1", params![entry_uuid])
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    // --- Generator history ---
+
+    fn append_generated(&mut self, record: &GeneratedRecord) -> Result<i64, String> {
+        self.conn
+            .execute(
+                "INSERT INTO generator_history (data_blob, nonce, created_at) VALUES (?1, ?2, ?3)",
+                params![record.data_blob, record.nonce, record.created_at],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    fn list_generated(&self) -> Result<Vec<GeneratedRecord>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, data_blob, nonce, created_at FROM generator_history ORDER BY id DESC",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(GeneratedRecord {
+                    id: row.get(0)?,
+                    data_blob: row.get(1)?,
+                    nonce: row.get(2)?,
+                    created_at: row.get(3)?,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        collect(rows)
+    }
+
+    fn delete_generated(&mut self, ids: &[i64]) -> Result<usize, String> {
+        let tx = self.conn.transaction().map_err(|e| e.to_string())?;
+        let mut deleted = 0;
+        for id in ids {
+            deleted += tx
+                .execute("DELETE FROM generator_history WHERE id = ?1", params![id])
+                .map_err(|e| e.to_string())?;
+        }
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok(deleted)
+    }
+
+    // --- Revisions ---
+
+    fn append_revision(&mut self, revision: &RevisionRecord) -> Result<i64, String> {
+        self.conn
+            .execute(
+                "INSERT INTO entry_revisions
+                 (entry_uuid, sync_version, uuid, data_blob, nonce, deleted_at, device_id, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    revision.entry_uuid,
+                    revision.sync_version,
+                    revision.uuid,
+                    revision.data_blob,
+                    revision.nonce,
+                    revision.deleted_at,
+                    revision.device_id,
+                    revision.created_at,
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    fn list_revisions(&self, entry_uuid: &str) -> Result<Vec<RevisionRecord>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT {} FROM entry_revisions WHERE entry_uuid = ?1 ORDER BY id DESC",
+                REVISION_COLUMNS
+            ))
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![entry_uuid], map_revision)
+            .map_err(|e| e.to_string())?;
+        collect(rows)
+    }
+
+    fn find_revision(&self, id: i64) -> Result<Option<RevisionRecord>, String> {
+        self.conn
+            .query_row(
+                &format!("SELECT {} FROM entry_revisions WHERE id = ?1", REVISION_COLUMNS),
+                params![id],
+                map_revision,
+            )
+            .optional()
+            .map_err(|e| e.to_string())
+    }
+
+    fn revisioned_entry_uuids(&self) -> Result<Vec<String>, String> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT DISTINCT entry_uuid FROM entry_revisions ORDER BY entry_uuid")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        collect(rows)
+    }
+
+    fn delete_revisions(&mut self, ids: &[i64]) -> Result<usize, String> {
+        let tx = self.conn.transaction().map_err(|e| e.to_string())?;
+        let mut deleted = 0;
+        for id in ids {
+            deleted += tx
+                .execute("DELETE FROM entry_revisions WHERE id = ?1", params![id])
+                .map_err(|e| e.to_string())?;
+        }
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok(deleted)
+    }
+
+    // --- Attachments ---
+
+    fn put_attachment_chunk(
+        &mut self,
+        blob_id: &str,
+        index: i64,
+        data: &[u8],
+        nonce: &[u8],
+    ) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO attachment_chunks (blob_id, chunk_index, data, nonce)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![blob_id, index, data, nonce],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn get_attachment_chunk(
+        &self,
+        blob_id: &str,
+        index: i64,
+    ) -> Result<Option<EncryptedChunk>, String> {
+        self.conn
+            .query_row(
+                "SELECT data, nonce FROM attachment_chunks WHERE blob_id = ?1 AND chunk_index = ?2",
+                params![blob_id, index],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|e| e.to_string())
+    }
+
+    fn insert_attachment_blob(&mut self, blob: &AttachmentBlobRecord) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT INTO attachment_blobs (blob_id, content_hash, size, chunk_count, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    blob.blob_id,
+                    blob.content_hash,
+                    blob.size,
+                    blob.chunk_count,
+                    blob.created_at,
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn find_attachment_blob_by_hash(
+        &self,
+        content_hash: &str,
+    ) -> Result<Option<AttachmentBlobRecord>, String> {
+        self.conn
+            .query_row(
+                "SELECT blob_id, content_hash, size, chunk_count, created_at
+                 FROM attachment_blobs WHERE content_hash = ?1",
+                params![content_hash],
+                |row| {
+                    Ok(AttachmentBlobRecord {
+                        blob_id: row.get(0)?,
+                        content_hash: row.get(1)?,
+                        size: row.get(2)?,
+                        chunk_count: row.get(3)?,
+                        created_at: row.get(4)?,
+                    })
+                },
+            )
+            .optional()
+            .map_err(|e| e.to_string())
+    }
+
+    fn delete_attachment_blob(&mut self, blob_id: &str) -> Result<(), String> {
+        let tx = self.conn.transaction().map_err(|e| e.to_string())?;
+        tx.execute("DELETE FROM attachment_chunks WHERE blob_id = ?1", params![blob_id])
+            .map_err(|e| e.to_string())?;
+        tx.execute("DELETE FROM attachment_blobs WHERE blob_id = ?1", params![blob_id])
+            .map_err(|e| e.to_string())?;
+        tx.commit().map_err(|e| e.to_string())
+    }
+
+    fn attachment_blob_ids(&self) -> Result<Vec<String>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT blob_id FROM attachment_chunks
+                 UNION
+                 SELECT blob_id FROM attachment_blobs
+                 ORDER BY blob_id",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        collect(rows)
+    }
+
+    fn insert_attachment(&mut self, attachment: &AttachmentRecord) -> Result<i64, String> {
+        self.conn
+            .execute(
+                "INSERT INTO attachments
+                 (entry_uuid, blob_id, meta_blob, meta_nonce, size, chunk_count, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    attachment.entry_uuid,
+                    attachment.blob_id,
+                    attachment.meta_blob,
+                    attachment.meta_nonce,
+                    attachment.size,
+                    attachment.chunk_count,
+                    attachment.created_at,
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    fn list_attachments(&self, entry_uuid: &str) -> Result<Vec<AttachmentRecord>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT {} FROM attachments WHERE entry_uuid = ?1 ORDER BY id",
+                ATTACHMENT_COLUMNS
+            ))
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![entry_uuid], map_attachment)
+            .map_err(|e| e.to_string())?;
+        collect(rows)
+    }
+
+    fn list_all_attachments(&self) -> Result<Vec<AttachmentRecord>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!("SELECT {} FROM attachments ORDER BY id", ATTACHMENT_COLUMNS))
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], map_attachment)
+            .map_err(|e| e.to_string())?;
+        collect(rows)
+    }
+
+    fn find_attachment(&self, id: i64) -> Result<Option<AttachmentRecord>, String> {
+        self.conn
+            .query_row(
+                &format!("SELECT {} FROM attachments WHERE id = ?1", ATTACHMENT_COLUMNS),
+                params![id],
+                map_attachment,
+            )
+            .optional()
+            .map_err(|e| e.to_string())
+    }
+
+    fn delete_attachment(&mut self, id: i64) -> Result<bool, String> {
+        let rows_deleted = self
+            .conn
+            .execute("DELETE FROM attachments WHERE id = ?1", params![id])
+            .map_err(|e| e.to_string())?;
+        Ok(rows_deleted > 0)
+    }
+
+    // --- Vault key ---
+
+    fn replace_vault_key(&mut self, batch: &RekeyBatch) -> Result<(), String> {
+        let tx = self.conn.transaction().map_err(|e| e.to_string())?;
+        let user = &batch.user;
+        let rows_updated = tx
+            .execute(
+                "UPDATE users SET password_hash = ?1, salt = ?2, encryption_salt = ?3
+                 WHERE username = ?4",
+                params![user.password_hash, user.salt, user.encryption_salt, user.username],
+            )
+            .map_err(|e| e.to_string())?;
+        if rows_updated == 0 {
+            return Err("User not found".to_string());
+        }
+        write_entries_in(&tx, &batch.entries)?;
+        for folder in &batch.folders {
+            overwrite_folder_in(&tx, folder)?;
+        }
+        for revision in &batch.revisions {
+            tx.execute(
+                "UPDATE entry_revisions SET data_blob = ?1, nonce = ?2 WHERE id = ?3",
+                params![revision.data_blob, revision.nonce, revision.id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        for record in &batch.generated {
+            tx.execute(
+                "UPDATE generator_history SET data_blob = ?1, nonce = ?2 WHERE id = ?3",
+                params![record.data_blob, record.nonce, record.id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        for attachment in &batch.attachments {
+            tx.execute(
+                "UPDATE attachments SET meta_blob = ?1, meta_nonce = ?2 WHERE id = ?3",
+                params![attachment.meta_blob, attachment.meta_nonce, attachment.id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        for (blob_id, content_hash) in &batch.blob_hashes {
+            tx.execute(
+                "UPDATE attachment_blobs SET content_hash = ?1 WHERE blob_id = ?2",
+                params![content_hash, blob_id],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        for (blob_id, index, data, nonce) in &batch.chunks {
+            tx.execute(
+                "UPDATE attachment_chunks SET data = ?1, nonce = ?2
+                 WHERE blob_id = ?3 AND chunk_index = ?4",
+                params![data, nonce, blob_id, index],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        tx.commit().map_err(|e| e.to_string())
+    }
+
+    // --- Profiles ---
+
+    fn list_profiles(&self) -> Result<Vec<ProfileRecord>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT p.id, p.name, p.created_at, COUNT(v.id) as entry_count
+                 FROM profiles p
+                 LEFT JOIN vault_entries v ON v.profile_id = p.id AND v.deleted_at IS NULL
+                 GROUP BY p.id
+                 ORDER BY p.id",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(ProfileRecord {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    created_at: row.get(2)?,
+                    entry_count: row.get(3)?,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        collect(rows)
+    }
+
+    fn find_profile_by_name(&self, name: &str) -> Result<Option<i64>, String> {
+        self.conn
+            .query_row(
+                "SELECT id FROM profiles WHERE name = ?1",
+                params![name],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())
+    }
+
+    fn create_profile(&mut self, name: &str) -> Result<i64, String> {
+        self.conn
+            .execute("INSERT INTO profiles (name) VALUES (?1)", params![name])
+            .map_err(|e| e.to_string())?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    fn rename_profile(&mut self, id: i64, name: &str) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE profiles SET name = ?1 WHERE id = ?2",
+                params![name, id],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn delete_profile(
+        &mut self,
+        id: i64,
+        entries: &[EntryRecord],
+        folders: &[FolderRecord],
+    ) -> Result<(), String> {
+        let tx = self.conn.transaction().map_err(|e| e.to_string())?;
+        write_entries_in(&tx, entries)?;
+        for folder in folders {
+            overwrite_folder_in(&tx, folder)?;
+        }
+        tx.execute("DELETE FROM profiles WHERE id = ?1", params![id])
+            .map_err(|e| e.to_string())?;
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    // --- Paired devices ---
+
+    fn list_paired_devices(&self) -> Result<Vec<PairedDevice>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, device_name, device_id, paired_at, last_sync_at
+                 FROM paired_devices ORDER BY paired_at DESC",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(PairedDevice {
+                    id: row.get(0)?,
+                    device_name: row.get(1)?,
+                    device_id: row.get(2)?,
+                    paired_at: row.get(3)?,
+                    last_sync_at: row.get(4)?,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        collect(rows)
+    }
+
+    fn add_paired_device(
+        &mut self,
+        device_name: &str,
+        device_id: &str,
+        public_key: &[u8],
+        shared_secret: &[u8],
+    ) -> Result<i64, String> {
+        self.conn
+            .execute(
+                "INSERT INTO paired_devices (device_name, device_id, public_key, shared_secret)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![device_name, device_id, public_key, shared_secret],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    fn forget_device(&mut self, device_id: &str) -> Result<(), String> {
+        self.conn
+            .execute(
+                "DELETE FROM paired_devices WHERE device_id = ?1",
+                params![device_id],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn get_last_sync_at(&self, device_id: &str) -> Result<Option<String>, String> {
+        self.conn
+            .query_row(
+                "SELECT last_sync_at FROM paired_devices WHERE device_id = ?1",
+                params![device_id],
+                |row| row.get::<_, Option<String>>(0),
+            )
+            .map_err(|e| e.to_string())
+    }
+
+    fn set_last_sync_at(&mut self, device_id: &str, at: &str) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE paired_devices SET last_sync_at = ?1 WHERE device_id = ?2",
+                params![at, device_id],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    // --- Sync log ---
+
+    fn append_sync_log(&mut self, entry: &SyncHistoryEntry) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT INTO sync_log (device_id, direction, entries_sent, entries_received, status, started_at, completed_at, error_message)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    entry.device_id,
+                    entry.direction,
+                    entry.entries_sent,
+                    entry.entries_received,
+                    entry.status,
+                    entry.started_at,
+                    entry.completed_at,
+                    entry.error_message,
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn sync_history(&self, limit: usize) -> Result<Vec<SyncHistoryEntry>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, device_id, direction, entries_sent, entries_received,
+                        status, started_at, completed_at, error_message
+                 FROM sync_log ORDER BY started_at DESC LIMIT ?1",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![limit as i64], |row| {
+                Ok(SyncHistoryEntry {
+                    id: row.get(0)?,
+                    device_id: row.get(1)?,
+                    direction: row.get(2)?,
+                    entries_sent: row.get(3)?,
+                    entries_received: row.get(4)?,
+                    status: row.get(5)?,
+                    started_at: row.get(6)?,
+                    completed_at: row.get(7)?,
+                    error_message: row.get(8)?,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        collect(rows)
+    }
+
+    // --- Settings ---
+
+    fn get_setting(&self, key: &str) -> Result<Option<String>, String> {
+        self.conn
+            .query_row(
+                "SELECT value FROM settings WHERE key = ?1",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())
+    }
+
+    fn set_setting(&mut self, key: &str, value: &str) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT INTO settings (key, value) VALUES (?1, ?2)
+                 ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+                params![key, value],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
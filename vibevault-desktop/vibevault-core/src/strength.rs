@@ -0,0 +1,1015 @@
+// Password strength estimation in the style of zxcvbn.
+//
+// A password is matched against patterns people actually use: common
+// passwords and words (also reversed or in l33t), the user's own inputs,
+// keyboard walks, sequences, repeats, years and dates. Each match gets an
+// estimated number of guesses, and the cheapest way to cover the whole
+// password with matches and brute-forced gaps is taken as the attacker's
+// cost. That cost maps to a 0–4 score; the master password policy requires
+// a minimum score as well as a minimum length.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use chrono::Datelike;
+use serde::{Deserialize, Serialize};
+
+use crate::generator::wordlist;
+use crate::Vault;
+
+/// Most common passwords, most common first
+const COMMON_PASSWORDS: &str = include_str!("../data/common_passwords.txt");
+/// Frequent English words, first names and surnames, most frequent first
+const ENGLISH_WORDS: &str = include_str!("../data/english_words.txt");
+
+/// Only this many leading characters are analyzed; anything after that is
+/// counted as brute force
+const MAX_ANALYZED_LENGTH: usize = 100;
+
+const BRUTEFORCE_CARDINALITY: f64 = 10.0;
+const MIN_GUESSES_SINGLE_CHAR: f64 = 10.0;
+const MIN_GUESSES_MULTI_CHAR: f64 = 50.0;
+/// Additive term that keeps long sequences of cheap matches from scoring
+/// lower than fewer, bigger ones
+const MIN_GUESSES_BEFORE_GROWING_SEQUENCE: f64 = 10_000.0;
+const MIN_YEAR_SPACE: f64 = 20.0;
+/// Guesses per second of an offline attack on a slow hash such as Argon2
+const GUESSES_PER_SECOND: f64 = 10_000.0;
+
+/// Lower bound for a configured master password minimum length
+pub const MIN_MASTER_PASSWORD_LENGTH: usize = 8;
+const MASTER_PASSWORD_POLICY_SETTING: &str = "master_password_policy";
+
+/// Kind of pattern a part of the password was recognized as
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PatternKind {
+    /// A common password
+    Password,
+    /// An English word or name
+    Word,
+    /// Part of the username or other user inputs
+    UserInput,
+    Keyboard,
+    Sequence,
+    Repeat,
+    Year,
+    Date,
+    Bruteforce,
+}
+
+/// A recognized part of the password
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StrengthMatch {
+    pub pattern: PatternKind,
+    pub token: String,
+    pub guesses_log10: f64,
+}
+
+/// Result of `estimate_strength`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StrengthEstimate {
+    /// 0 (too guessable) to 4 (very unguessable)
+    pub score: u8,
+    pub guesses_log10: f64,
+    /// Time an offline attack on the vault file would take, e.g. "3 days"
+    pub crack_time_display: String,
+    pub warning: Option<String>,
+    pub suggestions: Vec<String>,
+    /// The cheapest cover of the password by patterns, in password order
+    pub sequence: Vec<StrengthMatch>,
+}
+
+/// Requirements a new master password must meet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MasterPasswordPolicy {
+    /// Minimum number of characters
+    pub min_length: usize,
+    /// Minimum `StrengthEstimate::score`
+    pub min_score: u8,
+}
+
+impl Default for MasterPasswordPolicy {
+    fn default() -> Self {
+        MasterPasswordPolicy {
+            min_length: 12,
+            min_score: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Detail {
+    Dictionary {
+        kind: PatternKind,
+        rank: usize,
+        reversed: bool,
+        l33t: bool,
+    },
+    Keyboard {
+        turns: usize,
+    },
+    Sequence,
+    Repeat {
+        base_len: usize,
+    },
+    Year,
+    Date,
+    Bruteforce,
+}
+
+#[derive(Debug, Clone)]
+struct Match {
+    i: usize,
+    j: usize,
+    token: String,
+    guesses_log10: f64,
+    detail: Detail,
+}
+
+fn ranked(list: &'static str) -> HashMap<&'static str, usize> {
+    list.lines()
+        .map(str::trim)
+        .filter(|word| !word.is_empty())
+        .enumerate()
+        .map(|(i, word)| (word, i + 1))
+        .collect()
+}
+
+/// Built-in dictionaries: (kind, word → rank)
+fn dictionaries() -> &'static [(PatternKind, HashMap<&'static str, usize>)] {
+    static DICTIONARIES: OnceLock<Vec<(PatternKind, HashMap<&'static str, usize>)>> =
+        OnceLock::new();
+    DICTIONARIES.get_or_init(|| {
+        // Words of the generator's list are all equally likely, so each costs
+        // the size of the list
+        let words = wordlist();
+        let eff = words.iter().map(|w| (*w, words.len())).collect();
+        vec![
+            (PatternKind::Password, ranked(COMMON_PASSWORDS)),
+            (PatternKind::Word, ranked(ENGLISH_WORDS)),
+            (PatternKind::Word, eff),
+        ]
+    })
+}
+
+fn l33t_table(c: char) -> &'static [char] {
+    match c {
+        '4' | '@' => &['a'],
+        '8' => &['b'],
+        '(' | '{' | '[' | '<' => &['c'],
+        '3' => &['e'],
+        '6' | '9' => &['g'],
+        '1' => &['i', 'l'],
+        '!' | '|' => &['i', 'l'],
+        '0' => &['o'],
+        '$' | '5' => &['s'],
+        '7' | '+' => &['t'],
+        '%' => &['x'],
+        '2' => &['z'],
+        _ => &[],
+    }
+}
+
+fn log10_binomial(n: usize, k: usize) -> f64 {
+    if k > n {
+        return f64::NEG_INFINITY;
+    }
+    (0..k).map(|i| ((n - i) as f64 / (i + 1) as f64).log10()).sum()
+}
+
+fn log10_sum(a: f64, b: f64) -> f64 {
+    let (hi, lo) = if a > b { (a, b) } else { (b, a) };
+    hi + (1.0 + 10f64.powf(lo - hi)).log10()
+}
+
+/// How many ways the letters of `token` could have been capitalized
+fn uppercase_variations_log10(token: &[char]) -> f64 {
+    let upper = token.iter().filter(|c| c.is_uppercase()).count();
+    let lower = token.iter().filter(|c| c.is_lowercase()).count();
+    if upper == 0 {
+        return 0.0;
+    }
+    let first_only = token[0].is_uppercase() && upper == 1;
+    let last_only = token[token.len() - 1].is_uppercase() && upper == 1;
+    if lower == 0 || first_only || last_only {
+        return 2f64.log10();
+    }
+    let total: f64 = (1..=upper.min(lower))
+        .map(|k| 10f64.powf(log10_binomial(upper + lower, k)))
+        .sum();
+    total.log10()
+}
+
+/// How many ways the substituted characters could have been chosen
+fn l33t_variations_log10(original: &[char], plain: &[char]) -> f64 {
+    let substituted = original
+        .iter()
+        .zip(plain)
+        .filter(|(o, p)| o.to_ascii_lowercase() != **p)
+        .count();
+    if substituted == 0 {
+        0.0
+    } else {
+        (substituted as f64 * 2f64.log10()).max(2f64.log10())
+    }
+}
+
+/// Candidate plain spellings of a l33t token, at most a few
+fn unl33t(token: &[char]) -> Vec<Vec<char>> {
+    let mut candidates: Vec<Vec<char>> = vec![Vec::with_capacity(token.len())];
+    for c in token {
+        let options = l33t_table(*c);
+        if options.is_empty() {
+            for candidate in &mut candidates {
+                candidate.push(c.to_ascii_lowercase());
+            }
+            continue;
+        }
+        let mut next = Vec::new();
+        for candidate in &candidates {
+            for option in options {
+                if next.len() >= 8 {
+                    break;
+                }
+                let mut extended = candidate.clone();
+                extended.push(*option);
+                next.push(extended);
+            }
+        }
+        candidates = next;
+    }
+    candidates
+}
+
+fn dictionary_matches(chars: &[char], user_inputs: &HashMap<String, usize>) -> Vec<Match> {
+    let lower: Vec<char> = chars.iter().map(|c| c.to_lowercase().next().unwrap_or(*c)).collect();
+    let mut matches = Vec::new();
+
+    let lookup = |word: &str| -> Option<(PatternKind, usize)> {
+        let user = user_inputs
+            .get(word)
+            .map(|rank| (PatternKind::UserInput, *rank));
+        dictionaries()
+            .iter()
+            .filter_map(|(kind, dict)| dict.get(word).map(|rank| (*kind, *rank)))
+            .chain(user)
+            .min_by_key(|(_, rank)| *rank)
+    };
+
+    for i in 0..chars.len() {
+        for j in i + 2..chars.len() {
+            let original = &chars[i..=j];
+            let token: String = original.iter().collect();
+            let plain: String = lower[i..=j].iter().collect();
+            let reversed: String = lower[i..=j].iter().rev().collect();
+
+            let mut candidates = vec![(plain.clone(), false, false)];
+            if reversed != plain {
+                candidates.push((reversed, true, false));
+            }
+            if original.iter().any(|c| !l33t_table(*c).is_empty()) {
+                for spelled in unl33t(original) {
+                    let spelled: String = spelled.into_iter().collect();
+                    if spelled != plain {
+                        candidates.push((spelled, false, true));
+                    }
+                }
+            }
+
+            for (word, reversed, l33t) in candidates {
+                let (kind, rank) = match lookup(&word) {
+                    Some(found) => found,
+                    None => continue,
+                };
+                let mut guesses = (rank as f64).log10() + uppercase_variations_log10(original);
+                if reversed {
+                    guesses += 2f64.log10();
+                }
+                if l33t {
+                    let plain_chars: Vec<char> = word.chars().collect();
+                    guesses += l33t_variations_log10(original, &plain_chars);
+                }
+                matches.push(Match {
+                    i,
+                    j,
+                    token: token.clone(),
+                    guesses_log10: guesses,
+                    detail: Detail::Dictionary {
+                        kind,
+                        rank,
+                        reversed,
+                        l33t,
+                    },
+                });
+            }
+        }
+    }
+    matches
+}
+
+const QWERTY_ROWS: [(&str, &str); 4] = [
+    ("`1234567890-=", "~!@#$%^&*()_+"),
+    ("qwertyuiop[]\\", "QWERTYUIOP{}|"),
+    ("asdfghjkl;'", "ASDFGHJKL:\""),
+    ("zxcvbnm,./", "ZXCVBNM<>?"),
+];
+
+/// Key position of `c` on a QWERTY layout and whether it needs shift
+fn key_position(c: char) -> Option<(usize, usize, bool)> {
+    QWERTY_ROWS.iter().enumerate().find_map(|(row, (plain, shifted))| {
+        plain
+            .chars()
+            .position(|k| k == c)
+            .map(|col| (row, col, false))
+            .or_else(|| shifted.chars().position(|k| k == c).map(|col| (row, col, true)))
+    })
+}
+
+/// Direction from key `a` to an adjacent key `b`, if they are adjacent.
+/// Each row sits half a key to the right of the one above it.
+fn key_direction(a: (usize, usize), b: (usize, usize)) -> Option<u8> {
+    let (r1, c1) = (a.0 as i64, a.1 as i64);
+    let (r2, c2) = (b.0 as i64, b.1 as i64);
+    match (r2 - r1, c2 - c1) {
+        (0, -1) => Some(0),
+        (0, 1) => Some(1),
+        (-1, 0) => Some(2),
+        (-1, 1) => Some(3),
+        (1, -1) => Some(4),
+        (1, 0) => Some(5),
+        _ => None,
+    }
+}
+
+fn keyboard_matches(chars: &[char]) -> Vec<Match> {
+    let keys: usize = QWERTY_ROWS.iter().map(|(plain, _)| plain.len()).sum();
+    let average_degree: f64 = 4.6;
+    let mut matches = Vec::new();
+
+    let mut i = 0;
+    while i + 2 < chars.len() {
+        let mut j = i;
+        let mut turns = 0;
+        let mut last_direction = None;
+        let mut shifted = usize::from(key_position(chars[i]).is_some_and(|k| k.2));
+        while j + 1 < chars.len() {
+            let (a, b) = match (key_position(chars[j]), key_position(chars[j + 1])) {
+                (Some(a), Some(b)) => (a, b),
+                _ => break,
+            };
+            let direction = match key_direction((a.0, a.1), (b.0, b.1)) {
+                Some(direction) => direction,
+                None => break,
+            };
+            if last_direction != Some(direction) {
+                turns += 1;
+                last_direction = Some(direction);
+            }
+            if b.2 {
+                shifted += 1;
+            }
+            j += 1;
+        }
+
+        if j - i + 1 >= 3 {
+            let length = j - i + 1;
+            let mut total = 0.0;
+            for l in 2..=length {
+                for t in 1..=turns.min(l - 1) {
+                    total += 10f64.powf(log10_binomial(l - 1, t - 1))
+                        * keys as f64
+                        * average_degree.powi(t as i32);
+                }
+            }
+            let mut guesses = total.log10();
+            let unshifted = length - shifted;
+            if shifted > 0 {
+                guesses += if unshifted == 0 {
+                    2f64.log10()
+                } else {
+                    (1..=shifted.min(unshifted))
+                        .map(|k| 10f64.powf(log10_binomial(length, k)))
+                        .sum::<f64>()
+                        .log10()
+                };
+            }
+            matches.push(Match {
+                i,
+                j,
+                token: chars[i..=j].iter().collect(),
+                guesses_log10: guesses,
+                detail: Detail::Keyboard { turns },
+            });
+            i = j;
+        } else {
+            i += 1;
+        }
+    }
+    matches
+}
+
+fn sequence_matches(chars: &[char]) -> Vec<Match> {
+    let same_class = |a: char, b: char| {
+        (a.is_ascii_lowercase() && b.is_ascii_lowercase())
+            || (a.is_ascii_uppercase() && b.is_ascii_uppercase())
+            || (a.is_ascii_digit() && b.is_ascii_digit())
+    };
+    let mut matches = Vec::new();
+    let mut i = 0;
+    while i + 2 < chars.len() {
+        let delta = chars[i + 1] as i64 - chars[i] as i64;
+        if delta == 0 || delta.abs() > 5 || !same_class(chars[i], chars[i + 1]) {
+            i += 1;
+            continue;
+        }
+        let mut j = i + 1;
+        while j + 1 < chars.len()
+            && chars[j + 1] as i64 - chars[j] as i64 == delta
+            && same_class(chars[j], chars[j + 1])
+        {
+            j += 1;
+        }
+        if j - i + 1 >= 3 {
+            let first = chars[i];
+            let mut base: f64 = if "aAzZ019".contains(first) {
+                4.0
+            } else if first.is_ascii_digit() {
+                10.0
+            } else {
+                26.0
+            };
+            if delta < 0 {
+                base *= 2.0;
+            }
+            matches.push(Match {
+                i,
+                j,
+                token: chars[i..=j].iter().collect(),
+                guesses_log10: (base * (j - i + 1) as f64).log10(),
+                detail: Detail::Sequence,
+            });
+            i = j;
+        } else {
+            i += 1;
+        }
+    }
+    matches
+}
+
+fn repeat_matches(chars: &[char], user_inputs: &HashMap<String, usize>) -> Vec<Match> {
+    let mut matches = Vec::new();
+    let mut i = 0;
+    while i + 1 < chars.len() {
+        // Longest run of a repeated base starting at `i`, preferring the
+        // shortest base for the same run
+        let mut best: Option<(usize, usize)> = None;
+        for base_len in 1..=(chars.len() - i) / 2 {
+            let base = &chars[i..i + base_len];
+            let mut count = 1;
+            while i + (count + 1) * base_len <= chars.len()
+                && &chars[i + count * base_len..i + (count + 1) * base_len] == base
+            {
+                count += 1;
+            }
+            let covered = count * base_len;
+            let long_enough = if base_len == 1 { count >= 3 } else { count >= 2 };
+            if long_enough && best.is_none_or(|(_, len)| covered > len) {
+                best = Some((base_len, covered));
+            }
+        }
+
+        match best {
+            Some((base_len, covered)) => {
+                let base = &chars[i..i + base_len];
+                let base_guesses = most_guessable(base, user_inputs).0;
+                let count = covered / base_len;
+                matches.push(Match {
+                    i,
+                    j: i + covered - 1,
+                    token: chars[i..i + covered].iter().collect(),
+                    guesses_log10: base_guesses + (count as f64).log10(),
+                    detail: Detail::Repeat { base_len },
+                });
+                i += covered;
+            }
+            None => i += 1,
+        }
+    }
+    matches
+}
+
+fn year_guesses_log10(year: i32) -> f64 {
+    let reference = chrono::Utc::now().year();
+    ((year - reference).abs() as f64).max(MIN_YEAR_SPACE).log10()
+}
+
+/// A plausible (year, month, day) read from three numbers
+fn as_date(parts: [&str; 3]) -> Option<i32> {
+    let number = |s: &str| s.parse::<i32>().ok();
+    let year = |s: &str| match s.len() {
+        4 => number(s).filter(|y| (1900..=2050).contains(y)),
+        2 => number(s).map(|y| if y > 50 { 1900 + y } else { 2000 + y }),
+        _ => None,
+    };
+    let month_day = |m: &str, d: &str| {
+        matches!((number(m), number(d)), (Some(m), Some(d))
+            if (1..=12).contains(&m) && (1..=31).contains(&d))
+    };
+    let short = |s: &str| s.len() <= 2;
+
+    let [a, b, c] = parts;
+    if short(b) && short(c) && month_day(b, c) {
+        if let Some(y) = year(a) {
+            return Some(y);
+        }
+    }
+    if short(a) && short(b) && (month_day(b, a) || month_day(a, b)) {
+        if let Some(y) = year(c) {
+            return Some(y);
+        }
+    }
+    None
+}
+
+fn date_matches(chars: &[char]) -> Vec<Match> {
+    let mut matches = Vec::new();
+    for i in 0..chars.len() {
+        for j in i + 3..chars.len().min(i + 10) {
+            let token: String = chars[i..=j].iter().collect();
+            let all_digits = token.chars().all(|c| c.is_ascii_digit());
+
+            if all_digits && token.len() == 4 {
+                if let Some(year) = token.parse::<i32>().ok().filter(|y| (1900..=2050).contains(y)) {
+                    matches.push(Match {
+                        i,
+                        j,
+                        token: token.clone(),
+                        guesses_log10: year_guesses_log10(year),
+                        detail: Detail::Year,
+                    });
+                    continue;
+                }
+            }
+
+            let mut year = None;
+            let mut separator = false;
+            if all_digits && (5..=8).contains(&token.len()) {
+                // Try every split into day, month and year of 1, 2 or 4 digits
+                let n = token.len();
+                'splits: for a in 1..n {
+                    for b in a + 1..n {
+                        let parts = [&token[..a], &token[a..b], &token[b..]];
+                        if parts.iter().all(|p| matches!(p.len(), 1 | 2 | 4)) {
+                            year = as_date(parts);
+                            if year.is_some() {
+                                break 'splits;
+                            }
+                        }
+                    }
+                }
+            } else if !all_digits {
+                let sep = match token.chars().find(|c| !c.is_ascii_digit()) {
+                    Some(sep) if " -/._\\".contains(sep) => sep,
+                    _ => continue,
+                };
+                let parts: Vec<&str> = token.split(sep).collect();
+                if parts.len() == 3
+                    && parts
+                        .iter()
+                        .all(|p| matches!(p.len(), 1 | 2 | 4) && p.chars().all(|c| c.is_ascii_digit()))
+                {
+                    year = as_date([parts[0], parts[1], parts[2]]);
+                    separator = true;
+                }
+            }
+
+            if let Some(year) = year {
+                let mut guesses = 365f64.log10() + year_guesses_log10(year);
+                if separator {
+                    guesses += 4f64.log10();
+                }
+                matches.push(Match {
+                    i,
+                    j,
+                    token,
+                    guesses_log10: guesses,
+                    detail: Detail::Date,
+                });
+            }
+        }
+    }
+    matches
+}
+
+fn bruteforce_guesses_log10(length: usize) -> f64 {
+    let min = if length == 1 {
+        MIN_GUESSES_SINGLE_CHAR
+    } else {
+        MIN_GUESSES_MULTI_CHAR
+    };
+    (length as f64 * BRUTEFORCE_CARDINALITY.log10()).max(min.log10())
+}
+
+/// Every pattern match in `chars`, before the cheapest cover is chosen
+fn all_matches(chars: &[char], user_inputs: &HashMap<String, usize>) -> Vec<Match> {
+    let mut matches = dictionary_matches(chars, user_inputs);
+    matches.extend(keyboard_matches(chars));
+    matches.extend(sequence_matches(chars));
+    matches.extend(repeat_matches(chars, user_inputs));
+    matches.extend(date_matches(chars));
+    for m in &mut matches {
+        let min = if m.j == m.i {
+            MIN_GUESSES_SINGLE_CHAR
+        } else {
+            MIN_GUESSES_MULTI_CHAR
+        };
+        m.guesses_log10 = m.guesses_log10.max(min.log10());
+    }
+    matches
+}
+
+/// The cheapest cover of `chars` by matches and brute-forced gaps:
+/// (log10 of the guesses, the matches in order)
+fn most_guessable(chars: &[char], user_inputs: &HashMap<String, usize>) -> (f64, Vec<Match>) {
+    let n = chars.len();
+    if n == 0 {
+        return (0.0, Vec::new());
+    }
+
+    let mut candidates = all_matches(chars, user_inputs);
+    for i in 0..n {
+        for j in i..n {
+            candidates.push(Match {
+                i,
+                j,
+                token: chars[i..=j].iter().collect(),
+                guesses_log10: bruteforce_guesses_log10(j - i + 1),
+                detail: Detail::Bruteforce,
+            });
+        }
+    }
+    let mut ending_at: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for (index, m) in candidates.iter().enumerate() {
+        ending_at[m.j].push(index);
+    }
+
+    // product[k][l]: least log10 product of guesses of `l` matches covering
+    // chars[..=k], with the last match's index for backtracking
+    let mut product: Vec<Vec<Option<(f64, usize)>>> = vec![vec![None; n + 1]; n];
+    for k in 0..n {
+        for &index in &ending_at[k] {
+            let m = &candidates[index];
+            if m.i == 0 {
+                let better = product[k][1].is_none_or(|(p, _)| m.guesses_log10 < p);
+                if better {
+                    product[k][1] = Some((m.guesses_log10, index));
+                }
+                continue;
+            }
+            for l in 1..n {
+                if let Some((prev, _)) = product[m.i - 1][l] {
+                    let value = prev + m.guesses_log10;
+                    if product[k][l + 1].is_none_or(|(p, _)| value < p) {
+                        product[k][l + 1] = Some((value, index));
+                    }
+                }
+            }
+        }
+    }
+
+    // guesses = l! * product + D^(l - 1)
+    let mut best: Option<(f64, usize)> = None;
+    let mut factorial = 0.0;
+    for (l, cover) in product[n - 1].iter().enumerate().skip(1) {
+        factorial += (l as f64).log10();
+        if let Some((p, _)) = *cover {
+            let total = log10_sum(
+                factorial + p,
+                (l - 1) as f64 * MIN_GUESSES_BEFORE_GROWING_SEQUENCE.log10(),
+            );
+            if best.is_none_or(|(b, _)| total < b) {
+                best = Some((total, l));
+            }
+        }
+    }
+
+    let (guesses, mut l) = match best {
+        Some(best) => best,
+        None => return (bruteforce_guesses_log10(n), Vec::new()),
+    };
+    let mut sequence = Vec::with_capacity(l);
+    let mut k = n as i64 - 1;
+    while l > 0 && k >= 0 {
+        let index = match product[k as usize][l] {
+            Some((_, index)) => index,
+            None => break,
+        };
+        let m = candidates[index].clone();
+        k = m.i as i64 - 1;
+        l -= 1;
+        sequence.push(m);
+    }
+    sequence.reverse();
+    (guesses, sequence)
+}
+
+fn score(guesses_log10: f64) -> u8 {
+    let delta: f64 = 5.0;
+    let thresholds = [1e3, 1e6, 1e8, 1e10];
+    thresholds
+        .iter()
+        .position(|t| guesses_log10 < (t + delta).log10())
+        .map_or(4, |score| score as u8)
+}
+
+fn crack_time_display(guesses_log10: f64) -> String {
+    let seconds = 10f64.powf(guesses_log10) / GUESSES_PER_SECOND;
+    let units = [
+        ("minute", 60.0),
+        ("hour", 3600.0),
+        ("day", 86_400.0),
+        ("month", 2_629_800.0),
+        ("year", 31_557_600.0),
+    ];
+    if seconds < 1.0 {
+        return "less than a second".to_string();
+    }
+    if seconds >= 100.0 * 31_557_600.0 {
+        return "centuries".to_string();
+    }
+    let (name, size) = units
+        .iter()
+        .rev()
+        .find(|(_, size)| seconds >= *size)
+        .copied()
+        .unwrap_or(("second", 1.0));
+    let count = (seconds / size).round() as u64;
+    format!("{} {}{}", count, name, if count == 1 { "" } else { "s" })
+}
+
+fn feedback(score: u8, sequence: &[Match], password: &[char]) -> (Option<String>, Vec<String>) {
+    if password.is_empty() {
+        return (
+            None,
+            vec![
+                "Use a few words, avoid common phrases".to_string(),
+                "No need for symbols, digits, or uppercase letters".to_string(),
+            ],
+        );
+    }
+    if score > 2 {
+        return (None, Vec::new());
+    }
+
+    let mut suggestions = vec!["Add another word or two. Uncommon words are better.".to_string()];
+    let longest = match sequence.iter().max_by_key(|m| m.j - m.i) {
+        Some(m) => m,
+        None => return (None, suggestions),
+    };
+    let whole = sequence.len() == 1;
+
+    let warning = match &longest.detail {
+        Detail::Dictionary {
+            kind,
+            rank,
+            reversed,
+            l33t,
+        } => {
+            let token: Vec<char> = longest.token.chars().collect();
+            if token[0].is_uppercase() && token.iter().skip(1).all(|c| !c.is_uppercase()) {
+                suggestions.push("Capitalization doesn't help very much".to_string());
+            } else if token.iter().all(|c| !c.is_lowercase()) && token.iter().any(|c| c.is_uppercase()) {
+                suggestions.push("All-uppercase is almost as easy to guess as all-lowercase".to_string());
+            }
+            if *reversed && token.len() >= 4 {
+                suggestions.push("Reversed words aren't much harder to guess".to_string());
+            }
+            if *l33t {
+                suggestions.push(
+                    "Predictable substitutions like '@' instead of 'a' don't help very much"
+                        .to_string(),
+                );
+            }
+            match kind {
+                PatternKind::Password if whole && !*l33t && !*reversed => Some(if *rank <= 10 {
+                    "This is a top-10 common password".to_string()
+                } else if *rank <= 100 {
+                    "This is a top-100 common password".to_string()
+                } else {
+                    "This is a very common password".to_string()
+                }),
+                PatternKind::Password => {
+                    Some("This is similar to a commonly used password".to_string())
+                }
+                PatternKind::UserInput => {
+                    Some("Passwords containing your name or username are easy to guess".to_string())
+                }
+                _ if whole => Some("A word by itself is easy to guess".to_string()),
+                _ => Some("Common names and words are easy to guess".to_string()),
+            }
+        }
+        Detail::Keyboard { turns } => {
+            suggestions.push("Use a longer keyboard pattern with more turns".to_string());
+            Some(if *turns == 1 {
+                "Straight rows of keys are easy to guess".to_string()
+            } else {
+                "Short keyboard patterns are easy to guess".to_string()
+            })
+        }
+        Detail::Repeat { base_len } => {
+            suggestions.push("Avoid repeated words and characters".to_string());
+            Some(if *base_len == 1 {
+                "Repeats like \"aaa\" are easy to guess".to_string()
+            } else {
+                "Repeats like \"abcabcabc\" are only slightly harder to guess than \"abc\""
+                    .to_string()
+            })
+        }
+        Detail::Sequence => {
+            suggestions.push("Avoid sequences".to_string());
+            Some("Sequences like abc or 6543 are easy to guess".to_string())
+        }
+        Detail::Year => {
+            suggestions.push("Avoid recent years and years that are associated with you".to_string());
+            Some("Recent years are easy to guess".to_string())
+        }
+        Detail::Date => {
+            suggestions.push("Avoid dates and years that are associated with you".to_string());
+            Some("Dates are often easy to guess".to_string())
+        }
+        Detail::Bruteforce => None,
+    };
+    (warning, suggestions)
+}
+
+/// Estimate how hard `password` is to guess. `user_inputs` are strings an
+/// attacker would try first, such as the username or the entry's title and
+/// URL; their words are matched like a small dictionary.
+pub fn estimate_strength(password: &str, user_inputs: &[&str]) -> StrengthEstimate {
+    let mut inputs: HashMap<String, usize> = HashMap::new();
+    let words = user_inputs
+        .iter()
+        .flat_map(|input| input.split(|c: char| !c.is_alphanumeric()))
+        .chain(user_inputs.iter().copied())
+        .map(str::to_lowercase)
+        .filter(|word| word.chars().count() >= 3);
+    for (rank, word) in words.enumerate() {
+        inputs.entry(word).or_insert(rank + 1);
+    }
+
+    let chars: Vec<char> = password.chars().collect();
+    let analyzed = &chars[..chars.len().min(MAX_ANALYZED_LENGTH)];
+    let (mut guesses_log10, sequence) = most_guessable(analyzed, &inputs);
+    if chars.len() > analyzed.len() {
+        guesses_log10 += bruteforce_guesses_log10(chars.len() - analyzed.len());
+    }
+
+    let score = score(guesses_log10);
+    let (warning, suggestions) = feedback(score, &sequence, &chars);
+    StrengthEstimate {
+        score,
+        guesses_log10,
+        crack_time_display: crack_time_display(guesses_log10),
+        warning,
+        suggestions,
+        sequence: sequence
+            .into_iter()
+            .map(|m| StrengthMatch {
+                pattern: match m.detail {
+                    Detail::Dictionary { kind, .. } => kind,
+                    Detail::Keyboard { .. } => PatternKind::Keyboard,
+                    Detail::Sequence => PatternKind::Sequence,
+                    Detail::Repeat { .. } => PatternKind::Repeat,
+                    Detail::Year => PatternKind::Year,
+                    Detail::Date => PatternKind::Date,
+                    Detail::Bruteforce => PatternKind::Bruteforce,
+                },
+                token: m.token,
+                guesses_log10: m.guesses_log10,
+            })
+            .collect(),
+    }
+}
+
+impl Vault {
+    /// The master password policy of this device
+    pub fn master_password_policy(&self) -> MasterPasswordPolicy {
+        self.store
+            .get_setting(MASTER_PASSWORD_POLICY_SETTING)
+            .ok()
+            .flatten()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Change the master password policy. Needs a session once a master
+    /// user exists; before registration there is nothing to protect yet,
+    /// so the setup screen may configure it. The policy applies to
+    /// passwords chosen from now on; the current one is not re-checked.
+    pub fn set_master_password_policy(
+        &mut self,
+        token: Option<&str>,
+        policy: MasterPasswordPolicy,
+    ) -> Result<(), String> {
+        if self.is_registered()? {
+            self.validate_session(token.unwrap_or_default())?;
+        }
+        if policy.min_length < MIN_MASTER_PASSWORD_LENGTH {
+            return Err(format!(
+                "Minimum length must be at least {}",
+                MIN_MASTER_PASSWORD_LENGTH
+            ));
+        }
+        if policy.min_score > 4 {
+            return Err("Minimum score must be between 0 and 4".to_string());
+        }
+        let json = serde_json::to_string(&policy).map_err(|e| e.to_string())?;
+        self.store.set_setting(MASTER_PASSWORD_POLICY_SETTING, &json)
+    }
+
+    /// Check a new master password for `username` against the policy.
+    /// Registration calls this; so must any flow that changes the master
+    /// password.
+    pub fn check_master_password(
+        &self,
+        username: &str,
+        pass: &str,
+    ) -> Result<StrengthEstimate, String> {
+        let policy = self.master_password_policy();
+        if pass.chars().count() < policy.min_length {
+            return Err(format!(
+                "Master password must be at least {} characters",
+                policy.min_length
+            ));
+        }
+        let estimate = estimate_strength(pass, &[username, "vibevault"]);
+        if estimate.score < policy.min_score {
+            return Err(match &estimate.warning {
+                Some(warning) => format!("Master password is too weak: {}", warning),
+                None => "Master password is too weak".to_string(),
+            });
+        }
+        Ok(estimate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns(password: &str) -> Vec<PatternKind> {
+        estimate_strength(password, &[])
+            .sequence
+            .into_iter()
+            .map(|m| m.pattern)
+            .collect()
+    }
+
+    #[test]
+    fn test_common_passwords_score_zero() {
+        for password in ["password", "123456", "qwerty", "P@ssw0rd", "iloveyou", "drowssap"] {
+            let estimate = estimate_strength(password, &[]);
+            assert_eq!(estimate.score, 0, "{}", password);
+            assert!(estimate.warning.is_some(), "{}", password);
+        }
+        assert_eq!(
+            estimate_strength("password", &[]).warning.as_deref(),
+            Some("This is a top-10 common password")
+        );
+    }
+
+    #[test]
+    fn test_patterns_are_recognized() {
+        assert_eq!(patterns("zxcvfdsa"), [PatternKind::Keyboard]);
+        assert_eq!(patterns("abcdefgh"), [PatternKind::Sequence]);
+        assert_eq!(patterns("zzzzzzzz"), [PatternKind::Repeat]);
+        assert_eq!(patterns("1987"), [PatternKind::Year]);
+        assert_eq!(patterns("12/05/1987"), [PatternKind::Date]);
+        assert_eq!(patterns("19870512"), [PatternKind::Date]);
+        assert!(patterns("Margaret1987").contains(&PatternKind::Word));
+
+        let estimate = estimate_strength("alice2024", &["alice"]);
+        assert_eq!(estimate.sequence[0].pattern, PatternKind::UserInput);
+        assert!(estimate.score <= 1);
+    }
+
+    #[test]
+    fn test_strong_passwords_score_high() {
+        assert_eq!(estimate_strength("correct horse battery staple", &[]).score, 4);
+        assert_eq!(estimate_strength("kT9#vq2$Lm8!xZ4p", &[]).score, 4);
+        let estimate = estimate_strength("kT9#vq2$Lm8!xZ4p", &[]);
+        assert!(estimate.warning.is_none());
+        assert!(estimate.suggestions.is_empty());
+        assert_eq!(estimate.crack_time_display, "centuries");
+
+        // Longer variants of the same pattern never get cheaper
+        let short = estimate_strength("qwertyui", &[]).guesses_log10;
+        let long = estimate_strength("qwertyuiopasdf", &[]).guesses_log10;
+        assert!(long > short);
+        assert_eq!(estimate_strength("", &[]).score, 0);
+    }
+}
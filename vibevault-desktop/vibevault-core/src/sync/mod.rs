@@ -0,0 +1,102 @@
+pub mod pairing;
+pub mod transfer;
+
+use serde::{Deserialize, Serialize};
+
+use crate::search::SearchIndex;
+use crate::Vault;
+use transfer::{
+    export_favorites, export_folders, export_hotp_counters, export_usage, export_vault,
+    import_favorites, import_folders, import_hotp_counters, import_usage, import_vault,
+    MergeResult, SyncPayload,
+};
+
+/// Version of the sync payload format produced by `export_sync_payload`
+pub const SYNC_PAYLOAD_VERSION: u32 = 1;
+
+// --- Types for frontend communication ---
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairedDevice {
+    pub id: i64,
+    pub device_name: String,
+    pub device_id: String,
+    pub paired_at: String,
+    pub last_sync_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncHistoryEntry {
+    pub id: i64,
+    pub device_id: String,
+    pub direction: String,
+    pub entries_sent: i64,
+    pub entries_received: i64,
+    pub status: String,
+    pub started_at: String,
+    pub completed_at: Option<String>,
+    pub error_message: Option<String>,
+}
+
+impl Vault {
+    pub fn paired_devices(&mut self, token: &str) -> Result<Vec<PairedDevice>, String> {
+        self.validate_session(token)?;
+        self.store.list_paired_devices()
+    }
+
+    pub fn forget_device(&mut self, token: &str, device_id: &str) -> Result<(), String> {
+        self.validate_session(token)?;
+        self.store.forget_device(device_id)
+    }
+
+    /// The 50 most recent sync operations, newest first
+    pub fn sync_history(&mut self, token: &str) -> Result<Vec<SyncHistoryEntry>, String> {
+        self.validate_session(token)?;
+        self.store.sync_history(50)
+    }
+
+    /// Build a sync payload of entries, folders, usage counters, favorite
+    /// flags and HOTP counters changed after `since`. A full export (`since == None`) also
+    /// carries the encryption salt so the receiving device can derive the
+    /// same vault key.
+    pub fn export_sync_payload(
+        &mut self,
+        token: &str,
+        since: Option<&str>,
+    ) -> Result<SyncPayload, String> {
+        self.validate_session(token)?;
+        let encryption_salt = match since {
+            None => Some(self.store.first_encryption_salt()?),
+            Some(_) => None,
+        };
+        Ok(SyncPayload {
+            version: SYNC_PAYLOAD_VERSION,
+            encryption_salt,
+            entries: export_vault(self.store.as_ref(), since)?,
+            folders: export_folders(self.store.as_ref(), since)?,
+            usage: export_usage(self.store.as_ref(), since)?,
+            favorites: export_favorites(self.store.as_ref(), since)?,
+            hotp_counters: export_hotp_counters(self.store.as_ref(), since)?,
+        })
+    }
+
+    /// Merge a received sync payload into the local vault. Folders are merged
+    /// first; the returned counts cover entries. Usage, favorites and HOTP
+    /// counters merge on their own and never count as entry conflicts.
+    pub fn import_sync_payload(
+        &mut self,
+        token: &str,
+        payload: &SyncPayload,
+    ) -> Result<MergeResult, String> {
+        let key = self.validate_session(token)?;
+        import_folders(self.store.as_mut(), &payload.folders)?;
+        let result = import_vault(self.store.as_mut(), &payload.entries)?;
+        import_usage(self.store.as_mut(), &payload.usage)?;
+        import_favorites(self.store.as_mut(), &payload.favorites)?;
+        import_hotp_counters(self.store.as_mut(), &payload.hotp_counters)?;
+        if self.search_index.is_some() {
+            self.search_index = Some(SearchIndex::build(self.store.as_ref(), &key)?);
+        }
+        Ok(result)
+    }
+}
@@ -115,6 +115,12 @@ impl PairingSession {
     }
 }
 
+impl Default for PairingSession {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Derive a session key from a stored shared secret (for re-pairing without code)
 pub fn derive_session_key_from_secret(shared_secret: &[u8]) -> Result<[u8; 32], String> {
     let hkdf = Hkdf::<Sha256>::new(None, shared_secret);
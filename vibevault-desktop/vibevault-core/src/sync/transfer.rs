@@ -0,0 +1,636 @@
+use serde::{Deserialize, Serialize};
+
+use crate::revisions::{ensure_baseline_revision, modified_by, record_revision, UNKNOWN_DEVICE};
+use crate::store::{
+    EntryRecord, FavoriteRecord, FolderRecord, HotpCounterRecord, UsageRecord, VaultStore,
+};
+use crate::sync::SyncHistoryEntry;
+
+/// Sync payload exchanged between devices
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncPayload {
+    pub version: u32,
+    /// Hex-encoded encryption_salt — included on first sync only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encryption_salt: Option<String>,
+    pub entries: Vec<SyncEntry>,
+    /// Folders changed in the same window; absent from older peers
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub folders: Vec<SyncFolder>,
+    /// Per-device usage counters; absent from older peers
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub usage: Vec<SyncUsage>,
+    /// Favorite flags; absent from older peers
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub favorites: Vec<SyncFavorite>,
+    /// HOTP counters; absent from older peers
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hotp_counters: Vec<SyncHotpCounter>,
+}
+
+/// A single vault entry in the sync payload (still vault-encrypted)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncEntry {
+    pub entry_uuid: String,
+    pub uuid: String, // site name
+    #[serde(with = "base64_bytes")]
+    pub data_blob: Vec<u8>,
+    #[serde(with = "base64_bytes")]
+    pub nonce: Vec<u8>,
+    pub profile_name: String,
+    pub sync_version: i64,
+    pub created_at: String,
+    pub updated_at: String,
+    pub deleted_at: Option<String>,
+    /// Device that made this version of the entry, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_by: Option<String>,
+}
+
+/// A single folder in the sync payload (still vault-encrypted)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncFolder {
+    pub folder_uuid: String,
+    #[serde(with = "base64_bytes")]
+    pub data_blob: Vec<u8>,
+    #[serde(with = "base64_bytes")]
+    pub nonce: Vec<u8>,
+    pub profile_name: String,
+    pub sync_version: i64,
+    pub created_at: String,
+    pub updated_at: String,
+    pub deleted_at: Option<String>,
+}
+
+/// One device's usage counter for an entry
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncUsage {
+    pub entry_uuid: String,
+    pub device_id: String,
+    pub last_used_at: String,
+    pub use_count: i64,
+}
+
+/// The favorite flag of an entry
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncFavorite {
+    pub entry_uuid: String,
+    pub favorite: bool,
+    pub updated_at: String,
+}
+
+/// The next unused counter of an HOTP entry
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncHotpCounter {
+    pub entry_uuid: String,
+    pub counter: i64,
+    pub updated_at: String,
+}
+
+/// Base64 encoding/decoding for Vec<u8> fields in JSON
+mod base64_bytes {
+    use base64::{engine::general_purpose, Engine as _};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(bytes: &Vec<u8>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let encoded = general_purpose::STANDARD.encode(bytes);
+        serializer.serialize_str(&encoded)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        general_purpose::STANDARD
+            .decode(&s)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Result of merging a sync payload into the local database
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct MergeResult {
+    pub inserted: u32,
+    pub updated: u32,
+    pub deleted: u32,
+    pub skipped: u32,
+    pub conflicts: u32,
+}
+
+/// Export vault entries for sync. If `since` is provided, only entries modified after that timestamp.
+pub fn export_vault(
+    db: &dyn VaultStore,
+    since: Option<&str>,
+) -> Result<Vec<SyncEntry>, String> {
+    let profiles = db.list_profiles()?;
+
+    let mut entries = Vec::new();
+    for record in db.entries_updated_since(since)? {
+        let modified_by = modified_by(db, &record)?;
+        let entry_uuid = match record.entry_uuid {
+            Some(entry_uuid) => entry_uuid,
+            None => continue,
+        };
+        let profile_name = match profiles.iter().find(|p| p.id == record.profile_id) {
+            Some(p) => p.name.clone(),
+            None => continue,
+        };
+        entries.push(SyncEntry {
+            entry_uuid,
+            uuid: record.uuid,
+            data_blob: record.data_blob,
+            nonce: record.nonce,
+            profile_name,
+            sync_version: record.sync_version,
+            created_at: record.created_at,
+            updated_at: record.updated_at,
+            deleted_at: record.deleted_at,
+            modified_by,
+        });
+    }
+    Ok(entries)
+}
+
+/// Import sync entries into the local database using last-write-wins conflict resolution.
+pub fn import_vault(
+    db: &mut dyn VaultStore,
+    entries: &[SyncEntry],
+) -> Result<MergeResult, String> {
+    let mut result = MergeResult::default();
+
+    for entry in entries {
+        // Ensure profile exists (match by name)
+        let profile_id = ensure_profile(db, &entry.profile_name)?;
+
+        // Look up local entry by entry_uuid
+        let local = db.find_entry_by_uuid(&entry.entry_uuid)?;
+        let origin = entry.modified_by.as_deref().unwrap_or(UNKNOWN_DEVICE);
+
+        match local {
+            None => {
+                // Not found locally → insert
+                let record = EntryRecord {
+                    id: 0,
+                    uuid: entry.uuid.clone(),
+                    data_blob: entry.data_blob.clone(),
+                    nonce: entry.nonce.clone(),
+                    profile_id,
+                    entry_uuid: Some(entry.entry_uuid.clone()),
+                    created_at: entry.created_at.clone(),
+                    updated_at: entry.updated_at.clone(),
+                    deleted_at: entry.deleted_at.clone(),
+                    sync_version: entry.sync_version,
+                };
+                db.insert_entry(&record)?;
+                record_revision(db, &record, origin)?;
+                result.inserted += 1;
+            }
+            Some(mut local) => {
+                // Conflict resolution: last write wins
+                let should_update = remote_wins(
+                    (&entry.updated_at, entry.sync_version),
+                    (&local.updated_at, local.sync_version),
+                );
+
+                if should_update {
+                    ensure_baseline_revision(db, &local)?;
+
+                    // Check if this is a tombstone propagation
+                    if entry.deleted_at.is_some() && local.deleted_at.is_none() {
+                        // Remote is deleted, local is active → soft-delete locally
+                        local.deleted_at = entry.deleted_at.clone();
+                        local.updated_at = entry.updated_at.clone();
+                        local.sync_version = entry.sync_version;
+                        db.overwrite_entry(&local)?;
+                        record_revision(db, &local, origin)?;
+                        result.deleted += 1;
+                    } else {
+                        // Update with remote data
+                        local.uuid = entry.uuid.clone();
+                        local.data_blob = entry.data_blob.clone();
+                        local.nonce = entry.nonce.clone();
+                        local.profile_id = profile_id;
+                        local.updated_at = entry.updated_at.clone();
+                        local.deleted_at = entry.deleted_at.clone();
+                        local.sync_version = entry.sync_version;
+                        db.overwrite_entry(&local)?;
+                        record_revision(db, &local, origin)?;
+                        result.updated += 1;
+                    }
+                    result.conflicts += 1;
+                } else {
+                    result.skipped += 1;
+                }
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Export folders for sync. If `since` is provided, only folders modified after that timestamp.
+pub fn export_folders(
+    db: &dyn VaultStore,
+    since: Option<&str>,
+) -> Result<Vec<SyncFolder>, String> {
+    let profiles = db.list_profiles()?;
+
+    let mut folders = Vec::new();
+    for record in db.folders_updated_since(since)? {
+        let profile_name = match profiles.iter().find(|p| p.id == record.profile_id) {
+            Some(p) => p.name.clone(),
+            None => continue,
+        };
+        folders.push(SyncFolder {
+            folder_uuid: record.folder_uuid,
+            data_blob: record.data_blob,
+            nonce: record.nonce,
+            profile_name,
+            sync_version: record.sync_version,
+            created_at: record.created_at,
+            updated_at: record.updated_at,
+            deleted_at: record.deleted_at,
+        });
+    }
+    Ok(folders)
+}
+
+/// Import sync folders using the same last-write-wins rule as entries
+pub fn import_folders(
+    db: &mut dyn VaultStore,
+    folders: &[SyncFolder],
+) -> Result<MergeResult, String> {
+    let mut result = MergeResult::default();
+
+    for folder in folders {
+        let profile_id = ensure_profile(db, &folder.profile_name)?;
+        let record = FolderRecord {
+            id: 0,
+            folder_uuid: folder.folder_uuid.clone(),
+            profile_id,
+            data_blob: folder.data_blob.clone(),
+            nonce: folder.nonce.clone(),
+            created_at: folder.created_at.clone(),
+            updated_at: folder.updated_at.clone(),
+            deleted_at: folder.deleted_at.clone(),
+            sync_version: folder.sync_version,
+        };
+
+        match db.find_folder_by_uuid(&folder.folder_uuid)? {
+            None => {
+                db.insert_folder(&record)?;
+                result.inserted += 1;
+            }
+            Some(local) => {
+                if remote_wins(
+                    (&folder.updated_at, folder.sync_version),
+                    (&local.updated_at, local.sync_version),
+                ) {
+                    if folder.deleted_at.is_some() && local.deleted_at.is_none() {
+                        result.deleted += 1;
+                    } else {
+                        result.updated += 1;
+                    }
+                    db.overwrite_folder(&FolderRecord {
+                        id: local.id,
+                        created_at: local.created_at,
+                        ..record
+                    })?;
+                    result.conflicts += 1;
+                } else {
+                    result.skipped += 1;
+                }
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Export usage counters that changed after `since` (all when `None`)
+pub fn export_usage(db: &dyn VaultStore, since: Option<&str>) -> Result<Vec<SyncUsage>, String> {
+    Ok(db
+        .list_entry_usage()?
+        .into_iter()
+        .filter(|u| since.is_none_or(|since| u.last_used_at.as_str() > since))
+        .map(|u| SyncUsage {
+            entry_uuid: u.entry_uuid,
+            device_id: u.device_id,
+            last_used_at: u.last_used_at,
+            use_count: u.use_count,
+        })
+        .collect())
+}
+
+/// Merge usage counters. Each counter only grows on its own device, so the
+/// per-device maximum is always right and never conflicts.
+pub fn import_usage(db: &mut dyn VaultStore, usage: &[SyncUsage]) -> Result<(), String> {
+    for u in usage {
+        db.merge_entry_usage(&UsageRecord {
+            entry_uuid: u.entry_uuid.clone(),
+            device_id: u.device_id.clone(),
+            last_used_at: u.last_used_at.clone(),
+            use_count: u.use_count,
+        })?;
+    }
+    Ok(())
+}
+
+/// Export favorite flags that changed after `since` (all when `None`)
+pub fn export_favorites(
+    db: &dyn VaultStore,
+    since: Option<&str>,
+) -> Result<Vec<SyncFavorite>, String> {
+    Ok(db
+        .list_favorites()?
+        .into_iter()
+        .filter(|f| since.is_none_or(|since| f.updated_at.as_str() > since))
+        .map(|f| SyncFavorite {
+            entry_uuid: f.entry_uuid,
+            favorite: f.favorite,
+            updated_at: f.updated_at,
+        })
+        .collect())
+}
+
+/// Merge favorite flags; the later change wins and a tie keeps the local flag
+pub fn import_favorites(
+    db: &mut dyn VaultStore,
+    favorites: &[SyncFavorite],
+) -> Result<(), String> {
+    for f in favorites {
+        let newer = db
+            .find_favorite(&f.entry_uuid)?
+            .is_none_or(|local| compare_timestamps(&f.updated_at, &local.updated_at).is_gt());
+        if newer {
+            db.put_favorite(&FavoriteRecord {
+                entry_uuid: f.entry_uuid.clone(),
+                favorite: f.favorite,
+                updated_at: f.updated_at.clone(),
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Export HOTP counters that moved after `since` (all when `None`)
+pub fn export_hotp_counters(
+    db: &dyn VaultStore,
+    since: Option<&str>,
+) -> Result<Vec<SyncHotpCounter>, String> {
+    Ok(db
+        .list_hotp_counters()?
+        .into_iter()
+        .filter(|c| since.is_none_or(|since| c.updated_at.as_str() > since))
+        .map(|c| SyncHotpCounter {
+            entry_uuid: c.entry_uuid,
+            counter: c.counter,
+            updated_at: c.updated_at,
+        })
+        .collect())
+}
+
+/// Merge HOTP counters. A counter is never reused once any device has
+/// generated a code with it, so the larger value always wins.
+pub fn import_hotp_counters(
+    db: &mut dyn VaultStore,
+    counters: &[SyncHotpCounter],
+) -> Result<(), String> {
+    for c in counters {
+        db.merge_hotp_counter(&HotpCounterRecord {
+            entry_uuid: c.entry_uuid.clone(),
+            counter: c.counter,
+            updated_at: c.updated_at.clone(),
+        })?;
+    }
+    Ok(())
+}
+
+/// Last write wins on (updated_at, sync_version); a full tie keeps the local copy
+fn remote_wins(remote: (&str, i64), local: (&str, i64)) -> bool {
+    match compare_timestamps(remote.0, local.0) {
+        std::cmp::Ordering::Greater => true,
+        std::cmp::Ordering::Less => false,
+        // Tie: compare sync_version, higher wins; still tied → keep local
+        std::cmp::Ordering::Equal => remote.1 > local.1,
+    }
+}
+
+/// Ensure a profile with the given name exists, returning its ID
+fn ensure_profile(db: &mut dyn VaultStore, name: &str) -> Result<i64, String> {
+    match db.find_profile_by_name(name)? {
+        Some(id) => Ok(id),
+        None => db.create_profile(name),
+    }
+}
+
+/// Compare ISO 8601 / RFC 3339 timestamps as strings (lexicographic works for ISO dates)
+fn compare_timestamps(a: &str, b: &str) -> std::cmp::Ordering {
+    a.cmp(b)
+}
+
+/// Get the encryption_salt for the registered user (needed for first sync)
+pub fn get_encryption_salt(db: &dyn VaultStore) -> Result<String, String> {
+    db.first_encryption_salt()
+}
+
+/// Record last sync time for a paired device
+pub fn update_last_sync(db: &mut dyn VaultStore, device_id: &str) -> Result<(), String> {
+    let now = chrono::Utc::now().to_rfc3339();
+    db.set_last_sync_at(device_id, &now)
+}
+
+/// Get the last sync timestamp for a paired device (for delta sync)
+pub fn get_last_sync_at(db: &dyn VaultStore, device_id: &str) -> Result<Option<String>, String> {
+    db.get_last_sync_at(device_id)
+}
+
+/// Log a sync operation
+pub fn log_sync(
+    db: &mut dyn VaultStore,
+    device_id: &str,
+    direction: &str,
+    entries_sent: u32,
+    entries_received: u32,
+    status: &str,
+    error_message: Option<&str>,
+) -> Result<(), String> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let completed = if status != "failed" {
+        Some(now.clone())
+    } else {
+        None
+    };
+    db.append_sync_log(&SyncHistoryEntry {
+        id: 0,
+        device_id: device_id.to_string(),
+        direction: direction.to_string(),
+        entries_sent: entries_sent as i64,
+        entries_received: entries_received as i64,
+        status: status.to_string(),
+        started_at: now,
+        completed_at: completed,
+        error_message: error_message.map(|m| m.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemoryStore;
+
+    fn sync_entry(entry_uuid: &str, profile: &str, updated_at: &str, version: i64) -> SyncEntry {
+        SyncEntry {
+            entry_uuid: entry_uuid.to_string(),
+            uuid: "github".to_string(),
+            data_blob: vec![1, 2, 3],
+            nonce: vec![0u8; 12],
+            profile_name: profile.to_string(),
+            sync_version: version,
+            created_at: "2024-01-01T00:00:00+00:00".to_string(),
+            updated_at: updated_at.to_string(),
+            deleted_at: None,
+            modified_by: Some("phone".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_import_creates_missing_profiles() {
+        let mut store = MemoryStore::new();
+        let result = import_vault(
+            &mut store,
+            &[sync_entry("e-1", "Work", "2024-01-01T00:00:00+00:00", 1)],
+        )
+        .unwrap();
+
+        assert_eq!(result.inserted, 1);
+        let work = store.find_profile_by_name("Work").unwrap().unwrap();
+        assert_eq!(store.list_entries(work).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_import_last_write_wins() {
+        let mut store = MemoryStore::new();
+        import_vault(
+            &mut store,
+            &[sync_entry("e-1", "Personal", "2024-01-02T00:00:00+00:00", 2)],
+        )
+        .unwrap();
+
+        // Older remote copy is ignored
+        let result = import_vault(
+            &mut store,
+            &[sync_entry("e-1", "Personal", "2024-01-01T00:00:00+00:00", 5)],
+        )
+        .unwrap();
+        assert_eq!(result.skipped, 1);
+
+        // Newer remote tombstone propagates
+        let mut tombstone = sync_entry("e-1", "Personal", "2024-01-03T00:00:00+00:00", 3);
+        tombstone.deleted_at = Some("2024-01-03T00:00:00+00:00".to_string());
+        let result = import_vault(&mut store, &[tombstone]).unwrap();
+        assert_eq!(result.deleted, 1);
+        assert!(store.list_entries(1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_folders_roundtrip_and_tombstones() {
+        let mut source = MemoryStore::new();
+        source
+            .insert_folder(&FolderRecord {
+                id: 0,
+                folder_uuid: "f-1".to_string(),
+                profile_id: 1,
+                data_blob: vec![1],
+                nonce: vec![0u8; 12],
+                created_at: "2024-01-01T00:00:00+00:00".to_string(),
+                updated_at: "2024-01-01T00:00:00+00:00".to_string(),
+                deleted_at: None,
+                sync_version: 1,
+            })
+            .unwrap();
+
+        let mut target = MemoryStore::new();
+        let result = import_folders(&mut target, &export_folders(&source, None).unwrap()).unwrap();
+        assert_eq!(result.inserted, 1);
+        assert_eq!(target.list_folders(1).unwrap().len(), 1);
+
+        let mut tombstone = export_folders(&source, None).unwrap();
+        tombstone[0].deleted_at = Some("2024-01-02T00:00:00+00:00".to_string());
+        tombstone[0].updated_at = "2024-01-02T00:00:00+00:00".to_string();
+        tombstone[0].sync_version = 2;
+        let result = import_folders(&mut target, &tombstone).unwrap();
+        assert_eq!(result.deleted, 1);
+        assert!(target.list_folders(1).unwrap().is_empty());
+
+        // Replaying the older copy does not resurrect it
+        let result = import_folders(&mut target, &export_folders(&source, None).unwrap()).unwrap();
+        assert_eq!(result.skipped, 1);
+    }
+
+    #[test]
+    fn test_usage_and_favorites_merge() {
+        let mut desk = MemoryStore::new();
+        let mut phone = MemoryStore::new();
+        desk.record_entry_use("e-1", "desk", "2024-01-01T00:00:00+00:00").unwrap();
+        desk.record_entry_use("e-1", "desk", "2024-01-02T00:00:00+00:00").unwrap();
+        phone.record_entry_use("e-1", "phone", "2024-01-03T00:00:00+00:00").unwrap();
+        desk.put_favorite(&FavoriteRecord {
+            entry_uuid: "e-1".to_string(),
+            favorite: true,
+            updated_at: "2024-01-01T00:00:00+00:00".to_string(),
+        })
+        .unwrap();
+        phone
+            .put_favorite(&FavoriteRecord {
+                entry_uuid: "e-1".to_string(),
+                favorite: false,
+                updated_at: "2024-01-04T00:00:00+00:00".to_string(),
+            })
+            .unwrap();
+
+        // Syncing twice in both directions does not double count
+        for _ in 0..2 {
+            import_usage(&mut phone, &export_usage(&desk, None).unwrap()).unwrap();
+            import_usage(&mut desk, &export_usage(&phone, None).unwrap()).unwrap();
+            import_favorites(&mut phone, &export_favorites(&desk, None).unwrap()).unwrap();
+            import_favorites(&mut desk, &export_favorites(&phone, None).unwrap()).unwrap();
+        }
+        for store in [&desk, &phone] {
+            let total: i64 = store.list_entry_usage().unwrap().iter().map(|u| u.use_count).sum();
+            assert_eq!(total, 3);
+            assert!(!store.find_favorite("e-1").unwrap().unwrap().favorite);
+        }
+
+        let delta = export_usage(&desk, Some("2024-01-02T12:00:00+00:00")).unwrap();
+        assert_eq!(delta.len(), 1);
+        assert_eq!(delta[0].device_id, "phone");
+    }
+
+    #[test]
+    fn test_export_roundtrip() {
+        let mut source = MemoryStore::new();
+        import_vault(
+            &mut source,
+            &[
+                sync_entry("e-1", "Personal", "2024-01-01T00:00:00+00:00", 1),
+                sync_entry("e-2", "Work", "2024-01-05T00:00:00+00:00", 1),
+            ],
+        )
+        .unwrap();
+
+        let delta = export_vault(&source, Some("2024-01-02T00:00:00+00:00")).unwrap();
+        assert_eq!(delta.len(), 1);
+        assert_eq!(delta[0].entry_uuid, "e-2");
+        assert_eq!(delta[0].profile_name, "Work");
+
+        let mut target = MemoryStore::new();
+        let exported = export_vault(&source, None).unwrap();
+        let result = import_vault(&mut target, &exported).unwrap();
+        assert_eq!(result.inserted, 2);
+    }
+}
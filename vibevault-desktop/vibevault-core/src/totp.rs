@@ -0,0 +1,630 @@
+// One-time password parameters and code generation.
+//
+// An item's `totp_secret` holds either a bare base32 secret, which means
+// the common SHA-1 / 6 digits / 30 seconds TOTP, or a full `otpauth://`
+// URI as shown in setup QR codes. The URI is stored as entered so that no
+// parameter is lost; it is parsed whenever a code is generated.
+//
+// Besides TOTP the URI can select counter-based HOTP (`otpauth://hotp/`)
+// or Steam Guard (`encoder=steam`, `otpauth://steam/` or `steam://SECRET`).
+// An HOTP entry's counter lives outside the encrypted item in its own
+// record that only moves forward, so generating codes on two devices never
+// rolls it back when they sync.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use totp_rs::{Algorithm, TOTP};
+use zeroize::Zeroize;
+
+use crate::store::{EntryRecord, HotpCounterRecord};
+use crate::vault::{decode_base32_secret, decrypt_item, now_iso};
+use crate::Vault;
+
+pub const DEFAULT_DIGITS: usize = 6;
+pub const DEFAULT_PERIOD: u64 = 30;
+const MIN_DIGITS: usize = 6;
+const MAX_DIGITS: usize = 8;
+const MAX_PERIOD: u64 = 3600;
+/// Steam Guard codes are five characters from Steam's own alphabet
+const STEAM_DIGITS: usize = 5;
+
+/// How codes are derived: from the time, from a counter, or Steam's variant
+/// of TOTP
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OtpKind {
+    #[default]
+    Totp,
+    Hotp,
+    Steam,
+}
+
+/// HMAC algorithm of a one-time password
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum OtpAlgorithm {
+    #[default]
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl OtpAlgorithm {
+    fn parse(name: &str) -> Result<Self, String> {
+        match name.to_uppercase().replace('-', "").as_str() {
+            "SHA1" => Ok(OtpAlgorithm::Sha1),
+            "SHA256" => Ok(OtpAlgorithm::Sha256),
+            "SHA512" => Ok(OtpAlgorithm::Sha512),
+            _ => Err(format!("Unsupported TOTP algorithm: {}", name)),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            OtpAlgorithm::Sha1 => "SHA1",
+            OtpAlgorithm::Sha256 => "SHA256",
+            OtpAlgorithm::Sha512 => "SHA512",
+        }
+    }
+
+    fn hmac(self) -> Algorithm {
+        match self {
+            OtpAlgorithm::Sha1 => Algorithm::SHA1,
+            OtpAlgorithm::Sha256 => Algorithm::SHA256,
+            OtpAlgorithm::Sha512 => Algorithm::SHA512,
+        }
+    }
+}
+
+/// Everything needed to generate codes for one account
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TotpParams {
+    pub kind: OtpKind,
+    pub secret: Vec<u8>,
+    pub algorithm: OtpAlgorithm,
+    pub digits: usize,
+    /// Seconds each code is valid (TOTP and Steam)
+    pub period: u64,
+    /// Initial counter from the URI (HOTP)
+    pub counter: u64,
+    pub issuer: Option<String>,
+    /// Account name, usually the username or email address
+    pub label: Option<String>,
+}
+
+impl Drop for TotpParams {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+/// The current code of an entry and what the frontend needs to count down
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TotpCode {
+    pub kind: OtpKind,
+    pub code: String,
+    /// Seconds until `code` expires; 0 for HOTP codes, which do not expire
+    pub remaining_seconds: u64,
+    /// The code after `code`, for users who want to enter it just in time
+    pub next_code: String,
+    /// 0 for HOTP
+    pub period: u64,
+    /// Counter `code` was generated with (HOTP)
+    pub counter: Option<u64>,
+    pub digits: usize,
+    pub algorithm: OtpAlgorithm,
+    pub issuer: Option<String>,
+    pub label: Option<String>,
+}
+
+/// Codes of one entry in a batch; the secret itself never leaves the backend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryTotp {
+    pub id: i64,
+    pub entry_uuid: Option<String>,
+    pub title: String,
+    pub totp: TotpCode,
+}
+
+/// Percent-decode a URI component; `+` is a space in query values
+pub(crate) fn percent_decode(s: &str, plus_is_space: bool) -> Result<String, String> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = bytes
+                    .get(i + 1..i + 3)
+                    .and_then(|h| std::str::from_utf8(h).ok())
+                    .and_then(|h| u8::from_str_radix(h, 16).ok())
+                    .ok_or("Invalid percent-encoding in otpauth URI")?;
+                out.push(hex);
+                i += 3;
+            }
+            b'+' if plus_is_space => {
+                out.push(b' ');
+                i += 1;
+            }
+            b => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(out).map_err(|_| "Invalid UTF-8 in otpauth URI".to_string())
+}
+
+/// Percent-encode everything but unreserved characters
+pub(crate) fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// The decoded parts of an `otpauth://TYPE/LABEL?QUERY` URI
+pub(crate) struct OtpauthUri {
+    /// `totp` or `hotp`, lowercased
+    pub kind: String,
+    pub label: String,
+    /// Query parameters with lowercased names, in URI order
+    pub params: Vec<(String, String)>,
+}
+
+impl OtpauthUri {
+    /// First non-empty value of the parameter `name`
+    pub fn param(&self, name: &str) -> Option<String> {
+        self.params
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    }
+}
+
+/// Split an `otpauth://` URI into its decoded parts
+pub(crate) fn split_otpauth(uri: &str) -> Result<OtpauthUri, String> {
+    let rest = uri
+        .get(..10)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("otpauth://"))
+        .map(|_| &uri[10..])
+        .ok_or("Not an otpauth URI")?;
+    let (kind, rest) = rest.split_once('/').ok_or("otpauth URI has no label")?;
+    let (label, query) = rest.split_once('?').unwrap_or((rest, ""));
+
+    let mut params = Vec::new();
+    for pair in query.split('&').filter(|p| !p.is_empty()) {
+        let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+        params.push((
+            percent_decode(name, true)?.to_lowercase(),
+            percent_decode(value, true)?,
+        ));
+    }
+    Ok(OtpauthUri {
+        kind: kind.to_lowercase(),
+        label: percent_decode(label, false)?,
+        params,
+    })
+}
+
+fn steam_params(secret: Vec<u8>) -> TotpParams {
+    TotpParams {
+        kind: OtpKind::Steam,
+        secret,
+        algorithm: OtpAlgorithm::Sha1,
+        digits: STEAM_DIGITS,
+        period: DEFAULT_PERIOD,
+        counter: 0,
+        issuer: Some("Steam".to_string()),
+        label: None,
+    }
+}
+
+/// Parse a bare base32 secret, a `steam://` secret or an `otpauth://` URI
+pub fn parse_totp(input: &str) -> Result<TotpParams, String> {
+    let input = input.trim();
+    if let Some(secret) = input
+        .get(..8)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("steam://"))
+        .map(|_| &input[8..])
+    {
+        return Ok(steam_params(decode_base32_secret(secret)?));
+    }
+    if !input
+        .get(..10)
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case("otpauth://"))
+    {
+        return Ok(TotpParams {
+            kind: OtpKind::Totp,
+            secret: decode_base32_secret(input)?,
+            algorithm: OtpAlgorithm::Sha1,
+            digits: DEFAULT_DIGITS,
+            period: DEFAULT_PERIOD,
+            counter: 0,
+            issuer: None,
+            label: None,
+        });
+    }
+
+    let uri = split_otpauth(input)?;
+    let param = |name: &str| uri.param(name);
+    let steam = param("encoder").is_some_and(|e| e.eq_ignore_ascii_case("steam"));
+    let kind = match uri.kind.as_str() {
+        "totp" if steam => OtpKind::Steam,
+        "totp" => OtpKind::Totp,
+        "hotp" => OtpKind::Hotp,
+        "steam" => OtpKind::Steam,
+        other => return Err(format!("Unsupported OTP type: {}", other)),
+    };
+
+    let secret = decode_base32_secret(&param("secret").ok_or("otpauth URI has no secret")?)?;
+    let algorithm = match param("algorithm") {
+        Some(name) => OtpAlgorithm::parse(&name)?,
+        None => OtpAlgorithm::Sha1,
+    };
+    let digits = match param("digits") {
+        Some(d) => d
+            .parse::<usize>()
+            .ok()
+            .filter(|d| (MIN_DIGITS..=MAX_DIGITS).contains(d))
+            .ok_or_else(|| format!("TOTP digits must be between {} and {}", MIN_DIGITS, MAX_DIGITS))?,
+        None => DEFAULT_DIGITS,
+    };
+    let period = match param("period") {
+        Some(p) if kind == OtpKind::Totp => p
+            .parse::<u64>()
+            .ok()
+            .filter(|p| (1..=MAX_PERIOD).contains(p))
+            .ok_or_else(|| format!("TOTP period must be between 1 and {} seconds", MAX_PERIOD))?,
+        _ => DEFAULT_PERIOD,
+    };
+    let counter = match param("counter") {
+        Some(c) if kind == OtpKind::Hotp => c
+            .parse::<u64>()
+            .ok()
+            .filter(|c| i64::try_from(*c).is_ok())
+            .ok_or("Invalid HOTP counter")?,
+        _ => 0,
+    };
+
+    // The label is `Issuer:account` or just `account`; an `issuer`
+    // parameter wins over the prefix
+    let (prefix, account) = match uri.label.split_once(':') {
+        Some((issuer, account)) => (Some(issuer.trim().to_string()), account.trim().to_string()),
+        None => (None, uri.label.trim().to_string()),
+    };
+    let label = Some(account).filter(|a| !a.is_empty());
+    let issuer = param("issuer").or(prefix).filter(|i| !i.is_empty());
+    if kind == OtpKind::Steam {
+        let mut params = steam_params(secret);
+        params.label = label;
+        if issuer.is_some() {
+            params.issuer = issuer;
+        }
+        return Ok(params);
+    }
+    Ok(TotpParams {
+        kind,
+        secret,
+        algorithm,
+        digits,
+        period,
+        counter,
+        issuer,
+        label,
+    })
+}
+
+impl TotpParams {
+    /// Canonical `otpauth://` URI with every parameter spelled out
+    pub fn to_uri(&self) -> String {
+        let secret = base32::encode(base32::Alphabet::RFC4648 { padding: false }, &self.secret);
+        let label = match (&self.issuer, &self.label) {
+            (Some(issuer), Some(label)) => {
+                format!("{}:{}", percent_encode(issuer), percent_encode(label))
+            }
+            (Some(issuer), None) => percent_encode(issuer),
+            (None, Some(label)) => percent_encode(label),
+            (None, None) => String::new(),
+        };
+        let kind = match self.kind {
+            OtpKind::Hotp => "hotp",
+            OtpKind::Totp | OtpKind::Steam => "totp",
+        };
+        let mut uri = format!("otpauth://{}/{}?secret={}", kind, label, secret);
+        if let Some(issuer) = &self.issuer {
+            uri.push_str(&format!("&issuer={}", percent_encode(issuer)));
+        }
+        match self.kind {
+            OtpKind::Totp => uri.push_str(&format!(
+                "&algorithm={}&digits={}&period={}",
+                self.algorithm.name(),
+                self.digits,
+                self.period
+            )),
+            OtpKind::Hotp => uri.push_str(&format!(
+                "&algorithm={}&digits={}&counter={}",
+                self.algorithm.name(),
+                self.digits,
+                self.counter
+            )),
+            OtpKind::Steam => uri.push_str("&encoder=steam"),
+        }
+        uri
+    }
+
+    /// Generator whose time step is `step`; HOTP uses a step of one so the
+    /// "time" passed in is the counter
+    fn generator(&self, step: u64) -> TOTP {
+        let algorithm = match self.kind {
+            OtpKind::Steam => Algorithm::Steam,
+            OtpKind::Totp | OtpKind::Hotp => self.algorithm.hmac(),
+        };
+        TOTP::new_unchecked(algorithm, self.digits, 1, step, self.secret.clone())
+    }
+
+    /// Code for the period containing `unix_time` (TOTP and Steam)
+    pub fn code_at(&self, unix_time: u64) -> String {
+        self.generator(self.period).generate(unix_time)
+    }
+
+    /// Code for `counter` (HOTP)
+    pub fn hotp_code(&self, counter: u64) -> String {
+        self.generator(1).generate(counter)
+    }
+
+    /// Current and next code at `unix_time` (TOTP and Steam)
+    pub fn codes_at(&self, unix_time: u64) -> TotpCode {
+        let remaining_seconds = self.period - unix_time % self.period;
+        TotpCode {
+            kind: self.kind,
+            code: self.code_at(unix_time),
+            remaining_seconds,
+            next_code: self.code_at(unix_time + remaining_seconds),
+            period: self.period,
+            counter: None,
+            digits: self.digits,
+            algorithm: self.algorithm,
+            issuer: self.issuer.clone(),
+            label: self.label.clone(),
+        }
+    }
+
+    /// Code for `counter` and the one after it (HOTP)
+    pub fn hotp_codes(&self, counter: u64) -> TotpCode {
+        TotpCode {
+            kind: OtpKind::Hotp,
+            code: self.hotp_code(counter),
+            remaining_seconds: 0,
+            next_code: self.hotp_code(counter + 1),
+            period: 0,
+            counter: Some(counter),
+            digits: self.digits,
+            algorithm: self.algorithm,
+            issuer: self.issuer.clone(),
+            label: self.label.clone(),
+        }
+    }
+}
+
+pub(crate) fn unix_now() -> Result<u64, String> {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .map_err(|e| e.to_string())
+}
+
+/// TOTP parameters of a stored entry, or `None` if it has no TOTP configured
+fn entry_params(key: &[u8; 32], entry: &EntryRecord) -> Result<Option<(String, TotpParams)>, String> {
+    let item = decrypt_item(key, entry)?;
+    match item.totp_secret.as_deref().map(str::trim) {
+        Some(secret) if !secret.is_empty() => Ok(Some((item.title.clone(), parse_totp(secret)?))),
+        _ => Ok(None),
+    }
+}
+
+impl Vault {
+    /// Current and next code for a base32 secret or `otpauth://` URI. An
+    /// HOTP URI only previews the codes at its own counter; use
+    /// `entry_totp` to generate codes that advance the entry's counter.
+    pub fn totp_token(&mut self, token: &str, secret: &str) -> Result<TotpCode, String> {
+        self.validate_session(token)?;
+        let params = parse_totp(secret)?;
+        match params.kind {
+            OtpKind::Hotp => Ok(params.hotp_codes(params.counter)),
+            OtpKind::Totp | OtpKind::Steam => Ok(params.codes_at(unix_now()?)),
+        }
+    }
+
+    /// Current and next code of an entry of the active profile, decrypting
+    /// its TOTP secret in the backend. For an HOTP entry every call uses up
+    /// one counter value.
+    pub fn entry_totp(&mut self, token: &str, id: i64) -> Result<TotpCode, String> {
+        let (key, active_profile) = self.session_context(token)?;
+        let entry = self
+            .store
+            .find_entry(id)?
+            .filter(|e| e.profile_id == active_profile && e.deleted_at.is_none())
+            .ok_or("Entry not found or belongs to different profile")?;
+        let params = match entry_params(&key, &entry)? {
+            Some((_, params)) => params,
+            None => return Err("Entry has no TOTP secret".to_string()),
+        };
+        if params.kind != OtpKind::Hotp {
+            return Ok(params.codes_at(unix_now()?));
+        }
+
+        let entry_uuid = entry.entry_uuid.ok_or("Entry has no sync UUID")?;
+        let stored = self
+            .store
+            .find_hotp_counter(&entry_uuid)?
+            .map_or(0, |c| c.counter.max(0) as u64);
+        let counter = stored.max(params.counter);
+        let next = i64::try_from(counter + 1).map_err(|_| "HOTP counter overflow".to_string())?;
+        self.store.merge_hotp_counter(&HotpCounterRecord {
+            entry_uuid,
+            counter: next,
+            updated_at: now_iso(),
+        })?;
+        Ok(params.hotp_codes(counter))
+    }
+
+    /// Codes of every TOTP and Steam entry in `profile_id` (default: the
+    /// active profile), by title. HOTP entries are left out because each
+    /// code uses up a counter value, as are entries whose stored secret no
+    /// longer parses rather than failing the whole batch.
+    pub fn profile_totp_codes(
+        &mut self,
+        token: &str,
+        profile_id: Option<i64>,
+    ) -> Result<Vec<EntryTotp>, String> {
+        let (key, active_profile) = self.session_context(token)?;
+        let profile = profile_id.unwrap_or(active_profile);
+        if !self.store.list_profiles()?.iter().any(|p| p.id == profile) {
+            return Err("Profile not found".to_string());
+        }
+
+        // One timestamp for the batch so all countdowns agree
+        let now = unix_now()?;
+        let mut codes = Vec::new();
+        for entry in self.store.list_entries(profile)? {
+            if let Ok(Some((title, params))) = entry_params(&key, &entry) {
+                if params.kind == OtpKind::Hotp {
+                    continue;
+                }
+                codes.push(EntryTotp {
+                    id: entry.id,
+                    entry_uuid: entry.entry_uuid.clone(),
+                    title,
+                    totp: params.codes_at(now),
+                });
+            }
+        }
+        codes.sort_by_key(|c| c.title.to_lowercase());
+        Ok(codes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 6238 test secrets: the ASCII digits 1234567890 repeated to the
+    // key length of each algorithm
+    fn rfc_params(algorithm: OtpAlgorithm, key: &[u8]) -> TotpParams {
+        TotpParams {
+            kind: OtpKind::Totp,
+            secret: key.to_vec(),
+            algorithm,
+            digits: 8,
+            period: 30,
+            counter: 0,
+            issuer: None,
+            label: None,
+        }
+    }
+
+    #[test]
+    fn test_rfc6238_vectors() {
+        let sha1 = rfc_params(OtpAlgorithm::Sha1, b"12345678901234567890");
+        let sha256 = rfc_params(OtpAlgorithm::Sha256, b"12345678901234567890123456789012");
+        let sha512 = rfc_params(
+            OtpAlgorithm::Sha512,
+            b"1234567890123456789012345678901234567890123456789012345678901234",
+        );
+        assert_eq!(sha1.code_at(59), "94287082");
+        assert_eq!(sha256.code_at(59), "46119246");
+        assert_eq!(sha512.code_at(59), "90693936");
+        assert_eq!(sha1.code_at(1111111109), "07081804");
+        assert_eq!(sha256.code_at(1111111109), "68084774");
+        assert_eq!(sha512.code_at(1111111109), "25091201");
+    }
+
+    #[test]
+    fn test_rfc4226_hotp_vectors() {
+        let mut params = rfc_params(OtpAlgorithm::Sha1, b"12345678901234567890");
+        params.kind = OtpKind::Hotp;
+        params.digits = 6;
+        let expected = ["755224", "287082", "359152", "969429", "338314", "254676"];
+        for (counter, code) in expected.iter().enumerate() {
+            assert_eq!(params.hotp_code(counter as u64), *code);
+        }
+        let codes = params.hotp_codes(4);
+        assert_eq!((codes.code.as_str(), codes.next_code.as_str()), ("338314", "254676"));
+        assert_eq!((codes.counter, codes.remaining_seconds), (Some(4), 0));
+    }
+
+    #[test]
+    fn test_parse_hotp_and_steam() {
+        let hotp = parse_totp("otpauth://hotp/Bank:alice?secret=JBSWY3DPEHPK3PXP&counter=42").unwrap();
+        assert_eq!((hotp.kind, hotp.counter), (OtpKind::Hotp, 42));
+        assert_eq!(parse_totp(&hotp.to_uri()).unwrap(), hotp);
+        assert!(parse_totp("otpauth://hotp/x?secret=JBSWY3DPEHPK3PXP&counter=-1").is_err());
+
+        for input in [
+            "steam://JBSWY3DPEHPK3PXP",
+            "otpauth://totp/Steam:alice?secret=JBSWY3DPEHPK3PXP&encoder=steam",
+            "otpauth://steam/Steam:alice?secret=JBSWY3DPEHPK3PXP",
+        ] {
+            let steam = parse_totp(input).unwrap();
+            assert_eq!((steam.kind, steam.digits, steam.period), (OtpKind::Steam, 5, 30));
+            assert_eq!(steam.issuer.as_deref(), Some("Steam"));
+            let code = steam.code_at(1_700_000_000);
+            assert_eq!(code.len(), 5);
+            assert!(code.chars().all(|c| "23456789BCDFGHJKMNPQRTVWXY".contains(c)), "{}", code);
+            assert_eq!(parse_totp(&steam.to_uri()).unwrap().kind, OtpKind::Steam);
+        }
+    }
+
+    #[test]
+    fn test_parse_otpauth_uri() {
+        let params = parse_totp(
+            "otpauth://totp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ\
+             &issuer=ACME+Co&algorithm=SHA256&digits=8&period=60",
+        )
+        .unwrap();
+        assert_eq!(params.algorithm, OtpAlgorithm::Sha256);
+        assert_eq!(params.digits, 8);
+        assert_eq!(params.period, 60);
+        assert_eq!(params.issuer.as_deref(), Some("ACME Co"));
+        assert_eq!(params.label.as_deref(), Some("john.doe@email.com"));
+        assert_eq!(parse_totp(&params.to_uri()).unwrap(), params);
+
+        let bare = parse_totp("jbsw y3dp ehpk 3pxp").unwrap();
+        assert_eq!((bare.digits, bare.period), (6, 30));
+        assert_eq!(bare.algorithm, OtpAlgorithm::Sha1);
+
+        let prefix_only = parse_totp("otpauth://totp/GitHub:alice?secret=JBSWY3DPEHPK3PXP").unwrap();
+        assert_eq!(prefix_only.issuer.as_deref(), Some("GitHub"));
+
+        for invalid in [
+            "otpauth://totp/x?secret=JBSWY3DPEHPK3PXP&algorithm=MD5",
+            "otpauth://totp/x?secret=JBSWY3DPEHPK3PXP&digits=12",
+            "otpauth://totp/x?secret=JBSWY3DPEHPK3PXP&period=0",
+            "otpauth://totp/x?issuer=NoSecret",
+            "otpauth://totp/x?secret=not!base32",
+            "otpauth://totp/%ZZ?secret=JBSWY3DPEHPK3PXP",
+        ] {
+            assert!(parse_totp(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_remaining_and_next_code() {
+        let mut params = parse_totp("JBSWY3DPEHPK3PXP").unwrap();
+        params.period = 60;
+        let codes = params.codes_at(120 + 45);
+        assert_eq!(codes.remaining_seconds, 15);
+        assert_eq!(codes.code, params.code_at(120));
+        assert_eq!(codes.next_code, params.code_at(180));
+        assert_ne!(codes.code, codes.next_code);
+    }
+}
@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+
+use crate::attachments::delete_entry_attachments;
+use crate::item::VaultItem;
+use crate::vault::{decrypt_item, now_iso};
+use crate::Vault;
+
+/// A soft-deleted entry as shown in the trash view
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeletedItem {
+    pub id: i64,
+    pub entry_uuid: Option<String>,
+    pub deleted_at: String,
+    pub item: VaultItem,
+}
+
+impl Vault {
+    /// Tombstoned entries of the active profile, most recently deleted first.
+    /// They stay restorable until purged (automatically after 90 days).
+    pub fn list_deleted_entries(&mut self, token: &str) -> Result<Vec<DeletedItem>, String> {
+        let (key, active_profile) = self.session_context(token)?;
+
+        self.store
+            .list_deleted_entries(active_profile)?
+            .iter()
+            .map(|entry| {
+                Ok(DeletedItem {
+                    id: entry.id,
+                    entry_uuid: entry.entry_uuid.clone(),
+                    deleted_at: entry.deleted_at.clone().unwrap_or_default(),
+                    item: decrypt_item(&key, entry)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Bring a deleted entry of the active profile back. The sync version is
+    /// bumped so the restore wins over the tombstone on other devices.
+    pub fn restore_entry(&mut self, token: &str, id: i64) -> Result<(), String> {
+        let (key, active_profile) = self.session_context(token)?;
+
+        let restored = self.store.restore_entry(id, active_profile, &now_iso())?;
+        if !restored {
+            return Err("Deleted entry not found or belongs to different profile".to_string());
+        }
+
+        self.record_local_revision(id)?;
+        self.reindex_entry(&key, id)
+    }
+
+    /// Permanently remove a deleted entry of the active profile with its
+    /// revisions, attachments, usage statistics and favorite flag. Devices
+    /// that have not yet synced the tombstone keep their copy.
+    pub fn purge_entry(&mut self, token: &str, id: i64) -> Result<(), String> {
+        let (_key, active_profile) = self.session_context(token)?;
+
+        let entry_uuid = self
+            .store
+            .list_deleted_entries(active_profile)?
+            .into_iter()
+            .find(|e| e.id == id)
+            .ok_or("Deleted entry not found or belongs to different profile")?
+            .entry_uuid;
+
+        self.store.purge_entry(id, active_profile)?;
+        if let Some(index) = self.search_index.as_mut() {
+            index.remove(id);
+        }
+
+        if let Some(entry_uuid) = entry_uuid {
+            let revision_ids: Vec<i64> = self
+                .store
+                .list_revisions(&entry_uuid)?
+                .iter()
+                .map(|r| r.id)
+                .collect();
+            self.store.delete_revisions(&revision_ids)?;
+            delete_entry_attachments(self.store.as_mut(), &entry_uuid)?;
+            self.store.delete_entry_usage(&entry_uuid)?;
+            self.store.delete_favorite(&entry_uuid)?;
+            self.store.delete_hotp_counter(&entry_uuid)?;
+        }
+
+        Ok(())
+    }
+}
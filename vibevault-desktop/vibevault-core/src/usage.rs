@@ -0,0 +1,143 @@
+// Entry usage statistics and favorites.
+//
+// Neither is part of the encrypted entry blob: opening or starring an entry
+// must not bump its `sync_version`, or every password copied on two devices
+// would come back from the next sync as a conflict. Each device counts its
+// own uses in a row of its own, so merging is a per-device maximum, and
+// favorites are a last-write-wins flag per entry. Both travel in their own
+// lists of the sync payload.
+
+use std::collections::HashMap;
+
+use chrono::DateTime;
+
+use crate::listing::{EntrySort, EntrySummary};
+use crate::revisions::local_device_id;
+use crate::store::{FavoriteRecord, VaultStore};
+use crate::vault::now_iso;
+use crate::Vault;
+
+/// Uses of an entry on one device closer together than this count once,
+/// so a TOTP code refreshing on screen or a reveal followed by a copy does
+/// not inflate the count
+pub const USE_COALESCE_SECONDS: i64 = 60;
+
+/// Largest `limit` accepted by `recently_used_entries`
+pub const MAX_RECENT_ENTRIES: usize = 100;
+
+/// Usage of one entry summed over all devices
+#[derive(Debug, Clone, Default)]
+pub(crate) struct EntryUsage {
+    pub last_used_at: Option<String>,
+    pub use_count: i64,
+}
+
+/// Usage per entry UUID, summed over all devices
+pub(crate) fn usage_by_entry(
+    db: &dyn VaultStore,
+) -> Result<HashMap<String, EntryUsage>, String> {
+    let mut usage: HashMap<String, EntryUsage> = HashMap::new();
+    for record in db.list_entry_usage()? {
+        let total = usage.entry(record.entry_uuid).or_default();
+        total.use_count += record.use_count;
+        if total
+            .last_used_at
+            .as_ref()
+            .is_none_or(|last| record.last_used_at > *last)
+        {
+            total.last_used_at = Some(record.last_used_at);
+        }
+    }
+    Ok(usage)
+}
+
+/// Entry UUIDs that are currently marked as favorite
+pub(crate) fn favorite_uuids(db: &dyn VaultStore) -> Result<Vec<String>, String> {
+    Ok(db
+        .list_favorites()?
+        .into_iter()
+        .filter(|f| f.favorite)
+        .map(|f| f.entry_uuid)
+        .collect())
+}
+
+fn seconds_between(earlier: &str, later: &str) -> Option<i64> {
+    let earlier = DateTime::parse_from_rfc3339(earlier).ok()?;
+    let later = DateTime::parse_from_rfc3339(later).ok()?;
+    Some((later - earlier).num_seconds())
+}
+
+impl Vault {
+    /// Count a use of an entry of the active profile: its secret was
+    /// revealed or copied, or a TOTP code was generated for it
+    pub fn record_entry_use(&mut self, token: &str, id: i64) -> Result<(), String> {
+        let (_key, active_profile) = self.session_context(token)?;
+        let entry_uuid = self.active_entry_uuid(active_profile, id)?;
+        let device_id = local_device_id(self.store.as_mut())?;
+        let now = now_iso();
+
+        let previous = self
+            .store
+            .list_entry_usage()?
+            .into_iter()
+            .find(|u| u.entry_uuid == entry_uuid && u.device_id == device_id);
+        match previous {
+            Some(mut usage)
+                if seconds_between(&usage.last_used_at, &now)
+                    .is_some_and(|s| s < USE_COALESCE_SECONDS) =>
+            {
+                usage.last_used_at = now;
+                self.store.merge_entry_usage(&usage)
+            }
+            _ => self.store.record_entry_use(&entry_uuid, &device_id, &now),
+        }
+    }
+
+    /// Mark or unmark an entry of the active profile as favorite
+    pub fn set_favorite(&mut self, token: &str, id: i64, favorite: bool) -> Result<(), String> {
+        let (_key, active_profile) = self.session_context(token)?;
+        let entry_uuid = self.active_entry_uuid(active_profile, id)?;
+        self.store.put_favorite(&FavoriteRecord {
+            entry_uuid,
+            favorite,
+            updated_at: now_iso(),
+        })
+    }
+
+    /// The active profile's `limit` most recently used entries, newest first
+    pub fn recently_used_entries(
+        &mut self,
+        token: &str,
+        limit: usize,
+    ) -> Result<Vec<EntrySummary>, String> {
+        let (key, active_profile) = self.session_context(token)?;
+        if limit == 0 || limit > MAX_RECENT_ENTRIES {
+            return Err(format!("Limit must be between 1 and {}", MAX_RECENT_ENTRIES));
+        }
+        let mut summaries: Vec<EntrySummary> = self
+            .entry_summaries(&key, active_profile, EntrySort::LastUsed)?
+            .into_iter()
+            .filter(|s| s.last_used_at.is_some())
+            .collect();
+        summaries.truncate(limit);
+        Ok(summaries)
+    }
+
+    /// The active profile's favorite entries by title
+    pub fn favorite_entries(&mut self, token: &str) -> Result<Vec<EntrySummary>, String> {
+        let (key, active_profile) = self.session_context(token)?;
+        Ok(self
+            .entry_summaries(&key, active_profile, EntrySort::Name)?
+            .into_iter()
+            .filter(|s| s.favorite)
+            .collect())
+    }
+
+    fn active_entry_uuid(&self, active_profile: i64, id: i64) -> Result<String, String> {
+        self.store
+            .find_entry(id)?
+            .filter(|e| e.profile_id == active_profile && e.deleted_at.is_none())
+            .and_then(|e| e.entry_uuid)
+            .ok_or_else(|| "Entry not found or belongs to different profile".to_string())
+    }
+}
@@ -0,0 +1,366 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::Vault;
+use crate::crypto::{encrypt_aes256_gcm, decrypt_aes256_gcm};
+use crate::history::record_password_change;
+use crate::item::{StoredItem, VaultItem, VAULT_ITEM_VERSION};
+use crate::item_types::ItemType;
+use crate::revisions::{ensure_baseline_revision, local_device_id, record_revision};
+use crate::store::{EntryRecord, VaultStore};
+
+/// A decrypted vault entry as handed to the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultEntry {
+    pub id: i64,
+    pub uuid: String, // site name
+    pub data_blob: Vec<u8>,
+    pub entry_uuid: Option<String>,
+}
+
+/// Migrate plaintext entries to encrypted (called after unlock)
+pub fn migrate_plaintext_entries(db: &mut dyn VaultStore, key: &[u8; 32]) -> Result<(), String> {
+    let rows: Vec<EntryRecord> = db
+        .entries_updated_since(None)?
+        .into_iter()
+        .filter(|e| e.nonce.is_empty())
+        .collect();
+
+    for mut entry in rows {
+        let (ciphertext, new_nonce) = encrypt_aes256_gcm(key, &entry.data_blob)?;
+        entry.data_blob = ciphertext;
+        entry.nonce = new_nonce;
+        db.overwrite_entry(&entry)?;
+    }
+
+    Ok(())
+}
+
+/// Rewrite untyped blobs (pre-`VaultItem` JSON or free text) as versioned
+/// items. Runs after `migrate_plaintext_entries`; rows that cannot be
+/// decrypted with this key are left untouched. The sync version is not
+/// bumped: every device performs the same upgrade on its own copy.
+pub fn migrate_legacy_items(db: &mut dyn VaultStore, key: &[u8; 32]) -> Result<(), String> {
+    for mut entry in db.entries_updated_since(None)? {
+        if entry.nonce.is_empty() {
+            continue;
+        }
+        let plaintext = match decrypt_aes256_gcm(key, &entry.data_blob, &entry.nonce) {
+            Ok(p) => p,
+            Err(_) => continue,
+        };
+        let is_current = serde_json::from_slice::<serde_json::Value>(&plaintext)
+            .ok()
+            .and_then(|v| v.get("version").and_then(|v| v.as_u64()))
+            .is_some_and(|v| v >= VAULT_ITEM_VERSION as u64);
+        if is_current {
+            continue;
+        }
+        let item = match VaultItem::from_blob(&entry.uuid, &plaintext) {
+            Ok(item) => item,
+            Err(_) => continue,
+        };
+        let (ciphertext, nonce) = encrypt_aes256_gcm(key, &item.to_blob()?)?;
+        entry.data_blob = ciphertext;
+        entry.nonce = nonce;
+        db.overwrite_entry(&entry)?;
+    }
+
+    Ok(())
+}
+
+/// Decode a user-entered base32 TOTP secret (spaces, padding and case are ignored)
+pub fn decode_base32_secret(secret: &str) -> Result<Vec<u8>, String> {
+    let clean_secret = secret.replace(" ", "").replace("=", "").to_uppercase();
+    base32::decode(base32::Alphabet::RFC4648 { padding: false }, &clean_secret)
+        .filter(|bytes| !bytes.is_empty())
+        .ok_or_else(|| "Invalid Base32 Secret".to_string())
+}
+
+/// Decrypt a stored entry into its typed item
+pub(crate) fn decrypt_item(key: &[u8; 32], entry: &EntryRecord) -> Result<VaultItem, String> {
+    let plaintext = if entry.nonce.is_empty() {
+        entry.data_blob.clone()
+    } else {
+        decrypt_aes256_gcm(key, &entry.data_blob, &entry.nonce)?
+    };
+    VaultItem::from_blob(&entry.uuid, &plaintext)
+}
+
+pub(crate) fn now_iso() -> String {
+    chrono::Utc::now().to_rfc3339()
+}
+
+impl Vault {
+    /// Decrypt every active entry of the active profile
+    pub fn list_entries(&mut self, token: &str) -> Result<Vec<VaultEntry>, String> {
+        let (key, active_profile) = self.session_context(token)?;
+
+        let mut entries = Vec::new();
+        for entry in self.store.list_entries(active_profile)? {
+            // Decrypt: empty nonce = legacy plaintext fallback
+            let plaintext = if entry.nonce.is_empty() {
+                entry.data_blob
+            } else {
+                decrypt_aes256_gcm(&key, &entry.data_blob, &entry.nonce)?
+            };
+
+            entries.push(VaultEntry {
+                id: entry.id,
+                uuid: entry.uuid,
+                data_blob: plaintext,
+                entry_uuid: entry.entry_uuid,
+            });
+        }
+        Ok(entries)
+    }
+
+    /// Decrypt the active entries of the active profile whose item is of `item_type`
+    pub fn list_entries_of_type(
+        &mut self,
+        token: &str,
+        item_type: ItemType,
+    ) -> Result<Vec<VaultEntry>, String> {
+        let entries = self.list_entries(token)?;
+        Ok(entries
+            .into_iter()
+            .filter(|entry| {
+                VaultItem::from_blob(&entry.uuid, &entry.data_blob)
+                    .is_ok_and(|item| item.item_type == item_type)
+            })
+            .collect())
+    }
+
+    /// Encrypt and store a new entry in `profile_id` (default: the active profile).
+    /// Returns the new entry's sync UUID.
+    pub fn save_entry(
+        &mut self,
+        token: &str,
+        uuid: &str,
+        blob: &[u8],
+        profile_id: Option<i64>,
+    ) -> Result<String, String> {
+        let (key, active_profile) = self.session_context(token)?;
+        let target_profile = profile_id.unwrap_or(active_profile);
+
+        let (ciphertext, nonce) = encrypt_aes256_gcm(&key, blob)?;
+        let entry_uuid = Uuid::new_v4().to_string();
+        let now = now_iso();
+
+        let mut record = EntryRecord {
+            id: 0,
+            uuid: uuid.to_string(),
+            data_blob: ciphertext,
+            nonce,
+            profile_id: target_profile,
+            entry_uuid: Some(entry_uuid.clone()),
+            created_at: now.clone(),
+            updated_at: now,
+            deleted_at: None,
+            sync_version: 1,
+        };
+        record.id = self.store.insert_entry(&record)?;
+
+        let device_id = local_device_id(self.store.as_mut())?;
+        record_revision(self.store.as_mut(), &record, &device_id)?;
+        self.reindex_entry(&key, record.id)?;
+
+        Ok(entry_uuid)
+    }
+
+    /// Re-encrypt an entry of the active profile with new content
+    pub fn update_entry(
+        &mut self,
+        token: &str,
+        id: i64,
+        uuid: &str,
+        blob: &[u8],
+    ) -> Result<(), String> {
+        let (key, active_profile) = self.session_context(token)?;
+        let now = now_iso();
+
+        // Keep what a legacy blob leaves out, and the replaced password in
+        // the item's history
+        let blob = self.merge_with_stored_item(&key, active_profile, id, uuid, blob, &now)?;
+
+        self.write_entry_content(&key, active_profile, id, uuid, &blob, &now)
+    }
+
+    /// Encrypt `blob` over an entry of `profile_id`, bumping its sync version
+    pub(crate) fn write_entry_content(
+        &mut self,
+        key: &[u8; 32],
+        profile_id: i64,
+        id: i64,
+        uuid: &str,
+        blob: &[u8],
+        now: &str,
+    ) -> Result<(), String> {
+        let (ciphertext, nonce) = encrypt_aes256_gcm(key, blob)?;
+
+        if let Some(current) = self.store.find_entry(id)? {
+            ensure_baseline_revision(self.store.as_mut(), &current)?;
+        }
+
+        // Update entry, bump sync_version, update timestamp
+        let updated = self
+            .store
+            .update_entry_content(id, profile_id, uuid, &ciphertext, &nonce, now)?;
+
+        if !updated {
+            return Err("Entry not found or belongs to different profile".to_string());
+        }
+
+        self.record_local_revision(id)?;
+        self.reindex_entry(key, id)
+    }
+
+    /// Snapshot entry `id` as just changed on this device
+    pub(crate) fn record_local_revision(&mut self, id: i64) -> Result<(), String> {
+        let device_id = local_device_id(self.store.as_mut())?;
+        if let Some(entry) = self.store.find_entry(id)? {
+            record_revision(self.store.as_mut(), &entry, &device_id)?;
+        }
+        Ok(())
+    }
+
+    /// Complete a new blob from the stored item. A legacy blob (no
+    /// `version`, e.g. the editor's `{username, password, totpSecret,
+    /// notes}`) only replaces the fields it carries; URLs, custom fields,
+    /// tags, folder, recovery codes and the typed sections of the stored
+    /// item are kept, and its title follows `uuid`. Either way the replaced
+    /// password goes to the item's history. Blobs that are not JSON items
+    /// (or entries that cannot be found) pass through.
+    fn merge_with_stored_item(
+        &self,
+        key: &[u8; 32],
+        profile_id: i64,
+        id: i64,
+        uuid: &str,
+        blob: &[u8],
+        now: &str,
+    ) -> Result<Vec<u8>, String> {
+        let fields = match serde_json::from_slice::<serde_json::Value>(blob) {
+            Ok(serde_json::Value::Object(fields)) => fields,
+            _ => return Ok(blob.to_vec()),
+        };
+        let old_item = match self
+            .store
+            .list_entries(profile_id)?
+            .into_iter()
+            .find(|e| e.id == id)
+            .and_then(|entry| decrypt_item(key, &entry).ok())
+        {
+            Some(item) => item,
+            None => return Ok(blob.to_vec()),
+        };
+
+        let legacy = !fields.contains_key("version");
+        let new_item = if legacy {
+            let mut merged = match serde_json::to_value(&old_item) {
+                Ok(serde_json::Value::Object(merged)) => merged,
+                _ => return Ok(blob.to_vec()),
+            };
+            merged.extend(fields);
+            if !uuid.trim().is_empty() {
+                merged.insert("title".to_string(), uuid.trim().into());
+            }
+            serde_json::from_value::<VaultItem>(serde_json::Value::Object(merged))
+        } else {
+            serde_json::from_value::<VaultItem>(serde_json::Value::Object(fields))
+        };
+        let mut new_item = match new_item {
+            Ok(item) => item,
+            Err(_) => return Ok(blob.to_vec()),
+        };
+
+        record_password_change(&old_item, &mut new_item, now, self.password_history_depth);
+        if !legacy && new_item.password_history.is_empty() && old_item.password_history.is_empty() {
+            return Ok(blob.to_vec());
+        }
+        new_item.to_blob()
+    }
+
+    /// Soft-delete an entry of the active profile
+    pub fn delete_entry(&mut self, token: &str, id: i64) -> Result<(), String> {
+        let (key, active_profile) = self.session_context(token)?;
+
+        let now = now_iso();
+
+        if let Some(current) = self.store.find_entry(id)? {
+            ensure_baseline_revision(self.store.as_mut(), &current)?;
+        }
+
+        // Soft delete: set deleted_at timestamp instead of removing the row
+        let deleted = self.store.soft_delete_entry(id, active_profile, &now)?;
+
+        if !deleted {
+            return Err("Entry not found or belongs to different profile".to_string());
+        }
+
+        self.record_local_revision(id)?;
+        self.reindex_entry(&key, id)
+    }
+
+    /// Decrypt every active entry of the active profile as typed items,
+    /// optionally keeping only one item type
+    pub fn list_items(
+        &mut self,
+        token: &str,
+        item_type: Option<ItemType>,
+    ) -> Result<Vec<StoredItem>, String> {
+        let (key, active_profile) = self.session_context(token)?;
+
+        let mut items = Vec::new();
+        for entry in self.store.list_entries(active_profile)? {
+            let stored = to_stored_item(&key, &entry)?;
+            if item_type.is_none_or(|t| stored.item.item_type == t) {
+                items.push(stored);
+            }
+        }
+        Ok(items)
+    }
+
+    /// Decrypt one active entry of the active profile
+    pub fn get_item(&mut self, token: &str, id: i64) -> Result<StoredItem, String> {
+        let (key, active_profile) = self.session_context(token)?;
+
+        let entry = self
+            .store
+            .list_entries(active_profile)?
+            .into_iter()
+            .find(|e| e.id == id)
+            .ok_or("Entry not found or belongs to different profile")?;
+        to_stored_item(&key, &entry)
+    }
+
+    /// Validate, encrypt and store a new typed item. Returns the new entry's sync UUID.
+    pub fn save_item(
+        &mut self,
+        token: &str,
+        item: &VaultItem,
+        profile_id: Option<i64>,
+    ) -> Result<String, String> {
+        item.validate()?;
+        let blob = item.to_blob()?;
+        self.save_entry(token, item.title.trim(), &blob, profile_id)
+    }
+
+    /// Validate and replace the content of an existing entry with a typed item
+    pub fn update_item(&mut self, token: &str, id: i64, item: &VaultItem) -> Result<(), String> {
+        item.validate()?;
+        let blob = item.to_blob()?;
+        self.update_entry(token, id, item.title.trim(), &blob)
+    }
+}
+
+pub(crate) fn to_stored_item(key: &[u8; 32], entry: &EntryRecord) -> Result<StoredItem, String> {
+    Ok(StoredItem {
+        id: entry.id,
+        entry_uuid: entry.entry_uuid.clone(),
+        profile_id: entry.profile_id,
+        created_at: entry.created_at.clone(),
+        updated_at: entry.updated_at.clone(),
+        item: decrypt_item(key, entry)?,
+    })
+}
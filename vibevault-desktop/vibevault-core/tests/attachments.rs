@@ -0,0 +1,107 @@
+use vibevault_core::attachments::MAX_ATTACHMENT_SIZE;
+use vibevault_core::{DatabaseManager, Vault};
+
+mod common;
+
+use common::{save, unlocked_vault_on, PASS, USER};
+
+fn unlocked_vault() -> (Vault, String, i64) {
+    let (mut vault, token) =
+        unlocked_vault_on(Box::new(DatabaseManager::open_in_memory().unwrap()));
+    let id = save(&mut vault, &token, "GitHub");
+    (vault, token, id)
+}
+
+fn upload(vault: &mut Vault, token: &str, id: i64, name: &str, content: &[u8]) -> i64 {
+    let upload_id = vault
+        .begin_attachment_upload(token, id, name, Some("application/pdf"), content.len() as u64)
+        .unwrap();
+    for chunk in content.chunks(4) {
+        vault.upload_attachment_chunk(token, &upload_id, chunk).unwrap();
+    }
+    vault.finish_attachment_upload(token, &upload_id).unwrap().id
+}
+
+fn download(vault: &mut Vault, token: &str, attachment_id: i64, chunks: u32) -> Vec<u8> {
+    (0..chunks)
+        .flat_map(|i| vault.read_attachment_chunk(token, attachment_id, i).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_upload_download_and_dedup() {
+    let (mut vault, token, id) = unlocked_vault();
+    let content = b"recovery codes: 1234-5678 8765-4321";
+
+    let first = upload(&mut vault, &token, id, "codes.pdf", content);
+    let copy = upload(&mut vault, &token, id, "codes (copy).pdf", content);
+
+    let attachments = vault.list_attachments(&token, id).unwrap();
+    assert_eq!(attachments.len(), 2);
+    assert_eq!(attachments[0].file_name, "codes.pdf");
+    assert_eq!(attachments[0].size, content.len() as u64);
+    assert_eq!(attachments[0].chunk_count, 9);
+    assert_eq!(download(&mut vault, &token, first, 9), content);
+
+    // Identical content is stored once
+    assert_eq!(vault.store().attachment_blob_ids().unwrap().len(), 1);
+
+    // Deleting one copy keeps the shared content for the other
+    vault.delete_attachment(&token, first).unwrap();
+    assert_eq!(download(&mut vault, &token, copy, 9), content);
+    vault.delete_attachment(&token, copy).unwrap();
+    assert!(vault.store().attachment_blob_ids().unwrap().is_empty());
+}
+
+#[test]
+fn test_limits_and_incomplete_uploads() {
+    let (mut vault, token, id) = unlocked_vault();
+
+    assert!(vault
+        .begin_attachment_upload(&token, id, "huge.bin", None, MAX_ATTACHMENT_SIZE + 1)
+        .is_err());
+    assert!(vault.begin_attachment_upload(&token, id, "  ", None, 1).is_err());
+
+    let upload_id = vault
+        .begin_attachment_upload(&token, id, "key.txt", None, 4)
+        .unwrap();
+    assert!(vault
+        .upload_attachment_chunk(&token, &upload_id, b"too long")
+        .is_err());
+    vault.upload_attachment_chunk(&token, &upload_id, b"ab").unwrap();
+    assert!(vault.finish_attachment_upload(&token, &upload_id).is_err());
+    assert!(vault.store().attachment_blob_ids().unwrap().is_empty());
+    assert!(vault.list_attachments(&token, id).unwrap().is_empty());
+}
+
+#[test]
+fn test_attachments_removed_with_entry() {
+    let (mut vault, token, id) = unlocked_vault();
+    let attachment = upload(&mut vault, &token, id, "license.key", b"ABCD-EFGH");
+
+    // Soft delete hides the attachment but restore brings it back
+    vault.delete_entry(&token, id).unwrap();
+    assert!(vault.read_attachment_chunk(&token, attachment, 0).is_err());
+    vault.restore_entry(&token, id).unwrap();
+    assert_eq!(vault.read_attachment_chunk(&token, attachment, 0).unwrap(), b"ABCD");
+
+    vault.delete_entry(&token, id).unwrap();
+    vault.purge_entry(&token, id).unwrap();
+    assert!(vault.store().list_all_attachments().unwrap().is_empty());
+    assert!(vault.store().attachment_blob_ids().unwrap().is_empty());
+}
+
+#[test]
+fn test_abandoned_uploads_cleaned_at_unlock() {
+    let (mut vault, token, id) = unlocked_vault();
+    let upload_id = vault
+        .begin_attachment_upload(&token, id, "scan.pdf", None, 8)
+        .unwrap();
+    vault.upload_attachment_chunk(&token, &upload_id, b"half").unwrap();
+    assert_eq!(vault.store().attachment_blob_ids().unwrap().len(), 1);
+
+    vault.lock();
+    let token = vault.unlock(USER, PASS).unwrap();
+    assert!(vault.store().attachment_blob_ids().unwrap().is_empty());
+    assert!(vault.upload_attachment_chunk(&token, &upload_id, b"rest").is_err());
+}
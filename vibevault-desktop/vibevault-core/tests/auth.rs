@@ -0,0 +1,136 @@
+use vibevault_core::store::EntryRecord;
+use vibevault_core::{
+    DatabaseManager, MemoryStore, PasswordOptions, UnreadableRow, VaultItem, VaultStore,
+};
+
+mod common;
+
+use common::{unlocked_vault_on, PASS, USER};
+
+const NEW_PASS: &str = "violet stapler orbit marmalade";
+
+#[test]
+fn test_change_master_password_reencrypts_vault() {
+    let (mut vault, token) =
+        unlocked_vault_on(Box::new(DatabaseManager::open_in_memory().unwrap()));
+
+    let folder = vault.create_folder(&token, "Work", None).unwrap();
+    let mut item = VaultItem::new("GitHub");
+    item.password = Some("hunter2".to_string());
+    item.folder_id = Some(folder.clone());
+    vault.save_item(&token, &item, None).unwrap();
+    let id = vault.list_items(&token, None).unwrap()[0].id;
+    item.password = Some("tV8$nB4^cJ6&hG1@".to_string());
+    vault.update_item(&token, id, &item).unwrap();
+    let generated = vault
+        .generate_password(&token, &PasswordOptions::default())
+        .unwrap();
+
+    let content = b"recovery codes: 1234-5678";
+    let upload_id = vault
+        .begin_attachment_upload(&token, id, "codes.txt", None, content.len() as u64)
+        .unwrap();
+    for chunk in content.chunks(8) {
+        vault.upload_attachment_chunk(&token, &upload_id, chunk).unwrap();
+    }
+    let attachment = vault.finish_attachment_upload(&token, &upload_id).unwrap();
+
+    vault
+        .change_master_password(&token, USER, PASS, NEW_PASS)
+        .unwrap();
+    // The open session already uses the new key
+    assert_eq!(vault.get_item(&token, id).unwrap().item.title, "GitHub");
+
+    vault.lock();
+    assert!(vault.unlock(USER, PASS).is_err());
+    let token = vault.unlock(USER, NEW_PASS).unwrap();
+
+    let stored = vault.get_item(&token, id).unwrap().item;
+    assert_eq!(stored.password.as_deref(), Some("tV8$nB4^cJ6&hG1@"));
+    assert_eq!(stored.folder_id.as_deref(), Some(folder.as_str()));
+    assert_eq!(vault.list_folders(&token).unwrap()[0].name, "Work");
+    assert_eq!(vault.entry_revisions(&token, id).unwrap().len(), 2);
+    assert_eq!(
+        vault.generator_history(&token).unwrap()[0].value,
+        generated.value
+    );
+    assert_eq!(vault.list_attachments(&token, id).unwrap()[0].file_name, "codes.txt");
+    let downloaded: Vec<u8> = (0..attachment.chunk_count)
+        .flat_map(|i| vault.read_attachment_chunk(&token, attachment.id, i).unwrap())
+        .collect();
+    assert_eq!(downloaded, content);
+
+    // The dedup hashes follow the new key, so identical files still share a blob
+    let upload_id = vault
+        .begin_attachment_upload(&token, id, "copy.txt", None, content.len() as u64)
+        .unwrap();
+    vault.upload_attachment_chunk(&token, &upload_id, content).unwrap();
+    vault.finish_attachment_upload(&token, &upload_id).unwrap();
+    assert_eq!(vault.store().attachment_blob_ids().unwrap().len(), 1);
+}
+
+#[test]
+fn test_change_master_password_rejects_weak_or_wrong_password() {
+    let (mut vault, token) =
+        unlocked_vault_on(Box::new(DatabaseManager::open_in_memory().unwrap()));
+    vault.save_item(&token, &VaultItem::new("GitHub"), None).unwrap();
+
+    // The new password must meet the master password policy
+    assert!(vault
+        .change_master_password(&token, USER, PASS, "password1234")
+        .is_err());
+    assert!(vault.change_master_password(&token, USER, PASS, "").is_err());
+    // And the current one must be right
+    assert!(vault
+        .change_master_password(&token, USER, "wrong", NEW_PASS)
+        .is_err());
+
+    vault.lock();
+    assert!(vault.unlock(USER, NEW_PASS).is_err());
+    let token = vault.unlock(USER, PASS).unwrap();
+    assert_eq!(vault.list_items(&token, None).unwrap().len(), 1);
+}
+
+#[test]
+fn test_change_master_password_skips_unreadable_rows() {
+    let mut store = MemoryStore::new();
+    // A row that does not decrypt with the vault key
+    let broken = store
+        .insert_entry(&EntryRecord {
+            id: 0,
+            uuid: "broken".to_string(),
+            data_blob: vec![7; 32],
+            nonce: vec![0; 12],
+            profile_id: 1,
+            entry_uuid: Some("e-broken".to_string()),
+            created_at: "2024-01-01T00:00:00+00:00".to_string(),
+            updated_at: "2024-01-01T00:00:00+00:00".to_string(),
+            deleted_at: None,
+            sync_version: 1,
+        })
+        .unwrap();
+    store
+        .add_paired_device("Phone", "device-1", &[1; 32], &[2; 32])
+        .unwrap();
+    let (mut vault, token) = unlocked_vault_on(Box::new(store));
+    vault.save_item(&token, &VaultItem::new("GitHub"), None).unwrap();
+    let id = vault
+        .store()
+        .list_entries(1)
+        .unwrap()
+        .into_iter()
+        .find(|entry| entry.id != broken)
+        .unwrap()
+        .id;
+
+    let report = vault
+        .change_master_password(&token, USER, PASS, NEW_PASS)
+        .unwrap();
+    assert_eq!(report.skipped, vec![UnreadableRow::Entry(broken)]);
+    assert_eq!(report.forgotten_devices, vec!["Phone".to_string()]);
+    assert!(vault.store().list_paired_devices().unwrap().is_empty());
+
+    vault.lock();
+    let token = vault.unlock(USER, NEW_PASS).unwrap();
+    assert_eq!(vault.get_item(&token, id).unwrap().item.title, "GitHub");
+}
@@ -0,0 +1,60 @@
+use std::fs;
+
+use sha1::{Digest, Sha1};
+use vibevault_core::breach::build_breach_index;
+use vibevault_core::health::HealthScope;
+use vibevault_core::VaultItem;
+
+mod common;
+
+use common::unlocked_vault;
+
+#[test]
+fn test_breach_report() {
+    let dir = std::env::temp_dir().join(format!("vibevault-breach-{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    let mut hashes: Vec<String> = [("hunter2", 17_000), ("letmein", 900_000)]
+        .iter()
+        .map(|(p, c)| format!("{}:{}", hex::encode_upper(Sha1::digest(p.as_bytes())), c))
+        .collect();
+    hashes.sort();
+    fs::write(dir.join("pwned.txt"), hashes.join("\n")).unwrap();
+    let index = dir.join("hibp.idx");
+    build_breach_index(&dir.join("pwned.txt"), &index).unwrap();
+
+    let (mut vault, token) = unlocked_vault();
+    let work = vault.create_profile(&token, "Work").unwrap();
+    for (title, password, profile) in [
+        ("Forum", "hunter2", None),
+        ("Bank", "tV8$nB4^cJ6&hG1@", None),
+        ("Wiki", "letmein", Some(work)),
+    ] {
+        let mut item = VaultItem::new(title);
+        item.password = Some(password.to_string());
+        vault.save_item(&token, &item, profile).unwrap();
+    }
+    vault
+        .save_item(&token, &VaultItem::new("No password"), None)
+        .unwrap();
+
+    let report = vault
+        .breach_report(&token, &index, HealthScope::ActiveProfile)
+        .unwrap();
+    assert_eq!(report.passwords_checked, 2);
+    assert_eq!(report.breached.len(), 1);
+    assert_eq!(report.breached[0].entry.title, "Forum");
+    assert_eq!(report.breached[0].count, 17_000);
+    assert!(!serde_json::to_string(&report).unwrap().contains("hunter2"));
+
+    let all = vault
+        .breach_report(&token, &index, HealthScope::AllProfiles)
+        .unwrap();
+    let titles: Vec<&str> = all.breached.iter().map(|b| b.entry.title.as_str()).collect();
+    assert_eq!(titles, ["Wiki", "Forum"]);
+
+    vault.lock();
+    assert!(vault
+        .breach_report(&token, &index, HealthScope::ActiveProfile)
+        .is_err());
+    fs::remove_dir_all(dir).unwrap();
+}
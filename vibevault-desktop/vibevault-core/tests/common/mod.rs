@@ -0,0 +1,33 @@
+// Fixtures shared by the integration tests. Each test crate uses only
+// some of them.
+#![allow(dead_code)]
+
+use vibevault_core::{MemoryStore, Vault, VaultItem, VaultStore};
+
+pub const USER: &str = "alice";
+pub const PASS: &str = "correct horse battery staple";
+
+/// A registered and unlocked vault on a fresh in-memory store
+pub fn unlocked_vault() -> (Vault, String) {
+    unlocked_vault_on(Box::new(MemoryStore::new()))
+}
+
+/// A registered and unlocked vault on `store`
+pub fn unlocked_vault_on(store: Box<dyn VaultStore>) -> (Vault, String) {
+    let mut vault = Vault::new(store);
+    vault.register(USER, PASS).unwrap();
+    let token = vault.unlock(USER, PASS).unwrap();
+    (vault, token)
+}
+
+/// Save an empty item titled `title` and return its row ID
+pub fn save(vault: &mut Vault, token: &str, title: &str) -> i64 {
+    vault.save_item(token, &VaultItem::new(title), None).unwrap();
+    vault
+        .list_items(token, None)
+        .unwrap()
+        .into_iter()
+        .find(|s| s.item.title == title)
+        .unwrap()
+        .id
+}
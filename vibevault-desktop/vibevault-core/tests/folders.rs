@@ -0,0 +1,137 @@
+use vibevault_core::{MemoryStore, Vault};
+
+mod common;
+
+use common::{save, unlocked_vault, PASS, USER};
+
+#[test]
+fn test_folder_tree_operations() {
+    let (mut vault, token) = unlocked_vault();
+    let work = vault.create_folder(&token, "Work", None).unwrap();
+    let infra = vault.create_folder(&token, "Infra", Some(&work)).unwrap();
+    let aws = vault.create_folder(&token, "AWS", Some(&infra)).unwrap();
+
+    // Sibling names are unique, ignoring case
+    assert!(vault.create_folder(&token, "work", None).is_err());
+    assert!(vault.create_folder(&token, "work", Some(&work)).is_ok());
+
+    vault.rename_folder(&token, &infra, "Infrastructure").unwrap();
+    let folders = vault.list_folders(&token).unwrap();
+    let renamed = folders.iter().find(|f| f.id == infra).unwrap();
+    assert_eq!(renamed.name, "Infrastructure");
+    assert_eq!(renamed.parent_id.as_deref(), Some(work.as_str()));
+
+    // No cycles
+    assert!(vault.move_folder(&token, &work, Some(&aws)).is_err());
+    assert!(vault.move_folder(&token, &infra, Some(&infra)).is_err());
+    vault.move_folder(&token, &aws, None).unwrap();
+    let folders = vault.list_folders(&token).unwrap();
+    assert!(folders.iter().find(|f| f.id == aws).unwrap().parent_id.is_none());
+
+    // Names and structure are not stored in the clear
+    let records = vault.store().list_folders(1).unwrap();
+    assert!(records
+        .iter()
+        .all(|r| !String::from_utf8_lossy(&r.data_blob).contains("Infrastructure")));
+}
+
+#[test]
+fn test_delete_folder_keeps_entries() {
+    let (mut vault, token) = unlocked_vault();
+    let work = vault.create_folder(&token, "Work", None).unwrap();
+    let infra = vault.create_folder(&token, "Infra", Some(&work)).unwrap();
+    let deep = vault.create_folder(&token, "Deep", Some(&infra)).unwrap();
+
+    let grafana = save(&mut vault, &token, "Grafana");
+    let vpn = save(&mut vault, &token, "VPN");
+    vault.move_entries_to_folder(&token, &[grafana], Some(&deep)).unwrap();
+    vault.move_entries_to_folder(&token, &[vpn], Some(&infra)).unwrap();
+    assert!(vault.move_entries_to_folder(&token, &[vpn], Some("missing")).is_err());
+
+    vault.delete_folder(&token, &infra).unwrap();
+    let folders = vault.list_folders(&token).unwrap();
+    assert_eq!(folders.len(), 1);
+    assert_eq!(folders[0].id, work);
+
+    // Entries of the deleted subtree move up to its parent
+    for item in vault.list_items(&token, None).unwrap() {
+        assert_eq!(item.item.folder_id.as_deref(), Some(work.as_str()));
+    }
+}
+
+#[test]
+fn test_bulk_tags() {
+    let (mut vault, token) = unlocked_vault();
+    let a = save(&mut vault, &token, "GitHub");
+    let b = save(&mut vault, &token, "GitLab");
+
+    vault
+        .apply_tags(&token, &[a, b], &["dev".to_string(), " Infra ".to_string()], &[])
+        .unwrap();
+    vault
+        .apply_tags(&token, &[b], &["DEV".to_string()], &["infra".to_string()])
+        .unwrap();
+
+    assert_eq!(vault.get_item(&token, a).unwrap().item.tags, ["dev", "Infra"]);
+    assert_eq!(vault.get_item(&token, b).unwrap().item.tags, ["dev"]);
+    assert_eq!(vault.list_tags(&token).unwrap(), ["dev", "Infra"]);
+    assert_eq!(vault.search_entries(&token, "tag:infra").unwrap().len(), 1);
+
+    // One bad ID leaves every entry untouched
+    assert!(vault.apply_tags(&token, &[a, 999], &["x".to_string()], &[]).is_err());
+    assert_eq!(vault.get_item(&token, a).unwrap().item.tags, ["dev", "Infra"]);
+}
+
+#[test]
+fn test_legacy_update_keeps_folder_and_tags() {
+    let (mut vault, token) = unlocked_vault();
+    let work = vault.create_folder(&token, "Work", None).unwrap();
+    let id = save(&mut vault, &token, "GitHub");
+    vault.move_entries_to_folder(&token, &[id], Some(&work)).unwrap();
+    vault
+        .apply_tags(&token, &[id], &["dev".to_string()], &[])
+        .unwrap();
+
+    // The entry editor saves only username, password, TOTP secret and notes
+    let blob = br#"{"username":"alice","password":"n3w","notes":"edited"}"#;
+    vault.update_entry(&token, id, "GitHub", blob).unwrap();
+
+    let item = vault.get_item(&token, id).unwrap().item;
+    assert_eq!(item.folder_id.as_deref(), Some(work.as_str()));
+    assert_eq!(item.tags, ["dev"]);
+    assert_eq!(item.notes.as_deref(), Some("edited"));
+    assert_eq!(vault.list_tags(&token).unwrap(), ["dev"]);
+}
+
+#[test]
+fn test_folders_and_tags_survive_sync() {
+    let (mut desktop, desktop_token) = unlocked_vault();
+    let work = desktop.create_folder(&desktop_token, "Work", None).unwrap();
+    let id = save(&mut desktop, &desktop_token, "Jira");
+    desktop.move_entries_to_folder(&desktop_token, &[id], Some(&work)).unwrap();
+    desktop
+        .apply_tags(&desktop_token, &[id], &["tickets".to_string()], &[])
+        .unwrap();
+
+    let payload = desktop.export_sync_payload(&desktop_token, None).unwrap();
+    let mut phone = Vault::new(Box::new(MemoryStore::new()));
+    phone
+        .register_with_encryption_salt(USER, PASS, payload.encryption_salt.as_deref().unwrap())
+        .unwrap();
+    let phone_token = phone.unlock(USER, PASS).unwrap();
+    phone.import_sync_payload(&phone_token, &payload).unwrap();
+
+    let folders = phone.list_folders(&phone_token).unwrap();
+    assert_eq!(folders.len(), 1);
+    assert_eq!(folders[0].name, "Work");
+    let item = &phone.list_items(&phone_token, None).unwrap()[0].item;
+    assert_eq!(item.folder_id.as_deref(), Some(work.as_str()));
+    assert_eq!(item.tags, ["tickets"]);
+
+    // Deleting the folder on the phone propagates back as a tombstone
+    phone.delete_folder(&phone_token, &work).unwrap();
+    let delta = phone.export_sync_payload(&phone_token, None).unwrap();
+    desktop.import_sync_payload(&desktop_token, &delta).unwrap();
+    assert!(desktop.list_folders(&desktop_token).unwrap().is_empty());
+    assert!(desktop.list_items(&desktop_token, None).unwrap()[0].item.folder_id.is_none());
+}
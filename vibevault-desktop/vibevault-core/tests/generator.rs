@@ -0,0 +1,122 @@
+use vibevault_core::generator::{GeneratedKind, MAX_GENERATOR_HISTORY};
+use vibevault_core::{PassphraseOptions, PasswordOptions, RuleSource};
+
+mod common;
+
+use common::{unlocked_vault, PASS, USER};
+
+#[test]
+fn test_generated_values_are_kept_encrypted() {
+    let (mut vault, token) = unlocked_vault();
+    let password = vault
+        .generate_password(&token, &PasswordOptions::default())
+        .unwrap();
+    let passphrase = vault
+        .generate_passphrase(&token, &PassphraseOptions::default())
+        .unwrap();
+    assert!(passphrase.entropy_bits > 77.0);
+
+    let history = vault.generator_history(&token).unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].kind, GeneratedKind::Passphrase);
+    assert_eq!(history[0].value, passphrase.value);
+    assert_eq!(history[1].value, password.value);
+
+    // Nothing is stored in the clear
+    for record in vault.store().list_generated().unwrap() {
+        let blob = String::from_utf8_lossy(&record.data_blob);
+        assert!(!blob.contains(&password.value) && !blob.contains(&passphrase.value));
+    }
+
+    vault.lock();
+    let token = vault.unlock(USER, PASS).unwrap();
+    assert_eq!(vault.generator_history(&token).unwrap().len(), 2);
+
+    vault.clear_generator_history(&token).unwrap();
+    assert!(vault.generator_history(&token).unwrap().is_empty());
+}
+
+#[test]
+fn test_history_is_capped() {
+    let (mut vault, token) = unlocked_vault();
+    let options = PasswordOptions {
+        length: 8,
+        ..PasswordOptions::default()
+    };
+    let mut last = String::new();
+    for _ in 0..MAX_GENERATOR_HISTORY + 5 {
+        last = vault.generate_password(&token, &options).unwrap().value;
+    }
+    let history = vault.generator_history(&token).unwrap();
+    assert_eq!(history.len(), MAX_GENERATOR_HISTORY);
+    assert_eq!(history[0].value, last);
+
+    // Invalid options record nothing
+    let invalid = PasswordOptions {
+        length: 2,
+        ..PasswordOptions::default()
+    };
+    assert!(vault.generate_password(&token, &invalid).is_err());
+    assert_eq!(vault.generator_history(&token).unwrap().len(), MAX_GENERATOR_HISTORY);
+}
+
+#[test]
+fn test_site_rules_shape_generated_passwords() {
+    let (mut vault, token) = unlocked_vault();
+    let options = PasswordOptions {
+        length: 40,
+        ..PasswordOptions::default()
+    };
+
+    // Bundled rules match subdomains of the listed domain
+    let site = vault
+        .password_rules_for_url(&token, "https://secure.bankofamerica.com/login")
+        .unwrap()
+        .unwrap();
+    assert_eq!(site.domain, "bankofamerica.com");
+    assert_eq!(site.source, RuleSource::Bundled);
+    let generated = vault
+        .generate_password_for_url(&token, "https://secure.bankofamerica.com/login", &options)
+        .unwrap();
+    assert_eq!(generated.value.chars().count(), 20);
+    assert_eq!(generated.rules_domain.as_deref(), Some("bankofamerica.com"));
+
+    // User rules win over the bundled ones
+    vault
+        .set_password_rule_override(&token, "bankofamerica.com", Some("maxlength: 12; required: digit;"))
+        .unwrap();
+    let generated = vault
+        .generate_password_for_url(&token, "bankofamerica.com", &options)
+        .unwrap();
+    assert_eq!(generated.value.len(), 12);
+    assert!(generated.value.chars().all(|c| c.is_ascii_digit()));
+    assert!(vault
+        .set_password_rule_override(&token, "example.com", Some("required: emoji;"))
+        .is_err());
+    vault
+        .set_password_rule_override(&token, "bankofamerica.com", None)
+        .unwrap();
+    assert!(vault.password_rule_overrides(&token).unwrap().is_empty());
+
+    // An imported rules file replaces the bundled one
+    let imported = r#"{"example.org": {"password-rules": "minlength: 8; maxlength: 8; required: lower;"}}"#;
+    assert_eq!(vault.import_password_rules(&token, imported).unwrap(), 1);
+    assert!(vault
+        .password_rules_for_url(&token, "bankofamerica.com")
+        .unwrap()
+        .is_none());
+    let generated = vault
+        .generate_password_for_url(&token, "https://example.org", &options)
+        .unwrap();
+    assert_eq!(generated.value.len(), 8);
+    assert!(vault
+        .import_password_rules(&token, r#"{"a.com": {"password-rules": "nonsense"}}"#)
+        .is_err());
+
+    // No rules: the options are used as they are
+    let generated = vault
+        .generate_password_for_url(&token, "https://unknown.test", &options)
+        .unwrap();
+    assert_eq!(generated.value.chars().count(), 40);
+    assert!(generated.rules_domain.is_none());
+}
@@ -0,0 +1,185 @@
+use vibevault_core::health::{HealthIssueKind, HealthOptions, HealthScope, Severity};
+use vibevault_core::{ItemType, PasswordHistoryEntry, VaultItem};
+
+mod common;
+
+use common::unlocked_vault;
+
+const TOTP: &str = "JBSWY3DPEHPK3PXP";
+
+fn login(title: &str, password: &str, totp: bool) -> VaultItem {
+    let mut item = VaultItem::new(title);
+    item.username = Some("alice@example.com".to_string());
+    item.password = Some(password.to_string());
+    item.totp_secret = totp.then(|| TOTP.to_string());
+    item
+}
+
+fn kinds_of(report: &vibevault_core::health::HealthReport, title: &str) -> Vec<HealthIssueKind> {
+    report
+        .groups
+        .iter()
+        .flat_map(|g| &g.issues)
+        .filter(|i| i.entries.iter().any(|e| e.title == title))
+        .map(|i| i.kind)
+        .collect()
+}
+
+#[test]
+fn test_health_report() {
+    let (mut vault, token) = unlocked_vault();
+    for item in [
+        login("Forum", "hunter2", true),
+        login("Shop", "hunter2", true),
+        login("Mail", "Qz7!vR3#mW9pL2xK", true),
+        login("Chat", "Qz7!vR3#mW9pL2xk", true),
+        login("Bank", "tV8$nB4^cJ6&hG1@", false),
+    ] {
+        vault.save_item(&token, &item, None).unwrap();
+    }
+    let mut old = login("Router", "pF5*dS2(gH8)jK4_", true);
+    old.password_history = vec![PasswordHistoryEntry {
+        password: "admin".to_string(),
+        replaced_at: "2019-03-01T12:00:00+00:00".to_string(),
+    }];
+    vault.save_item(&token, &old, None).unwrap();
+    let mut note = VaultItem::with_type("Recipe", ItemType::SecureNote);
+    note.notes = Some("flour".to_string());
+    vault.save_item(&token, &note, None).unwrap();
+
+    let report = vault.health_report(&token, &HealthOptions::default()).unwrap();
+    assert_eq!(report.entries_checked, 7);
+    assert_eq!(report.healthy_entries, 1);
+
+    let severities: Vec<Severity> = report.groups.iter().map(|g| g.severity).collect();
+    let mut sorted = severities.clone();
+    sorted.sort();
+    assert_eq!(severities, sorted);
+    assert_eq!(severities[0], Severity::Critical);
+
+    assert_eq!(kinds_of(&report, "Forum"), [HealthIssueKind::Weak, HealthIssueKind::Reused]);
+    assert_eq!(kinds_of(&report, "Mail"), [HealthIssueKind::NearDuplicate]);
+    assert_eq!(kinds_of(&report, "Bank"), [HealthIssueKind::MissingTotp]);
+    assert_eq!(kinds_of(&report, "Router"), [HealthIssueKind::Stale]);
+    assert!(kinds_of(&report, "Recipe").is_empty());
+
+    let reused = report
+        .groups
+        .iter()
+        .flat_map(|g| &g.issues)
+        .find(|i| i.kind == HealthIssueKind::Reused)
+        .unwrap();
+    assert_eq!(reused.entries.len(), 2);
+
+    // The report carries entry references, never passwords
+    let json = serde_json::to_string(&report).unwrap();
+    for secret in ["hunter2", "Qz7!vR3", "tV8$nB4", "pF5*dS2", "admin"] {
+        assert!(!json.contains(secret), "{}", secret);
+    }
+
+    assert!(vault
+        .health_report(
+            &token,
+            &HealthOptions {
+                stale_after_months: 0,
+                ..HealthOptions::default()
+            }
+        )
+        .is_err());
+}
+
+#[test]
+fn test_reuse_across_profiles() {
+    let (mut vault, token) = unlocked_vault();
+    let work = vault.create_profile(&token, "Work").unwrap();
+    vault
+        .save_item(&token, &login("Personal", "tV8$nB4^cJ6&hG1@", true), None)
+        .unwrap();
+    vault
+        .save_item(&token, &login("Work", "tV8$nB4^cJ6&hG1@", true), Some(work))
+        .unwrap();
+
+    let active = vault.health_report(&token, &HealthOptions::default()).unwrap();
+    assert_eq!(active.entries_checked, 1);
+    assert!(active.groups.is_empty());
+
+    let all = vault
+        .health_report(
+            &token,
+            &HealthOptions {
+                scope: HealthScope::AllProfiles,
+                ..HealthOptions::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(all.entries_checked, 2);
+    assert_eq!(kinds_of(&all, "Work"), [HealthIssueKind::Reused]);
+    let profiles: Vec<i64> = all.groups[0].issues[0].entries.iter().map(|e| e.profile_id).collect();
+    assert!(profiles.contains(&work));
+}
+
+#[test]
+fn test_low_recovery_codes() {
+    let (mut vault, token) = unlocked_vault();
+    let mut note = VaultItem::with_type("Backup codes", ItemType::SecureNote);
+    note.notes = Some("GitHub".to_string());
+    vault.save_item(&token, &note, None).unwrap();
+    let id = vault.list_items(&token, None).unwrap()[0].id;
+
+    let codes: Vec<String> = ["1234 5678", "2345-6789", "", "3456 7890", "12345678"]
+        .iter()
+        .map(|c| c.to_string())
+        .collect();
+    let status = vault.add_recovery_codes(&token, id, &codes).unwrap();
+    assert_eq!((status.total, status.remaining, status.running_low), (3, 3, false));
+    // Adding the same batch again is a no-op
+    let status = vault.add_recovery_codes(&token, id, &codes).unwrap();
+    assert_eq!(status.total, 3);
+    assert!(vault.health_report(&token, &HealthOptions::default()).unwrap().groups.is_empty());
+
+    let status = vault.mark_recovery_code_used(&token, id, "12345678").unwrap();
+    assert_eq!((status.remaining, status.running_low), (2, true));
+    assert!(vault.mark_recovery_code_used(&token, id, "1234-5678").is_err());
+    assert!(vault.mark_recovery_code_used(&token, id, "0000 0000").is_err());
+
+    let stored = vault.recovery_codes(&token, id).unwrap();
+    assert!(stored[0].used_at.is_some());
+    assert!(stored[1].used_at.is_none());
+
+    let report = vault.health_report(&token, &HealthOptions::default()).unwrap();
+    assert_eq!(kinds_of(&report, "Backup codes"), [HealthIssueKind::LowRecoveryCodes]);
+    assert_eq!(report.groups[0].severity, Severity::Medium);
+    let json = serde_json::to_string(&report).unwrap();
+    assert!(!json.contains("2345"));
+
+    vault.mark_recovery_code_used(&token, id, "2345 6789").unwrap();
+    vault.mark_recovery_code_used(&token, id, "34567890").unwrap();
+    assert_eq!(vault.recovery_code_status(&token, id).unwrap().remaining, 0);
+    let report = vault.health_report(&token, &HealthOptions::default()).unwrap();
+    assert_eq!(report.groups[0].severity, Severity::High);
+}
+
+#[test]
+fn test_legacy_update_keeps_recovery_codes() {
+    let (mut vault, token) = unlocked_vault();
+    vault
+        .save_item(&token, &login("GitHub", "tV8$nB4^cJ6&hG1@", true), None)
+        .unwrap();
+    let id = vault.list_items(&token, None).unwrap()[0].id;
+    let codes: Vec<String> = ["1111-2222", "3333-4444", "5555-6666"]
+        .iter()
+        .map(|c| c.to_string())
+        .collect();
+    vault.add_recovery_codes(&token, id, &codes).unwrap();
+    vault.mark_recovery_code_used(&token, id, "1111-2222").unwrap();
+
+    // The entry editor saves only username, password, TOTP secret and notes
+    let blob = br#"{"username":"alice","password":"pF5*dS2(gH8)jK4_","totpSecret":"JBSWY3DPEHPK3PXP"}"#;
+    vault.update_entry(&token, id, "GitHub", blob).unwrap();
+
+    let kept = vault.recovery_codes(&token, id).unwrap();
+    assert_eq!(kept.len(), 3);
+    assert!(kept[0].used_at.is_some());
+    let status = vault.recovery_code_status(&token, id).unwrap();
+    assert_eq!((status.total, status.remaining), (3, 2));
+}
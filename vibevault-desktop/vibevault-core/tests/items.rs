@@ -0,0 +1,226 @@
+use vibevault_core::{CardDetails, CustomField, ItemType, Vault, VaultItem};
+
+mod common;
+
+use common::{unlocked_vault, PASS, USER};
+
+#[test]
+fn test_legacy_blobs_migrate_on_unlock() {
+    let (mut vault, token) = unlocked_vault();
+    let legacy = br#"{"username":"alice","password":"hunter2","notes":"old"}"#;
+    vault.save_entry(&token, "github", legacy, None).unwrap();
+
+    // Typed reads work before migration...
+    let items = vault.list_items(&token, None).unwrap();
+    assert_eq!(items[0].item.title, "github");
+    assert_eq!(items[0].item.password.as_deref(), Some("hunter2"));
+
+    // ...and the blob itself is rewritten on the next unlock
+    vault.lock();
+    let token = vault.unlock(USER, PASS).unwrap();
+    let raw = vault.list_entries(&token).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&raw[0].data_blob).unwrap();
+    assert_eq!(json["version"], 1);
+    assert_eq!(json["title"], "github");
+    assert_eq!(json["username"], "alice");
+}
+
+#[test]
+fn test_typed_save_and_update() {
+    let (mut vault, token) = unlocked_vault();
+
+    let mut item = VaultItem::new("GitLab");
+    item.username = Some("alice".to_string());
+    item.urls = vec!["https://gitlab.com".to_string()];
+    vault.save_item(&token, &item, None).unwrap();
+
+    let stored = vault.list_items(&token, None).unwrap().remove(0);
+    assert_eq!(stored.item, item);
+
+    item.password = Some("s3cret".to_string());
+    vault.update_item(&token, stored.id, &item).unwrap();
+    assert_eq!(vault.get_item(&token, stored.id).unwrap().item, item);
+
+    // Invalid items never reach storage
+    item.title = String::new();
+    assert!(vault.update_item(&token, stored.id, &item).is_err());
+    assert!(vault.save_item(&token, &item, None).is_err());
+    assert_eq!(vault.list_items(&token, None).unwrap().len(), 1);
+}
+
+#[test]
+fn test_filter_by_item_type() {
+    let (mut vault, token) = unlocked_vault();
+    vault.save_item(&token, &VaultItem::new("GitHub"), None).unwrap();
+
+    let mut card = VaultItem::with_type("Visa", ItemType::Card);
+    card.card = Some(CardDetails {
+        number: "4111111111111111".to_string(),
+        expiry_month: 4,
+        expiry_year: 2031,
+        ..Default::default()
+    });
+    vault.save_item(&token, &card, None).unwrap();
+
+    let mut note = VaultItem::with_type("Door code", ItemType::SecureNote);
+    note.notes = Some("4821".to_string());
+    vault.save_item(&token, &note, None).unwrap();
+
+    let cards = vault.list_items(&token, Some(ItemType::Card)).unwrap();
+    assert_eq!(cards.len(), 1);
+    assert_eq!(cards[0].item, card);
+    assert_eq!(vault.list_items(&token, None).unwrap().len(), 3);
+
+    let logins = vault.list_entries_of_type(&token, ItemType::Login).unwrap();
+    assert_eq!(logins.len(), 1);
+    assert_eq!(logins[0].uuid, "GitHub");
+
+    // A card failing the Luhn check is rejected
+    card.card.as_mut().unwrap().number = "4111111111111112".to_string();
+    assert!(vault.save_item(&token, &card, None).is_err());
+}
+
+#[test]
+fn test_legacy_update_keeps_structured_fields() {
+    let (mut vault, token) = unlocked_vault();
+
+    let mut login = VaultItem::new("GitLab");
+    login.username = Some("alice".to_string());
+    login.password = Some("first".to_string());
+    login.urls = vec!["https://gitlab.com".to_string()];
+    login.custom_fields = vec![CustomField {
+        name: "PIN".to_string(),
+        value: "1234".to_string(),
+        hidden: true,
+    }];
+    vault.save_item(&token, &login, None).unwrap();
+    let mut card = VaultItem::with_type("Visa", ItemType::Card);
+    card.card = Some(CardDetails {
+        number: "4111111111111111".to_string(),
+        expiry_month: 4,
+        expiry_year: 2031,
+        ..Default::default()
+    });
+    vault.save_item(&token, &card, None).unwrap();
+    let items = vault.list_items(&token, None).unwrap();
+    let (login_id, card_id) = (items[0].id, items[1].id);
+
+    // What the entry editor sends: only the four legacy fields
+    let blob = br#"{"username":"bob","password":"second","totpSecret":"","notes":"moved"}"#;
+    vault.update_entry(&token, login_id, "GitLab (work)", blob).unwrap();
+    let updated = vault.get_item(&token, login_id).unwrap().item;
+    assert_eq!(updated.title, "GitLab (work)");
+    assert_eq!(updated.username.as_deref(), Some("bob"));
+    assert_eq!(updated.password.as_deref(), Some("second"));
+    assert_eq!(updated.notes.as_deref(), Some("moved"));
+    assert_eq!(updated.urls, login.urls);
+    assert_eq!(updated.custom_fields, login.custom_fields);
+    assert_eq!(updated.password_history[0].password, "first");
+
+    vault
+        .update_entry(&token, card_id, "Visa", br#"{"notes":"expires soon"}"#)
+        .unwrap();
+    let updated = vault.get_item(&token, card_id).unwrap().item;
+    assert_eq!(updated.item_type, ItemType::Card);
+    assert_eq!(updated.card, card.card);
+    assert_eq!(updated.notes.as_deref(), Some("expires soon"));
+
+    // A full item still replaces the stored one, so fields can be cleared
+    let mut cleared = vault.get_item(&token, login_id).unwrap().item;
+    cleared.urls.clear();
+    cleared.custom_fields.clear();
+    vault.update_item(&token, login_id, &cleared).unwrap();
+    let updated = vault.get_item(&token, login_id).unwrap().item;
+    assert!(updated.urls.is_empty() && updated.custom_fields.is_empty());
+}
+
+#[test]
+fn test_password_history_and_restore() {
+    let (mut vault, token) = unlocked_vault();
+
+    let mut item = VaultItem::new("Bank");
+    item.password = Some("first".to_string());
+    vault.save_item(&token, &item, None).unwrap();
+    let id = vault.list_items(&token, None).unwrap()[0].id;
+
+    // The legacy raw-blob update path records history too
+    let blob = br#"{"username":"alice","password":"second"}"#;
+    vault.update_entry(&token, id, "Bank", blob).unwrap();
+    let history = vault.password_history(&token, id).unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].password, "first");
+
+    // Rotation failed on the website: go back to the old password
+    vault.restore_password(&token, id, 0).unwrap();
+    let restored = vault.get_item(&token, id).unwrap().item;
+    assert_eq!(restored.password.as_deref(), Some("first"));
+    assert_eq!(restored.password_history[0].password, "second");
+    assert_eq!(restored.password_history.len(), 1);
+
+    assert!(vault.restore_password(&token, id, 5).is_err());
+
+    vault.set_password_history_depth(&token, 0).unwrap();
+    item.password = Some("third".to_string());
+    vault.update_item(&token, id, &item).unwrap();
+    assert!(vault.password_history(&token, id).unwrap().is_empty());
+}
+
+#[test]
+fn test_history_depth_survives_restart() {
+    let dir = std::env::temp_dir().join(format!("vibevault-depth-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("vault.db");
+
+    let mut vault = Vault::open(&path).unwrap();
+    vault.register(USER, PASS).unwrap();
+    let token = vault.unlock(USER, PASS).unwrap();
+    vault.set_password_history_depth(&token, 3).unwrap();
+    drop(vault);
+
+    let mut vault = Vault::open(&path).unwrap();
+    vault.unlock(USER, PASS).unwrap();
+    assert_eq!(vault.password_history_depth(), 3);
+    drop(vault);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_trash_restore_and_purge() {
+    let (mut vault, token) = unlocked_vault();
+    vault.save_item(&token, &VaultItem::new("GitHub"), None).unwrap();
+    vault.save_item(&token, &VaultItem::new("GitLab"), None).unwrap();
+    let items = vault.list_items(&token, None).unwrap();
+    let (github, gitlab) = (items[0].id, items[1].id);
+
+    vault.delete_entry(&token, github).unwrap();
+    vault.delete_entry(&token, gitlab).unwrap();
+    let trash = vault.list_deleted_entries(&token).unwrap();
+    assert_eq!(trash.len(), 2);
+    assert!(trash.iter().any(|d| d.item.title == "GitHub"));
+
+    // Trash is per profile
+    let work = vault.create_profile(&token, "Work").unwrap();
+    vault.set_active_profile(&token, work).unwrap();
+    assert!(vault.list_deleted_entries(&token).unwrap().is_empty());
+    assert!(vault.restore_entry(&token, github).is_err());
+    vault.set_active_profile(&token, 1).unwrap();
+
+    vault.restore_entry(&token, github).unwrap();
+    let restored = vault.get_item(&token, github).unwrap();
+    assert_eq!(restored.item.title, "GitHub");
+
+    // The restore is exported with a newer version than the tombstone
+    let payload = vault.export_sync_payload(&token, None).unwrap();
+    let synced = payload
+        .entries
+        .iter()
+        .find(|e| Some(&e.entry_uuid) == restored.entry_uuid.as_ref())
+        .unwrap();
+    assert_eq!(synced.sync_version, 3);
+    assert!(synced.deleted_at.is_none());
+
+    assert!(vault.purge_entry(&token, github).is_err());
+    vault.purge_entry(&token, gitlab).unwrap();
+    assert!(vault.list_deleted_entries(&token).unwrap().is_empty());
+    assert_eq!(vault.list_items(&token, None).unwrap().len(), 1);
+}
@@ -0,0 +1,112 @@
+use vibevault_core::{EntrySort, Vault, VaultItem};
+
+mod common;
+
+use common::unlocked_vault;
+
+fn save(vault: &mut Vault, token: &str, title: &str, url: &str) -> i64 {
+    let mut item = VaultItem::new(title);
+    item.username = Some(format!("{}@example.com", title.to_lowercase()));
+    item.password = Some(format!("{}-secret", title));
+    item.urls = vec![url.to_string()];
+    vault.save_item(token, &item, None).unwrap();
+    vault
+        .list_items(token, None)
+        .unwrap()
+        .into_iter()
+        .find(|s| s.item.title == title)
+        .unwrap()
+        .id
+}
+
+fn all_titles(vault: &mut Vault, token: &str, sort: EntrySort, limit: usize) -> Vec<String> {
+    let mut titles = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let page = vault
+            .list_entry_summaries(token, sort, cursor.as_deref(), limit)
+            .unwrap();
+        assert!(page.entries.len() <= limit);
+        titles.extend(page.entries.into_iter().map(|e| e.title));
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => return titles,
+        }
+    }
+}
+
+#[test]
+fn test_paginated_listing_by_name() {
+    let (mut vault, token) = unlocked_vault();
+    for (title, url) in [
+        ("jira", "https://jira.example.com"),
+        ("GitHub", "https://www.github.com/login"),
+        ("AWS", "https://console.aws.amazon.com"),
+        ("bank", "https://bank.example"),
+        ("Confluence", "confluence.example.com/wiki"),
+    ] {
+        save(&mut vault, &token, title, url);
+    }
+
+    assert_eq!(
+        all_titles(&mut vault, &token, EntrySort::Name, 2),
+        ["AWS", "bank", "Confluence", "GitHub", "jira"]
+    );
+
+    let page = vault
+        .list_entry_summaries(&token, EntrySort::Name, None, 2)
+        .unwrap();
+    assert_eq!(page.total, 5);
+    assert_eq!(page.entries[0].favicon_domain.as_deref(), Some("console.aws.amazon.com"));
+    assert_eq!(page.entries[0].username.as_deref(), Some("aws@example.com"));
+
+    // Summaries never carry secrets
+    let json = serde_json::to_string(&page).unwrap();
+    assert!(!json.contains("secret"));
+
+    assert!(vault
+        .list_entry_summaries(&token, EntrySort::Name, Some("garbage"), 2)
+        .is_err());
+    assert!(vault
+        .list_entry_summaries(&token, EntrySort::Name, None, 0)
+        .is_err());
+}
+
+#[test]
+fn test_listing_by_last_used() {
+    let (mut vault, token) = unlocked_vault();
+    let a = save(&mut vault, &token, "A", "a.example");
+    save(&mut vault, &token, "B", "b.example");
+    let c = save(&mut vault, &token, "C", "c.example");
+
+    vault.record_entry_use(&token, c).unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    vault.record_entry_use(&token, a).unwrap();
+    assert!(vault.record_entry_use(&token, 999).is_err());
+
+    // Never-used entries come last
+    assert_eq!(
+        all_titles(&mut vault, &token, EntrySort::LastUsed, 1),
+        ["A", "C", "B"]
+    );
+    let page = vault
+        .list_entry_summaries(&token, EntrySort::LastUsed, None, 10)
+        .unwrap();
+    assert_eq!(page.entries[0].use_count, 1);
+    assert!(page.entries[2].last_used_at.is_none());
+    assert!(page.next_cursor.is_none());
+}
+
+#[test]
+fn test_listing_follows_edits_and_deletes() {
+    let (mut vault, token) = unlocked_vault();
+    let a = save(&mut vault, &token, "Alpha", "alpha.example");
+    let b = save(&mut vault, &token, "Beta", "beta.example");
+
+    let mut item = vault.get_item(&token, a).unwrap().item;
+    item.title = "Zulu".to_string();
+    vault.update_item(&token, a, &item).unwrap();
+    vault.delete_entry(&token, b).unwrap();
+
+    assert_eq!(all_titles(&mut vault, &token, EntrySort::Updated, 10), ["Zulu"]);
+}
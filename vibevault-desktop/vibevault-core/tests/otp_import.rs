@@ -0,0 +1,319 @@
+use base64::{engine::general_purpose, Engine as _};
+use serde_json::json;
+use sha2::Sha256;
+use vibevault_core::crypto::encrypt_aes256_gcm;
+use vibevault_core::totp::{parse_totp, OtpAlgorithm, OtpKind};
+use vibevault_core::{AuthenticatorApp, VaultItem};
+
+mod common;
+
+use common::unlocked_vault;
+
+/// Protobuf length-delimited field
+fn bytes_field(number: u8, value: &[u8]) -> Vec<u8> {
+    let mut out = vec![(number << 3) | 2, value.len() as u8];
+    out.extend_from_slice(value);
+    out
+}
+
+/// Protobuf varint field (values below 128)
+fn varint_field(number: u8, value: u8) -> Vec<u8> {
+    vec![number << 3, value]
+}
+
+fn migration_uri(accounts: &[Vec<u8>]) -> String {
+    let payload: Vec<u8> = accounts.iter().flat_map(|a| bytes_field(1, a)).collect();
+    let data = general_purpose::STANDARD.encode(payload);
+    format!(
+        "otpauth-migration://offline?data={}",
+        data.replace('+', "%2B").replace('/', "%2F").replace('=', "%3D")
+    )
+}
+
+#[test]
+fn test_import_migration_qr() {
+    let (mut vault, token) = unlocked_vault();
+    let secret = parse_totp("JBSWY3DPEHPK3PXP").unwrap().secret.clone();
+    let github = [
+        bytes_field(1, &secret),
+        bytes_field(2, b"GitHub:alice"),
+        bytes_field(3, b"GitHub"),
+        varint_field(4, 2),
+        varint_field(5, 2),
+        varint_field(6, 2),
+    ]
+    .concat();
+    let bank = [
+        bytes_field(1, &secret),
+        bytes_field(2, b"alice@bank.example"),
+        varint_field(6, 1),
+        varint_field(7, 7),
+    ]
+    .concat();
+
+    let imported = vault
+        .import_otp_qr_text(&token, &migration_uri(&[github, bank]), None)
+        .unwrap();
+    let titles: Vec<&str> = imported.iter().map(|i| i.title.as_str()).collect();
+    assert_eq!(titles, ["GitHub", "alice@bank.example"]);
+    assert!(imported.iter().all(|i| i.created));
+
+    let github = vault.get_item(&token, imported[0].id).unwrap().item;
+    assert_eq!(github.username.as_deref(), Some("alice"));
+    let code = vault.entry_totp(&token, imported[0].id).unwrap();
+    assert_eq!((code.digits, code.kind), (8, OtpKind::Totp));
+    let bank = vault.entry_totp(&token, imported[1].id).unwrap();
+    assert_eq!((bank.kind, bank.counter), (OtpKind::Hotp, Some(7)));
+
+    // Several accounts cannot be attached to a single entry
+    let existing = vault
+        .save_item(&token, &VaultItem::new("Existing"), None)
+        .unwrap();
+    let id = vault
+        .list_items(&token, None)
+        .unwrap()
+        .into_iter()
+        .find(|s| s.entry_uuid.as_deref() == Some(existing.as_str()))
+        .unwrap()
+        .id;
+    let two = migration_uri(&[bytes_field(1, &secret), bytes_field(1, &secret)]);
+    assert!(vault.import_otp_qr_text(&token, &two, Some(id)).is_err());
+}
+
+#[test]
+fn test_import_accounts_is_all_or_nothing() {
+    let (mut vault, token) = unlocked_vault();
+    let github = parse_totp("otpauth://totp/GitHub:alice?secret=JBSWY3DPEHPK3PXP").unwrap();
+    let mut too_long = github.clone();
+    too_long.issuer = Some("x".repeat(300));
+
+    assert!(vault
+        .import_otp_accounts(&token, &[github.clone(), too_long], None)
+        .is_err());
+    assert!(vault.list_items(&token, None).unwrap().is_empty());
+
+    let imported = vault.import_otp_accounts(&token, &[github], None).unwrap();
+    assert_eq!(imported[0].title, "GitHub");
+    assert_eq!(vault.list_items(&token, None).unwrap().len(), 1);
+}
+
+#[test]
+fn test_attach_qr_to_existing_entry() {
+    let (mut vault, token) = unlocked_vault();
+    let mut item = VaultItem::new("Example");
+    item.password = Some("hunter2".to_string());
+    vault.save_item(&token, &item, None).unwrap();
+    let id = vault.list_items(&token, None).unwrap()[0].id;
+
+    let imported = vault
+        .import_otp_qr_text(
+            &token,
+            "otpauth://totp/Example:alice?secret=JBSWY3DPEHPK3PXP&period=60",
+            Some(id),
+        )
+        .unwrap();
+    assert_eq!(imported.len(), 1);
+    assert!(!imported[0].created);
+    assert_eq!(vault.list_items(&token, None).unwrap().len(), 1);
+
+    let stored = vault.get_item(&token, id).unwrap().item;
+    assert_eq!(stored.password.as_deref(), Some("hunter2"));
+    assert_eq!(vault.entry_totp(&token, id).unwrap().period, 60);
+
+    assert!(vault
+        .import_otp_qr_text(&token, "https://example.com/not-2fa", Some(id))
+        .is_err());
+}
+
+fn aegis_entries() -> serde_json::Value {
+    json!([
+        {"type": "totp", "name": "alice", "issuer": "GitHub", "note": "work account",
+         "info": {"secret": "JBSWY3DPEHPK3PXP", "algo": "SHA256", "digits": 8, "period": 60}},
+        {"type": "hotp", "name": "alice", "issuer": "Bank",
+         "info": {"secret": "JBSWY3DPEHPK3PXP", "algo": "SHA1", "digits": 6, "counter": 3}},
+        {"type": "steam", "name": "alice", "issuer": "Steam",
+         "info": {"secret": "JBSWY3DPEHPK3PXP", "algo": "SHA1", "digits": 5, "period": 30}},
+        {"type": "motp", "name": "alice", "issuer": "Legacy",
+         "info": {"secret": "abcdef", "pin": "1234"}},
+        {"type": "totp", "name": "ALICE", "issuer": "github",
+         "info": {"secret": "JBSWY3DPEHPK3PXP", "algo": "SHA1", "digits": 6, "period": 30}}
+    ])
+}
+
+#[test]
+fn test_import_aegis_plain() {
+    let (mut vault, token) = unlocked_vault();
+    let mut existing = VaultItem::new("Mail");
+    existing.totp_secret = Some("otpauth://totp/Mail:alice?secret=JBSWY3DPEHPK3PXP".to_string());
+    vault.save_item(&token, &existing, None).unwrap();
+
+    let mut entries = aegis_entries();
+    entries.as_array_mut().unwrap().push(json!(
+        {"type": "totp", "name": "alice", "issuer": "Mail",
+         "info": {"secret": "JBSWY3DPEHPK3PXP", "algo": "SHA1", "digits": 6, "period": 30}}
+    ));
+    let backup = json!({
+        "version": 1,
+        "header": {"slots": null, "params": null},
+        "db": {"version": 2, "entries": entries}
+    });
+    let report = vault
+        .import_authenticator_backup(&token, &backup.to_string(), None)
+        .unwrap();
+    assert_eq!(report.app, AuthenticatorApp::Aegis);
+    let titles: Vec<&str> = report.imported.iter().map(|i| i.title.as_str()).collect();
+    assert_eq!(titles, ["GitHub", "Bank", "Steam"]);
+    assert_eq!(report.duplicates, ["github (ALICE)", "Mail (alice)"]);
+    assert_eq!(report.skipped.len(), 1);
+    assert_eq!(report.skipped[0].name, "Legacy (alice)");
+
+    let github = vault.entry_totp(&token, report.imported[0].id).unwrap();
+    assert_eq!((github.algorithm, github.digits, github.period), (OtpAlgorithm::Sha256, 8, 60));
+    let notes = vault.get_item(&token, report.imported[0].id).unwrap().item.notes;
+    assert_eq!(notes.as_deref(), Some("work account"));
+    let bank = vault.entry_totp(&token, report.imported[1].id).unwrap();
+    assert_eq!(bank.counter, Some(3));
+    assert_eq!(vault.entry_totp(&token, report.imported[2].id).unwrap().kind, OtpKind::Steam);
+
+    // Importing the same backup again finds only duplicates
+    let again = vault
+        .import_authenticator_backup(&token, &backup.to_string(), None)
+        .unwrap();
+    assert!(again.imported.is_empty());
+    assert_eq!(again.duplicates.len(), 5);
+}
+
+/// AES-GCM output split the way Aegis stores it: ciphertext and tag apart
+fn seal(key: &[u8; 32], plaintext: &[u8]) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+    let (mut sealed, nonce) = encrypt_aes256_gcm(key, plaintext).unwrap();
+    let tag = sealed.split_off(sealed.len() - 16);
+    (sealed, tag, nonce)
+}
+
+#[test]
+fn test_import_aegis_encrypted() {
+    let (mut vault, token) = unlocked_vault();
+    let master = [7u8; 32];
+    let salt = [3u8; 32];
+    let mut derived = [0u8; 32];
+    let params = scrypt::Params::new(10, 8, 1, 32).unwrap();
+    scrypt::scrypt(b"backup pass", &salt, &params, &mut derived).unwrap();
+    let (key, key_tag, key_nonce) = seal(&derived, &master);
+    let db = json!({"version": 2, "entries": aegis_entries()});
+    let (db, db_tag, db_nonce) = seal(&master, db.to_string().as_bytes());
+    let backup = json!({
+        "version": 1,
+        "header": {
+            "slots": [{
+                "type": 1, "uuid": "0f0e0d0c", "key": hex::encode(key),
+                "key_params": {"nonce": hex::encode(key_nonce), "tag": hex::encode(key_tag)},
+                "n": 1024, "r": 8, "p": 1, "salt": hex::encode(salt), "repaired": true
+            }],
+            "params": {"nonce": hex::encode(db_nonce), "tag": hex::encode(db_tag)}
+        },
+        "db": general_purpose::STANDARD.encode(db)
+    })
+    .to_string();
+
+    assert!(vault.import_authenticator_backup(&token, &backup, None).is_err());
+    assert_eq!(
+        vault
+            .import_authenticator_backup(&token, &backup, Some("wrong"))
+            .unwrap_err(),
+        "Wrong backup password"
+    );
+    let report = vault
+        .import_authenticator_backup(&token, &backup, Some("backup pass"))
+        .unwrap();
+    assert_eq!(report.imported.len(), 3);
+    assert_eq!(report.duplicates.len(), 1);
+}
+
+#[test]
+fn test_import_2fas_and_andotp() {
+    let (mut vault, token) = unlocked_vault();
+    let services = json!([
+        {"name": "GitLab", "secret": "JBSWY3DPEHPK3PXP",
+         "otp": {"account": "alice", "issuer": "GitLab", "digits": 7, "period": 30,
+                 "algorithm": "SHA512", "tokenType": "TOTP"}},
+        {"name": "Steam", "secret": "JBSWY3DPEHPK3PXP",
+         "otp": {"label": "alice", "tokenType": "STEAM", "digits": 5, "algorithm": "SHA1"}}
+    ]);
+    let plain = json!({"services": services, "schemaVersion": 4});
+    let report = vault
+        .import_authenticator_backup(&token, &plain.to_string(), None)
+        .unwrap();
+    assert_eq!(report.app, AuthenticatorApp::TwoFas);
+    assert_eq!(report.imported.len(), 2);
+    let gitlab = vault.entry_totp(&token, report.imported[0].id).unwrap();
+    assert_eq!((gitlab.algorithm, gitlab.digits), (OtpAlgorithm::Sha512, 7));
+
+    // Encrypted 2FAS backups hold the same services under a PBKDF2 key
+    let (mut vault, token) = unlocked_vault();
+    let salt = [9u8; 32];
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(b"2fas pass", &salt, 10_000, &mut key);
+    let (sealed, iv) = encrypt_aes256_gcm(&key, services.to_string().as_bytes()).unwrap();
+    let services_encrypted = [sealed, salt.to_vec(), iv]
+        .iter()
+        .map(|part| general_purpose::STANDARD.encode(part))
+        .collect::<Vec<_>>()
+        .join(":");
+    let encrypted = json!({
+        "services": [],
+        "servicesEncrypted": services_encrypted,
+        "schemaVersion": 4
+    })
+    .to_string();
+    assert!(vault
+        .import_authenticator_backup(&token, &encrypted, Some("nope"))
+        .is_err());
+    let report = vault
+        .import_authenticator_backup(&token, &encrypted, Some("2fas pass"))
+        .unwrap();
+    assert_eq!(report.imported.len(), 2);
+
+    let andotp = json!([
+        {"secret": "JBSWY3DPEHPK3PXP", "issuer": "Forum", "label": "alice", "digits": 6,
+         "type": "TOTP", "algorithm": "SHA1", "period": 30, "tags": []},
+        {"secret": "JBSWY3DPEHPK3PXP", "issuer": "Bank", "label": "alice", "digits": 6,
+         "type": "HOTP", "algorithm": "SHA1", "counter": 12, "tags": []},
+        {"secret": "not base32!", "issuer": "Broken", "label": "alice", "digits": 6,
+         "type": "TOTP", "algorithm": "SHA1", "period": 30}
+    ]);
+    let report = vault
+        .import_authenticator_backup(&token, &andotp.to_string(), None)
+        .unwrap();
+    assert_eq!(report.app, AuthenticatorApp::AndOtp);
+    assert_eq!(report.imported.len(), 2);
+    assert_eq!(report.skipped[0].name, "Broken (alice)");
+    assert_eq!(
+        vault.entry_totp(&token, report.imported[1].id).unwrap().counter,
+        Some(12)
+    );
+
+    assert!(vault
+        .import_authenticator_backup(&token, "{\"entries\": []}", None)
+        .is_err());
+}
+
+#[cfg(feature = "qr-image")]
+#[test]
+fn test_import_qr_image() {
+    use vibevault_core::otp_import::{decode_qr_image, parse_otp_qr_image};
+
+    let png = include_bytes!("fixtures/totp-qr.png");
+    assert_eq!(
+        decode_qr_image(png).unwrap(),
+        ["otpauth://totp/Example:alice@example.com?secret=JBSWY3DPEHPK3PXP&issuer=Example"]
+    );
+    assert!(decode_qr_image(b"not an image").is_err());
+
+    let accounts = parse_otp_qr_image(png).unwrap();
+    let (mut vault, token) = unlocked_vault();
+    let imported = vault.import_otp_accounts(&token, &accounts, None).unwrap();
+    assert_eq!(imported.len(), 1);
+    assert_eq!(imported[0].title, "Example");
+    let item = vault.get_item(&token, imported[0].id).unwrap().item;
+    assert_eq!(item.username.as_deref(), Some("alice@example.com"));
+}
@@ -0,0 +1,94 @@
+use vibevault_core::{DatabaseManager, Vault};
+
+mod common;
+
+use common::{save, unlocked_vault_on};
+
+fn unlocked_vault() -> (Vault, String) {
+    unlocked_vault_on(Box::new(DatabaseManager::open_in_memory().unwrap()))
+}
+
+fn titles(vault: &mut Vault, token: &str) -> Vec<String> {
+    let mut titles: Vec<String> = vault
+        .list_items(token, None)
+        .unwrap()
+        .into_iter()
+        .map(|s| s.item.title)
+        .collect();
+    titles.sort();
+    titles
+}
+
+#[test]
+fn test_move_entries() {
+    let (mut vault, token) = unlocked_vault();
+    let work = vault.create_profile(&token, "Work").unwrap();
+    let folder = vault.create_folder(&token, "Dev", None).unwrap();
+    let github = save(&mut vault, &token, "GitHub");
+    let jira = save(&mut vault, &token, "Jira");
+    vault.move_entries_to_folder(&token, &[jira], Some(&folder)).unwrap();
+    let before = vault.store().find_entry(jira).unwrap().unwrap();
+
+    // One unknown ID aborts the whole move
+    assert!(vault.move_entries(&token, &[jira, 999], work).is_err());
+    assert_eq!(titles(&mut vault, &token), ["GitHub", "Jira"]);
+    assert!(vault.move_entries(&token, &[jira], 42).is_err());
+
+    vault.move_entries(&token, &[jira], work).unwrap();
+    assert_eq!(titles(&mut vault, &token), ["GitHub"]);
+    assert_eq!(vault.search_entries(&token, "jira").unwrap().len(), 0);
+
+    let after = vault.store().find_entry(jira).unwrap().unwrap();
+    assert_eq!(after.entry_uuid, before.entry_uuid);
+    assert_eq!(after.sync_version, before.sync_version + 1);
+
+    vault.set_active_profile(&token, work).unwrap();
+    let moved = vault.get_item(&token, jira).unwrap();
+    assert!(moved.item.folder_id.is_none());
+    assert_eq!(vault.search_entries(&token, "jira").unwrap().len(), 1);
+    assert!(vault.get_item(&token, github).is_err());
+}
+
+#[test]
+fn test_copy_entries() {
+    let (mut vault, token) = unlocked_vault();
+    let work = vault.create_profile(&token, "Work").unwrap();
+    let github = save(&mut vault, &token, "GitHub");
+    let original = vault.get_item(&token, github).unwrap();
+
+    let copies = vault.copy_entries(&token, &[github, github], work).unwrap();
+    assert_eq!(copies.len(), 1);
+    assert_ne!(Some(&copies[0]), original.entry_uuid.as_ref());
+    assert_eq!(titles(&mut vault, &token), ["GitHub"]);
+
+    vault.set_active_profile(&token, work).unwrap();
+    let copied = &vault.list_items(&token, None).unwrap()[0];
+    assert_eq!(copied.entry_uuid.as_ref(), Some(&copies[0]));
+    assert_eq!(copied.item, original.item);
+    let record = vault.store().find_entry(copied.id).unwrap().unwrap();
+    assert_eq!(record.sync_version, 1);
+}
+
+#[test]
+fn test_delete_profile_moving_entries() {
+    let (mut vault, token) = unlocked_vault();
+    let work = vault.create_profile(&token, "Work").unwrap();
+    vault.set_active_profile(&token, work).unwrap();
+    save(&mut vault, &token, "Jira");
+    save(&mut vault, &token, "Confluence");
+    let old = save(&mut vault, &token, "Old");
+    vault.delete_entry(&token, old).unwrap();
+    vault.create_folder(&token, "Projects", None).unwrap();
+
+    assert!(vault.delete_profile(&token, work, None).is_err());
+    assert!(vault.delete_profile(&token, work, Some(work)).is_err());
+
+    vault.delete_profile(&token, work, Some(1)).unwrap();
+    assert_eq!(vault.list_profiles(&token).unwrap().len(), 1);
+    // The active profile falls back to where the entries went
+    assert_eq!(vault.active_profile(&token).unwrap(), 1);
+    assert_eq!(titles(&mut vault, &token), ["Confluence", "Jira"]);
+    assert!(vault.list_folders(&token).unwrap().is_empty());
+    // The tombstone survives so the delete still syncs
+    assert_eq!(vault.list_deleted_entries(&token).unwrap().len(), 1);
+}
@@ -0,0 +1,110 @@
+use vibevault_core::{DatabaseManager, MemoryStore, RevisionRetention, Vault, VaultItem};
+
+mod common;
+
+use common::{unlocked_vault_on, PASS, USER};
+
+fn login(title: &str, password: &str) -> VaultItem {
+    let mut item = VaultItem::new(title);
+    item.password = Some(password.to_string());
+    item
+}
+
+#[test]
+fn test_revisions_diff_and_rollback() {
+    let (mut vault, token) =
+        unlocked_vault_on(Box::new(DatabaseManager::open_in_memory().unwrap()));
+    let device_id = vault.device_id().unwrap();
+
+    vault.save_item(&token, &login("GitHub", "first"), None).unwrap();
+    let id = vault.list_items(&token, None).unwrap()[0].id;
+    let mut edited = login("GitHub", "second");
+    edited.notes = Some("rotated".to_string());
+    vault.update_item(&token, id, &edited).unwrap();
+
+    let revisions = vault.entry_revisions(&token, id).unwrap();
+    assert_eq!(revisions.len(), 2);
+    assert_eq!(revisions[0].sync_version, 2);
+    assert_eq!(revisions[1].sync_version, 1);
+    assert!(revisions.iter().all(|r| r.device_id == device_id));
+
+    let changes = vault
+        .diff_revisions(&token, id, revisions[1].id, revisions[0].id)
+        .unwrap();
+    let fields: Vec<_> = changes.iter().map(|c| c.field.as_str()).collect();
+    assert_eq!(fields, ["notes", "password", "passwordHistory"]);
+
+    // Rolling back is itself a new, syncable revision
+    vault.rollback_entry(&token, id, revisions[1].id).unwrap();
+    let current = vault.get_item(&token, id).unwrap().item;
+    assert_eq!(current.password.as_deref(), Some("first"));
+    assert_eq!(current.notes, None);
+    let revisions = vault.entry_revisions(&token, id).unwrap();
+    assert_eq!(revisions.len(), 3);
+    assert_eq!(revisions[0].sync_version, 3);
+}
+
+#[test]
+fn test_imported_revisions_keep_origin_device() {
+    let mut desktop = Vault::new(Box::new(DatabaseManager::open_in_memory().unwrap()));
+    desktop.register(USER, PASS).unwrap();
+    let desktop_token = desktop.unlock(USER, PASS).unwrap();
+    let desktop_id = desktop.device_id().unwrap();
+    desktop
+        .save_item(&desktop_token, &login("GitHub", "first"), None)
+        .unwrap();
+
+    let payload = desktop.export_sync_payload(&desktop_token, None).unwrap();
+    assert_eq!(payload.entries[0].modified_by.as_deref(), Some(desktop_id.as_str()));
+
+    let mut phone = Vault::new(Box::new(MemoryStore::new()));
+    phone
+        .register_with_encryption_salt(USER, PASS, payload.encryption_salt.as_deref().unwrap())
+        .unwrap();
+    let phone_token = phone.unlock(USER, PASS).unwrap();
+    phone.import_sync_payload(&phone_token, &payload).unwrap();
+
+    let id = phone.list_items(&phone_token, None).unwrap()[0].id;
+    let revisions = phone.entry_revisions(&phone_token, id).unwrap();
+    assert_eq!(revisions.len(), 1);
+    assert_eq!(revisions[0].device_id, desktop_id);
+    assert_ne!(phone.device_id().unwrap(), desktop_id);
+}
+
+#[test]
+fn test_retention_survives_restart() {
+    let dir = std::env::temp_dir().join(format!("vibevault-retention-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("vault.db");
+
+    let mut vault = Vault::open(&path).unwrap();
+    vault.register(USER, PASS).unwrap();
+    let token = vault.unlock(USER, PASS).unwrap();
+    vault
+        .set_revision_retention(
+            &token,
+            RevisionRetention {
+                keep_latest: 2,
+                max_age_days: 0,
+            },
+        )
+        .unwrap();
+    vault.save_item(&token, &login("GitHub", "p0"), None).unwrap();
+    let id = vault.list_items(&token, None).unwrap()[0].id;
+    for i in 1..5 {
+        vault
+            .update_item(&token, id, &login("GitHub", &format!("p{}", i)))
+            .unwrap();
+    }
+    assert_eq!(vault.entry_revisions(&token, id).unwrap().len(), 5);
+    drop(vault);
+
+    // The next unlock compacts with the stored retention, not the default
+    let mut vault = Vault::open(&path).unwrap();
+    let token = vault.unlock(USER, PASS).unwrap();
+    let retention = vault.revision_retention();
+    assert_eq!((retention.keep_latest, retention.max_age_days), (2, 0));
+    assert_eq!(vault.entry_revisions(&token, id).unwrap().len(), 2);
+    drop(vault);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,100 @@
+use vibevault_core::{MemoryStore, Vault, VaultItem};
+
+mod common;
+
+use common::{unlocked_vault, PASS, USER};
+
+fn item(title: &str, username: &str, url: &str, tags: &[&str]) -> VaultItem {
+    let mut item = VaultItem::new(title);
+    item.username = Some(username.to_string());
+    item.urls = vec![url.to_string()];
+    item.tags = tags.iter().map(|t| t.to_string()).collect();
+    item
+}
+
+fn titles(vault: &mut Vault, token: &str, query: &str) -> Vec<String> {
+    vault
+        .search_entries(token, query)
+        .unwrap()
+        .into_iter()
+        .map(|hit| hit.entry.item.title)
+        .collect()
+}
+
+#[test]
+fn test_search_filters_and_ranking() {
+    let (mut vault, token) = unlocked_vault();
+    vault
+        .save_item(&token, &item("GitHub", "alice", "https://github.com", &["dev"]), None)
+        .unwrap();
+    vault
+        .save_item(&token, &item("Grafana", "alice", "https://grafana.internal", &["infra"]), None)
+        .unwrap();
+    let mut notes_only = item("Router", "admin", "192.168.1.1", &["home"]);
+    notes_only.notes = Some("Reset via github recovery".to_string());
+    vault.save_item(&token, &notes_only, None).unwrap();
+
+    // Title match outranks a match in the notes
+    assert_eq!(titles(&mut vault, &token, "github"), ["GitHub", "Router"]);
+    // Typos still find the entry
+    assert_eq!(titles(&mut vault, &token, "grafnaa"), ["Grafana"]);
+    // Filters combine with AND
+    assert_eq!(titles(&mut vault, &token, "user:alice tag:infra"), ["Grafana"]);
+    assert!(titles(&mut vault, &token, "user:admin tag:infra").is_empty());
+    assert_eq!(titles(&mut vault, &token, "url:192.168"), ["Router"]);
+    // An empty query lists everything
+    assert_eq!(titles(&mut vault, &token, "").len(), 3);
+    assert!(vault.search_entries(&token, "type:spaceship").is_err());
+}
+
+#[test]
+fn test_search_index_follows_writes() {
+    let (mut vault, token) = unlocked_vault();
+    vault
+        .save_item(&token, &item("GitHub", "alice", "github.com", &[]), None)
+        .unwrap();
+    let id = vault.list_items(&token, None).unwrap()[0].id;
+
+    vault
+        .update_item(&token, id, &item("GitLab", "alice", "gitlab.com", &[]))
+        .unwrap();
+    assert!(titles(&mut vault, &token, "title:github").is_empty());
+    assert_eq!(titles(&mut vault, &token, "gitlab"), ["GitLab"]);
+
+    vault.delete_entry(&token, id).unwrap();
+    assert!(titles(&mut vault, &token, "gitlab").is_empty());
+    vault.restore_entry(&token, id).unwrap();
+    assert_eq!(titles(&mut vault, &token, "gitlab"), ["GitLab"]);
+
+    // Other profiles are not searched
+    let work = vault.create_profile(&token, "Work").unwrap();
+    vault
+        .save_item(&token, &item("Jira", "alice", "jira.work", &[]), Some(work))
+        .unwrap();
+    assert!(titles(&mut vault, &token, "jira").is_empty());
+
+    // The index is dropped at lock and rebuilt at unlock
+    vault.lock();
+    assert!(vault.search_entries(&token, "gitlab").is_err());
+    let token = vault.unlock(USER, PASS).unwrap();
+    assert_eq!(titles(&mut vault, &token, "gitlab"), ["GitLab"]);
+}
+
+#[test]
+fn test_search_sees_synced_entries() {
+    let (mut desktop, desktop_token) = unlocked_vault();
+    desktop
+        .save_item(&desktop_token, &item("Vercel", "alice", "vercel.com", &["deploy"]), None)
+        .unwrap();
+    let payload = desktop.export_sync_payload(&desktop_token, None).unwrap();
+
+    let mut phone = Vault::new(Box::new(MemoryStore::new()));
+    phone
+        .register_with_encryption_salt(USER, PASS, payload.encryption_salt.as_deref().unwrap())
+        .unwrap();
+    let phone_token = phone.unlock(USER, PASS).unwrap();
+    assert!(titles(&mut phone, &phone_token, "vercel").is_empty());
+
+    phone.import_sync_payload(&phone_token, &payload).unwrap();
+    assert_eq!(titles(&mut phone, &phone_token, "tag:deploy"), ["Vercel"]);
+}
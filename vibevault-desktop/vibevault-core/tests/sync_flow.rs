@@ -0,0 +1,85 @@
+use vibevault_core::{DatabaseManager, MemoryStore, Vault};
+
+mod common;
+
+use common::{unlocked_vault, PASS, USER};
+
+#[test]
+fn test_register_unlock_save_sync() {
+    // Desktop: register, unlock and save an entry
+    let mut desktop = Vault::new(Box::new(DatabaseManager::open_in_memory().unwrap()));
+    assert!(!desktop.is_registered().unwrap());
+    desktop.register(USER, PASS).unwrap();
+    assert!(desktop.is_registered().unwrap());
+
+    let desktop_token = desktop.unlock(USER, PASS).unwrap();
+    desktop
+        .save_entry(&desktop_token, "github", b"hunter2", None)
+        .unwrap();
+    let entries = desktop.list_entries(&desktop_token).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].data_blob, b"hunter2");
+
+    // First sync: the full payload carries the encryption salt
+    let payload = desktop.export_sync_payload(&desktop_token, None).unwrap();
+    let salt = payload.encryption_salt.clone().expect("full export carries salt");
+
+    // Second device joins with the same salt and imports the payload
+    let mut phone = Vault::new(Box::new(MemoryStore::new()));
+    phone
+        .register_with_encryption_salt(USER, PASS, &salt)
+        .unwrap();
+    let phone_token = phone.unlock(USER, PASS).unwrap();
+    let merged = phone.import_sync_payload(&phone_token, &payload).unwrap();
+    assert_eq!(merged.inserted, 1);
+
+    let phone_entries = phone.list_entries(&phone_token).unwrap();
+    assert_eq!(phone_entries.len(), 1);
+    assert_eq!(phone_entries[0].uuid, "github");
+    assert_eq!(phone_entries[0].data_blob, b"hunter2");
+    assert_eq!(phone_entries[0].entry_uuid, entries[0].entry_uuid);
+
+    // Edit on the second device and sync the delta back
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    let since = chrono::Utc::now().to_rfc3339();
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    phone
+        .update_entry(&phone_token, phone_entries[0].id, "github", b"correct-horse")
+        .unwrap();
+    let delta = phone.export_sync_payload(&phone_token, Some(&since)).unwrap();
+    assert!(delta.encryption_salt.is_none());
+    assert_eq!(delta.entries.len(), 1);
+
+    let merged = desktop.import_sync_payload(&desktop_token, &delta).unwrap();
+    assert_eq!(merged.updated, 1);
+    let entries = desktop.list_entries(&desktop_token).unwrap();
+    assert_eq!(entries[0].data_blob, b"correct-horse");
+
+    // Deleting propagates as a tombstone
+    desktop.delete_entry(&desktop_token, entries[0].id).unwrap();
+    let full = desktop.export_sync_payload(&desktop_token, None).unwrap();
+    let merged = phone.import_sync_payload(&phone_token, &full).unwrap();
+    assert_eq!(merged.deleted, 1);
+    assert!(phone.list_entries(&phone_token).unwrap().is_empty());
+
+    // Locking ends the session
+    desktop.lock();
+    assert!(desktop.list_entries(&desktop_token).is_err());
+}
+
+#[test]
+fn test_profiles_scope_entries() {
+    let (mut vault, token) = unlocked_vault();
+
+    let work = vault.create_profile(&token, "Work").unwrap();
+    vault.save_entry(&token, "jira", b"secret", Some(work)).unwrap();
+    assert!(vault.list_entries(&token).unwrap().is_empty());
+
+    vault.set_active_profile(&token, work).unwrap();
+    assert_eq!(vault.list_entries(&token).unwrap().len(), 1);
+    assert!(vault.delete_profile(&token, work, None).is_err());
+
+    let profiles = vault.list_profiles(&token).unwrap();
+    assert_eq!(profiles.len(), 2);
+    assert_eq!(profiles[1].entry_count, 1);
+}
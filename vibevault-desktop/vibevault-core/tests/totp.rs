@@ -0,0 +1,132 @@
+use vibevault_core::{MemoryStore, OtpAlgorithm, OtpKind, Vault, VaultItem};
+
+mod common;
+
+use common::{unlocked_vault, PASS, USER};
+
+#[test]
+fn test_totp_from_otpauth_uri() {
+    let (mut vault, token) = unlocked_vault();
+
+    let uri = "otpauth://totp/Example:alice@example.com?secret=JBSWY3DPEHPK3PXP\
+               &issuer=Example&algorithm=SHA512&digits=8&period=60";
+    let mut item = VaultItem::new("Example");
+    item.totp_secret = Some(uri.to_string());
+    vault.save_item(&token, &item, None).unwrap();
+
+    let code = vault.totp_token(&token, uri).unwrap();
+    assert_eq!(code.code.len(), 8);
+    assert_eq!(code.next_code.len(), 8);
+    assert_eq!(code.period, 60);
+    assert_eq!(code.algorithm, OtpAlgorithm::Sha512);
+    assert!((1..=60).contains(&code.remaining_seconds));
+    assert_eq!(code.issuer.as_deref(), Some("Example"));
+    assert_eq!(code.label.as_deref(), Some("alice@example.com"));
+
+    let bare = vault.totp_token(&token, "JBSWY3DPEHPK3PXP").unwrap();
+    assert_eq!((bare.code.len(), bare.period), (6, 30));
+
+    assert!(vault
+        .totp_token(&token, "otpauth://motp/x?secret=JBSWY3DPEHPK3PXP")
+        .is_err());
+
+    vault.lock();
+    assert!(vault.totp_token(&token, uri).is_err());
+}
+
+#[test]
+fn test_totp_by_entry() {
+    let (mut vault, token) = unlocked_vault();
+    let work = vault.create_profile(&token, "Work").unwrap();
+
+    let mut github = VaultItem::new("github");
+    github.totp_secret = Some("JBSWY3DPEHPK3PXP".to_string());
+    vault.save_item(&token, &github, None).unwrap();
+    let mut bank = VaultItem::new("Bank");
+    bank.totp_secret =
+        Some("otpauth://totp/Bank:alice?secret=GEZDGNBVGY3TQOJQ&digits=8".to_string());
+    vault.save_item(&token, &bank, None).unwrap();
+    vault.save_item(&token, &VaultItem::new("No 2FA"), None).unwrap();
+    let mut jira = VaultItem::new("Jira");
+    jira.totp_secret = Some("JBSWY3DPEHPK3PXP".to_string());
+    vault.save_item(&token, &jira, Some(work)).unwrap();
+
+    let codes = vault.profile_totp_codes(&token, None).unwrap();
+    let titles: Vec<&str> = codes.iter().map(|c| c.title.as_str()).collect();
+    assert_eq!(titles, ["Bank", "github"]);
+    assert_eq!(codes[0].totp.code.len(), 8);
+    // Same secret, same time step: the per-entry path agrees with the raw one
+    // (or the next one, should a step boundary fall between the two calls)
+    let single = vault.entry_totp(&token, codes[1].id).unwrap();
+    let raw = vault.totp_token(&token, "JBSWY3DPEHPK3PXP").unwrap();
+    assert!(raw.code == single.code || raw.code == single.next_code);
+    assert!(!serde_json::to_string(&codes).unwrap().contains("JBSWY3DPEHPK3PXP"));
+
+    let work_codes = vault.profile_totp_codes(&token, Some(work)).unwrap();
+    assert_eq!(work_codes.len(), 1);
+    // Single lookups are limited to the active profile
+    assert!(vault.entry_totp(&token, work_codes[0].id).is_err());
+    assert!(vault.profile_totp_codes(&token, Some(9999)).is_err());
+
+    let plain = vault.list_items(&token, None).unwrap();
+    let no_2fa = plain.iter().find(|s| s.item.title == "No 2FA").unwrap();
+    assert!(vault.entry_totp(&token, no_2fa.id).is_err());
+}
+
+#[test]
+fn test_hotp_counter_advances_and_syncs() {
+    // RFC 4226 secret "12345678901234567890"
+    const HOTP: &str = "otpauth://hotp/Bank:alice?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&counter=1";
+    let mut desktop = Vault::new(Box::new(MemoryStore::new()));
+    desktop.register(USER, PASS).unwrap();
+    let desktop_token = desktop.unlock(USER, PASS).unwrap();
+    let mut bank = VaultItem::new("Bank");
+    bank.totp_secret = Some(HOTP.to_string());
+    desktop.save_item(&desktop_token, &bank, None).unwrap();
+    let id = desktop.list_items(&desktop_token, None).unwrap()[0].id;
+
+    // Previewing the raw URI never consumes a counter
+    let preview = desktop.totp_token(&desktop_token, HOTP).unwrap();
+    assert_eq!((preview.kind, preview.counter), (OtpKind::Hotp, Some(1)));
+    assert_eq!(preview.code, "287082");
+
+    let first = desktop.entry_totp(&desktop_token, id).unwrap();
+    let second = desktop.entry_totp(&desktop_token, id).unwrap();
+    assert_eq!((first.code.as_str(), first.counter), ("287082", Some(1)));
+    assert_eq!((second.code.as_str(), second.counter), ("359152", Some(2)));
+    assert_eq!(first.next_code, second.code);
+    assert!(desktop.profile_totp_codes(&desktop_token, None).unwrap().is_empty());
+
+    // The phone picks up the counter and continues where the desktop stopped
+    let payload = desktop.export_sync_payload(&desktop_token, None).unwrap();
+    let mut phone = Vault::new(Box::new(MemoryStore::new()));
+    phone
+        .register_with_encryption_salt(USER, PASS, payload.encryption_salt.as_ref().unwrap())
+        .unwrap();
+    let phone_token = phone.unlock(USER, PASS).unwrap();
+    phone.import_sync_payload(&phone_token, &payload).unwrap();
+    let phone_id = phone.list_items(&phone_token, None).unwrap()[0].id;
+    assert_eq!(phone.entry_totp(&phone_token, phone_id).unwrap().counter, Some(3));
+    assert_eq!(phone.entry_totp(&phone_token, phone_id).unwrap().counter, Some(4));
+
+    // An older copy from the desktop does not roll the phone back
+    phone.import_sync_payload(&phone_token, &payload).unwrap();
+    assert_eq!(phone.entry_totp(&phone_token, phone_id).unwrap().counter, Some(5));
+    let back = phone.export_sync_payload(&phone_token, None).unwrap();
+    desktop.import_sync_payload(&desktop_token, &back).unwrap();
+    assert_eq!(desktop.entry_totp(&desktop_token, id).unwrap().counter, Some(6));
+}
+
+#[test]
+fn test_steam_entry() {
+    let (mut vault, token) = unlocked_vault();
+    let mut steam = VaultItem::new("Steam");
+    steam.totp_secret = Some("steam://JBSWY3DPEHPK3PXP".to_string());
+    vault.save_item(&token, &steam, None).unwrap();
+
+    let codes = vault.profile_totp_codes(&token, None).unwrap();
+    assert_eq!(codes.len(), 1);
+    assert_eq!(codes[0].totp.kind, OtpKind::Steam);
+    assert_eq!(codes[0].totp.code.len(), 5);
+    assert_eq!(codes[0].totp.period, 30);
+}
@@ -0,0 +1,82 @@
+use vibevault_core::{EntrySort, EntrySummary, MemoryStore, Vault};
+
+mod common;
+
+use common::{save, unlocked_vault, PASS, USER};
+
+fn titles(summaries: &[EntrySummary]) -> Vec<&str> {
+    summaries.iter().map(|s| s.title.as_str()).collect()
+}
+
+#[test]
+fn test_recently_used_and_favorites() {
+    let (mut vault, token) = unlocked_vault();
+    let github = save(&mut vault, &token, "GitHub");
+    let bank = save(&mut vault, &token, "Bank");
+    save(&mut vault, &token, "Jira");
+
+    vault.record_entry_use(&token, github).unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    vault.record_entry_use(&token, bank).unwrap();
+    // A burst of uses (reveal, then copy) counts once
+    vault.record_entry_use(&token, bank).unwrap();
+
+    let recent = vault.recently_used_entries(&token, 10).unwrap();
+    assert_eq!(titles(&recent), ["Bank", "GitHub"]);
+    assert_eq!(recent[0].use_count, 1);
+    assert_eq!(titles(&vault.recently_used_entries(&token, 1).unwrap()), ["Bank"]);
+    assert!(vault.recently_used_entries(&token, 0).is_err());
+
+    vault.set_favorite(&token, github, true).unwrap();
+    vault.set_favorite(&token, bank, true).unwrap();
+    vault.set_favorite(&token, bank, false).unwrap();
+    assert!(vault.set_favorite(&token, 999, true).is_err());
+    let favorites = vault.favorite_entries(&token).unwrap();
+    assert_eq!(titles(&favorites), ["GitHub"]);
+    assert!(favorites[0].favorite);
+
+    // Usage and favorites never touch the entry itself
+    let record = vault.store().find_entry(github).unwrap().unwrap();
+    assert_eq!(record.sync_version, 1);
+
+    let page = vault
+        .list_entry_summaries(&token, EntrySort::MostUsed, None, 10)
+        .unwrap();
+    assert_eq!(page.entries[2].title, "Jira");
+}
+
+#[test]
+fn test_usage_and_favorites_sync_without_conflicts() {
+    let (mut desktop, desktop_token) = unlocked_vault();
+    let id = save(&mut desktop, &desktop_token, "GitHub");
+
+    let payload = desktop.export_sync_payload(&desktop_token, None).unwrap();
+    let mut phone = Vault::new(Box::new(MemoryStore::new()));
+    phone
+        .register_with_encryption_salt(USER, PASS, payload.encryption_salt.as_deref().unwrap())
+        .unwrap();
+    let phone_token = phone.unlock(USER, PASS).unwrap();
+    phone.import_sync_payload(&phone_token, &payload).unwrap();
+    let phone_id = phone.list_items(&phone_token, None).unwrap()[0].id;
+
+    // Both devices use the entry; the phone also stars it
+    desktop.record_entry_use(&desktop_token, id).unwrap();
+    phone.record_entry_use(&phone_token, phone_id).unwrap();
+    phone.set_favorite(&phone_token, phone_id, true).unwrap();
+
+    let from_phone = phone.export_sync_payload(&phone_token, None).unwrap();
+    let result = desktop.import_sync_payload(&desktop_token, &from_phone).unwrap();
+    assert_eq!(result.conflicts, 0);
+    let from_desktop = desktop.export_sync_payload(&desktop_token, None).unwrap();
+    let result = phone.import_sync_payload(&phone_token, &from_desktop).unwrap();
+    assert_eq!(result.conflicts, 0);
+
+    // Syncing again does not count the same uses twice
+    desktop.import_sync_payload(&desktop_token, &from_phone).unwrap();
+
+    for (vault, token) in [(&mut desktop, &desktop_token), (&mut phone, &phone_token)] {
+        let favorites = vault.favorite_entries(token).unwrap();
+        assert_eq!(titles(&favorites), ["GitHub"]);
+        assert_eq!(favorites[0].use_count, 2);
+    }
+}